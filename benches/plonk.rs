@@ -7,6 +7,21 @@
 // Copyright (c) ZK-GARAGE. All rights reserved.
 
 //! PLONK Benchmarks
+//!
+//! The `variables` benchmarks compare the storage of the values of the
+//! variables of the composer, a vector indexed by variable, with the hash map
+//! it replaced, within a single run.
+//!
+//! The other benchmarks are grouped by commitment scheme, such as
+//! `KZG10/prove`. The effect of a change on them is measured by running the
+//! benchmarks of the change on its base first, so that both runs have the same
+//! benchmark IDs, and comparing the change against the saved baseline:
+//!
+//! ```text
+//! git checkout <base> && git checkout <change> -- benches Cargo.toml
+//! cargo bench -- --save-baseline before
+//! git checkout -f <change> && cargo bench -- --baseline before
+//! ```
 
 use ark_bls12_377::Bls12_377;
 use ark_bls12_381::Bls12_381;
@@ -16,6 +31,7 @@ use ark_ff::{FftField, PrimeField};
 use blake2;
 use core::marker::PhantomData;
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use hashbrown::HashMap;
 use plonk::commitment::{HomomorphicCommitment, IPA, KZG10};
 use plonk::prelude::*;
 use rand_core::OsRng;
@@ -60,11 +76,45 @@ where
         }
         Ok(())
     }
+}
 
-    #[inline]
-    fn padded_circuit_size(&self) -> usize {
-        self.size
+/// Compares reading the values of the wires of a circuit of `2^degree` gates,
+/// as the prover does, from a vector indexed by variable and from a hash map.
+fn variable_storage_benchmarks(c: &mut Criterion) {
+    type F = <Bls12_381 as PairingEngine>::Fr;
+
+    let mut variables_benchmarks = c.benchmark_group("variables");
+    for degree in [10, 14, 18] {
+        let size = 1usize << degree;
+        let values = (0..size as u64).map(F::from).collect::<Vec<_>>();
+        let map = values
+            .iter()
+            .copied()
+            .enumerate()
+            .collect::<HashMap<_, _>>();
+        // Four wires per gate, spread over every variable.
+        let wires = (0..4 * size)
+            .map(|i| i.wrapping_mul(7919) % size)
+            .collect::<Vec<_>>();
+
+        variables_benchmarks.bench_with_input(
+            BenchmarkId::new("vec", degree),
+            &degree,
+            |b, _| {
+                b.iter(|| {
+                    wires.iter().map(|var| values[*var]).collect::<Vec<_>>()
+                })
+            },
+        );
+        variables_benchmarks.bench_with_input(
+            BenchmarkId::new("hash_map", degree),
+            &degree,
+            |b, _| {
+                b.iter(|| wires.iter().map(|var| map[var]).collect::<Vec<_>>())
+            },
+        );
     }
+    variables_benchmarks.finish();
 }
 
 fn kzg10_benchmarks(c: &mut Criterion) {
@@ -98,6 +148,24 @@ where
     let pp = HC::setup(1 << MAXIMUM_DEGREE, None, &mut OsRng)
         .expect("Unable to sample public parameters.");
 
+    let mut witness_benchmarks =
+        c.benchmark_group(format!("{0}/witness", name));
+    for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
+        let mut circuit = BenchCircuit::<F, P>::new(degree);
+        witness_benchmarks.bench_with_input(
+            BenchmarkId::from_parameter(degree),
+            &degree,
+            |b, _| {
+                b.iter(|| {
+                    circuit
+                        .gen_witness(&mut OsRng)
+                        .expect("Unable to generate witness.")
+                })
+            },
+        );
+    }
+    witness_benchmarks.finish();
+
     let mut compiling_benchmarks =
         c.benchmark_group(format!("{0}/compile", name));
     for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
@@ -108,7 +176,7 @@ where
             |b, _| {
                 b.iter(|| {
                     circuit
-                        .compile::<HC>(&pp)
+                        .compile::<HC>(&pp, &mut OsRng)
                         .expect("Unable to compile circuit.")
                 })
             },
@@ -116,31 +184,36 @@ where
     }
     compiling_benchmarks.finish();
 
-    let mut proving_benchmarks = c.benchmark_group("prove");
+    let mut proving_benchmarks = c.benchmark_group(format!("{0}/prove", name));
     for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
         let mut circuit = BenchCircuit::<F, P>::new(degree);
         let (pk_p, _) = circuit
-            .compile::<HC>(&pp)
+            .compile::<HC>(&pp, &mut OsRng)
             .expect("Unable to compile circuit.");
         proving_benchmarks.bench_with_input(
             BenchmarkId::from_parameter(degree),
             &degree,
             |b, _| {
                 b.iter(|| {
-                    circuit.gen_proof::<HC>(&pp, pk_p.clone(), &label).unwrap()
+                    circuit
+                        .gen_proof::<HC>(&pp, pk_p.clone(), &label, &mut OsRng)
+                        .unwrap()
                 })
             },
         );
     }
     proving_benchmarks.finish();
 
-    let mut verifying_benchmarks = c.benchmark_group("verify");
+    let mut verifying_benchmarks =
+        c.benchmark_group(format!("{0}/verify", name));
     for degree in MINIMUM_DEGREE..MAXIMUM_DEGREE {
         let mut circuit = BenchCircuit::<F, P>::new(degree);
-        let (pk_p, (vk, _pi_pos)) =
-            circuit.compile(&pp).expect("Unable to compile circuit.");
-        let (proof, pi) =
-            circuit.gen_proof::<HC>(&pp, pk_p.clone(), &label).unwrap();
+        let (pk_p, (vk, _pi_pos)) = circuit
+            .compile(&pp, &mut OsRng)
+            .expect("Unable to compile circuit.");
+        let (proof, pi) = circuit
+            .gen_proof::<HC>(&pp, pk_p.clone(), &label, &mut OsRng)
+            .unwrap();
         verifying_benchmarks.bench_with_input(
             BenchmarkId::from_parameter(degree),
             &degree,
@@ -149,6 +222,7 @@ where
                     plonk::circuit::verify_proof::<F, P, HC>(
                         &pp,
                         vk.clone(),
+                        vk.circuit_id(),
                         &proof,
                         &pi,
                         &label,
@@ -164,6 +238,6 @@ where
 criterion_group! {
     name = plonk;
     config = Criterion::default().sample_size(10);
-    targets = kzg10_benchmarks, ipa_benchmarks
+    targets = variable_storage_benchmarks, kzg10_benchmarks, ipa_benchmarks
 }
 criterion_main!(plonk);
@@ -112,10 +112,6 @@ fn main() -> Result<(), Error> {
 
             Ok(())
         }
-
-        fn padded_circuit_size(&self) -> usize {
-            1 << 9
-        }
     }
 
     // Generate CRS
@@ -125,7 +121,8 @@ fn main() -> Result<(), Error> {
 
     let mut circuit = TestCircuit::<BlsScalar, JubJubParameters>::default();
     // Compile the circuit
-    let (pk_p, (vk, _pi_pos)) = circuit.compile::<PC>(&pp)?;
+    let (pk_p, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+    let circuit_id = pk_p.circuit_id();
 
     // Prover POV
     let x = 1u64;
@@ -142,7 +139,7 @@ fn main() -> Result<(), Error> {
                 r: BlsScalar::from(r),
                 dummy: JubJubScalar::from(2u64),
             };
-        circuit.gen_proof::<PC>(&pp, pk_p, b"Test")
+        circuit.gen_proof::<PC>(&pp, pk_p, b"Test", &mut OsRng)
     }?;
 
     // Verifier POV
@@ -150,6 +147,7 @@ fn main() -> Result<(), Error> {
     verify_proof::<BlsScalar, JubJubParameters, PC>(
         &pp,
         verifier_data.key,
+        circuit_id,
         &proof,
         &verifier_data.pi,
         b"Test",
@@ -85,10 +85,6 @@ fn main() -> Result<(), Error> {
             composer.assert_equal_public_point(scalar_mul_result, self.f);
             Ok(())
         }
-
-        fn padded_circuit_size(&self) -> usize {
-            1 << 9
-        }
     }
 
     // Generate CRS
@@ -98,7 +94,8 @@ fn main() -> Result<(), Error> {
 
     let mut circuit = TestCircuit::<BlsScalar, JubJubParameters>::default();
     // Compile the circuit
-    let (pk_p, (vk, _pi_pos)) = circuit.compile::<PC>(&pp)?;
+    let (pk_p, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+    let circuit_id = pk_p.circuit_id();
 
     let (x, y) = JubJubParameters::AFFINE_GENERATOR_COEFFS;
     let generator: GroupAffine<JubJubParameters> = GroupAffine::new(x, y);
@@ -116,7 +113,7 @@ fn main() -> Result<(), Error> {
                 e: JubJubScalar::from(2u64),
                 f: point_f_pi,
             };
-        circuit.gen_proof::<PC>(&pp, pk_p, b"Test")
+        circuit.gen_proof::<PC>(&pp, pk_p, b"Test", &mut OsRng)
     }?;
 
     // Verifier POV
@@ -124,6 +121,7 @@ fn main() -> Result<(), Error> {
     verify_proof::<BlsScalar, JubJubParameters, PC>(
         &pp,
         verifier_data.key,
+        circuit_id,
         &proof,
         &verifier_data.pi,
         b"Test",
@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Commands of the multi-party ceremony computing KZG parameters.
+
+use crate::{read, write, CliError};
+use ark_bls12_381::Bls12_381;
+use ark_ec::AffineCurve;
+use ark_serialize::CanonicalSerialize;
+use clap::Subcommand;
+use plonk_core::srs::Ceremony;
+use rand_core::OsRng;
+use std::path::PathBuf;
+
+/// Commands of a ceremony, whose transcript is passed from one participant to
+/// the next as a file.
+#[derive(Subcommand)]
+pub enum CeremonyCommand {
+    /// Starts the transcript of a ceremony.
+    Init {
+        /// Maximum degree supported by the parameters of the ceremony.
+        #[clap(long)]
+        degree: usize,
+        /// File the transcript is written to.
+        #[clap(long)]
+        transcript: PathBuf,
+    },
+
+    /// Adds a contribution with fresh randomness to a transcript.
+    Contribute {
+        /// File of the transcript, updated in place.
+        #[clap(long)]
+        transcript: PathBuf,
+    },
+
+    /// Verifies the chain of contributions of a transcript.
+    Verify {
+        /// File of the transcript.
+        #[clap(long)]
+        transcript: PathBuf,
+    },
+
+    /// Verifies a transcript and writes its public parameters.
+    Export {
+        /// File of the transcript.
+        #[clap(long)]
+        transcript: PathBuf,
+        /// File the parameters are written to.
+        #[clap(long)]
+        params: PathBuf,
+    },
+}
+
+/// Returns the hexadecimal encoding of the serialization of `point`.
+fn hex<G: AffineCurve>(point: &G) -> String {
+    let mut bytes = Vec::new();
+    // Serializing to a `Vec` cannot fail.
+    point.serialize(&mut bytes).unwrap();
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Runs `command`.
+pub fn run(command: CeremonyCommand) -> Result<(), CliError> {
+    match command {
+        CeremonyCommand::Init { degree, transcript } => {
+            write(&transcript, &Ceremony::<Bls12_381>::new(degree)?)
+        }
+        CeremonyCommand::Contribute { transcript } => {
+            let mut ceremony = read::<Ceremony<Bls12_381>>(&transcript)?;
+            let contribution = ceremony.contribute(&mut OsRng);
+            // Participants publish the key of their contribution, so that
+            // they can check it is part of the final transcript.
+            println!("Contribution key: {}", hex(&contribution.s_g));
+            write(&transcript, &ceremony)
+        }
+        CeremonyCommand::Verify { transcript } => {
+            let ceremony = read::<Ceremony<Bls12_381>>(&transcript)?;
+            ceremony.verify(&mut OsRng)?;
+            println!(
+                "The transcript is valid, with {} contributions up to degree \
+                 {}:",
+                ceremony.contributions().len(),
+                ceremony.max_degree()
+            );
+            for (i, contribution) in ceremony.contributions().iter().enumerate()
+            {
+                println!("{}: {}", i, hex(&contribution.s_g));
+            }
+            Ok(())
+        }
+        CeremonyCommand::Export { transcript, params } => {
+            let ceremony = read::<Ceremony<Bls12_381>>(&transcript)?;
+            write(&params, &ceremony.verify(&mut OsRng)?)
+        }
+    }
+}
@@ -0,0 +1,165 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Summaries of the files handled by the tool.
+
+use crate::{read, read_container, registry, CliError, Fr, P};
+use ark_ff::PrimeField;
+use ark_poly_commit::PCUniversalParams;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::ArgEnum;
+use core::fmt::Write;
+use plonk_core::{
+    circuit::Circuit,
+    commitment::HomomorphicCommitment,
+    constraint_system::{CircuitIr, StandardComposer, Witness},
+    proof_system::{
+        pi::PublicInputs, CircuitId, Proof, ProverKey, VerifierKey,
+    },
+};
+use std::path::Path;
+
+/// Kinds of inspected targets.
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub enum Kind {
+    /// Public parameters.
+    Params,
+    /// Prover key.
+    ProverKey,
+    /// Verifier key.
+    VerifierKey,
+    /// Proof.
+    Proof,
+    /// Public inputs.
+    PublicInputs,
+    /// Circuit IR.
+    Ir,
+    /// Witness.
+    Witness,
+    /// Circuit of the registry, given by name.
+    Circuit,
+}
+
+/// Returns the hexadecimal encoding of `circuit_id`.
+pub fn hex(circuit_id: CircuitId) -> String {
+    circuit_id
+        .as_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Parses a [`CircuitId`] from its hexadecimal encoding, as printed by
+/// [`inspect`].
+pub fn parse_circuit_id(encoding: &str) -> Result<CircuitId, String> {
+    let invalid = || format!("invalid circuit identifier `{}`", encoding);
+    if encoding.len() != 64 || !encoding.is_ascii() {
+        return Err(invalid());
+    }
+    let bytes = (0..32)
+        .map(|i| u8::from_str_radix(&encoding[2 * i..2 * i + 2], 16))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    CircuitId::deserialize(&bytes[..]).map_err(|_| invalid())
+}
+
+/// Returns a summary of `target`, which is the path of a file of the given
+/// `kind` or the name of a circuit of the registry.
+pub fn inspect<PC>(kind: Kind, target: &str) -> Result<String, CliError>
+where
+    PC: HomomorphicCommitment<Fr>,
+{
+    let path = Path::new(target);
+    let mut summary = String::new();
+    // Writing to a `String` cannot fail.
+    match kind {
+        Kind::Params => {
+            let pp = read::<PC::UniversalParams>(path)?;
+            writeln!(summary, "max degree: {}", pp.max_degree()).unwrap();
+        }
+        Kind::ProverKey => {
+            let pk = read_container::<ProverKey<Fr>>(path)?;
+            writeln!(summary, "circuit size: {}", pk.padded_circuit_size())
+                .unwrap();
+            writeln!(summary, "circuit id: {}", hex(pk.circuit_id())).unwrap();
+        }
+        Kind::VerifierKey => {
+            let vk = read_container::<VerifierKey<Fr, PC>>(path)?;
+            writeln!(summary, "circuit size: {}", vk.padded_circuit_size())
+                .unwrap();
+            writeln!(summary, "circuit id: {}", hex(vk.circuit_id())).unwrap();
+            writeln!(summary, "public input positions: {:?}", vk.pi_pos())
+                .unwrap();
+            writeln!(
+                summary,
+                "custom gates: {:?}",
+                vk.custom_gate_names().collect::<Vec<_>>()
+            )
+            .unwrap();
+        }
+        Kind::Proof => {
+            let proof = read_container::<Proof<Fr, PC>>(path)?;
+            writeln!(summary, "size: {} bytes", proof.serialized_size())
+                .unwrap();
+        }
+        Kind::PublicInputs => {
+            let pi = read::<PublicInputs<Fr>>(path)?;
+            let n = pi
+                .get_pos()
+                .max()
+                .map_or(1, |pos| (pos + 1).next_power_of_two());
+            let values = pi.as_evals(n);
+            for pos in pi.get_pos() {
+                writeln!(summary, "{}: 0x{}", pos, values[*pos].into_repr())
+                    .unwrap();
+            }
+        }
+        Kind::Ir => {
+            let ir = read::<CircuitIr<Fr>>(path)?;
+            writeln!(summary, "gates: {}", ir.n).unwrap();
+            writeln!(summary, "blinding: {:?}", ir.blinding).unwrap();
+            writeln!(summary, "gate layout: {:?}", ir.gate_layout).unwrap();
+            writeln!(summary, "variables: {}", ir.copy_constraints.len())
+                .unwrap();
+            writeln!(summary, "public input positions: {:?}", ir.pi_pos)
+                .unwrap();
+            writeln!(
+                summary,
+                "custom gates: {:?}",
+                ir.custom_selectors
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>()
+            )
+            .unwrap();
+            writeln!(summary, "lookup table rows: {}", ir.lookup_table.len())
+                .unwrap();
+        }
+        Kind::Witness => {
+            let witness = read::<Witness<Fr>>(path)?;
+            writeln!(summary, "circuit id: {}", hex(witness.circuit_id))
+                .unwrap();
+            writeln!(summary, "variables: {}", witness.values.len()).unwrap();
+            writeln!(
+                summary,
+                "public input positions: {:?}",
+                witness.public_inputs.get_pos().collect::<Vec<_>>()
+            )
+            .unwrap();
+        }
+        Kind::Circuit => {
+            let entry = registry::find(target)?;
+            let mut composer = StandardComposer::<Fr, P>::new().setup_only();
+            entry.placeholder().gadget(&mut composer)?;
+            writeln!(summary, "{}: {}", entry.name, entry.description).unwrap();
+            writeln!(summary, "inputs: {}", entry.inputs.join(", ")).unwrap();
+            writeln!(summary, "circuit id: {}", hex(composer.circuit_id()))
+                .unwrap();
+            write!(summary, "{}", composer.profile()).unwrap();
+        }
+    }
+    Ok(summary)
+}
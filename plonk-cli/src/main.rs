@@ -0,0 +1,862 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Command-line tool to set up, compile, prove and verify PLONK circuits over
+//! BLS12-381, with JubJub as the embedded curve.
+//!
+//! Keys and proofs are stored as files in the versioned container format of
+//! [`plonk_core::container`], and the other files in the canonical
+//! serialization of arkworks. Circuits come either from the
+//! registry of example circuits or from a serialized [`CircuitIr`], in which
+//! case their witness is read from a serialized [`Witness`].
+
+mod ceremony;
+mod inspect;
+mod registry;
+
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, SerializationError,
+};
+use clap::{ArgEnum, Args, Parser, Subcommand};
+use plonk_core::{
+    circuit::{compile_ir, gen_proof_from_witness, verify_proof, Circuit},
+    commitment::{HomomorphicCommitment, IPA, KZG10},
+    constraint_system::{CircuitIr, Witness},
+    container::Containable,
+    error::{to_pc_error, Error},
+    proof_system::{
+        pi::PublicInputs, CircuitId, Proof, ProverKey, VerifierKey,
+    },
+    srs::read_ptau,
+};
+use rand_core::OsRng;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Scalar field of the circuits.
+type Fr = ark_bls12_381::Fr;
+
+/// Embedded curve of the circuits.
+type P = ark_ed_on_bls12_381::EdwardsParameters;
+
+/// KZG commitment scheme.
+type Kzg = KZG10<ark_bls12_381::Bls12_381>;
+
+/// IPA commitment scheme.
+type Ipa = IPA<ark_bls12_381::G1Affine, blake2::Blake2b>;
+
+/// Label the transcripts of the proofs are initialized with.
+const TRANSCRIPT_LABEL: &[u8] = b"plonk-cli";
+
+/// Errors of the tool.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// A file could not be read or written.
+    #[error("{}: {error}", path.display())]
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+        /// Underlying error.
+        error: io::Error,
+    },
+
+    /// A file could not be serialized or deserialized.
+    #[error("{}: invalid contents ({error:?})", path.display())]
+    Serialization {
+        /// Path of the file.
+        path: PathBuf,
+        /// Underlying error.
+        error: SerializationError,
+    },
+
+    /// A container file could not be read.
+    #[error("{}: {error}", path.display())]
+    Container {
+        /// Path of the file.
+        path: PathBuf,
+        /// Underlying error.
+        error: Error,
+    },
+
+    /// The circuit is not in the registry.
+    #[error("unknown circuit `{name}`, available circuits: {available}")]
+    UnknownCircuit {
+        /// Name of the circuit.
+        name: String,
+        /// Names of the circuits of the registry.
+        available: String,
+    },
+
+    /// The wrong number of inputs was passed to a circuit of the registry.
+    #[error("circuit `{name}` takes {expected} inputs, {found} were passed")]
+    InputCount {
+        /// Name of the circuit.
+        name: &'static str,
+        /// Number of inputs of the circuit.
+        expected: usize,
+        /// Number of inputs passed.
+        found: usize,
+    },
+
+    /// An input is not a decimal field element.
+    #[error("invalid input `{input}`, expected a decimal field element")]
+    InvalidInput {
+        /// The input as passed.
+        input: String,
+    },
+
+    /// A circuit read from an IR file was proven without a witness.
+    #[error("`--witness` is required to prove a circuit read from `--ir`")]
+    MissingWitness,
+
+    /// A ceremony was run or a `.ptau` file was imported with a commitment
+    /// scheme other than KZG.
+    #[error(
+        "ceremonies and `.ptau` files hold KZG parameters, `--scheme ipa` is \
+         not supported"
+    )]
+    CeremonyScheme,
+
+    /// KZG parameters were set up without `--insecure`.
+    #[error(
+        "`setup` samples the secret of KZG parameters itself, pass \
+         `--insecure` to use them for tests, or compute parameters with \
+         `plonk ceremony` or `plonk import-ptau`"
+    )]
+    InsecureSetup,
+
+    /// An error of the proof system.
+    #[error(transparent)]
+    Plonk(#[from] Error),
+}
+
+/// Sets up, compiles, proves and verifies PLONK circuits over BLS12-381.
+#[derive(Parser)]
+#[clap(name = "plonk", version, about)]
+struct Cli {
+    /// Polynomial commitment scheme of the parameters, keys and proofs.
+    #[clap(long, arg_enum, global = true, default_value = "kzg")]
+    scheme: Scheme,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// Polynomial commitment schemes supported by the tool.
+#[derive(ArgEnum, Clone, Copy, Debug)]
+enum Scheme {
+    /// KZG commitments, which need a trusted setup.
+    Kzg,
+    /// Inner product argument commitments, which need no trusted setup.
+    Ipa,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates public parameters supporting circuits up to a degree.
+    ///
+    /// KZG parameters are generated from a secret known to this process, with
+    /// which proofs can be forged, so they are only fit for tests.
+    Setup {
+        /// Maximum degree supported by the parameters.
+        #[clap(long)]
+        degree: usize,
+        /// File the parameters are written to.
+        #[clap(long)]
+        params: PathBuf,
+        /// Acknowledges that KZG parameters are only fit for tests.
+        #[clap(long)]
+        insecure: bool,
+    },
+
+    /// Reads KZG parameters up to a degree from a `.ptau` file of a powers of
+    /// tau ceremony over BLS12-381.
+    ImportPtau {
+        /// File of the powers of tau.
+        #[clap(long)]
+        ptau: PathBuf,
+        /// Maximum degree supported by the parameters.
+        #[clap(long)]
+        degree: usize,
+        /// File the parameters are written to.
+        #[clap(long)]
+        params: PathBuf,
+    },
+
+    /// Writes the IR of a circuit of the registry, to be compiled and proven
+    /// with `--ir`, and its witness for the given inputs.
+    Export {
+        /// Name of a circuit of the registry.
+        #[clap(long)]
+        circuit: String,
+        /// File the IR of the circuit is written to.
+        #[clap(long)]
+        ir: PathBuf,
+        /// Inputs of the circuit, as decimal field elements.
+        #[clap(long = "input", requires = "witness")]
+        inputs: Vec<String>,
+        /// File the witness of the circuit for the inputs is written to.
+        #[clap(long)]
+        witness: Option<PathBuf>,
+    },
+
+    /// Compiles a circuit into its prover and verifier keys.
+    Compile {
+        /// File of the public parameters.
+        #[clap(long)]
+        params: PathBuf,
+        #[clap(flatten)]
+        circuit: CircuitSource,
+        /// File the prover key is written to.
+        #[clap(long)]
+        prover_key: PathBuf,
+        /// File the verifier key is written to.
+        #[clap(long)]
+        verifier_key: PathBuf,
+    },
+
+    /// Proves a circuit with the given inputs or witness.
+    Prove {
+        /// File of the public parameters.
+        #[clap(long)]
+        params: PathBuf,
+        #[clap(flatten)]
+        circuit: CircuitSource,
+        /// Inputs of a circuit of the registry, as decimal field elements.
+        #[clap(long = "input", conflicts_with = "ir")]
+        inputs: Vec<String>,
+        /// File of the witness of a circuit read from an IR file.
+        #[clap(long, requires = "ir")]
+        witness: Option<PathBuf>,
+        /// File of the prover key of the circuit.
+        #[clap(long)]
+        prover_key: PathBuf,
+        /// File the proof is written to.
+        #[clap(long)]
+        proof: PathBuf,
+        /// File the public inputs are written to.
+        #[clap(long)]
+        public_inputs: PathBuf,
+    },
+
+    /// Verifies a proof against its public inputs.
+    Verify {
+        /// File of the public parameters.
+        #[clap(long)]
+        params: PathBuf,
+        /// File of the verifier key of the circuit.
+        #[clap(long)]
+        verifier_key: PathBuf,
+        /// File of the proof.
+        #[clap(long)]
+        proof: PathBuf,
+        /// File of the public inputs.
+        #[clap(long)]
+        public_inputs: PathBuf,
+        /// Identifier of the circuit the verifier key must have been compiled
+        /// from, in hexadecimal as printed by `plonk inspect`. Any key is
+        /// accepted when it is not given.
+        #[clap(long, value_parser = inspect::parse_circuit_id)]
+        circuit_id: Option<CircuitId>,
+    },
+
+    /// Runs a multi-party ceremony computing KZG parameters.
+    Ceremony {
+        #[clap(subcommand)]
+        command: ceremony::CeremonyCommand,
+    },
+
+    /// Prints a summary of a file or of a circuit of the registry.
+    Inspect {
+        /// Kind of the inspected file.
+        #[clap(arg_enum)]
+        kind: inspect::Kind,
+        /// Path of the file, or name of the circuit of the registry.
+        target: String,
+    },
+}
+
+/// Circuit to compile or prove.
+#[derive(Args)]
+struct CircuitSource {
+    /// Name of a circuit of the registry.
+    #[clap(long, required_unless_present = "ir", conflicts_with = "ir")]
+    circuit: Option<String>,
+    /// File of the IR of a circuit.
+    #[clap(long)]
+    ir: Option<PathBuf>,
+}
+
+/// Reads the value serialized in the file at `path`.
+fn read<T>(path: &Path) -> Result<T, CliError>
+where
+    T: CanonicalDeserialize,
+{
+    let file = File::open(path).map_err(|error| CliError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    T::deserialize(BufReader::new(file)).map_err(|error| {
+        CliError::Serialization {
+            path: path.to_path_buf(),
+            error,
+        }
+    })
+}
+
+/// Writes `value` serialized to the file at `path`.
+fn write<T>(path: &Path, value: &T) -> Result<(), CliError>
+where
+    T: CanonicalSerialize,
+{
+    let io_error = |error: io::Error| CliError::Io {
+        path: path.to_path_buf(),
+        error,
+    };
+    let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+    value
+        .serialize(&mut writer)
+        .map_err(|error| CliError::Serialization {
+            path: path.to_path_buf(),
+            error,
+        })?;
+    writer.flush().map_err(io_error)
+}
+
+/// Reads the value stored in the container file at `path`.
+fn read_container<T>(path: &Path) -> Result<T, CliError>
+where
+    T: Containable,
+{
+    let file = File::open(path).map_err(|error| CliError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    T::read_container(BufReader::new(file)).map_err(|error| {
+        CliError::Container {
+            path: path.to_path_buf(),
+            error,
+        }
+    })
+}
+
+/// Writes `value` in a container to the file at `path`.
+fn write_container<T>(path: &Path, value: &T) -> Result<(), CliError>
+where
+    T: Containable,
+{
+    let io_error = |error: io::Error| CliError::Io {
+        path: path.to_path_buf(),
+        error,
+    };
+    let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+    value.write_container(&mut writer).map_err(|error| {
+        CliError::Serialization {
+            path: path.to_path_buf(),
+            error,
+        }
+    })?;
+    writer.flush().map_err(io_error)
+}
+
+/// Runs `command` with the commitment scheme `PC`.
+fn run<PC>(command: Command) -> Result<(), CliError>
+where
+    PC: HomomorphicCommitment<Fr>,
+{
+    match command {
+        Command::Setup { degree, params, .. } => {
+            let pp = PC::setup(degree, None, &mut OsRng)
+                .map_err(to_pc_error::<Fr, PC>)?;
+            write(&params, &pp)
+        }
+        Command::ImportPtau {
+            ptau,
+            degree,
+            params,
+        } => {
+            let file = File::open(&ptau).map_err(|error| CliError::Io {
+                path: ptau.clone(),
+                error,
+            })?;
+            let pp = read_ptau::<ark_bls12_381::Bls12_381, _, _>(
+                BufReader::new(file),
+                degree,
+                &mut OsRng,
+            )?;
+            write(&params, &pp)
+        }
+        Command::Export {
+            circuit,
+            ir,
+            inputs,
+            witness,
+        } => {
+            let entry = registry::find(&circuit)?;
+            write(&ir, &entry.placeholder().gen_ir(&mut OsRng)?)?;
+            match witness {
+                Some(witness) => write(
+                    &witness,
+                    &entry.with_inputs(&inputs)?.gen_witness(&mut OsRng)?,
+                ),
+                None => Ok(()),
+            }
+        }
+        Command::Compile {
+            params,
+            circuit,
+            prover_key,
+            verifier_key,
+        } => {
+            let pp = read::<PC::UniversalParams>(&params)?;
+            let (pk, (vk, _)) = match (circuit.circuit, circuit.ir) {
+                (Some(name), _) => registry::find(&name)?
+                    .placeholder()
+                    .compile::<PC>(&pp, &mut OsRng)?,
+                (None, Some(ir)) => compile_ir::<Fr, P, PC>(
+                    &pp,
+                    &read::<CircuitIr<Fr>>(&ir)?,
+                    &[],
+                )?,
+                (None, None) => unreachable!("enforced by the arguments"),
+            };
+            write_container(&prover_key, &pk)?;
+            write_container(&verifier_key, &vk)
+        }
+        Command::Prove {
+            params,
+            circuit,
+            inputs,
+            witness,
+            prover_key,
+            proof,
+            public_inputs,
+        } => {
+            let pp = read::<PC::UniversalParams>(&params)?;
+            let pk = read_container::<ProverKey<Fr>>(&prover_key)?;
+            let (generated_proof, pi) = match (circuit.circuit, circuit.ir) {
+                (Some(name), _) => registry::find(&name)?
+                    .with_inputs(&inputs)?
+                    .gen_proof::<PC>(&pp, pk, TRANSCRIPT_LABEL, &mut OsRng)?,
+                (None, Some(ir)) => {
+                    let witness = witness.ok_or(CliError::MissingWitness)?;
+                    gen_proof_from_witness::<Fr, P, PC>(
+                        &pp,
+                        &read::<CircuitIr<Fr>>(&ir)?,
+                        &[],
+                        pk,
+                        &read::<Witness<Fr>>(&witness)?,
+                        TRANSCRIPT_LABEL,
+                        &mut OsRng,
+                    )?
+                }
+                (None, None) => unreachable!("enforced by the arguments"),
+            };
+            write_container(&proof, &generated_proof)?;
+            write(&public_inputs, &pi)
+        }
+        Command::Verify {
+            params,
+            verifier_key,
+            proof,
+            public_inputs,
+            circuit_id,
+        } => {
+            let verifier_key =
+                read_container::<VerifierKey<Fr, PC>>(&verifier_key)?;
+            let circuit_id =
+                circuit_id.unwrap_or_else(|| verifier_key.circuit_id());
+            verify_proof::<Fr, P, PC>(
+                &read::<PC::UniversalParams>(&params)?,
+                verifier_key,
+                circuit_id,
+                &read_container::<Proof<Fr, PC>>(&proof)?,
+                &read::<PublicInputs<Fr>>(&public_inputs)?,
+                TRANSCRIPT_LABEL,
+            )
+            .map(|()| println!("The proof is valid."))
+            .map_err(Into::into)
+        }
+        Command::Ceremony { command } => ceremony::run(command),
+        Command::Inspect { kind, target } => {
+            print!("{}", inspect::inspect::<PC>(kind, &target)?);
+            Ok(())
+        }
+    }
+}
+
+/// Runs the command of `cli` with its commitment scheme.
+fn run_cli(cli: Cli) -> Result<(), CliError> {
+    match cli.scheme {
+        Scheme::Ipa
+            if matches!(
+                cli.command,
+                Command::Ceremony { .. } | Command::ImportPtau { .. }
+            ) =>
+        {
+            Err(CliError::CeremonyScheme)
+        }
+        Scheme::Kzg
+            if matches!(
+                cli.command,
+                Command::Setup {
+                    insecure: false,
+                    ..
+                }
+            ) =>
+        {
+            Err(CliError::InsecureSetup)
+        }
+        Scheme::Kzg => {
+            if matches!(cli.command, Command::Setup { .. }) {
+                eprintln!(
+                    "warning: the secret of these KZG parameters was sampled \
+                     by this process, they must not be used outside of tests"
+                );
+            }
+            run::<Kzg>(cli.command)
+        }
+        Scheme::Ipa => run::<Ipa>(cli.command),
+    }
+}
+
+fn main() -> ExitCode {
+    match run_cli(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::CommandFactory;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_cli_arguments() {
+        Cli::command().debug_assert();
+    }
+
+    /// Tests compiling, proving and verifying a circuit of the registry
+    /// through files.
+    fn test_registry_circuit<PC>(
+        name: &str,
+        inputs: &[&str],
+        other_inputs: &[&str],
+    ) -> Result<(), CliError>
+    where
+        PC: HomomorphicCommitment<Fr>,
+    {
+        let dir = TempDir::new("plonk-cli").unwrap();
+        let path = |file: &str| dir.path().join(file);
+        let source = || CircuitSource {
+            circuit: Some(name.to_string()),
+            ir: None,
+        };
+        let prove = |inputs: &[&str], proof: &str, public_inputs: &str| {
+            run::<PC>(Command::Prove {
+                params: path("params"),
+                circuit: source(),
+                inputs: inputs.iter().map(|input| input.to_string()).collect(),
+                witness: None,
+                prover_key: path("pk"),
+                proof: path(proof),
+                public_inputs: path(public_inputs),
+            })
+        };
+        let verify_as = |proof: &str, public_inputs: &str, circuit_id| {
+            run::<PC>(Command::Verify {
+                params: path("params"),
+                verifier_key: path("vk"),
+                proof: path(proof),
+                public_inputs: path(public_inputs),
+                circuit_id,
+            })
+        };
+        let verify = |proof: &str, public_inputs: &str| {
+            verify_as(proof, public_inputs, None)
+        };
+
+        run::<PC>(Command::Setup {
+            degree: 1 << 10,
+            params: path("params"),
+            insecure: true,
+        })?;
+        run::<PC>(Command::Compile {
+            params: path("params"),
+            circuit: source(),
+            prover_key: path("pk"),
+            verifier_key: path("vk"),
+        })?;
+        prove(inputs, "proof", "pi")?;
+        prove(other_inputs, "other_proof", "other_pi")?;
+
+        verify("proof", "pi")?;
+        verify("other_proof", "other_pi")?;
+        assert!(verify("proof", "other_pi").is_err());
+
+        // The key is checked against the expected circuit.
+        let circuit_id =
+            read_container::<VerifierKey<Fr, PC>>(&path("vk"))?.circuit_id();
+        let encoding = inspect::hex(circuit_id);
+        let parsed_id = inspect::parse_circuit_id(&encoding).unwrap();
+        assert_eq!(parsed_id, circuit_id);
+        verify_as("proof", "pi", Some(parsed_id))?;
+        let other_id = inspect::parse_circuit_id(&"00".repeat(32)).unwrap();
+        assert!(matches!(
+            verify_as("proof", "pi", Some(other_id)),
+            Err(CliError::Plonk(Error::CircuitIdMismatch))
+        ));
+        assert!(inspect::parse_circuit_id(&encoding[1..]).is_err());
+        assert!(matches!(
+            prove(&inputs[1..], "proof", "pi"),
+            Err(CliError::InputCount { .. })
+        ));
+        assert!(matches!(
+            run::<PC>(Command::Verify {
+                params: path("params"),
+                verifier_key: path("pk"),
+                proof: path("proof"),
+                public_inputs: path("pi"),
+                circuit_id: None,
+            }),
+            Err(CliError::Container { .. })
+        ));
+
+        for (kind, target) in [
+            (inspect::Kind::VerifierKey, path("vk")),
+            (inspect::Kind::PublicInputs, path("pi")),
+        ] {
+            inspect::inspect::<PC>(kind, target.to_str().unwrap())?;
+        }
+        assert!(inspect::inspect::<PC>(inspect::Kind::Circuit, name)?
+            .contains(name));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sum_xor_kzg() -> Result<(), CliError> {
+        test_registry_circuit::<Kzg>("sum-xor", &["20", "5"], &["3", "100"])
+    }
+
+    #[test]
+    fn test_sum_xor_ipa() -> Result<(), CliError> {
+        test_registry_circuit::<Ipa>("sum-xor", &["20", "5"], &["3", "100"])
+    }
+
+    #[test]
+    fn test_jubjub_mul_kzg() -> Result<(), CliError> {
+        test_registry_circuit::<Kzg>("jubjub-mul", &["2"], &["7"])
+    }
+
+    /// Tests compiling and proving a circuit of the registry through its IR
+    /// and witness files.
+    fn test_ir_circuit<PC>() -> Result<(), CliError>
+    where
+        PC: HomomorphicCommitment<Fr>,
+    {
+        let dir = TempDir::new("plonk-cli").unwrap();
+        let path = |file: &str| dir.path().join(file);
+        let export = |inputs: &[&str], witness: &str| {
+            run::<PC>(Command::Export {
+                circuit: "sum-xor".to_string(),
+                ir: path("ir"),
+                inputs: inputs.iter().map(|input| input.to_string()).collect(),
+                witness: Some(path(witness)),
+            })
+        };
+        let ir_source = || CircuitSource {
+            circuit: None,
+            ir: Some(path("ir")),
+        };
+        let prove =
+            |witness: Option<&str>, proof: &str, public_inputs: &str| {
+                run::<PC>(Command::Prove {
+                    params: path("params"),
+                    circuit: ir_source(),
+                    inputs: Vec::new(),
+                    witness: witness.map(path),
+                    prover_key: path("ir_pk"),
+                    proof: path(proof),
+                    public_inputs: path(public_inputs),
+                })
+            };
+        let verify = |verifier_key: &str, proof: &str, public_inputs: &str| {
+            run::<PC>(Command::Verify {
+                params: path("params"),
+                verifier_key: path(verifier_key),
+                proof: path(proof),
+                public_inputs: path(public_inputs),
+                circuit_id: None,
+            })
+        };
+
+        run::<PC>(Command::Setup {
+            degree: 1 << 10,
+            params: path("params"),
+            insecure: true,
+        })?;
+        export(&["20", "5"], "witness")?;
+        export(&["3", "100"], "other_witness")?;
+        assert!(matches!(
+            export(&["20"], "witness"),
+            Err(CliError::InputCount { .. })
+        ));
+        run::<PC>(Command::Compile {
+            params: path("params"),
+            circuit: ir_source(),
+            prover_key: path("ir_pk"),
+            verifier_key: path("ir_vk"),
+        })?;
+        run::<PC>(Command::Compile {
+            params: path("params"),
+            circuit: CircuitSource {
+                circuit: Some("sum-xor".to_string()),
+                ir: None,
+            },
+            prover_key: path("pk"),
+            verifier_key: path("vk"),
+        })?;
+
+        // The IR compiles to the keys of the circuit of the registry.
+        let read_file = |file: &str| std::fs::read(path(file)).unwrap();
+        assert_eq!(read_file("ir_pk"), read_file("pk"));
+        assert_eq!(read_file("ir_vk"), read_file("vk"));
+
+        prove(Some("witness"), "proof", "pi")?;
+        prove(Some("other_witness"), "other_proof", "other_pi")?;
+        verify("ir_vk", "proof", "pi")?;
+        verify("vk", "other_proof", "other_pi")?;
+        assert!(verify("ir_vk", "proof", "other_pi").is_err());
+        assert!(matches!(
+            prove(None, "proof", "pi"),
+            Err(CliError::MissingWitness)
+        ));
+
+        for (kind, target) in [
+            (inspect::Kind::Ir, path("ir")),
+            (inspect::Kind::Witness, path("witness")),
+        ] {
+            inspect::inspect::<PC>(kind, target.to_str().unwrap())?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ir_circuit_kzg() -> Result<(), CliError> {
+        test_ir_circuit::<Kzg>()
+    }
+
+    #[test]
+    fn test_ir_circuit_ipa() -> Result<(), CliError> {
+        test_ir_circuit::<Ipa>()
+    }
+
+    #[test]
+    fn test_ceremony() -> Result<(), CliError> {
+        let dir = TempDir::new("plonk-cli").unwrap();
+        let path = |file: &str| dir.path().join(file);
+        let transcript = || path("transcript");
+        let run_ceremony = |command| run::<Kzg>(Command::Ceremony { command });
+
+        run_ceremony(ceremony::CeremonyCommand::Init {
+            degree: 1 << 10,
+            transcript: transcript(),
+        })?;
+        assert!(run_ceremony(ceremony::CeremonyCommand::Verify {
+            transcript: transcript()
+        })
+        .is_err());
+        for _ in 0..2 {
+            run_ceremony(ceremony::CeremonyCommand::Contribute {
+                transcript: transcript(),
+            })?;
+        }
+        run_ceremony(ceremony::CeremonyCommand::Verify {
+            transcript: transcript(),
+        })?;
+        run_ceremony(ceremony::CeremonyCommand::Export {
+            transcript: transcript(),
+            params: path("params"),
+        })?;
+
+        let source = || CircuitSource {
+            circuit: Some("sum-xor".to_string()),
+            ir: None,
+        };
+        run::<Kzg>(Command::Compile {
+            params: path("params"),
+            circuit: source(),
+            prover_key: path("pk"),
+            verifier_key: path("vk"),
+        })?;
+        run::<Kzg>(Command::Prove {
+            params: path("params"),
+            circuit: source(),
+            inputs: vec!["20".to_string(), "5".to_string()],
+            witness: None,
+            prover_key: path("pk"),
+            proof: path("proof"),
+            public_inputs: path("pi"),
+        })?;
+        run::<Kzg>(Command::Verify {
+            params: path("params"),
+            verifier_key: path("vk"),
+            proof: path("proof"),
+            public_inputs: path("pi"),
+            circuit_id: None,
+        })
+    }
+
+    #[test]
+    fn test_kzg_setup_is_insecure() -> Result<(), CliError> {
+        let dir = TempDir::new("plonk-cli").unwrap();
+        let params = dir.path().join("params");
+        let setup = |args: &[&str]| {
+            let mut argv = vec!["plonk", "setup", "--degree", "16", "--params"];
+            argv.push(params.to_str().unwrap());
+            argv.extend(args);
+            run_cli(Cli::parse_from(argv))
+        };
+
+        assert!(matches!(setup(&[]), Err(CliError::InsecureSetup)));
+        assert!(!params.exists());
+        setup(&["--insecure"])?;
+        setup(&["--scheme", "ipa"])?;
+        assert!(matches!(
+            run_cli(Cli::parse_from([
+                "plonk",
+                "--scheme",
+                "ipa",
+                "import-ptau",
+                "--ptau",
+                "powers.ptau",
+                "--degree",
+                "16",
+                "--params",
+                params.to_str().unwrap(),
+            ])),
+            Err(CliError::CeremonyScheme)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_circuit() {
+        assert!(matches!(
+            inspect::inspect::<Kzg>(inspect::Kind::Circuit, "unknown"),
+            Err(CliError::UnknownCircuit { .. })
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Registry of the example circuits built into the tool.
+
+use crate::{CliError, Fr, P};
+use ark_ec::{twisted_edwards_extended::GroupAffine, TEModelParameters};
+use ark_ff::{One, Zero};
+use plonk_core::{
+    circuit::Circuit, constraint_system::StandardComposer, error::Error,
+};
+
+/// Gadget of an example circuit, taking the values of its inputs.
+type Gadget = fn(&mut StandardComposer<Fr, P>, &[Fr]) -> Result<(), Error>;
+
+/// Example circuit of the registry.
+pub struct Entry {
+    /// Name the circuit is selected with.
+    pub name: &'static str,
+
+    /// Description of the statement proven by the circuit.
+    pub description: &'static str,
+
+    /// Names of the inputs of the circuit, in the order they are passed in.
+    pub inputs: &'static [&'static str],
+
+    /// Gadget of the circuit.
+    gadget: Gadget,
+}
+
+/// Example circuits of the registry.
+pub const CIRCUITS: &[Entry] = &[
+    Entry {
+        name: "sum-xor",
+        description: "a and b are 8 bit values whose sum and xor are public",
+        inputs: &["a", "b"],
+        gadget: sum_xor,
+    },
+    Entry {
+        name: "jubjub-mul",
+        description: "the public point is the JubJub generator times e",
+        inputs: &["e"],
+        gadget: jubjub_mul,
+    },
+];
+
+/// Returns the example circuit called `name`.
+pub fn find(name: &str) -> Result<&'static Entry, CliError> {
+    CIRCUITS
+        .iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| CliError::UnknownCircuit {
+            name: name.to_string(),
+            available: CIRCUITS
+                .iter()
+                .map(|entry| entry.name)
+                .collect::<Vec<_>>()
+                .join(", "),
+        })
+}
+
+impl Entry {
+    /// Returns the circuit with its inputs set to `inputs`, parsed as decimal
+    /// field elements.
+    pub fn with_inputs(
+        &'static self,
+        inputs: &[String],
+    ) -> Result<RegisteredCircuit, CliError> {
+        if inputs.len() != self.inputs.len() {
+            return Err(CliError::InputCount {
+                name: self.name,
+                expected: self.inputs.len(),
+                found: inputs.len(),
+            });
+        }
+        let inputs = inputs
+            .iter()
+            .map(|input| {
+                input.parse::<Fr>().map_err(|_| CliError::InvalidInput {
+                    input: input.clone(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(RegisteredCircuit {
+            entry: self,
+            inputs,
+        })
+    }
+
+    /// Returns the circuit with placeholder inputs, to be compiled.
+    pub fn placeholder(&'static self) -> RegisteredCircuit {
+        RegisteredCircuit {
+            entry: self,
+            inputs: vec![Fr::zero(); self.inputs.len()],
+        }
+    }
+}
+
+/// Example circuit of the registry together with its inputs.
+pub struct RegisteredCircuit {
+    /// Entry of the circuit in the registry.
+    entry: &'static Entry,
+
+    /// Values of the inputs of the circuit.
+    inputs: Vec<Fr>,
+}
+
+impl Circuit<Fr, P> for RegisteredCircuit {
+    const CIRCUIT_ID: [u8; 32] = [0; 32];
+
+    fn gadget(
+        &mut self,
+        composer: &mut StandardComposer<Fr, P>,
+    ) -> Result<(), Error> {
+        (self.entry.gadget)(composer, &self.inputs)
+    }
+}
+
+fn sum_xor(
+    composer: &mut StandardComposer<Fr, P>,
+    inputs: &[Fr],
+) -> Result<(), Error> {
+    let a = composer.add_input(inputs[0]);
+    let b = composer.add_input(inputs[1]);
+    let zero = composer.zero_var();
+
+    composer.range_gate(a, 8);
+    composer.range_gate(b, 8);
+    composer.arithmetic_gate(|gate| {
+        gate.witness(a, b, Some(zero))
+            .add(Fr::one(), Fr::one())
+            .pi(-(inputs[0] + inputs[1]))
+    });
+    let xor = composer.xor_gate(a, b, 8);
+    let xor_value = composer.value_of_var(xor);
+    composer.constrain_to_constant(xor, Fr::zero(), Some(-xor_value));
+    Ok(())
+}
+
+fn jubjub_mul(
+    composer: &mut StandardComposer<Fr, P>,
+    inputs: &[Fr],
+) -> Result<(), Error> {
+    let e = composer.add_input(inputs[0]);
+    let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+    let point = composer.fixed_base_scalar_mul(e, GroupAffine::new(x, y));
+    let public_point = GroupAffine::new(
+        composer.value_of_var(*point.x()),
+        composer.value_of_var(*point.y()),
+    );
+    composer.assert_equal_public_point(point, public_point);
+    Ok(())
+}
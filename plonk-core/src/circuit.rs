@@ -7,16 +7,24 @@
 //! Tools & traits for PLONK circuits
 
 use crate::{
-    commitment::HomomorphicCommitment,
+    commitment::{HomomorphicCommitment, PairingAccumulator, KZG10},
+    constraint_system::{Blinding, CircuitIr, GateLayout, Witness},
     error::{to_pc_error, Error},
     prelude::StandardComposer,
     proof_system::{
-        pi::PublicInputs, Proof, Prover, ProverKey, Verifier, VerifierKey,
+        pi::PublicInputs, prover::MAX_BLINDER_DEGREE, CircuitId, CustomGate,
+        Proof, Prover, ProverKey, Verifier, VerifierKey,
     },
+    transcript::TranscriptProtocol,
 };
-use ark_ec::models::TEModelParameters;
+use ark_ec::{models::TEModelParameters, PairingEngine};
 use ark_ff::PrimeField;
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::{PCUniversalParams, PolynomialCommitment};
 use ark_serialize::*;
+use core::marker::PhantomData;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
 
 /// Collection of structs/objects that the Verifier will use in order to
 /// de/serialize data needed for Circuit proof verification.
@@ -149,10 +157,6 @@ where
 ///            composer.assert_equal_public_point(scalar_mul_result, self.f);
 ///            Ok(())
 ///        }
-///
-///        fn padded_circuit_size(&self) -> usize {
-///            1 << 11
-///        }
 ///    }
 ///
 /// // Generate CRS
@@ -163,7 +167,8 @@ where
 ///
 /// let mut circuit = TestCircuit::<BlsScalar, JubJubParameters>::default();
 /// // Compile the circuit
-/// let (pk_p, (vk, _pi_pos)) = circuit.compile::<PC>(&pp)?;
+/// let (pk_p, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+/// let circuit_id = pk_p.circuit_id();
 ///
 /// let (x, y) = JubJubParameters::AFFINE_GENERATOR_COEFFS;
 /// let generator: GroupAffine<JubJubParameters> = GroupAffine::new(x, y);
@@ -182,7 +187,7 @@ where
 ///         e: JubJubScalar::from(2u64),
 ///         f: point_f_pi,
 ///     };
-///     circuit.gen_proof::<PC>(&pp, pk_p, b"Test")
+///     circuit.gen_proof::<PC>(&pp, pk_p, b"Test", &mut OsRng)
 /// }?;
 ///
 /// let verifier_data = VerifierData::new(vk, pi);
@@ -199,6 +204,7 @@ where
 /// verify_proof::<BlsScalar, JubJubParameters, PC>(
 ///     &pp,
 ///     verifier_data.key,
+///     circuit_id,
 ///     &proof,
 ///     &verifier_data.pi,
 ///     b"Test",
@@ -211,8 +217,29 @@ where
     P: TEModelParameters<BaseField = F>,
 {
     /// Circuit identifier associated constant.
+    ///
+    /// This is a label chosen by the implementor and it is not checked
+    /// against the circuit. The [`CircuitId`] derived from the circuit itself
+    /// is stored in the [`ProverKey`] and [`VerifierKey`] returned by
+    /// [`Circuit::compile`], and [`Circuit::gen_proof`] and [`verify_proof`]
+    /// reject keys of other circuits.
+    ///
+    /// [`CircuitId`]: crate::proof_system::CircuitId
     const CIRCUIT_ID: [u8; 32];
 
+    /// Strategy used to make the proofs of the circuit zero-knowledge.
+    ///
+    /// Circuits relying on the layout of earlier releases can set this to
+    /// [`Blinding::DummyGates`].
+    const BLINDING: Blinding = Blinding::Polynomials;
+
+    /// Layout of the keys and proofs of the circuit.
+    ///
+    /// Circuits whose proofs are checked by a verifier expecting every gate
+    /// type, such as the `EvmVerifier` of the `evm` feature, must set this to
+    /// [`GateLayout::Full`]: their keys and proofs are rejected otherwise.
+    const GATE_LAYOUT: GateLayout = GateLayout::Compact;
+
     /// Gadget implementation used to fill the composer.
     fn gadget(
         &mut self,
@@ -222,40 +249,64 @@ where
     /// Compiles the circuit by using a function that returns a `Result`
     /// with the [`ProverKey`], [`VerifierKey`] and a vector of the intended
     /// positions for public inputs and the circuit size.
+    ///
+    /// The gadget is run on a setup-only composer (see
+    /// [`StandardComposer::setup_only`]), so the witness data of `self` is
+    /// only a placeholder.
     #[allow(clippy::type_complexity)]
     fn compile<PC>(
         &mut self,
         u_params: &PC::UniversalParams,
+        rng: &mut (impl CryptoRng + RngCore),
     ) -> Result<(ProverKey<F>, (VerifierKey<F, PC>, Vec<usize>)), Error>
     where
         F: PrimeField,
         PC: HomomorphicCommitment<F>,
     {
+        // Lay out the circuit once, without computing its witnesses, and
+        // derive both keys from a single preprocessing of it.
+        let mut composer =
+            StandardComposer::<F, P>::with_blinding(0, Self::BLINDING, rng)
+                .setup_only();
+        composer.set_gate_layout(Self::GATE_LAYOUT);
+        self.gadget(&mut composer)?;
+        pad_circuit(self.padded_circuit_size(), &mut composer)?;
+
         // Setup PublicParams
-        let circuit_size = self.padded_circuit_size();
-        let (ck, _) = PC::trim(u_params, circuit_size, 0, None)
-            .map_err(to_pc_error::<F, PC>)?;
+        let (ck, _) = trim_params::<F, PC>(u_params, composer.circuit_bound())?;
+        let (prover_key, verifier_key) =
+            composer.preprocess(&ck, PhantomData::<PC>)?;
+        Ok((prover_key, (verifier_key, composer.intended_pi_pos)))
+    }
 
-        //Generate & save `ProverKey` with some random values.
-        let mut prover = Prover::<F, P, PC>::new(b"CircuitCompilation");
-        self.gadget(prover.mut_cs())?;
-        prover.preprocess(&ck)?;
-
-        // Generate & save `VerifierKey` with some random values.
-        let mut verifier = Verifier::new(b"CircuitCompilation");
-        self.gadget(verifier.mut_cs())?;
-        verifier.preprocess(&ck)?;
-        Ok((
-            prover
-                .prover_key
-                .expect("Unexpected error. Missing ProverKey in compilation"),
-            (
-                verifier.verifier_key.expect(
-                    "Unexpected error. Missing VerifierKey in compilation",
-                ),
-                verifier.cs.intended_pi_pos,
-            ),
-        ))
+    /// Lays out the circuit like [`Circuit::compile`] and returns its
+    /// [`CircuitIr`], from which [`compile_ir`] computes the same keys.
+    fn gen_ir(
+        &mut self,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> Result<CircuitIr<F>, Error> {
+        let mut composer =
+            StandardComposer::<F, P>::with_blinding(0, Self::BLINDING, rng)
+                .setup_only();
+        composer.set_gate_layout(Self::GATE_LAYOUT);
+        self.gadget(&mut composer)?;
+        pad_circuit(self.padded_circuit_size(), &mut composer)?;
+        Ok(composer.export_ir())
+    }
+
+    /// Runs the gadget and returns the resulting [`Witness`], which can be
+    /// proven in another process with [`Prover::with_witness`].
+    ///
+    /// See [`StandardComposer::with_blinding`] for the use of `rng`.
+    fn gen_witness(
+        &mut self,
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> Result<Witness<F>, Error> {
+        let mut composer =
+            StandardComposer::<F, P>::with_blinding(0, Self::BLINDING, rng);
+        self.gadget(&mut composer)?;
+        pad_circuit(self.padded_circuit_size(), &mut composer)?;
+        composer.export_witness()
     }
 
     /// Generates a proof using the provided [`ProverKey`] and
@@ -266,35 +317,203 @@ where
         u_params: &PC::UniversalParams,
         prover_key: ProverKey<F>,
         transcript_init: &'static [u8],
+        rng: &mut (impl CryptoRng + RngCore),
     ) -> Result<(Proof<F, PC>, PublicInputs<F>), Error>
     where
         F: PrimeField,
         P: TEModelParameters<BaseField = F>,
         PC: HomomorphicCommitment<F>,
     {
-        let circuit_size = self.padded_circuit_size();
-        let (ck, _) = PC::trim(u_params, circuit_size, 0, None)
-            .map_err(to_pc_error::<F, PC>)?;
+        self.gen_proof_with_transcript::<PC, Transcript>(
+            u_params,
+            prover_key,
+            transcript_init,
+            rng,
+        )
+    }
+
+    /// Generates a proof like [`Circuit::gen_proof`], using `T` as the
+    /// transcript backend instead of the default [`Transcript`].
+    fn gen_proof_with_transcript<PC, T>(
+        &mut self,
+        u_params: &PC::UniversalParams,
+        prover_key: ProverKey<F>,
+        transcript_init: &'static [u8],
+        rng: &mut (impl CryptoRng + RngCore),
+    ) -> Result<(Proof<F, PC>, PublicInputs<F>), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+        T: TranscriptProtocol,
+    {
+        let (ck, _) =
+            trim_params::<F, PC>(u_params, prover_key.padded_circuit_size())?;
         // New Prover instance
-        let mut prover = Prover::new(transcript_init);
+        let mut prover = Prover::<F, P, PC, T>::with_blinding(
+            transcript_init,
+            Self::BLINDING,
+            rng,
+        );
         // Fill witnesses for Prover
         self.gadget(prover.mut_cs())?;
+        pad_circuit(self.padded_circuit_size(), prover.mut_cs())?;
         // Add ProverKey to Prover
         prover.prover_key = Some(prover_key);
         let pi = prover.cs.get_pi().clone();
 
-        Ok((prover.prove(&ck)?, pi))
+        Ok((prover.prove(&ck, rng)?, pi))
+    }
+
+    /// Returns the size the circuit is padded to.
+    ///
+    /// By default the circuit is not padded, and its size is the one of the
+    /// circuit laid out by the gadget, see
+    /// [`StandardComposer::circuit_bound`], which is stored in the keys and
+    /// used to trim the public parameters when proving and verifying.
+    /// Circuits can override it with a larger size, but not with a smaller
+    /// one: the gadget is then padded with [`StandardComposer::pad_to`]
+    /// before preprocessing and proving, so the keys are of the next power of
+    /// two.
+    fn padded_circuit_size(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Pads the circuit laid out in `composer` to the size `declared` by the
+/// circuit, if any, failing if the circuit needs more than that.
+fn pad_circuit<F, P>(
+    declared: Option<usize>,
+    composer: &mut StandardComposer<F, P>,
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    if let Some(declared) = declared {
+        let required = composer.circuit_bound();
+        if declared < required {
+            return Err(Error::CircuitSizeTooSmall { declared, required });
+        }
+        composer.pad_to(declared);
+    }
+    Ok(())
+}
+
+/// Trims the universal parameters to prove and verify circuits of
+/// `circuit_size`, failing with [`Error::SRSTooSmall`] if they do not support
+/// the degree this requires.
+pub(crate) fn trim_params<F, PC>(
+    u_params: &PC::UniversalParams,
+    circuit_size: usize,
+) -> Result<(PC::CommitterKey, PC::VerifierKey), Error>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    let required = circuit_size + MAX_BLINDER_DEGREE;
+    let supported = u_params.max_degree();
+    if required > supported {
+        return Err(Error::SRSTooSmall {
+            required,
+            supported,
+        });
     }
+    PC::trim(u_params, required, 0, None).map_err(to_pc_error::<F, PC>)
+}
 
-    /// Returns the Circuit size padded to the next power of two.
-    fn padded_circuit_size(&self) -> usize;
+/// Compiles the circuit described by `ir` like [`Circuit::compile`], without
+/// running its gadget.
+///
+/// The custom gates of the circuit have to be passed in `custom_gates`, see
+/// [`StandardComposer::from_ir`].
+#[allow(clippy::type_complexity)]
+pub fn compile_ir<F, P, PC>(
+    u_params: &PC::UniversalParams,
+    ir: &CircuitIr<F>,
+    custom_gates: &[CustomGate<F>],
+) -> Result<(ProverKey<F>, (VerifierKey<F, PC>, Vec<usize>)), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+{
+    let mut composer = StandardComposer::<F, P>::from_ir(ir, custom_gates)?;
+    let (ck, _) = trim_params::<F, PC>(u_params, composer.circuit_bound())?;
+    let (prover_key, verifier_key) =
+        composer.preprocess(&ck, PhantomData::<PC>)?;
+    Ok((prover_key, (verifier_key, composer.intended_pi_pos)))
+}
+
+/// Generates a proof of `witness` for the circuit described by `ir`, using
+/// the provided [`ProverKey`] of the circuit. Returns a [`Proof`] and the
+/// [`PublicInputs`] of the witness.
+///
+/// See [`Prover::with_witness`].
+pub fn gen_proof_from_witness<F, P, PC>(
+    u_params: &PC::UniversalParams,
+    ir: &CircuitIr<F>,
+    custom_gates: &[CustomGate<F>],
+    prover_key: ProverKey<F>,
+    witness: &Witness<F>,
+    transcript_init: &'static [u8],
+    rng: &mut (impl CryptoRng + RngCore),
+) -> Result<(Proof<F, PC>, PublicInputs<F>), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+{
+    let (ck, _) =
+        trim_params::<F, PC>(u_params, prover_key.padded_circuit_size())?;
+    let mut prover = Prover::<F, P, PC>::with_witness(
+        transcript_init,
+        ir,
+        custom_gates,
+        witness,
+    )?;
+    prover.prover_key = Some(prover_key);
+    Ok((prover.prove(&ck, rng)?, witness.public_inputs.clone()))
+}
+
+/// Returns a verifier of the circuit with `plonk_verifier_key`, together with
+/// the verifier key of the commitment scheme, failing with
+/// [`Error::CircuitIdMismatch`] if `plonk_verifier_key` is not a key of the
+/// circuit identified by `circuit_id`.
+#[allow(clippy::type_complexity)]
+fn verifier_of<F, P, PC>(
+    u_params: &PC::UniversalParams,
+    plonk_verifier_key: VerifierKey<F, PC>,
+    circuit_id: CircuitId,
+    transcript_init: &'static [u8],
+) -> Result<(Verifier<F, P, PC>, PC::VerifierKey), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+{
+    if plonk_verifier_key.circuit_id() != circuit_id {
+        return Err(Error::CircuitIdMismatch);
+    }
+    let mut verifier: Verifier<F, P, PC> = Verifier::new(transcript_init);
+    let padded_circuit_size = plonk_verifier_key.padded_circuit_size();
+    verifier.verifier_key = Some(plonk_verifier_key);
+    let (_, vk) = trim_params::<F, PC>(u_params, padded_circuit_size)?;
+    Ok((verifier, vk))
 }
 
 /// Verifies a proof using the provided `CircuitInputs` & `VerifierKey`
 /// instances.
+///
+/// The key must be the one of the circuit identified by `circuit_id`, such as
+/// the [`CircuitId`] recorded when the circuit was compiled, otherwise this
+/// fails with [`Error::CircuitIdMismatch`]. The custom gates of the circuit
+/// are taken from the key, see [`VerifierKey::attach_custom_gates`] for keys
+/// which were deserialized.
 pub fn verify_proof<F, P, PC>(
     u_params: &PC::UniversalParams,
     plonk_verifier_key: VerifierKey<F, PC>,
+    circuit_id: CircuitId,
     proof: &Proof<F, PC>,
     public_inputs: &PublicInputs<F>,
     transcript_init: &'static [u8],
@@ -304,19 +523,78 @@ where
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
 {
-    let mut verifier: Verifier<F, P, PC> = Verifier::new(transcript_init);
-    let padded_circuit_size = plonk_verifier_key.padded_circuit_size();
-    verifier.verifier_key = Some(plonk_verifier_key);
-    let (_, vk) = PC::trim(u_params, padded_circuit_size, 0, None)
-        .map_err(to_pc_error::<F, PC>)?;
-
+    let (mut verifier, vk) = verifier_of::<F, P, PC>(
+        u_params,
+        plonk_verifier_key,
+        circuit_id,
+        transcript_init,
+    )?;
     verifier.verify(proof, &vk, public_inputs)
 }
 
+/// Verifies a batch of proofs for the same circuit, each one together with
+/// its `PublicInputs`, using a single check of the commitment scheme.
+///
+/// The key is checked against `circuit_id` like in [`verify_proof`]. See
+/// [`Verifier::verify_batch`] for details.
+pub fn verify_proofs_batch<F, P, PC>(
+    u_params: &PC::UniversalParams,
+    plonk_verifier_key: VerifierKey<F, PC>,
+    circuit_id: CircuitId,
+    proofs: &[(Proof<F, PC>, PublicInputs<F>)],
+    transcript_init: &'static [u8],
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+    PC: HomomorphicCommitment<F>,
+{
+    let (mut verifier, vk) = verifier_of::<F, P, PC>(
+        u_params,
+        plonk_verifier_key,
+        circuit_id,
+        transcript_init,
+    )?;
+    verifier.verify_batch(proofs, &vk)
+}
+
+/// Verifies a proof up to the final pairing check and returns the
+/// [`PairingAccumulator`] it has to be performed on.
+///
+/// The key is checked against `circuit_id` like in [`verify_proof`]. See
+/// [`Verifier::verify_deferred`] for details.
+pub fn verify_proof_deferred<E, P>(
+    u_params: &<KZG10<E> as PolynomialCommitment<
+        E::Fr,
+        DensePolynomial<E::Fr>,
+    >>::UniversalParams,
+    plonk_verifier_key: VerifierKey<E::Fr, KZG10<E>>,
+    circuit_id: CircuitId,
+    proof: &Proof<E::Fr, KZG10<E>>,
+    public_inputs: &PublicInputs<E::Fr>,
+    transcript_init: &'static [u8],
+) -> Result<PairingAccumulator<E>, Error>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+{
+    let (mut verifier, vk) = verifier_of::<E::Fr, P, KZG10<E>>(
+        u_params,
+        plonk_verifier_key,
+        circuit_id,
+        transcript_init,
+    )?;
+    verifier.verify_deferred(proof, &vk, public_inputs)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{constraint_system::StandardComposer, util};
+    use crate::{
+        constraint_system::StandardComposer,
+        proof_system::{ActiveGates, CircuitId},
+        util,
+    };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
     use ark_ec::{
@@ -383,10 +661,6 @@ mod test {
             composer.assert_equal_public_point(scalar_mul_result, self.f);
             Ok(())
         }
-
-        fn padded_circuit_size(&self) -> usize {
-            1 << 9
-        }
     }
 
     fn test_full<F, P, PC>() -> Result<(), Error>
@@ -403,7 +677,8 @@ mod test {
         let mut circuit = TestCircuit::<F, P>::default();
 
         // Compile the circuit
-        let (pk, (vk, _pi_pos)) = circuit.compile::<PC>(&pp)?;
+        let (pk, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+        let circuit_id = vk.circuit_id();
 
         let (x, y) = P::AFFINE_GENERATOR_COEFFS;
         let generator: GroupAffine<P> = GroupAffine::new(x, y);
@@ -433,7 +708,7 @@ mod test {
                 }
             }
 
-            circuit.gen_proof::<PC>(&pp, pk, b"Test")?
+            circuit.gen_proof::<PC>(&pp, pk, b"Test", &mut OsRng)?
         };
 
         let verifier_data = VerifierData::new(vk, pi);
@@ -453,6 +728,7 @@ mod test {
         assert!(verify_proof::<F, P, PC>(
             &pp,
             verifier_data.key,
+            circuit_id,
             &proof,
             &verifier_data.pi,
             b"Test",
@@ -462,6 +738,608 @@ mod test {
         Ok(())
     }
 
+    fn test_circuit_binding<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Generate CRS
+        let pp = PC::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+
+        let mut circuit = TestCircuit::<F, P>::default();
+
+        // Compile the circuit
+        let (pk, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+        assert_eq!(pk.circuit_id(), vk.circuit_id());
+        assert_eq!(pk.circuit_digest(), vk.circuit_digest());
+        let circuit_id = vk.circuit_id();
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        let mut circuit: TestCircuit<F, P> = TestCircuit {
+            a: F::from(20u64),
+            b: F::from(5u64),
+            c: F::from(25u64),
+            d: F::from(100u64),
+            e: P::ScalarField::from(2u64),
+            f: AffineCurve::mul(
+                &generator,
+                P::ScalarField::from(2u64).into_repr(),
+            )
+            .into_affine(),
+        };
+
+        // A key of another circuit is rejected by the prover.
+        let mut other_pk = pk.clone();
+        other_pk.circuit_id = CircuitId([0; 32]);
+        assert!(matches!(
+            circuit.gen_proof::<PC>(&pp, other_pk, b"Test", &mut OsRng),
+            Err(Error::CircuitIdMismatch)
+        ));
+
+        let (proof, pi) =
+            circuit.gen_proof::<PC>(&pp, pk, b"Test", &mut OsRng)?;
+        assert!(verify_proof::<F, P, PC>(
+            &pp,
+            vk.clone(),
+            circuit_id,
+            &proof,
+            &pi,
+            b"Test"
+        )
+        .is_ok());
+
+        // The verifier rejects a key of another circuit than the expected
+        // one, and a key claiming another identifier does not verify the
+        // proof even when that identifier is expected.
+        let mut other_vk = vk.clone();
+        other_vk.circuit_id = CircuitId([0; 32]);
+        assert!(matches!(
+            verify_proof::<F, P, PC>(
+                &pp,
+                other_vk.clone(),
+                circuit_id,
+                &proof,
+                &pi,
+                b"Test"
+            ),
+            Err(Error::CircuitIdMismatch)
+        ));
+        assert!(matches!(
+            verify_proof::<F, P, PC>(
+                &pp,
+                vk.clone(),
+                other_vk.circuit_id(),
+                &proof,
+                &pi,
+                b"Test"
+            ),
+            Err(Error::CircuitIdMismatch)
+        ));
+        assert!(verify_proof::<F, P, PC>(
+            &pp,
+            other_vk.clone(),
+            other_vk.circuit_id(),
+            &proof,
+            &pi,
+            b"Test"
+        )
+        .is_err());
+
+        // A circuit sharing every commitment but reserving an extra public
+        // input position must reject the proof.
+        let unused_pos = vk.pi_pos().iter().max().unwrap() + 1;
+        let mut other_vk = vk.clone();
+        other_vk.pi_pos.push(unused_pos);
+        assert_ne!(vk.circuit_digest(), other_vk.circuit_digest());
+        assert!(verify_proof::<F, P, PC>(
+            &pp, other_vk, circuit_id, &proof, &pi, b"Test"
+        )
+        .is_err());
+
+        // Public inputs outside of the layout of the circuit are rejected.
+        let mut wrong_pi = pi;
+        wrong_pi.add_input(unused_pos, &F::one())?;
+        assert!(matches!(
+            verify_proof::<F, P, PC>(
+                &pp,
+                vk,
+                circuit_id,
+                &proof,
+                &wrong_pi,
+                b"Test"
+            ),
+            Err(Error::InvalidPublicInputPosition { position })
+                if position == unused_pos
+        ));
+
+        Ok(())
+    }
+
+    // Test circuit declaring a padded size.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct PaddedCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        circuit: TestCircuit<F, P>,
+        size: usize,
+    }
+
+    impl<F, P> Circuit<F, P> for PaddedCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            self.circuit.gadget(composer)
+        }
+
+        fn padded_circuit_size(&self) -> Option<usize> {
+            Some(self.size)
+        }
+    }
+
+    fn test_padded_circuit_size<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Generate CRS
+        let pp = PC::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+
+        // The size is computed from the circuit and stored in the keys.
+        let (pk, (vk, _pi_pos)) =
+            TestCircuit::<F, P>::default().compile::<PC>(&pp, &mut OsRng)?;
+        let size = vk.padded_circuit_size();
+        assert_eq!(pk.padded_circuit_size(), size);
+        assert!(size.is_power_of_two());
+
+        // Public parameters which are too small are reported.
+        let small_pp =
+            PC::setup(size, None, &mut OsRng).map_err(to_pc_error::<F, PC>)?;
+        assert!(matches!(
+            TestCircuit::<F, P>::default().compile::<PC>(&small_pp, &mut OsRng),
+            Err(Error::SRSTooSmall { required, supported })
+                if required == size + MAX_BLINDER_DEGREE && supported == size
+        ));
+
+        // A declared size can be larger than the circuit, but not smaller.
+        let mut circuit = PaddedCircuit::<F, P> {
+            size: 2 * size,
+            ..Default::default()
+        };
+        let (pk, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+        let circuit_id = vk.circuit_id();
+        assert_eq!(pk.padded_circuit_size(), 2 * size);
+        assert_eq!(vk.padded_circuit_size(), 2 * size);
+
+        // The prover pads the circuit the same way.
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        circuit.circuit = TestCircuit {
+            a: F::from(20u64),
+            b: F::from(5u64),
+            c: F::from(25u64),
+            d: F::from(100u64),
+            e: P::ScalarField::from(2u64),
+            f: AffineCurve::mul(
+                &generator,
+                P::ScalarField::from(2u64).into_repr(),
+            )
+            .into_affine(),
+        };
+        let (proof, pi) =
+            circuit.gen_proof::<PC>(&pp, pk, b"Test", &mut OsRng)?;
+        verify_proof::<F, P, PC>(&pp, vk, circuit_id, &proof, &pi, b"Test")?;
+
+        circuit.size = size / 2;
+        assert!(matches!(
+            circuit.compile::<PC>(&pp, &mut OsRng),
+            Err(Error::CircuitSizeTooSmall { declared, required })
+                if declared == size / 2 && required == size
+        ));
+
+        Ok(())
+    }
+
+    // Test circuit keeping the terms of every gate type.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    pub struct FullLayoutCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        circuit: TestCircuit<F, P>,
+    }
+
+    impl<F, P> Circuit<F, P> for FullLayoutCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+        const GATE_LAYOUT: GateLayout = GateLayout::Full;
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            self.circuit.gadget(composer)
+        }
+    }
+
+    fn test_gate_layout<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Generate CRS
+        let pp = PC::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+
+        let (pk, (vk, _pi_pos)) =
+            TestCircuit::<F, P>::default().compile::<PC>(&pp, &mut OsRng)?;
+        let (full_pk, (full_vk, _pi_pos)) =
+            FullLayoutCircuit::<F, P>::default()
+                .compile::<PC>(&pp, &mut OsRng)?;
+
+        // The test circuit only uses range and fixed base gates.
+        let active_gates = ActiveGates {
+            range: true,
+            logic: false,
+            fixed_base: true,
+            variable_base: false,
+            lookup: false,
+        };
+        assert_eq!(pk.active_gates(), active_gates);
+        assert_eq!(vk.active_gates(), active_gates);
+        assert_eq!(full_pk.active_gates(), ActiveGates::ALL);
+        assert_eq!(full_vk.active_gates(), ActiveGates::ALL);
+        assert_eq!(vk.circuit_id(), full_vk.circuit_id());
+        let circuit_id = vk.circuit_id();
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        let circuit = || TestCircuit::<F, P> {
+            a: F::from(20u64),
+            b: F::from(5u64),
+            c: F::from(25u64),
+            d: F::from(100u64),
+            e: P::ScalarField::from(2u64),
+            f: AffineCurve::mul(
+                &generator,
+                P::ScalarField::from(2u64).into_repr(),
+            )
+            .into_affine(),
+        };
+
+        // Proofs of the compact layout leave out the lookup argument.
+        let (proof, pi) =
+            circuit().gen_proof::<PC>(&pp, pk, b"Test", &mut OsRng)?;
+        assert!(proof.f_comm.is_none() && proof.z_2_comm.is_none());
+        assert!(proof.evaluations.lookup_evals.is_none());
+        verify_proof::<F, P, PC>(
+            &pp,
+            vk.clone(),
+            circuit_id,
+            &proof,
+            &pi,
+            b"Test",
+        )?;
+
+        let (full_proof, full_pi) =
+            FullLayoutCircuit { circuit: circuit() }
+                .gen_proof::<PC>(&pp, full_pk, b"Test", &mut OsRng)?;
+        assert!(full_proof.f_comm.is_some());
+        verify_proof::<F, P, PC>(
+            &pp,
+            full_vk.clone(),
+            circuit_id,
+            &full_proof,
+            &full_pi,
+            b"Test",
+        )?;
+
+        // A proof is only verified against a key of the same layout.
+        assert!(matches!(
+            verify_proof::<F, P, PC>(
+                &pp,
+                vk,
+                circuit_id,
+                &full_proof,
+                &full_pi,
+                b"Test"
+            ),
+            Err(Error::ProofLayoutMismatch)
+        ));
+        assert!(matches!(
+            verify_proof::<F, P, PC>(
+                &pp, full_vk, circuit_id, &proof, &pi, b"Test"
+            ),
+            Err(Error::ProofLayoutMismatch)
+        ));
+
+        Ok(())
+    }
+
+    /// Tests that compiling the IR of a circuit gives the keys of the circuit
+    /// compiled directly, for both gate layouts.
+    fn test_compile_ir<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let pp = PC::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+        let key_bytes = |(pk, (vk, pi_pos)): (
+            ProverKey<F>,
+            (VerifierKey<F, PC>, Vec<usize>),
+        )| {
+            let (mut pk_bytes, mut vk_bytes) = (Vec::new(), Vec::new());
+            pk.serialize(&mut pk_bytes).unwrap();
+            vk.serialize(&mut vk_bytes).unwrap();
+            (pk_bytes, vk_bytes, pi_pos)
+        };
+
+        let compact = (
+            TestCircuit::<F, P>::default().compile::<PC>(&pp, &mut OsRng)?,
+            TestCircuit::<F, P>::default().gen_ir(&mut OsRng)?,
+        );
+        let full = (
+            FullLayoutCircuit::<F, P>::default()
+                .compile::<PC>(&pp, &mut OsRng)?,
+            FullLayoutCircuit::<F, P>::default().gen_ir(&mut OsRng)?,
+        );
+        assert_eq!(compact.1.gate_layout, GateLayout::Compact);
+        assert_eq!(full.1.gate_layout, GateLayout::Full);
+        assert_ne!(key_bytes(compact.0.clone()), key_bytes(full.0.clone()));
+
+        for (keys, ir) in [compact, full] {
+            let mut ir_bytes = Vec::new();
+            ir.serialize(&mut ir_bytes).unwrap();
+            let ir = CircuitIr::<F>::deserialize(&ir_bytes[..]).unwrap();
+            assert_eq!(
+                key_bytes(keys),
+                key_bytes(compile_ir::<F, P, PC>(&pp, &ir, &[])?)
+            );
+        }
+        Ok(())
+    }
+
+    /// Tests proving a witness generated in another process from the IR of
+    /// the circuit and its compiled prover key.
+    fn test_offline_witness<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let pp = PC::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+        let (pk, (vk, _)) =
+            TestCircuit::<F, P>::default().compile::<PC>(&pp, &mut OsRng)?;
+        let circuit_id = vk.circuit_id();
+
+        let mut composer = StandardComposer::<F, P>::new().setup_only();
+        TestCircuit::<F, P>::default().gadget(&mut composer)?;
+        let ir = TestCircuit::<F, P>::default().gen_ir(&mut OsRng)?;
+        assert_eq!(ir, composer.export_ir());
+        assert!(matches!(
+            composer.export_witness(),
+            Err(Error::InvalidWitness)
+        ));
+
+        // Witness generation POV
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+        let mut circuit = TestCircuit::<F, P> {
+            a: F::from(20u64),
+            b: F::from(5u64),
+            c: F::from(25u64),
+            d: F::from(100u64),
+            e: P::ScalarField::from(2u64),
+            f: AffineCurve::mul(
+                &generator,
+                P::ScalarField::from(2u64).into_repr(),
+            )
+            .into_affine(),
+        };
+        let mut witness_bytes = Vec::new();
+        circuit
+            .gen_witness(&mut OsRng)?
+            .serialize(&mut witness_bytes)
+            .unwrap();
+
+        // Prover POV
+        let witness = Witness::<F>::deserialize(&witness_bytes[..]).unwrap();
+        let mut prover =
+            Prover::<F, P, PC>::with_witness(b"Test", &ir, &[], &witness)?;
+        prover.prover_key = Some(pk);
+        let (ck, _) = trim_params::<F, PC>(&pp, prover.circuit_bound())?;
+        let proof = prover.prove(&ck, &mut OsRng)?;
+
+        verify_proof::<F, P, PC>(
+            &pp,
+            vk,
+            circuit_id,
+            &proof,
+            &witness.public_inputs,
+            b"Test",
+        )?;
+
+        // Witnesses only fit the circuit they were generated for.
+        assert!(matches!(
+            StandardComposer::<F, P>::new().assign_witness(&witness),
+            Err(Error::InvalidWitness)
+        ));
+        Ok(())
+    }
+
+    fn test_batch<F, P, PC>() -> Result<(), Error>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        // Generate CRS
+        let pp = PC::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)?;
+
+        let mut circuit = TestCircuit::<F, P>::default();
+
+        // Compile the circuit
+        let (pk, (vk, _pi_pos)) = circuit.compile::<PC>(&pp, &mut OsRng)?;
+        let circuit_id = vk.circuit_id();
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+
+        // Prover POV
+        let mut proofs = [(20u64, 5u64, 2u64), (3, 7, 5), (40, 31, 11)]
+            .iter()
+            .map(|&(a, b, e)| {
+                let mut circuit: TestCircuit<F, P> = TestCircuit {
+                    a: F::from(a),
+                    b: F::from(b),
+                    c: F::from(a + b),
+                    d: F::from(a * b),
+                    e: P::ScalarField::from(e),
+                    f: AffineCurve::mul(
+                        &generator,
+                        P::ScalarField::from(e).into_repr(),
+                    )
+                    .into_affine(),
+                };
+                circuit.gen_proof::<PC>(&pp, pk.clone(), b"Test", &mut OsRng)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Verifier POV
+        assert!(verify_proofs_batch::<F, P, PC>(
+            &pp,
+            vk.clone(),
+            circuit_id,
+            &proofs,
+            b"Test"
+        )
+        .is_ok());
+
+        // Swapping the public inputs of two proofs must break the batch.
+        let pi = proofs[0].1.clone();
+        proofs[0].1 = proofs[1].1.clone();
+        proofs[1].1 = pi;
+        assert!(verify_proofs_batch::<F, P, PC>(
+            &pp,
+            vk.clone(),
+            circuit_id,
+            &proofs,
+            b"Test"
+        )
+        .is_err());
+
+        // An empty batch is not a successful verification.
+        assert!(matches!(
+            verify_proofs_batch::<F, P, PC>(&pp, vk, circuit_id, &[], b"Test"),
+            Err(Error::EmptyBatch)
+        ));
+
+        Ok(())
+    }
+
+    fn test_deferred<E, P>() -> Result<(), Error>
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+    {
+        type PC<E> = crate::commitment::KZG10<E>;
+
+        // Generate CRS
+        let pp = PC::<E>::setup(1 << 10, None, &mut OsRng)
+            .map_err(to_pc_error::<E::Fr, PC<E>>)?;
+        let (_, pc_vk) = PC::<E>::trim(&pp, 1 << 9, 0, None)
+            .map_err(to_pc_error::<E::Fr, PC<E>>)?;
+
+        let mut circuit = TestCircuit::<E::Fr, P>::default();
+
+        // Compile the circuit
+        let (pk, (vk, _pi_pos)) = circuit.compile::<PC<E>>(&pp, &mut OsRng)?;
+        let circuit_id = vk.circuit_id();
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let generator: GroupAffine<P> = GroupAffine::new(x, y);
+
+        // Prover POV
+        let proofs = [(20u64, 5u64, 2u64), (3, 7, 5)]
+            .iter()
+            .map(|&(a, b, e)| {
+                let mut circuit: TestCircuit<E::Fr, P> = TestCircuit {
+                    a: E::Fr::from(a),
+                    b: E::Fr::from(b),
+                    c: E::Fr::from(a + b),
+                    d: E::Fr::from(a * b),
+                    e: P::ScalarField::from(e),
+                    f: AffineCurve::mul(
+                        &generator,
+                        P::ScalarField::from(e).into_repr(),
+                    )
+                    .into_affine(),
+                };
+                circuit.gen_proof::<PC<E>>(&pp, pk.clone(), b"Test", &mut OsRng)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        // Verifier POV
+        let accumulators = proofs
+            .iter()
+            .map(|(proof, pi)| {
+                verify_proof_deferred::<E, P>(
+                    &pp,
+                    vk.clone(),
+                    circuit_id,
+                    proof,
+                    pi,
+                    b"Test",
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        assert!(accumulators.iter().all(|acc| acc.check(&pc_vk)));
+
+        let combined =
+            accumulators[0].combine(&accumulators[1], E::Fr::from(7u64));
+        assert!(combined.check(&pc_vk));
+
+        // An accumulator for the wrong public inputs must not pass.
+        let wrong = verify_proof_deferred::<E, P>(
+            &pp,
+            vk,
+            circuit_id,
+            &proofs[0].0,
+            &proofs[1].1,
+            b"Test",
+        )?;
+        assert!(!wrong.check(&pc_vk));
+        assert!(!accumulators[0]
+            .combine(&wrong, E::Fr::from(7u64))
+            .check(&pc_vk));
+
+        Ok(())
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn test_full_on_Bls12_381() -> Result<(), Error> {
@@ -506,4 +1384,148 @@ mod test {
             >,
         >()
     }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_circuit_binding_on_Bls12_381() -> Result<(), Error> {
+        test_circuit_binding::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_circuit_binding_on_Bls12_381_ipa() -> Result<(), Error> {
+        test_circuit_binding::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_381 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_padded_circuit_size_on_Bls12_381() -> Result<(), Error> {
+        test_padded_circuit_size::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_padded_circuit_size_on_Bls12_381_ipa() -> Result<(), Error> {
+        test_padded_circuit_size::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_381 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_batch_on_Bls12_381() -> Result<(), Error> {
+        test_batch::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_batch_on_Bls12_381_ipa() -> Result<(), Error> {
+        test_batch::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_381 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_offline_witness_on_Bls12_381() -> Result<(), Error> {
+        test_offline_witness::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_offline_witness_on_Bls12_381_ipa() -> Result<(), Error> {
+        test_offline_witness::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_381 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_gate_layout_on_Bls12_381() -> Result<(), Error> {
+        test_gate_layout::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_gate_layout_on_Bls12_381_ipa() -> Result<(), Error> {
+        test_gate_layout::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_381 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compile_ir_on_Bls12_381() -> Result<(), Error> {
+        test_compile_ir::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::KZG10<Bls12_381>,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_compile_ir_on_Bls12_381_ipa() -> Result<(), Error> {
+        test_compile_ir::<
+            <Bls12_381 as PairingEngine>::Fr,
+            ark_ed_on_bls12_381::EdwardsParameters,
+            crate::commitment::IPA<
+                <Bls12_381 as PairingEngine>::G1Affine,
+                blake2::Blake2b,
+            >,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_deferred_on_Bls12_381() -> Result<(), Error> {
+        test_deferred::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>()
+    }
 }
@@ -1,8 +1,15 @@
 //! Useful commitment stuff
 use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine};
-use ark_ff::{Field, PrimeField};
+use ark_ff::{Field, One, PrimeField, Zero};
 use ark_poly::univariate::DensePolynomial;
-use ark_poly_commit::{sonic_pc::SonicKZG10, PolynomialCommitment};
+use ark_poly_commit::{
+    kzg10,
+    sonic_pc::{SonicKZG10, VerifierKey as SonicVerifierKey},
+    LabeledCommitment, PolynomialCommitment,
+};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
 
 /// A homomorphic polynomial commitment
 pub trait HomomorphicCommitment<F>:
@@ -11,11 +18,103 @@ where
     F: PrimeField,
     Self::VerifierKey: core::fmt::Debug,
 {
+    /// Returns the name of the scheme and of its parameters, which tags the
+    /// keys and proofs committing with it in serialized containers.
+    ///
+    /// See [`Containable`](crate::container::Containable).
+    fn name() -> String;
+
+    /// Curve the commitments are points of.
+    type Point: AffineCurve;
+
+    /// Returns the point `commitment` consists of.
+    fn commitment_point(commitment: &Self::Commitment) -> Self::Point;
+
     /// Combine a linear combination of homomorphic commitments
     fn multi_scalar_mul(
         commitments: &[Self::Commitment],
         scalars: &[F],
     ) -> Self::Commitment;
+
+    /// Checks all of the `claims` at once, weighting the `i`-th claim with
+    /// `randomizer^i`.
+    ///
+    /// The default implementation checks every claim on its own. Schemes which
+    /// can fold several openings into a single check should override it.
+    fn batch_check_openings(
+        verifier_key: &Self::VerifierKey,
+        claims: &[OpeningClaim<F, Self>],
+        _randomizer: F,
+    ) -> Result<bool, Self::Error> {
+        for claim in claims {
+            if !claim.check(verifier_key)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Claim that a set of committed polynomials evaluate to `evals` at `point`,
+/// together with the batch opening proof that backs it.
+#[derive(derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Debug(
+        bound = "PC::Commitment: core::fmt::Debug, PC::Proof: core::fmt::Debug"
+    )
+)]
+pub struct OpeningClaim<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    /// Commitments to the opened polynomials.
+    pub commitments: Vec<LabeledCommitment<PC::Commitment>>,
+
+    /// Evaluation point.
+    pub point: F,
+
+    /// Claimed evaluations of the polynomials at `point`.
+    pub evals: Vec<F>,
+
+    /// Batch opening proof.
+    pub proof: PC::Proof,
+
+    /// Challenge used to aggregate the polynomials in the opening proof.
+    pub challenge: F,
+}
+
+impl<F, PC> OpeningClaim<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    /// Checks the claim against the commitment scheme `verifier_key`.
+    pub fn check(
+        &self,
+        verifier_key: &PC::VerifierKey,
+    ) -> Result<bool, PC::Error> {
+        PC::check(
+            verifier_key,
+            &self.commitments,
+            &self.point,
+            self.evals.iter().copied(),
+            &self.proof,
+            self.challenge,
+            None,
+        )
+    }
+
+    /// Returns the commitment and the evaluation that the claim aggregates to.
+    pub fn aggregate(&self) -> (PC::Commitment, F) {
+        let commitments = self
+            .commitments
+            .iter()
+            .map(|commitment| commitment.commitment().clone())
+            .collect::<Vec<_>>();
+        linear_combination::<F, PC>(&self.evals, &commitments, self.challenge)
+    }
 }
 
 /// The Default KZG-style commitment scheme
@@ -30,6 +129,16 @@ impl<E> HomomorphicCommitment<E::Fr> for KZG10<E>
 where
     E: PairingEngine,
 {
+    fn name() -> String {
+        "kzg10".to_string()
+    }
+
+    type Point = E::G1Affine;
+
+    fn commitment_point(commitment: &KZG10Commitment<E>) -> E::G1Affine {
+        commitment.0
+    }
+
     fn multi_scalar_mul(
         commitments: &[KZG10Commitment<E>],
         scalars: &[E::Fr],
@@ -46,6 +155,131 @@ where
                 .into(),
         )
     }
+
+    /// Folds every claim into a single [`PairingAccumulator`], which is
+    /// checked with one multi-pairing.
+    fn batch_check_openings(
+        verifier_key: &Self::VerifierKey,
+        claims: &[OpeningClaim<E::Fr, Self>],
+        randomizer: E::Fr,
+    ) -> Result<bool, Self::Error> {
+        Ok(
+            PairingAccumulator::from_claims(verifier_key, claims, randomizer)
+                .check(verifier_key),
+        )
+    }
+}
+
+/// Pairing accumulator for [`KZG10`] opening claims.
+///
+/// The claims it was built from hold if and only if
+/// `e(left, H) = e(right, beta * H)`, so the final pairing check can be
+/// deferred and accumulators of many proofs can be combined before checking
+/// them all at once.
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = "")
+)]
+pub struct PairingAccumulator<E>
+where
+    E: PairingEngine,
+{
+    /// Point paired with `H`.
+    pub left: E::G1Affine,
+
+    /// Point paired with `beta * H`.
+    pub right: E::G1Affine,
+}
+
+impl<E> PairingAccumulator<E>
+where
+    E: PairingEngine,
+{
+    /// Folds the `claims` into an accumulator, weighting the `i`-th claim
+    /// with `randomizer^i`.
+    ///
+    /// Every claim `(C, v, z, W)` contributes `C - v * G + z * W` to `left`
+    /// and `W` to `right`.
+    pub fn from_claims(
+        verifier_key: &SonicVerifierKey<E>,
+        claims: &[OpeningClaim<E::Fr, KZG10<E>>],
+        randomizer: E::Fr,
+    ) -> Self {
+        let mut points = Vec::with_capacity(2 * claims.len() + 2);
+        let mut scalars = Vec::with_capacity(2 * claims.len() + 2);
+        let mut witnesses = Vec::with_capacity(claims.len());
+        let mut weights = Vec::with_capacity(claims.len());
+        let mut g_scalar = E::Fr::zero();
+        let mut gamma_g_scalar = E::Fr::zero();
+        for (claim, weight) in
+            claims.iter().zip(crate::util::powers_of(randomizer))
+        {
+            let (commitment, value) = claim.aggregate();
+            let witness = kzg10::Commitment::<E>(claim.proof.w);
+
+            points.push(commitment);
+            scalars.push(weight);
+            points.push(witness);
+            scalars.push(weight * claim.point);
+            g_scalar -= weight * value;
+            if let Some(random_v) = claim.proof.random_v {
+                gamma_g_scalar -= weight * random_v;
+            }
+
+            witnesses.push(witness);
+            weights.push(weight);
+        }
+        points.push(kzg10::Commitment(verifier_key.g));
+        scalars.push(g_scalar);
+        points.push(kzg10::Commitment(verifier_key.gamma_g));
+        scalars.push(gamma_g_scalar);
+
+        Self {
+            left: KZG10::<E>::multi_scalar_mul(&points, &scalars).0,
+            right: KZG10::<E>::multi_scalar_mul(&witnesses, &weights).0,
+        }
+    }
+
+    /// Combines two accumulators into `self + randomizer * other`, which
+    /// passes the pairing check only if both of them do (except with
+    /// negligible probability over the choice of `randomizer`).
+    pub fn combine(&self, other: &Self, randomizer: E::Fr) -> Self {
+        let scalars = [E::Fr::one(), randomizer];
+        Self {
+            left: KZG10::<E>::multi_scalar_mul(
+                &[kzg10::Commitment(self.left), kzg10::Commitment(other.left)],
+                &scalars,
+            )
+            .0,
+            right: KZG10::<E>::multi_scalar_mul(
+                &[
+                    kzg10::Commitment(self.right),
+                    kzg10::Commitment(other.right),
+                ],
+                &scalars,
+            )
+            .0,
+        }
+    }
+
+    /// Performs the deferred pairing check `e(left, H) = e(right, beta * H)`.
+    pub fn check(&self, verifier_key: &SonicVerifierKey<E>) -> bool {
+        E::product_of_pairings(&[
+            (
+                E::G1Prepared::from(self.left),
+                verifier_key.prepared_h.clone(),
+            ),
+            (
+                E::G1Prepared::from(-self.right),
+                verifier_key.prepared_beta_h.clone(),
+            ),
+        ])
+        .is_one()
+    }
 }
 
 /// Shortened type for Inner Product Argument polynomial commitment schemes
@@ -67,6 +301,22 @@ where
     G: AffineCurve,
     D: Digest,
 {
+    /// Returns `ipa` followed by the start of the hash of the empty message
+    /// with `D`, which tells the digests of the scheme apart.
+    fn name() -> String {
+        let tag: String = D::digest(b"")[..4]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!("ipa-{}", tag)
+    }
+
+    type Point = G;
+
+    fn commitment_point(commitment: &IPACommitment<G, D>) -> G {
+        commitment.comm
+    }
+
     fn multi_scalar_mul(
         commitments: &[IPACommitment<G, D>],
         scalars: &[<G as ark_ec::AffineCurve>::ScalarField],
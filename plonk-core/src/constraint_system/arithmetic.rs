@@ -149,12 +149,12 @@ where
         let c = gate_witness.2.unwrap_or_else(|| {
             self.add_input(
                 ((gate.mul_selector
-                    * (self.variables[&gate_witness.0]
-                        * self.variables[&gate_witness.1]))
-                    + gate.add_selectors.0 * self.variables[&gate_witness.0]
-                    + gate.add_selectors.1 * self.variables[&gate_witness.1]
+                    * (self.value_of_var(gate_witness.0)
+                        * self.value_of_var(gate_witness.1)))
+                    + gate.add_selectors.0 * self.value_of_var(gate_witness.0)
+                    + gate.add_selectors.1 * self.value_of_var(gate_witness.1)
                     + gate.const_selector
-                    + q4 * self.variables[&w4]
+                    + q4 * self.value_of_var(w4)
                     + gate.pi.unwrap_or_default())
                     * (-gate.out_selector),
             )
@@ -15,18 +15,91 @@
 //! ECC op. gates, Range checks, Logical gates (Bitwise ops) etc.
 
 use crate::{
-    constraint_system::Variable, error::Error, permutation::Permutation,
+    constraint_system::{profile::NamespaceSpan, Variable},
+    error::Error,
+    permutation::Permutation,
 };
 
 use crate::lookup::LookupTable;
-use crate::proof_system::pi::PublicInputs;
+use crate::proof_system::{pi::PublicInputs, CircuitId, CustomGate};
 use ark_ec::{models::TEModelParameters, ModelParameters};
 use ark_ff::{PrimeField, ToConstraintField};
+use ark_serialize::CanonicalSerialize;
+use blake2::{digest::Digest, Blake2s};
 use core::cmp::max;
+use core::iter;
 use core::marker::PhantomData;
-use hashbrown::HashMap;
 use rand_core::{CryptoRng, RngCore};
 
+/// Domain tag of the [`CircuitId`], to be bumped whenever the description
+/// it is derived from changes.
+const CIRCUIT_ID_DOMAIN_TAG: &[u8] = b"plonk-circuit-id-v1";
+
+/// Smallest circuit bound of a [`StandardComposer`] using
+/// [`Blinding::Polynomials`].
+///
+/// The blinders raise the degree of the wire polynomials to `n + 2`, which
+/// keeps the highest degree gate, the fifth powers of the arithmetic gate,
+/// within the quotient domain of size `8n` only from this size on.
+pub(crate) const MIN_BLINDED_CIRCUIT_BOUND: usize = 16;
+
+/// Strategy used to make the proofs of a circuit zero-knowledge.
+///
+/// The strategy changes the layout of the circuit, so the [`Prover`] and
+/// [`Verifier`] of a circuit have to agree on it.
+///
+/// [`Prover`]: crate::proof_system::Prover
+/// [`Verifier`]: crate::proof_system::Verifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Blinding {
+    /// Random multiples of the vanishing polynomial are added to the wire and
+    /// permutation polynomials by the prover, as described in the PLONK
+    /// paper.
+    Polynomials,
+
+    /// Gates holding random witnesses are added at the start of the circuit
+    /// by [`StandardComposer::add_blinding_factors`], leaving the wire and
+    /// permutation polynomials unblinded. This is the layout of the circuits
+    /// and proofs of earlier releases.
+    DummyGates,
+}
+
+impl Default for Blinding {
+    #[inline]
+    fn default() -> Self {
+        Self::Polynomials
+    }
+}
+
+/// Layout of the keys and proofs of a circuit.
+///
+/// The layout changes the keys of a circuit, so the [`Prover`] and
+/// [`Verifier`] of a circuit have to agree on it.
+///
+/// [`Prover`]: crate::proof_system::Prover
+/// [`Verifier`]: crate::proof_system::Verifier
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GateLayout {
+    /// The gate types whose selectors are zero over the whole circuit are
+    /// left out of the keys and proofs: their selector commitments, their
+    /// terms of the quotient and linearisation polynomials, and the
+    /// commitments and evaluations only they need. A circuit without lookup
+    /// gates skips the lookup argument altogether.
+    Compact,
+
+    /// Every gate type is kept in the keys and proofs, whether the circuit
+    /// uses it or not. This is the only layout supported by the EVM
+    /// verifier.
+    Full,
+}
+
+impl Default for GateLayout {
+    #[inline]
+    fn default() -> Self {
+        Self::Compact
+    }
+}
+
 /// The StandardComposer is the circuit-builder tool that the `plonk` repository
 /// provides to create, stored and transformed circuit descriptions
 /// into a [`Proof`](crate::proof_system::Proof) at some point.
@@ -96,6 +169,15 @@ where
     /// Lookup gate selector
     pub(crate) q_lookup: Vec<F>,
 
+    /// Custom gates registered with the composer.
+    pub(crate) custom_gates: Vec<CustomGate<F>>,
+    /// Custom gate selectors, one per registered custom gate.
+    ///
+    /// Unlike the rest of the selectors, these are only extended up to the
+    /// last gate that enables them and get zero-padded up to `n` during
+    /// preprocessing.
+    pub(crate) q_custom: Vec<Vec<F>>,
+
     /// Sparse representation of the Public Inputs linking the positions of the
     /// non-zero ones to it's actual values.
     pub(crate) public_inputs: PublicInputs<F>,
@@ -120,12 +202,32 @@ where
     /// wire to be the variable that references zero
     pub(crate) zero_var: Variable,
 
-    /// These are the actual variable values.
-    pub(crate) variables: HashMap<Variable, F>,
+    /// These are the actual variable values, indexed by [`Variable`].
+    ///
+    /// A setup-only composer leaves the variables added from then on
+    /// unassigned, so only the first ones have values.
+    pub(crate) variables: Vec<F>,
 
     /// Permutation argument.
     pub(crate) perm: Permutation,
 
+    /// Blinding strategy of the circuit.
+    pub(crate) blinding: Blinding,
+
+    /// Layout of the keys and proofs of the circuit.
+    pub(crate) gate_layout: GateLayout,
+
+    /// Whether the composer only records the shape of the circuit, leaving
+    /// the variables unassigned.
+    pub(crate) setup_only: bool,
+
+    /// Namespaces entered while building the circuit, in the order they were
+    /// entered.
+    pub(crate) namespaces: Vec<NamespaceSpan>,
+
+    /// Index of the namespace being run, if any.
+    pub(crate) current_namespace: Option<usize>,
+
     /// Type Parameter Marker
     __: PhantomData<P>,
 }
@@ -141,8 +243,151 @@ where
     }
 
     /// Returns the smallest power of two needed for the circuit.
+    ///
+    /// With [`Blinding::Polynomials`] the bound is at least 16, the smallest
+    /// size for which the blinded polynomials fit into the quotient domain.
     pub fn circuit_bound(&self) -> usize {
-        self.total_size().next_power_of_two()
+        let bound = self.total_size().next_power_of_two();
+        match self.blinding {
+            Blinding::Polynomials => max(bound, MIN_BLINDED_CIRCUIT_BOUND),
+            Blinding::DummyGates => bound,
+        }
+    }
+
+    /// Pads the circuit with gates which are always satisfied until it holds
+    /// at least `size` gates, which makes its
+    /// [`circuit_bound`](StandardComposer::circuit_bound) at least `size`.
+    pub fn pad_to(&mut self, size: usize) {
+        let zero = self.zero_var;
+        while self.n < size {
+            self.poly_gate(
+                zero,
+                zero,
+                zero,
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                F::zero(),
+                None,
+            );
+        }
+    }
+
+    /// Returns the [`Blinding`] strategy of the circuit.
+    pub fn blinding(&self) -> Blinding {
+        self.blinding
+    }
+
+    /// Returns the [`GateLayout`] of the keys and proofs of the circuit.
+    pub fn gate_layout(&self) -> GateLayout {
+        self.gate_layout
+    }
+
+    /// Sets the [`GateLayout`] of the keys and proofs of the circuit.
+    ///
+    /// The layout is fixed by preprocessing, so it has to be set before the
+    /// keys of the circuit are computed.
+    pub fn set_gate_layout(&mut self, gate_layout: GateLayout) {
+        self.gate_layout = gate_layout;
+    }
+
+    /// Turns the composer into a setup-only composer, which records the
+    /// gates and copy constraints of the circuit but none of its witnesses.
+    ///
+    /// The variables added from then on are left unassigned and read as
+    /// zero, and the built-in gadgets skip the computation of their
+    /// witnesses, so a circuit can be laid out from placeholder data. The
+    /// keys preprocessed from a setup-only composer are the same as the ones
+    /// of a composer holding the witnesses, but proving with it fails with
+    /// [`Error::SetupOnlyComposer`].
+    pub fn setup_only(mut self) -> Self {
+        self.setup_only = true;
+        self
+    }
+
+    /// Returns whether the composer only records the shape of the circuit.
+    ///
+    /// Gadgets can check this to skip computing the values of their
+    /// witnesses, which are discarded by [`StandardComposer::add_input`].
+    pub fn is_setup_only(&self) -> bool {
+        self.setup_only
+    }
+
+    /// Returns the [`CircuitId`] of the circuit built so far.
+    ///
+    /// The identifier is the Blake2s hash of the selectors, the wiring, the
+    /// lookup table and the public input positions of the circuit, padded to
+    /// [`StandardComposer::circuit_bound`]. It does not depend on the witness
+    /// values nor on the commitment scheme, so it can be computed without
+    /// preprocessing the circuit.
+    pub fn circuit_id(&self) -> CircuitId {
+        let size = self.circuit_bound();
+        let mut hasher = Blake2s::new();
+        hasher.update(CIRCUIT_ID_DOMAIN_TAG);
+        hasher.update((size as u64).to_le_bytes());
+        hasher.update([self.blinding as u8]);
+
+        for selector in [
+            &self.q_m,
+            &self.q_l,
+            &self.q_r,
+            &self.q_o,
+            &self.q_4,
+            &self.q_c,
+            &self.q_hl,
+            &self.q_hr,
+            &self.q_h4,
+            &self.q_arith,
+            &self.q_range,
+            &self.q_logic,
+            &self.q_fixed_group_add,
+            &self.q_variable_group_add,
+            &self.q_lookup,
+        ] {
+            absorb_padded(
+                &mut hasher,
+                selector.iter().copied(),
+                size,
+                F::zero(),
+            );
+        }
+        for (gate, selector) in self.custom_gates.iter().zip(&self.q_custom) {
+            absorb_padded(
+                &mut hasher,
+                gate.name().bytes(),
+                gate.name().len(),
+                0,
+            );
+            absorb_padded(
+                &mut hasher,
+                selector.iter().copied(),
+                size,
+                F::zero(),
+            );
+        }
+
+        // The wiring is described by the variable in each wire, padded with
+        // the zero variable like preprocessing does.
+        let zero_var = self.zero_var.0 as u64;
+        for wire in [&self.w_l, &self.w_r, &self.w_o, &self.w_4] {
+            let variables = wire.iter().map(|var| var.0 as u64);
+            absorb_padded(&mut hasher, variables, size, zero_var);
+        }
+
+        let table = self.lookup_table.0.iter().flatten().copied();
+        absorb_padded(
+            &mut hasher,
+            table,
+            4 * self.lookup_table.size(),
+            F::zero(),
+        );
+        let pi_pos = self.intended_pi_pos.iter().map(|&pos| pos as u64);
+        absorb_padded(&mut hasher, pi_pos, self.intended_pi_pos.len(), 0);
+
+        let mut id = [0; 32];
+        id.copy_from_slice(&hasher.finalize());
+        CircuitId(id)
     }
 
     /// Returns a reference to the [`PublicInputs`] stored in the
@@ -167,6 +412,25 @@ where
     }
 }
 
+/// Absorbs the length `size` followed by the `values`, extended to `size`
+/// elements with `padding`, into the `hasher`.
+fn absorb_padded<T, I>(hasher: &mut Blake2s, values: I, size: usize, padding: T)
+where
+    T: CanonicalSerialize + Copy,
+    I: IntoIterator<Item = T>,
+{
+    let mut bytes = Vec::new();
+    (size as u64)
+        .serialize(&mut bytes)
+        .expect("serializing into a vector does not fail");
+    for value in values.into_iter().chain(iter::repeat(padding)).take(size) {
+        value
+            .serialize(&mut bytes)
+            .expect("serializing into a vector does not fail");
+    }
+    hasher.update(&bytes);
+}
+
 impl<F, P> Default for StandardComposer<F, P>
 where
     F: PrimeField,
@@ -225,6 +489,8 @@ where
             q_fixed_group_add: Vec::with_capacity(expected_size),
             q_variable_group_add: Vec::with_capacity(expected_size),
             q_lookup: Vec::with_capacity(expected_size),
+            custom_gates: Vec::new(),
+            q_custom: Vec::new(),
             public_inputs: PublicInputs::new(),
             intended_pi_pos: Vec::new(),
             w_l: Vec::with_capacity(expected_size),
@@ -233,8 +499,13 @@ where
             w_4: Vec::with_capacity(expected_size),
             lookup_table: LookupTable::new(),
             zero_var: Variable(0),
-            variables: HashMap::with_capacity(expected_size),
+            variables: Vec::with_capacity(expected_size),
             perm: Permutation::new(),
+            blinding: Blinding::default(),
+            gate_layout: GateLayout::default(),
+            setup_only: false,
+            namespaces: Vec::new(),
+            current_namespace: None,
             __: PhantomData::<P>,
         };
 
@@ -242,8 +513,30 @@ where
         composer.zero_var =
             composer.add_witness_to_circuit_description(F::zero());
 
+        composer
+    }
+
+    /// Creates a new circuit with an expected circuit size, like
+    /// [`StandardComposer::with_expected_size`], which is made zero-knowledge
+    /// with the given [`Blinding`] strategy.
+    ///
+    /// The witnesses of the dummy gates of [`Blinding::DummyGates`] are
+    /// sampled from `rng`.
+    pub fn with_blinding<R>(
+        expected_size: usize,
+        blinding: Blinding,
+        rng: &mut R,
+    ) -> Self
+    where
+        R: CryptoRng + RngCore + ?Sized,
+    {
+        let mut composer = Self::with_expected_size(expected_size);
+        composer.blinding = blinding;
+
         // Add dummy constraints
-        composer.add_blinding_factors(&mut rand_core::OsRng);
+        if blinding == Blinding::DummyGates {
+            composer.add_blinding_factors(rng);
+        }
 
         composer
     }
@@ -257,13 +550,17 @@ where
     /// to generate and allocate a new [`Variable`] `var`.
     ///
     /// The Composer then links the variable to the [`PrimeField`]
-    /// and returns it for its use in the system.
+    /// and returns it for its use in the system. A setup-only composer
+    /// leaves the variable unassigned.
     pub fn add_input(&mut self, s: F) -> Variable {
         // Get a new Variable from the permutation
         let var = self.perm.new_variable();
         // The composer now links the Variable returned from
         // the Permutation to the value F.
-        self.variables.insert(var, s);
+        if !self.setup_only {
+            debug_assert_eq!(var.0, self.variables.len());
+            self.variables.push(s);
+        }
 
         var
     }
@@ -370,11 +667,11 @@ where
     /// the input is 0 and whose value is 0 otherwise
     pub fn is_zero_with_output(&mut self, a: Variable) -> Variable {
         // Get relevant field values
-        let a_value = self.variables.get(&a).unwrap();
+        let a_value = self.value_of_var(a);
         let y_value = a_value.inverse().unwrap_or_else(F::one);
 
         // This has value 1 if input value is zero, value 0 otherwise
-        let b_value = F::one() - *a_value * y_value;
+        let b_value = F::one() - a_value * y_value;
 
         let y = self.add_input(y_value);
         let b = self.add_input(b_value);
@@ -482,10 +779,10 @@ where
         bit: Variable,
         value: Variable,
     ) -> Variable {
-        let value_scalar = self.variables.get(&value).unwrap();
-        let bit_scalar = self.variables.get(&bit).unwrap();
+        let value_scalar = self.value_of_var(value);
+        let bit_scalar = self.value_of_var(bit);
 
-        let f_x_scalar = F::one() - bit_scalar + (*bit_scalar * value_scalar);
+        let f_x_scalar = F::one() - bit_scalar + (bit_scalar * value_scalar);
         let f_x = self.add_input(f_x_scalar);
 
         self.poly_gate(
@@ -689,6 +986,8 @@ where
     /// The function by itself will print each circuit gate info until one of
     /// the gates does not satisfy the equation or there are no more gates. If
     /// the cause is an unsatisfied gate equation, the function will panic.
+    /// [`StandardComposer::unsatisfied_constraints`] reports every
+    /// unsatisfied gate, copy constraint and lookup instead.
     #[cfg(feature = "trace")]
     pub fn check_circuit_satisfied(&mut self) {
         use ark_ff::BigInteger;
@@ -697,22 +996,22 @@ where
         let w_l: Vec<&F> = self
             .w_l
             .iter()
-            .map(|w_l_i| self.variables.get(w_l_i).unwrap())
+            .map(|w_l_i| &self.variables[w_l_i.0])
             .collect();
         let w_r: Vec<&F> = self
             .w_r
             .iter()
-            .map(|w_r_i| self.variables.get(w_r_i).unwrap())
+            .map(|w_r_i| &self.variables[w_r_i.0])
             .collect();
         let w_o: Vec<&F> = self
             .w_o
             .iter()
-            .map(|w_o_i| self.variables.get(w_o_i).unwrap())
+            .map(|w_o_i| &self.variables[w_o_i.0])
             .collect();
         let w_4: Vec<&F> = self
             .w_4
             .iter()
-            .map(|w_4_i| self.variables.get(w_4_i).unwrap())
+            .map(|w_4_i| &self.variables[w_4_i.0])
             .collect();
         // Computes f(f-1)(f-2)(f-3)
         let delta = |f: F| -> F {
@@ -865,12 +1164,15 @@ where
     /// [`conditional_select`](StandardComposer::conditional_select),
     /// [`is_eq_with_output`](StandardComposer::is_eq_with_output)
     /// or other similar method that returns a `Variable`.
+    ///
+    /// The unassigned variables of a setup-only composer read as zero.
     #[inline]
     pub fn value_of_var(&self, var: Variable) -> F {
-        self.variables
-            .get(&var)
-            .copied()
-            .expect("the variable does not exist")
+        match self.variables.get(var.0) {
+            Some(value) => *value,
+            None if self.setup_only => F::zero(),
+            None => panic!("the variable does not exist"),
+        }
     }
 }
 
@@ -885,21 +1187,94 @@ mod test {
     };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_ec::twisted_edwards_extended::GroupAffine;
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::test_rng;
     use rand_core::OsRng;
 
-    /// Tests that a circuit initially has 3 gates.
+    /// Tests the initial number of gates of a circuit.
     fn test_initial_circuit_size<F, P>()
     where
         F: PrimeField,
         P: TEModelParameters<BaseField = F>,
     {
-        // NOTE: Circuit size is n+4 because
+        // NOTE: The only gate forces the first witness to be zero. This is
+        // used when the advice wire is not being used.
+        assert_eq!(1, StandardComposer::<F, P>::new().n);
+
+        // NOTE: Circuit size is n+4 with dummy gate blinding because
         // - We have an extra gate which forces the first witness to be zero.
         //   This is used when the advice wire is not being used.
         // - We have two gates which add random values to blind the wires.
         // - Another gate which adds 2 pairs of equal points to blind the
         //   permutation polynomial
-        assert_eq!(4, StandardComposer::<F, P>::new().n)
+        assert_eq!(
+            4,
+            StandardComposer::<F, P>::with_blinding(
+                0,
+                Blinding::DummyGates,
+                &mut OsRng
+            )
+            .n
+        )
+    }
+
+    /// Tests that a setup-only composer lays out the same circuit from
+    /// placeholder data without assigning any witness.
+    fn test_setup_only<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let gadget = |composer: &mut StandardComposer<F, P>,
+                      scalar: F,
+                      point: GroupAffine<P>| {
+            let scalar = composer.add_input(scalar);
+            let point = composer.add_affine(point);
+            let is_zero = composer.is_zero_with_output(scalar);
+            composer.boolean_gate(is_zero);
+            composer.range_gate(scalar, 16);
+            composer.variable_base_scalar_mul(scalar, point);
+        };
+
+        let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+        let mut composer = StandardComposer::new();
+        gadget(&mut composer, F::from(1234u64), GroupAffine::new(x, y));
+
+        // The placeholder point is not even on the curve.
+        let mut setup = StandardComposer::<F, P>::new().setup_only();
+        gadget(
+            &mut setup,
+            F::zero(),
+            GroupAffine::new(F::zero(), F::zero()),
+        );
+
+        assert!(setup.is_setup_only());
+        assert_eq!(setup.n, composer.n);
+        assert_eq!(setup.circuit_id(), composer.circuit_id());
+        // Only the zero variable, added before the composer was turned into a
+        // setup-only one, is assigned.
+        assert_eq!(setup.variables.len(), 1);
+    }
+
+    /// Tests that a setup-only composer is preprocessed but not proven.
+    fn test_setup_only_cannot_prove<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let u_params = PC::setup(2 * 30, None, &mut OsRng).unwrap();
+        let mut prover: Prover<F, P, PC> = Prover::new(b"demo");
+        *prover.mut_cs() = StandardComposer::new().setup_only();
+        dummy_gadget(10, prover.mut_cs());
+        let (ck, _) =
+            PC::trim(&u_params, prover.commit_key_degree(), 0, None).unwrap();
+        prover.preprocess(&ck).unwrap();
+        assert!(matches!(
+            prover.prove(&ck, &mut OsRng),
+            Err(Error::SetupOnlyComposer)
+        ));
     }
 
     /// Tests that an empty circuit proof passes.
@@ -1033,7 +1408,7 @@ mod test {
 
         // Compute multiple proofs
         for _ in 0..3 {
-            proofs.push(prover.prove(&ck).unwrap());
+            proofs.push(prover.prove(&ck, &mut OsRng).unwrap());
 
             // Add another witness instance
             dummy_gadget(10, prover.mut_cs());
@@ -1054,10 +1429,132 @@ mod test {
         }
     }
 
+    /// Tests that the wire and permutation commitments of two proofs of the
+    /// same witness differ.
+    fn test_polynomial_blinding<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let to_bytes = |commitment: &PC::Commitment| {
+            let mut bytes = Vec::new();
+            commitment.serialize(&mut bytes).unwrap();
+            bytes
+        };
+
+        let u_params = PC::setup(2 * 30, None, &mut OsRng).unwrap();
+
+        let mut prover: Prover<F, P, PC> = Prover::new(b"demo");
+        dummy_gadget(10, prover.mut_cs());
+        let (ck, _) =
+            PC::trim(&u_params, prover.commit_key_degree(), 0, None).unwrap();
+        prover.preprocess(&ck).unwrap();
+        let first = prover.prove(&ck, &mut OsRng).unwrap();
+        dummy_gadget(10, prover.mut_cs());
+        let second = prover.prove(&ck, &mut OsRng).unwrap();
+
+        for (first, second) in [
+            (&first.a_comm, &second.a_comm),
+            (&first.b_comm, &second.b_comm),
+            (&first.c_comm, &second.c_comm),
+            (&first.d_comm, &second.d_comm),
+            (&first.z_comm, &second.z_comm),
+        ] {
+            assert_ne!(to_bytes(first), to_bytes(second));
+        }
+    }
+
+    /// Tests that proofs are reproducible with a seeded random generator.
+    fn test_seeded_proofs<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let u_params = PC::setup(2 * 30, None, &mut OsRng).unwrap();
+
+        let prove = || {
+            let mut prover: Prover<F, P, PC> = Prover::new(b"demo");
+            dummy_gadget(10, prover.mut_cs());
+            let (ck, _) =
+                PC::trim(&u_params, prover.commit_key_degree(), 0, None)
+                    .unwrap();
+            let proof = prover.prove(&ck, &mut test_rng()).unwrap();
+            let mut bytes = Vec::new();
+            proof.serialize(&mut bytes).unwrap();
+            bytes
+        };
+
+        assert_eq!(prove(), prove());
+    }
+
+    /// Tests that the circuit identifier only depends on the circuit
+    /// description.
+    fn test_circuit_id<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let circuit = |value: F, n: usize| {
+            let mut composer = StandardComposer::<F, P>::new();
+            let var = composer.add_input(value);
+            for _ in 0..n {
+                composer.arithmetic_gate(|gate| {
+                    gate.witness(var, var, None).add(F::one(), F::one())
+                });
+            }
+            composer.circuit_id()
+        };
+        assert_eq!(circuit(F::one(), 10), circuit(F::from(2u64), 10));
+        assert_ne!(circuit(F::one(), 10), circuit(F::one(), 11));
+
+        // Padding the circuit while preprocessing keeps the identifier.
+        let u_params = PC::setup(2 * 30, None, &mut OsRng).unwrap();
+        let mut prover: Prover<F, P, PC> = Prover::new(b"demo");
+        dummy_gadget(10, prover.mut_cs());
+        let circuit_id = prover.cs.circuit_id();
+        let (ck, _) =
+            PC::trim(&u_params, prover.commit_key_degree(), 0, None).unwrap();
+        prover.preprocess(&ck).unwrap();
+        assert_eq!(prover.cs.circuit_id(), circuit_id);
+        assert_eq!(prover.prover_key.unwrap().circuit_id(), circuit_id);
+    }
+
+    /// Tests that proofs of circuits blinded with dummy gates still verify.
+    fn test_dummy_gate_blinding<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let u_params = PC::setup(2 * 30, None, &mut OsRng).unwrap();
+
+        let mut prover: Prover<F, P, PC> =
+            Prover::with_blinding(b"demo", Blinding::DummyGates, &mut OsRng);
+        dummy_gadget(10, prover.mut_cs());
+        let (ck, vk) =
+            PC::trim(&u_params, prover.commit_key_degree(), 0, None).unwrap();
+        prover.preprocess(&ck).unwrap();
+        let public_inputs = prover.cs.get_pi().clone();
+        let proof = prover.prove(&ck, &mut OsRng).unwrap();
+
+        // The blinding strategy survives clearing the witness.
+        assert_eq!(prover.cs.blinding(), Blinding::DummyGates);
+
+        let mut verifier: Verifier<F, P, PC> =
+            Verifier::with_blinding(b"demo", Blinding::DummyGates, &mut OsRng);
+        dummy_gadget(10, verifier.mut_cs());
+        verifier.preprocess(&ck).unwrap();
+        assert!(verifier.verify(&proof, &vk, &public_inputs).is_ok());
+    }
+
     // Tests for Bls12_381
     batch_test_field_params!(
         [
-            test_initial_circuit_size
+            test_initial_circuit_size,
+            test_setup_only
         ],
         [] => (
             Bls12_381,
@@ -1069,7 +1566,8 @@ mod test {
     // Tests for Bls12_377
     batch_test_field_params!(
         [
-            test_initial_circuit_size
+            test_initial_circuit_size,
+            test_setup_only
         ],
         [] => (
             Bls12_377,
@@ -1084,7 +1582,12 @@ mod test {
             test_correct_is_zero_with_output,
             test_correct_is_eq_with_output,
             test_conditional_select,
-            test_multiple_proofs
+            test_multiple_proofs,
+            test_polynomial_blinding,
+            test_seeded_proofs,
+            test_circuit_id,
+            test_dummy_gate_blinding,
+            test_setup_only_cannot_prove
         ],
         [] => (
             Bls12_381,
@@ -1099,7 +1602,12 @@ mod test {
             test_correct_is_zero_with_output,
             test_correct_is_eq_with_output,
             test_conditional_select,
-            test_multiple_proofs
+            test_multiple_proofs,
+            test_polynomial_blinding,
+            test_seeded_proofs,
+            test_circuit_id,
+            test_dummy_gate_blinding,
+            test_setup_only_cannot_prove
         ],
         [] => (
             Bls12_377,
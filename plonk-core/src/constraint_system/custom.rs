@@ -0,0 +1,396 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Custom Gates
+//!
+//! This module allows users to plug their own [`GateConstraint`]s into the
+//! [`StandardComposer`] without modifying the crate. Every registered gate gets
+//! its own selector polynomial and separation challenge, which are handled by
+//! preprocessing, proving and verification the same way as for the built-in
+//! gates.
+
+use crate::{
+    constraint_system::{StandardComposer, Variable},
+    error::Error,
+    proof_system::{CustomGate, GateConstraint},
+};
+use ark_ec::ModelParameters;
+use ark_ff::PrimeField;
+
+/// Handle to a custom gate registered with a [`StandardComposer`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CustomGateId(usize);
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: ModelParameters<BaseField = F>,
+{
+    /// Registers the [`GateConstraint`] `G` under `name` and returns the
+    /// handle used to add it to the circuit with
+    /// [`StandardComposer::custom_gate`].
+    ///
+    /// Registering a gate which is already registered under the same `name`
+    /// returns the existing handle, so gadgets can register the gates they use
+    /// every time they are called. Registering another gate under the name of
+    /// a registered one fails with [`Error::CustomGateNameConflict`].
+    pub fn register_custom_gate<G>(
+        &mut self,
+        name: &'static str,
+    ) -> Result<CustomGateId, Error>
+    where
+        G: GateConstraint<F> + 'static,
+    {
+        if let Some(id) = self
+            .custom_gates
+            .iter()
+            .position(|gate| gate.name() == name)
+        {
+            return if self.custom_gates[id].is::<G>() {
+                Ok(CustomGateId(id))
+            } else {
+                Err(Error::CustomGateNameConflict {
+                    name: name.to_string(),
+                })
+            };
+        }
+        self.custom_gates.push(CustomGate::new::<G>(name));
+        self.q_custom.push(Vec::new());
+        Ok(CustomGateId(self.custom_gates.len() - 1))
+    }
+
+    /// Returns the custom gates registered with the composer.
+    pub fn custom_gates(&self) -> &[CustomGate<F>] {
+        &self.custom_gates
+    }
+
+    /// Adds a gate which enables the custom gate `gate` over the wires
+    /// `[a, b, c, d]`.
+    ///
+    /// The arithmetic gate is turned off for this row, so `q_l`, `q_r` and
+    /// `q_c` are free to be used by the custom gate as constants through the
+    /// `q_l_eval`, `q_r_eval` and `q_c_eval` custom evaluations.
+    pub fn custom_gate(
+        &mut self,
+        gate: CustomGateId,
+        wires: [Variable; 4],
+        q_l: F,
+        q_r: F,
+        q_c: F,
+    ) {
+        let q_custom = &mut self.q_custom[gate.0];
+        q_custom.resize(self.n, F::zero());
+        q_custom.push(F::one());
+
+        self.push_row(wires, q_l, q_r, q_c);
+    }
+
+    /// Adds a gate with every selector turned off, which only places `wires`
+    /// in the circuit.
+    ///
+    /// This is meant to be used right after [`StandardComposer::custom_gate`]
+    /// when the custom gate reads the wires of the next row through the
+    /// `a_next_eval`, `b_next_eval` and `d_next_eval` custom evaluations.
+    pub fn add_wire_row(&mut self, wires: [Variable; 4]) {
+        self.push_row(wires, F::zero(), F::zero(), F::zero());
+    }
+
+    /// Adds a row with the arithmetic gate and every built-in gate turned off.
+    fn push_row(&mut self, wires: [Variable; 4], q_l: F, q_r: F, q_c: F) {
+        let [a, b, c, d] = wires;
+        self.w_l.push(a);
+        self.w_r.push(b);
+        self.w_o.push(c);
+        self.w_4.push(d);
+
+        self.q_m.push(F::zero());
+        self.q_l.push(q_l);
+        self.q_r.push(q_r);
+        self.q_o.push(F::zero());
+        self.q_c.push(q_c);
+        self.q_4.push(F::zero());
+        self.q_arith.push(F::zero());
+
+        // add high degree selectors
+        self.q_hl.push(F::zero());
+        self.q_hr.push(F::zero());
+        self.q_h4.push(F::zero());
+
+        self.q_range.push(F::zero());
+        self.q_logic.push(F::zero());
+        self.q_fixed_group_add.push(F::zero());
+        self.q_variable_group_add.push(F::zero());
+        self.q_lookup.push(F::zero());
+
+        self.perm.add_variables_to_map(a, b, c, d, self.n);
+        self.n += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        batch_test, batch_test_field_params,
+        circuit::{verify_proof, Circuit},
+        commitment::HomomorphicCommitment,
+        constraint_system::{helper::*, StandardComposer},
+        error::Error,
+        proof_system::{
+            linearisation_poly::CustomEvaluations, range::Range, CustomGate,
+            CustomValues, GateConstraint, VerifierKey, WitnessValues,
+        },
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+    use ark_ff::PrimeField;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use core::marker::PhantomData;
+    use rand_core::OsRng;
+
+    /// Gate enforcing `c = a * b` and `d_next = c + d`.
+    struct MulAcc<F>(PhantomData<F>);
+
+    struct MulAccVals<F>
+    where
+        F: PrimeField,
+    {
+        d_next_val: F,
+    }
+
+    impl<F> CustomValues<F> for MulAccVals<F>
+    where
+        F: PrimeField,
+    {
+        fn from_evaluations(
+            custom_evals: &CustomEvaluations<F>,
+        ) -> Result<Self, Error> {
+            Ok(Self {
+                d_next_val: custom_evals.get("d_next_eval")?,
+            })
+        }
+    }
+
+    impl<F> GateConstraint<F> for MulAcc<F>
+    where
+        F: PrimeField,
+    {
+        type CustomVals = MulAccVals<F>;
+
+        fn constraints(
+            separation_challenge: F,
+            wit_vals: WitnessValues<F>,
+            custom_vals: Self::CustomVals,
+        ) -> F {
+            let mul = wit_vals.a_val * wit_vals.b_val - wit_vals.c_val;
+            let acc = custom_vals.d_next_val - wit_vals.c_val - wit_vals.d_val;
+            (mul + acc * separation_challenge) * separation_challenge
+        }
+    }
+
+    /// Computes `sum(a_i * b_i)` with the `MulAcc` gate and checks it against
+    /// `expected`.
+    fn mul_acc_gadget<F, P>(
+        composer: &mut StandardComposer<F, P>,
+        values: &[(u64, u64)],
+        expected: u64,
+    ) where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mul_acc = composer
+            .register_custom_gate::<MulAcc<F>>("mul_acc")
+            .unwrap();
+        let mut acc = composer.zero_var();
+        let mut acc_value = F::zero();
+        for (a, b) in values {
+            let product = F::from(*a) * F::from(*b);
+            let a = composer.add_input(F::from(*a));
+            let b = composer.add_input(F::from(*b));
+            let c = composer.add_input(product);
+            composer.custom_gate(
+                mul_acc,
+                [a, b, c, acc],
+                F::zero(),
+                F::zero(),
+                F::zero(),
+            );
+            acc_value += product;
+            acc = composer.add_input(acc_value);
+        }
+        let zero = composer.zero_var();
+        composer.add_wire_row([zero, zero, zero, acc]);
+        composer.constrain_to_constant(acc, F::from(expected), None);
+    }
+
+    fn test_custom_gate<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                mul_acc_gadget(composer, &[(2, 3), (4, 5), (6, 7)], 68);
+            },
+            200,
+        );
+        assert!(res.is_ok());
+    }
+
+    fn test_incorrect_custom_gate<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let res = gadget_tester::<F, P, PC>(
+            |composer: &mut StandardComposer<F, P>| {
+                let mul_acc = composer
+                    .register_custom_gate::<MulAcc<F>>("mul_acc")
+                    .unwrap();
+                let a = composer.add_input(F::from(2u64));
+                let b = composer.add_input(F::from(3u64));
+                let c = composer.add_input(F::from(7u64));
+                let zero = composer.zero_var();
+                composer.custom_gate(
+                    mul_acc,
+                    [a, b, c, zero],
+                    F::zero(),
+                    F::zero(),
+                    F::zero(),
+                );
+                composer.add_wire_row([zero, zero, zero, c]);
+            },
+            200,
+        );
+        assert!(res.is_err());
+    }
+
+    /// Circuit checking `2 * 3 + 4 * 5 = 26` with the `MulAcc` gate.
+    struct MulAccCircuit;
+
+    impl<F, P> Circuit<F, P> for MulAccCircuit
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            mul_acc_gadget(composer, &[(2, 3), (4, 5)], 26);
+            Ok(())
+        }
+    }
+
+    fn test_custom_gate_key<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let pp = PC::setup(1 << 10, None, &mut OsRng).unwrap();
+        let (pk, (vk, _)) =
+            Circuit::<F, P>::compile::<PC>(&mut MulAccCircuit, &pp, &mut OsRng)
+                .unwrap();
+        let circuit_id = vk.circuit_id();
+        let (proof, pi) = Circuit::<F, P>::gen_proof::<PC>(
+            &mut MulAccCircuit,
+            &pp,
+            pk,
+            b"Test",
+            &mut OsRng,
+        )
+        .unwrap();
+
+        // The key returned by the compilation carries the custom gate.
+        assert!(verify_proof::<F, P, PC>(
+            &pp,
+            vk.clone(),
+            circuit_id,
+            &proof,
+            &pi,
+            b"Test"
+        )
+        .is_ok());
+
+        // A deserialized key needs it to be attached again.
+        let mut bytes = Vec::new();
+        vk.serialize(&mut bytes).unwrap();
+        let mut vk = VerifierKey::<F, PC>::deserialize(&bytes[..]).unwrap();
+        assert!(matches!(
+            verify_proof::<F, P, PC>(
+                &pp,
+                vk.clone(),
+                circuit_id,
+                &proof,
+                &pi,
+                b"Test"
+            ),
+            Err(Error::CustomGateNotRegistered { name }) if name == "mul_acc"
+        ));
+        vk.attach_custom_gates(&[CustomGate::new::<MulAcc<F>>("mul_acc")])
+            .unwrap();
+        assert!(verify_proof::<F, P, PC>(
+            &pp, vk, circuit_id, &proof, &pi, b"Test"
+        )
+        .is_ok());
+    }
+
+    fn test_register_custom_gate_twice<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        let first = composer.register_custom_gate::<MulAcc<F>>("mul_acc");
+        let second = composer.register_custom_gate::<MulAcc<F>>("mul_acc");
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert_eq!(composer.custom_gates().len(), 1);
+
+        // Another gate cannot take the name of a registered one.
+        assert!(matches!(
+            composer.register_custom_gate::<Range<F>>("mul_acc"),
+            Err(Error::CustomGateNameConflict { name }) if name == "mul_acc"
+        ));
+        assert_eq!(composer.custom_gates().len(), 1);
+    }
+
+    // Test for Bls12_381
+    batch_test!(
+        [
+            test_custom_gate,
+            test_incorrect_custom_gate,
+            test_custom_gate_key
+        ],
+        [] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Test for Bls12_377
+    batch_test!(
+        [
+            test_custom_gate,
+            test_incorrect_custom_gate,
+            test_custom_gate_key
+        ],
+        [] => (
+            Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+
+    // Test for Bls12_381
+    batch_test_field_params!(
+        [test_register_custom_gate_twice],
+        [] => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+}
@@ -59,7 +59,7 @@ where
             compute_wnaf_point_multiples(base_point.into());
         point_multiples.reverse();
 
-        let scalar_value = self.variables.get(&scalar).unwrap();
+        let scalar_value = self.value_of_var(scalar);
 
         // Convert scalar to wnaf_2(k)
         let wnaf_entries =
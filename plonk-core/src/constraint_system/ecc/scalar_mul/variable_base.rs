@@ -30,15 +30,7 @@ where
         point: Point<P>,
     ) -> Point<P> {
         // Turn scalar into bits
-        let raw_scalar = *self
-            .variables
-            .get(&curve_var)
-            // We can unwrap safely here since it should be impossible to obtain
-            // a `Variable` without first linking it inside of the
-            // HashMap from which we are calling the `get()` now. Therefore, if
-            // the `get()` fn fails now, somethig is going really
-            // bad.
-            .expect("Variable in existance without referenced scalar");
+        let raw_scalar = self.value_of_var(curve_var);
         let scalar_bits_var = self.scalar_decomposition(curve_var, raw_scalar);
 
         let identity = Point::identity(self);
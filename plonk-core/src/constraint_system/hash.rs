@@ -22,12 +22,17 @@ where
         vars: &[Variable; 3],
         selectors: &[F; 5],
     ) -> Variable {
-        let w4_val = (selectors[0]
-            * self.value_of_var(vars[0]).pow([SBOX_ALPHA])
-            + selectors[1] * self.value_of_var(vars[1]).pow([SBOX_ALPHA])
-            + selectors[2] * self.value_of_var(vars[2]).pow([SBOX_ALPHA])
-            + selectors[3])
-            / -selectors[4];
+        // Compute the output, unless only the shape of the circuit is
+        // recorded
+        let w4_val = if self.is_setup_only() {
+            F::zero()
+        } else {
+            (selectors[0] * self.value_of_var(vars[0]).pow([SBOX_ALPHA])
+                + selectors[1] * self.value_of_var(vars[1]).pow([SBOX_ALPHA])
+                + selectors[2] * self.value_of_var(vars[2]).pow([SBOX_ALPHA])
+                + selectors[3])
+                / -selectors[4]
+        };
         let w4_var = self.add_input(w4_val);
 
         // add wires
@@ -75,12 +80,17 @@ where
         vars: &[Variable; 3],
         selectors: &[F; 5],
     ) -> Variable {
-        let w4_val = (selectors[0]
-            * self.value_of_var(vars[0]).pow([SBOX_ALPHA])
-            + selectors[1] * self.value_of_var(vars[1])
-            + selectors[2] * self.value_of_var(vars[2])
-            + selectors[3])
-            / -selectors[4];
+        // Compute the output, unless only the shape of the circuit is
+        // recorded
+        let w4_val = if self.is_setup_only() {
+            F::zero()
+        } else {
+            (selectors[0] * self.value_of_var(vars[0]).pow([SBOX_ALPHA])
+                + selectors[1] * self.value_of_var(vars[1])
+                + selectors[2] * self.value_of_var(vars[2])
+                + selectors[3])
+                / -selectors[4]
+        };
         let w4_var = self.add_input(w4_val);
 
         // add wires
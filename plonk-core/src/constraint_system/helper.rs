@@ -8,9 +8,10 @@ use super::StandardComposer;
 use crate::{
     commitment::HomomorphicCommitment,
     error::{to_pc_error, Error},
+    lookup::LookupTable,
     proof_system::{Prover, Verifier},
 };
-use ark_ec::TEModelParameters;
+use ark_ec::{twisted_edwards_extended::GroupAffine, TEModelParameters};
 use ark_ff::PrimeField;
 use rand_core::OsRng;
 
@@ -32,6 +33,43 @@ pub(crate) fn dummy_gadget<F, P>(
     }
 }
 
+/// Adds a gadget using most gate types, which checks that:
+/// 1) `a + b = c` where `c` is a public input
+/// 2) `a` and `b` are 8 bit values
+/// 3) `a xor b = d` through a lookup table of 4 bit values
+/// 4) `a * G` is computed with the variable base scalar multiplication
+#[allow(dead_code)]
+pub(crate) fn sum_range_xor_gadget<F, P>(
+    composer: &mut StandardComposer<F, P>,
+    a: F,
+    b: F,
+    c: F,
+    d: F,
+) where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    composer.lookup_table = LookupTable::xor_table(0, 4);
+    let a = composer.add_input(a);
+    let b = composer.add_input(b);
+    let d = composer.add_input(d);
+    let negative_one = composer.add_input(-F::one());
+    let zero = composer.zero_var();
+
+    composer.arithmetic_gate(|gate| {
+        gate.witness(a, b, Some(zero))
+            .add(F::one(), F::one())
+            .pi(-c)
+    });
+    composer.range_gate(a, 8);
+    composer.range_gate(b, 8);
+    composer.lookup_gate(a, b, d, Some(negative_one), None);
+
+    let (x, y) = P::AFFINE_GENERATOR_COEFFS;
+    let generator = composer.add_affine(GroupAffine::new(x, y));
+    composer.variable_base_scalar_mul(a, generator);
+}
+
 /// Takes a generic gadget function with no auxillary input and tests whether it
 /// passes an end-to-end test.
 #[allow(dead_code)]
@@ -61,7 +99,7 @@ where
 
         // Commit Key
         let (ck, _) =
-            PC::trim(&universal_params, prover.circuit_bound(), 0, None)
+            PC::trim(&universal_params, prover.commit_key_degree(), 0, None)
                 .map_err(to_pc_error::<F, PC>)?;
 
         // Preprocess circuit
@@ -72,12 +110,12 @@ where
         let public_inputs = prover.cs.get_pi().clone();
 
         // Compute Proof
-        (prover.prove(&ck)?, public_inputs)
+        (prover.prove(&ck, &mut OsRng)?, public_inputs)
     };
     // Verifiers view
     //
     // Create a Verifier object
-    let mut verifier = Verifier::new(b"demo");
+    let mut verifier = Verifier::<F, P, PC>::new(b"demo");
 
     // Additionally key the transcript
     verifier.key_transcript(b"key", b"additional seed information");
@@ -87,7 +125,7 @@ where
 
     // Compute Commit and Verifier Key
     let (ck, vk) =
-        PC::trim(&universal_params, verifier.circuit_bound(), 0, None)
+        PC::trim(&universal_params, verifier.commit_key_degree(), 0, None)
             .map_err(to_pc_error::<F, PC>)?;
 
     // Preprocess circuit
@@ -0,0 +1,558 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Serializable intermediate representation of circuits.
+//!
+//! A [`CircuitIr`] holds the structure of a circuit laid out in a
+//! [`StandardComposer`], without its witness. It can be stored as an artifact
+//! and turned back into a setup-only composer by
+//! [`StandardComposer::from_ir`], from which keys can be preprocessed without
+//! running the gadget again.
+
+use crate::{
+    constraint_system::{
+        Blinding, GateLayout, StandardComposer, Variable, WireData,
+    },
+    error::Error,
+    lookup::LookupTable,
+    proof_system::CustomGate,
+};
+use ark_ec::ModelParameters;
+use ark_ff::PrimeField;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
+
+/// Version of the serialization format of [`CircuitIr`].
+///
+/// Version 2 added the [`GateLayout`] of the circuit.
+pub const CIRCUIT_IR_VERSION: u32 = 2;
+
+/// Names of the selectors of the built-in gates, in the order they are stored
+/// in a [`CircuitIr`].
+const SELECTOR_NAMES: [&str; 15] = [
+    "q_m",
+    "q_l",
+    "q_r",
+    "q_o",
+    "q_4",
+    "q_c",
+    "q_hl",
+    "q_hr",
+    "q_h4",
+    "q_arith",
+    "q_range",
+    "q_logic",
+    "q_fixed_group_add",
+    "q_variable_group_add",
+    "q_lookup",
+];
+
+/// Intermediate representation of a circuit.
+///
+/// The representation is serialized with [`CIRCUIT_IR_VERSION`] in front of
+/// it, and deserializing a representation of another version fails.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitIr<F>
+where
+    F: PrimeField,
+{
+    /// Number of gates
+    pub n: usize,
+    /// Blinding strategy
+    pub blinding: Blinding,
+    /// Layout of the keys and proofs
+    pub gate_layout: GateLayout,
+    /// Selectors of the built-in gates, by name, with one value per gate
+    pub selectors: Vec<(String, Vec<F>)>,
+    /// Selectors of the custom gates, by name of the gate, with one value
+    /// per gate
+    pub custom_selectors: Vec<(String, Vec<F>)>,
+    /// Indices of the variables in the left, right, output and fourth wires,
+    /// with one index per gate
+    pub wires: [Vec<usize>; 4],
+    /// Wires each variable is copied to, indexed by variable. The first
+    /// variable is the zero variable.
+    pub copy_constraints: Vec<Vec<WireData>>,
+    /// Positions reserved for public inputs
+    pub pi_pos: Vec<usize>,
+    /// Rows of the lookup table
+    pub lookup_table: Vec<[F; 4]>,
+}
+
+impl<F> CircuitIr<F>
+where
+    F: PrimeField,
+{
+    /// Checks that the representation describes a well-formed circuit.
+    fn check(&self) -> Result<(), Error> {
+        let selectors_ok = self.selectors.len() == SELECTOR_NAMES.len()
+            && self.selectors.iter().zip(SELECTOR_NAMES).all(
+                |((name, values), expected)| {
+                    name == expected && values.len() == self.n
+                },
+            );
+        let custom_selectors_ok = self
+            .custom_selectors
+            .iter()
+            .all(|(_, values)| values.len() == self.n);
+
+        let variables = self.copy_constraints.len();
+        let wires_ok = variables > 0
+            && self.wires.iter().all(|wire| {
+                wire.len() == self.n && wire.iter().all(|&var| var < variables)
+            });
+        // The copy constraints must list every wire exactly once, under the
+        // variable it holds, or the keys would leave some wires unconstrained.
+        let mut listed = [
+            vec![false; self.n],
+            vec![false; self.n],
+            vec![false; self.n],
+            vec![false; self.n],
+        ];
+        let copy_constraints_ok = wires_ok
+            && self
+                .copy_constraints
+                .iter()
+                .enumerate()
+                .all(|(var, wires)| {
+                    wires.iter().all(|wire| {
+                        let (column, i) = match *wire {
+                            WireData::Left(i) => (0, i),
+                            WireData::Right(i) => (1, i),
+                            WireData::Output(i) => (2, i),
+                            WireData::Fourth(i) => (3, i),
+                        };
+                        self.wires[column].get(i) == Some(&var)
+                            && !core::mem::replace(&mut listed[column][i], true)
+                    })
+                })
+            && listed.iter().flatten().all(|&listed| listed);
+        let pi_pos_ok = self.pi_pos.iter().all(|&pos| pos < self.n);
+
+        if selectors_ok
+            && custom_selectors_ok
+            && copy_constraints_ok
+            && pi_pos_ok
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidCircuitIr)
+        }
+    }
+}
+
+impl<F> CanonicalSerialize for CircuitIr<F>
+where
+    F: PrimeField,
+{
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        CIRCUIT_IR_VERSION.serialize(&mut writer)?;
+        self.n.serialize(&mut writer)?;
+        (self.blinding as u8).serialize(&mut writer)?;
+        (self.gate_layout as u8).serialize(&mut writer)?;
+        self.selectors.serialize(&mut writer)?;
+        self.custom_selectors.serialize(&mut writer)?;
+        for wire in &self.wires {
+            wire.serialize(&mut writer)?;
+        }
+        self.copy_constraints.len().serialize(&mut writer)?;
+        for wires in &self.copy_constraints {
+            wires.len().serialize(&mut writer)?;
+            for wire in wires {
+                let (column, i) = match *wire {
+                    WireData::Left(i) => (0u8, i),
+                    WireData::Right(i) => (1, i),
+                    WireData::Output(i) => (2, i),
+                    WireData::Fourth(i) => (3, i),
+                };
+                column.serialize(&mut writer)?;
+                i.serialize(&mut writer)?;
+            }
+        }
+        self.pi_pos.serialize(&mut writer)?;
+        self.lookup_table.len().serialize(&mut writer)?;
+        for row in &self.lookup_table {
+            for value in row {
+                value.serialize(&mut writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        CIRCUIT_IR_VERSION.serialized_size()
+            + self.n.serialized_size()
+            + 2
+            + self.selectors.serialized_size()
+            + self.custom_selectors.serialized_size()
+            + self
+                .wires
+                .iter()
+                .map(|wire| wire.serialized_size())
+                .sum::<usize>()
+            + self.copy_constraints.len().serialized_size()
+            + self
+                .copy_constraints
+                .iter()
+                .map(|wires| {
+                    wires.len().serialized_size()
+                        + wires.len() * (1 + 0usize.serialized_size())
+                })
+                .sum::<usize>()
+            + self.pi_pos.serialized_size()
+            + self.lookup_table.len().serialized_size()
+            + self
+                .lookup_table
+                .iter()
+                .flatten()
+                .map(|value| value.serialized_size())
+                .sum::<usize>()
+    }
+}
+
+impl<F> CanonicalDeserialize for CircuitIr<F>
+where
+    F: PrimeField,
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        if u32::deserialize(&mut reader)? != CIRCUIT_IR_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        let n = usize::deserialize(&mut reader)?;
+        let blinding = match u8::deserialize(&mut reader)? {
+            0 => Blinding::Polynomials,
+            1 => Blinding::DummyGates,
+            _ => return Err(SerializationError::InvalidData),
+        };
+        let gate_layout = match u8::deserialize(&mut reader)? {
+            0 => GateLayout::Compact,
+            1 => GateLayout::Full,
+            _ => return Err(SerializationError::InvalidData),
+        };
+        let selectors = Vec::deserialize(&mut reader)?;
+        let custom_selectors = Vec::deserialize(&mut reader)?;
+        let wires = [
+            Vec::deserialize(&mut reader)?,
+            Vec::deserialize(&mut reader)?,
+            Vec::deserialize(&mut reader)?,
+            Vec::deserialize(&mut reader)?,
+        ];
+        let copy_constraints: Vec<Vec<WireData>> =
+            (0..usize::deserialize(&mut reader)?)
+                .map(|_| {
+                    (0..usize::deserialize(&mut reader)?)
+                        .map(|_| {
+                            let column = u8::deserialize(&mut reader)?;
+                            let i = usize::deserialize(&mut reader)?;
+                            match column {
+                                0 => Ok(WireData::Left(i)),
+                                1 => Ok(WireData::Right(i)),
+                                2 => Ok(WireData::Output(i)),
+                                3 => Ok(WireData::Fourth(i)),
+                                _ => Err(SerializationError::InvalidData),
+                            }
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<_, _>>()?;
+        let pi_pos = Vec::deserialize(&mut reader)?;
+        let lookup_table = (0..usize::deserialize(&mut reader)?)
+            .map(|_| {
+                Ok([
+                    F::deserialize(&mut reader)?,
+                    F::deserialize(&mut reader)?,
+                    F::deserialize(&mut reader)?,
+                    F::deserialize(&mut reader)?,
+                ])
+            })
+            .collect::<Result<_, SerializationError>>()?;
+
+        Ok(Self {
+            n,
+            blinding,
+            gate_layout,
+            selectors,
+            custom_selectors,
+            wires,
+            copy_constraints,
+            pi_pos,
+            lookup_table,
+        })
+    }
+}
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: ModelParameters<BaseField = F>,
+{
+    /// Returns the [`CircuitIr`] of the circuit built so far.
+    pub fn export_ir(&self) -> CircuitIr<F> {
+        let selectors = [
+            &self.q_m,
+            &self.q_l,
+            &self.q_r,
+            &self.q_o,
+            &self.q_4,
+            &self.q_c,
+            &self.q_hl,
+            &self.q_hr,
+            &self.q_h4,
+            &self.q_arith,
+            &self.q_range,
+            &self.q_logic,
+            &self.q_fixed_group_add,
+            &self.q_variable_group_add,
+            &self.q_lookup,
+        ];
+        let custom_selectors = self
+            .custom_gates
+            .iter()
+            .zip(&self.q_custom)
+            .map(|(gate, q_custom)| {
+                let mut values = q_custom.clone();
+                values.resize(self.n, F::zero());
+                (gate.name().to_string(), values)
+            })
+            .collect();
+        let wire_indices =
+            |wire: &[Variable]| wire.iter().map(|var| var.0).collect();
+        let copy_constraints = self.perm.variable_map.clone();
+
+        CircuitIr {
+            n: self.n,
+            blinding: self.blinding,
+            gate_layout: self.gate_layout,
+            selectors: SELECTOR_NAMES
+                .iter()
+                .zip(selectors)
+                .map(|(name, values)| (name.to_string(), values.clone()))
+                .collect(),
+            custom_selectors,
+            wires: [
+                wire_indices(&self.w_l),
+                wire_indices(&self.w_r),
+                wire_indices(&self.w_o),
+                wire_indices(&self.w_4),
+            ],
+            copy_constraints,
+            pi_pos: self.intended_pi_pos.clone(),
+            lookup_table: self.lookup_table.0.clone(),
+        }
+    }
+
+    /// Rebuilds the circuit described by `ir` into a setup-only composer,
+    /// from which its keys can be preprocessed.
+    ///
+    /// The custom gates of the circuit have to be passed in `custom_gates`,
+    /// since the representation only stores their names and selectors.
+    pub fn from_ir(
+        ir: &CircuitIr<F>,
+        custom_gates: &[CustomGate<F>],
+    ) -> Result<Self, Error> {
+        ir.check()?;
+
+        let mut composer = Self::with_expected_size(ir.n).setup_only();
+        composer.variables.clear();
+        composer.n = ir.n;
+        composer.blinding = ir.blinding;
+        composer.gate_layout = ir.gate_layout;
+        for (selector, (_, values)) in [
+            &mut composer.q_m,
+            &mut composer.q_l,
+            &mut composer.q_r,
+            &mut composer.q_o,
+            &mut composer.q_4,
+            &mut composer.q_c,
+            &mut composer.q_hl,
+            &mut composer.q_hr,
+            &mut composer.q_h4,
+            &mut composer.q_arith,
+            &mut composer.q_range,
+            &mut composer.q_logic,
+            &mut composer.q_fixed_group_add,
+            &mut composer.q_variable_group_add,
+            &mut composer.q_lookup,
+        ]
+        .into_iter()
+        .zip(&ir.selectors)
+        {
+            *selector = values.clone();
+        }
+
+        composer.custom_gates = ir
+            .custom_selectors
+            .iter()
+            .map(|(name, _)| {
+                custom_gates
+                    .iter()
+                    .find(|gate| gate.name() == name)
+                    .copied()
+                    .ok_or_else(|| Error::CustomGateNotRegistered {
+                        name: name.clone(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        composer.q_custom = ir
+            .custom_selectors
+            .iter()
+            .map(|(_, values)| values.clone())
+            .collect();
+
+        for (wire, indices) in [
+            &mut composer.w_l,
+            &mut composer.w_r,
+            &mut composer.w_o,
+            &mut composer.w_4,
+        ]
+        .into_iter()
+        .zip(&ir.wires)
+        {
+            *wire = indices.iter().map(|&var| Variable(var)).collect();
+        }
+        composer.perm.variable_map = ir.copy_constraints.clone();
+        composer.zero_var = Variable(0);
+
+        composer.intended_pi_pos = ir.pi_pos.clone();
+        composer.lookup_table = LookupTable(ir.lookup_table.clone());
+        Ok(composer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test, circuit::trim_params, commitment::HomomorphicCommitment,
+        constraint_system::helper::sum_range_xor_gadget,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+    use core::marker::PhantomData;
+    use rand_core::OsRng;
+
+    fn test_composer<F, P>() -> StandardComposer<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::new();
+        sum_range_xor_gadget(
+            &mut composer,
+            F::from(5u64),
+            F::from(10u64),
+            F::from(15u64),
+            F::from(5u64 ^ 10),
+        );
+        composer
+    }
+
+    /// Tests that keys preprocessed from a deserialized IR match the keys of
+    /// the original circuit.
+    fn test_ir_round_trip<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut composer = test_composer::<F, P>();
+        let ir = composer.export_ir();
+        let mut bytes = Vec::new();
+        ir.serialize(&mut bytes).unwrap();
+        let ir = CircuitIr::<F>::deserialize(&bytes[..]).unwrap();
+        assert_eq!(ir, composer.export_ir());
+
+        let mut rebuilt = StandardComposer::<F, P>::from_ir(&ir, &[]).unwrap();
+        assert_eq!(rebuilt.circuit_id(), composer.circuit_id());
+        assert_eq!(rebuilt.export_ir(), ir);
+
+        let u_params =
+            PC::setup(2 * composer.circuit_bound(), None, &mut OsRng).unwrap();
+        let (ck, _) =
+            trim_params::<F, PC>(&u_params, composer.circuit_bound()).unwrap();
+        let (pk, vk) = composer.preprocess(&ck, PhantomData::<PC>).unwrap();
+        let (rebuilt_pk, rebuilt_vk) =
+            rebuilt.preprocess(&ck, PhantomData::<PC>).unwrap();
+        assert!(pk == rebuilt_pk);
+        let (mut vk_bytes, mut rebuilt_vk_bytes) = (Vec::new(), Vec::new());
+        vk.serialize(&mut vk_bytes).unwrap();
+        rebuilt_vk.serialize(&mut rebuilt_vk_bytes).unwrap();
+        assert_eq!(vk_bytes, rebuilt_vk_bytes);
+    }
+
+    /// Tests that malformed representations are rejected.
+    fn test_malformed_ir<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let ir = test_composer::<F, P>().export_ir();
+
+        let mut bytes = Vec::new();
+        ir.serialize(&mut bytes).unwrap();
+        bytes[0] ^= 0xff;
+        assert!(CircuitIr::<F>::deserialize(&bytes[..]).is_err());
+
+        let mut tampered = ir.clone();
+        tampered.wires[0][0] = ir.copy_constraints.len();
+        assert!(matches!(
+            StandardComposer::<F, P>::from_ir(&tampered, &[]),
+            Err(Error::InvalidCircuitIr)
+        ));
+
+        let mut tampered = ir.clone();
+        tampered.copy_constraints[0].push(WireData::Left(tampered.n));
+        assert!(matches!(
+            StandardComposer::<F, P>::from_ir(&tampered, &[]),
+            Err(Error::InvalidCircuitIr)
+        ));
+
+        // Every wire must be listed exactly once in the copy constraints.
+        let mut tampered = ir.clone();
+        let wire = tampered.copy_constraints[0].pop().unwrap();
+        assert!(matches!(
+            StandardComposer::<F, P>::from_ir(&tampered, &[]),
+            Err(Error::InvalidCircuitIr)
+        ));
+        tampered.copy_constraints[0].extend([wire, wire]);
+        assert!(matches!(
+            StandardComposer::<F, P>::from_ir(&tampered, &[]),
+            Err(Error::InvalidCircuitIr)
+        ));
+
+        let mut tampered = ir;
+        tampered.pi_pos.push(tampered.n);
+        assert!(matches!(
+            StandardComposer::<F, P>::from_ir(&tampered, &[]),
+            Err(Error::InvalidCircuitIr)
+        ));
+    }
+
+    // Tests for Bls12_381
+    batch_test!(
+        [test_ir_round_trip, test_malformed_ir],
+        [] => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12_377
+    batch_test!(
+        [test_ir_round_trip, test_malformed_ir],
+        [] => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
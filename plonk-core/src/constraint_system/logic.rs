@@ -53,14 +53,16 @@ where
         let mut left_quad: u8;
         let mut right_quad: u8;
         // Get vars as bits and reverse them to get the Little Endian repr.
-        let a_bits: Vec<_> = self.variables[&a]
+        let a_bits: Vec<_> = self
+            .value_of_var(a)
             .into_repr()
             .to_bits_be()
             .iter()
             .skip(256 - num_bits)
             .map(|bit| *bit as u8)
             .collect();
-        let b_bits: Vec<_> = self.variables[&b]
+        let b_bits: Vec<_> = self
+            .value_of_var(b)
             .into_repr()
             .to_bits_be()
             .iter()
@@ -74,11 +74,16 @@ where
 mod test {
     use super::*;
     use crate::{
-        batch_test, commitment::HomomorphicCommitment,
-        constraint_system::helper::*, lookup::LookupTable,
+        batch_test,
+        commitment::HomomorphicCommitment,
+        constraint_system::helper::*,
+        error::to_pc_error,
+        lookup::LookupTable,
+        proof_system::{Prover, Verifier},
     };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use ark_serialize::CanonicalSerialize;
     use rand_core::{OsRng, RngCore};
 
     fn test_plookup_xor<F, P, PC>()
@@ -148,10 +153,83 @@ mod test {
         assert!(res.is_ok(), "{:?}", res.err().unwrap());
     }
 
+    /// Adds the same xor lookups on every call.
+    fn fixed_xor_gadget<F, P>(composer: &mut StandardComposer<F, P>)
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        composer.lookup_table = LookupTable::<F>::xor_table(0, 4);
+        let negative_one = composer.add_input(-F::one());
+        for (a, b) in [(3u64, 5u64), (12, 9), (7, 7)] {
+            let a_var = composer.add_input(F::from(a));
+            let b_var = composer.add_input(F::from(b));
+            let xor_var = composer.add_input(F::from(a ^ b));
+            composer.lookup_gate(
+                a_var,
+                b_var,
+                xor_var,
+                Some(negative_one),
+                None,
+            );
+        }
+    }
+
+    fn test_plookup_blinding<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let to_bytes = |commitment: &PC::Commitment| {
+            let mut bytes = Vec::new();
+            commitment.serialize(&mut bytes).unwrap();
+            bytes
+        };
+
+        let universal_params = PC::setup(512, None, &mut OsRng)
+            .map_err(to_pc_error::<F, PC>)
+            .unwrap();
+
+        let mut prover = Prover::<F, P, PC>::new(b"blinding");
+        fixed_xor_gadget(prover.mut_cs());
+        let (ck, vk) =
+            PC::trim(&universal_params, prover.commit_key_degree(), 0, None)
+                .map_err(to_pc_error::<F, PC>)
+                .unwrap();
+        prover.preprocess(&ck).unwrap();
+        let public_inputs = prover.cs.get_pi().clone();
+
+        // Prove the same witness twice.
+        let first = prover.prove(&ck, &mut OsRng).unwrap();
+        fixed_xor_gadget(prover.mut_cs());
+        let second = prover.prove(&ck, &mut OsRng).unwrap();
+
+        let mut verifier = Verifier::<F, P, PC>::new(b"blinding");
+        fixed_xor_gadget(verifier.mut_cs());
+        verifier.preprocess(&ck).unwrap();
+        for proof in [&first, &second] {
+            let res = verifier.verify(proof, &vk, &public_inputs);
+            assert!(res.is_ok(), "{:?}", res.err().unwrap());
+        }
+
+        // The blinders make the lookup commitments of the two proofs differ.
+        for (first, second) in [
+            (&first.f_comm, &second.f_comm),
+            (&first.h_1_comm, &second.h_1_comm),
+            (&first.h_2_comm, &second.h_2_comm),
+            (&first.z_2_comm, &second.z_2_comm),
+        ] {
+            let (first, second) = (first.as_ref(), second.as_ref());
+            assert_ne!(to_bytes(first.unwrap()), to_bytes(second.unwrap()));
+        }
+    }
+
     // Bls12-381 tests
     batch_test!(
         [
-            test_plookup_xor
+            test_plookup_xor,
+            test_plookup_blinding
         ],
         [] => (
             Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
@@ -161,7 +239,8 @@ mod test {
     // Bls12-377 tests
     batch_test!(
         [
-            test_plookup_xor
+            test_plookup_xor,
+            test_plookup_blinding
         ],
         [] => (
             Bls12_377, ark_ed_on_bls12_377::EdwardsParameters
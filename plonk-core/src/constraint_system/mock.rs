@@ -0,0 +1,588 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Satisfiability checks with structured diagnostics.
+//!
+//! [`MockProver`] lays out a [`Circuit`] and checks its witness against every
+//! gate, copy constraint and lookup of the circuit without committing to
+//! anything, which makes it cheap enough to run in unit tests and before
+//! proving.
+
+use crate::{
+    circuit::Circuit,
+    constraint_system::{StandardComposer, WireData, SBOX_ALPHA},
+    error::Error,
+    proof_system::{
+        ecc::{CAVals, CurveAddition, FBSMVals, FixedBaseScalarMul},
+        logic::{Logic, LogicVals},
+        range::{Range, RangeVals},
+        CustomEvaluations, CustomValues, GateConstraint, WitnessValues,
+    },
+};
+use ark_ec::TEModelParameters;
+use ark_ff::PrimeField;
+use blake2::{digest::Digest, Blake2s};
+use core::fmt;
+use rand_core::{CryptoRng, RngCore};
+
+/// Seed of the separation challenge the constraints of the gates are combined
+/// with.
+const SEPARATION_CHALLENGE_SEED: &[u8] = b"plonk-mock-prover-v1";
+
+/// Kind of gate a [`Failure`] refers to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GateKind {
+    /// Arithmetic gate, including the public input of the row.
+    Arithmetic,
+    /// Range gate.
+    Range,
+    /// Logic gate.
+    Logic,
+    /// Fixed base scalar multiplication gate.
+    FixedBaseScalarMul,
+    /// Curve addition gate of the variable base scalar multiplication.
+    CurveAddition,
+    /// Lookup gate.
+    Lookup,
+    /// Custom gate registered under the given name.
+    Custom(&'static str),
+}
+
+/// Constraint of a circuit which its witness does not satisfy.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, Eq, PartialEq)]
+pub enum Failure<F>
+where
+    F: PrimeField,
+{
+    /// The gate at `index` does not hold for its wire values. For lookup
+    /// gates, the wire values are missing from the lookup table.
+    Gate {
+        /// Index of the gate
+        index: usize,
+        /// Kind of the failing gate
+        kind: GateKind,
+        /// Values of the wires of the gate
+        wires: WitnessValues<F>,
+        /// Values of the selectors of the gate, by name
+        selectors: Vec<(&'static str, F)>,
+    },
+    /// The value of `wire` differs from the value of `first`, the first wire
+    /// of its cycle in the copy permutation, which every wire of the cycle
+    /// must hold.
+    CopyConstraint {
+        /// First wire of the cycle
+        first: WireData,
+        /// Wire holding a different value
+        wire: WireData,
+        /// Value of the first wire of the cycle
+        expected: F,
+        /// Value of the wire
+        found: F,
+    },
+}
+
+impl<F> fmt::Display for Failure<F>
+where
+    F: PrimeField,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gate {
+                index, kind, wires, ..
+            } => write!(
+                f,
+                "{:?} gate {} is not satisfied by the wires ({}, {}, {}, {})",
+                kind, index, wires.a_val, wires.b_val, wires.c_val, wires.d_val
+            ),
+            Self::CopyConstraint {
+                first,
+                wire,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{:?} holds {} instead of the value {} of {:?}, which it is \
+                 copied from",
+                wire, found, expected, first
+            ),
+        }
+    }
+}
+
+/// Checks the witness of a circuit against its constraints, reporting every
+/// unsatisfied one instead of failing at the first.
+pub struct MockProver<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Composer holding the circuit and its witness.
+    cs: StandardComposer<F, P>,
+}
+
+impl<F, P> MockProver<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Lays out `circuit` with its witness the same way
+    /// [`Circuit::gen_proof`] does.
+    pub fn run<C, R>(circuit: &mut C, rng: &mut R) -> Result<Self, Error>
+    where
+        C: Circuit<F, P>,
+        R: CryptoRng + RngCore,
+    {
+        let mut cs = StandardComposer::with_blinding(0, C::BLINDING, rng);
+        circuit.gadget(&mut cs)?;
+        Ok(Self { cs })
+    }
+
+    /// Returns the composer the circuit was laid out in.
+    pub fn composer(&self) -> &StandardComposer<F, P> {
+        &self.cs
+    }
+
+    /// Returns `Ok` if the witness satisfies the circuit and every
+    /// [`Failure`] otherwise.
+    pub fn verify(&self) -> Result<(), Vec<Failure<F>>> {
+        let failures = self.cs.unsatisfied_constraints();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+}
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: TEModelParameters<BaseField = F>,
+{
+    /// Returns every gate, copy constraint and lookup of the circuit which
+    /// its witness does not satisfy, in gate order.
+    ///
+    /// The gates read the wires of the next row the same way the prover
+    /// does, so the last gate wraps around to the padding or to the first
+    /// gate of the circuit.
+    pub fn unsatisfied_constraints(&self) -> Vec<Failure<F>> {
+        let mut failures = Vec::new();
+        let bound = self.circuit_bound();
+        let pi = self.public_inputs.as_evals(bound);
+        let challenge = F::from_le_bytes_mod_order(&Blake2s::digest(
+            SEPARATION_CHALLENGE_SEED,
+        ));
+
+        for i in 0..self.n {
+            let wires = self.wire_values(i);
+            let next = if i + 1 < self.n {
+                self.wire_values(i + 1)
+            } else if self.n < bound {
+                WitnessValues::default()
+            } else {
+                self.wire_values(0)
+            };
+            let custom_vals = CustomEvaluations {
+                vals: vec![
+                    ("a_next_eval".to_string(), next.a_val),
+                    ("b_next_eval".to_string(), next.b_val),
+                    ("d_next_eval".to_string(), next.d_val),
+                    ("q_l_eval".to_string(), self.q_l[i]),
+                    ("q_r_eval".to_string(), self.q_r[i]),
+                    ("q_c_eval".to_string(), self.q_c[i]),
+                    ("q_hl_eval".to_string(), self.q_hl[i]),
+                    ("q_hr_eval".to_string(), self.q_hr[i]),
+                    ("q_h4_eval".to_string(), self.q_h4[i]),
+                ],
+            };
+
+            let arithmetic = (wires.a_val * wires.b_val * self.q_m[i]
+                + wires.a_val * self.q_l[i]
+                + wires.b_val * self.q_r[i]
+                + wires.c_val * self.q_o[i]
+                + wires.d_val * self.q_4[i]
+                + wires.a_val.pow([SBOX_ALPHA]) * self.q_hl[i]
+                + wires.b_val.pow([SBOX_ALPHA]) * self.q_hr[i]
+                + wires.d_val.pow([SBOX_ALPHA]) * self.q_h4[i]
+                + self.q_c[i])
+                * self.q_arith[i]
+                + pi[i];
+            // A gate reading an evaluation which is missing is reported as
+            // unsatisfied.
+            let range = RangeVals::from_evaluations(&custom_vals).map(|vals| {
+                Range::quotient_term(self.q_range[i], challenge, wires, vals)
+            });
+            let logic = LogicVals::from_evaluations(&custom_vals).map(|vals| {
+                Logic::quotient_term(self.q_logic[i], challenge, wires, vals)
+            });
+            let fixed_base_scalar_mul =
+                FBSMVals::from_evaluations(&custom_vals).map(|vals| {
+                    FixedBaseScalarMul::<_, P>::quotient_term(
+                        self.q_fixed_group_add[i],
+                        challenge,
+                        wires,
+                        vals,
+                    )
+                });
+            let curve_addition =
+                CAVals::from_evaluations(&custom_vals).map(|vals| {
+                    CurveAddition::<_, P>::quotient_term(
+                        self.q_variable_group_add[i],
+                        challenge,
+                        wires,
+                        vals,
+                    )
+                });
+            let lookup = !self.q_lookup[i].is_zero()
+                && !self.lookup_table.0.contains(&[
+                    wires.a_val,
+                    wires.b_val,
+                    wires.c_val,
+                    wires.d_val,
+                ]);
+
+            let mut unsatisfied = Vec::new();
+            for (kind, term) in [
+                (GateKind::Arithmetic, Ok(arithmetic)),
+                (GateKind::Range, range),
+                (GateKind::Logic, logic),
+                (GateKind::FixedBaseScalarMul, fixed_base_scalar_mul),
+                (GateKind::CurveAddition, curve_addition),
+            ] {
+                if term.map_or(true, |term| !term.is_zero()) {
+                    unsatisfied.push(kind);
+                }
+            }
+            if lookup {
+                unsatisfied.push(GateKind::Lookup);
+            }
+            for (gate, q_custom) in self.custom_gates.iter().zip(&self.q_custom)
+            {
+                let selector = q_custom.get(i).copied().unwrap_or_default();
+                if gate
+                    .quotient_term(selector, challenge, wires, &custom_vals)
+                    .map_or(true, |term| !term.is_zero())
+                {
+                    unsatisfied.push(GateKind::Custom(gate.name()));
+                }
+            }
+
+            failures.extend(unsatisfied.into_iter().map(|kind| {
+                Failure::Gate {
+                    index: i,
+                    kind,
+                    wires,
+                    selectors: self.selector_values(i),
+                }
+            }));
+        }
+
+        failures.extend(self.broken_copy_constraints());
+        failures
+    }
+
+    /// Returns the values of the wires of gate `i`.
+    fn wire_values(&self, i: usize) -> WitnessValues<F> {
+        WitnessValues {
+            a_val: self.value_of_var(self.w_l[i]),
+            b_val: self.value_of_var(self.w_r[i]),
+            c_val: self.value_of_var(self.w_o[i]),
+            d_val: self.value_of_var(self.w_4[i]),
+        }
+    }
+
+    /// Returns the values of the selectors of gate `i`, by name.
+    fn selector_values(&self, i: usize) -> Vec<(&'static str, F)> {
+        let mut selectors = vec![
+            ("q_m", self.q_m[i]),
+            ("q_l", self.q_l[i]),
+            ("q_r", self.q_r[i]),
+            ("q_o", self.q_o[i]),
+            ("q_4", self.q_4[i]),
+            ("q_c", self.q_c[i]),
+            ("q_hl", self.q_hl[i]),
+            ("q_hr", self.q_hr[i]),
+            ("q_h4", self.q_h4[i]),
+            ("q_arith", self.q_arith[i]),
+            ("q_range", self.q_range[i]),
+            ("q_logic", self.q_logic[i]),
+            ("q_fixed_group_add", self.q_fixed_group_add[i]),
+            ("q_variable_group_add", self.q_variable_group_add[i]),
+            ("q_lookup", self.q_lookup[i]),
+        ];
+        selectors.extend(self.custom_gates.iter().zip(&self.q_custom).map(
+            |(gate, q_custom)| {
+                (gate.name(), q_custom.get(i).copied().unwrap_or_default())
+            },
+        ));
+        selectors
+    }
+
+    /// Returns the wires whose values differ from the value of the first
+    /// wire of their cycle in the copy permutation, ordered by cycle.
+    ///
+    /// The cycles are walked through the sigma permutation the keys of the
+    /// circuit are preprocessed from, and the cycles are visited in the order
+    /// of their first wire, taking the wires of each gate from left to
+    /// fourth.
+    fn broken_copy_constraints(&self) -> Vec<Failure<F>> {
+        let sigmas = self.perm.compute_sigma_permutations(self.n);
+        let position = |wire: WireData| match wire {
+            WireData::Left(i) => i,
+            WireData::Right(i) => self.n + i,
+            WireData::Output(i) => 2 * self.n + i,
+            WireData::Fourth(i) => 3 * self.n + i,
+        };
+        let value = |wire: WireData| {
+            let variable = match wire {
+                WireData::Left(i) => self.w_l[i],
+                WireData::Right(i) => self.w_r[i],
+                WireData::Output(i) => self.w_o[i],
+                WireData::Fourth(i) => self.w_4[i],
+            };
+            self.value_of_var(variable)
+        };
+
+        let mut failures = Vec::new();
+        let mut visited = vec![false; 4 * self.n];
+        for first in (0..self.n).flat_map(|i| {
+            [
+                WireData::Left(i),
+                WireData::Right(i),
+                WireData::Output(i),
+                WireData::Fourth(i),
+            ]
+        }) {
+            if visited[position(first)] {
+                continue;
+            }
+            let expected = value(first);
+            let mut wire = first;
+            // A wire mapped to several variables makes the mapping fail to
+            // be a permutation, so the walk also stops at visited wires.
+            while !visited[position(wire)] {
+                visited[position(wire)] = true;
+                let found = value(wire);
+                if found != expected {
+                    failures.push(Failure::CopyConstraint {
+                        first,
+                        wire,
+                        expected,
+                        found,
+                    });
+                }
+                wire = match wire {
+                    WireData::Left(i) => sigmas[0][i],
+                    WireData::Right(i) => sigmas[1][i],
+                    WireData::Output(i) => sigmas[2][i],
+                    WireData::Fourth(i) => sigmas[3][i],
+                };
+            }
+        }
+        failures
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test_field_params,
+        constraint_system::helper::sum_range_xor_gadget,
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use rand_core::OsRng;
+
+    // Circuit laid out by `sum_range_xor_gadget`.
+    #[derive(derivative::Derivative)]
+    #[derivative(Debug(bound = ""), Default(bound = ""))]
+    struct TestCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        a: F,
+        b: F,
+        c: F,
+        d: F,
+        _marker: core::marker::PhantomData<P>,
+    }
+
+    impl<F, P> Circuit<F, P> for TestCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<F, P>,
+        ) -> Result<(), Error> {
+            sum_range_xor_gadget(composer, self.a, self.b, self.c, self.d);
+            Ok(())
+        }
+    }
+
+    fn circuit<F, P>(a: u64, b: u64) -> TestCircuit<F, P>
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        TestCircuit {
+            a: F::from(a),
+            b: F::from(b),
+            c: F::from(a + b),
+            d: F::from(a ^ b),
+            ..Default::default()
+        }
+    }
+
+    fn gate_kinds<F>(failures: &[Failure<F>]) -> Vec<GateKind>
+    where
+        F: PrimeField,
+    {
+        failures
+            .iter()
+            .filter_map(|failure| match failure {
+                Failure::Gate { kind, .. } => Some(*kind),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn test_satisfied_circuit<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mock =
+            MockProver::run(&mut circuit::<F, P>(5, 10), &mut OsRng).unwrap();
+        assert_eq!(mock.verify(), Ok(()));
+    }
+
+    fn test_unsatisfied_gates<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        // Wrong public input.
+        let mut wrong_sum = circuit::<F, P>(5, 10);
+        wrong_sum.c += F::one();
+        let failures = MockProver::run(&mut wrong_sum, &mut OsRng)
+            .unwrap()
+            .verify()
+            .unwrap_err();
+        assert_eq!(failures.len(), 1);
+        match &failures[0] {
+            Failure::Gate {
+                index,
+                kind,
+                wires,
+                selectors,
+            } => {
+                // The first gate constrains the zero variable.
+                assert_eq!(*index, 1);
+                assert_eq!(*kind, GateKind::Arithmetic);
+                assert_eq!(wires.a_val, F::from(5u64));
+                assert_eq!(wires.b_val, F::from(10u64));
+                assert!(selectors.contains(&("q_arith", F::one())));
+            }
+            failure => panic!("unexpected failure {}", failure),
+        }
+
+        // Values out of the range and missing from the lookup table.
+        let failures =
+            MockProver::run(&mut circuit::<F, P>(256, 10), &mut OsRng)
+                .unwrap()
+                .verify()
+                .unwrap_err();
+        let kinds = gate_kinds(&failures);
+        assert!(kinds.contains(&GateKind::Range));
+        assert!(kinds.contains(&GateKind::Lookup));
+        assert!(!kinds.contains(&GateKind::Arithmetic));
+    }
+
+    fn test_broken_copy_constraint<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        let a = composer.add_input(F::from(3u64));
+        let b = composer.add_input(F::from(4u64));
+        composer.assert_equal(a, a);
+        let gate = composer.n - 1;
+        // Rewire the right wire of the gate behind the back of the
+        // permutation.
+        composer.w_r[gate] = b;
+
+        let failures = composer.unsatisfied_constraints();
+        assert!(failures.contains(&Failure::CopyConstraint {
+            first: WireData::Left(gate),
+            wire: WireData::Right(gate),
+            expected: F::from(3u64),
+            found: F::from(4u64),
+        }));
+        assert!(gate_kinds(&failures).contains(&GateKind::Arithmetic));
+
+        // Copy a wire holding `a` into the cycle of `b` in the permutation,
+        // leaving the wires and gates untouched.
+        let mut composer = StandardComposer::<F, P>::new();
+        let a = composer.add_input(F::from(3u64));
+        let b = composer.add_input(F::from(4u64));
+        composer.assert_equal(a, a);
+        let gate = composer.n - 1;
+        composer.assert_equal(b, b);
+        let other_gate = composer.n - 1;
+        composer.perm.variable_map[a.0]
+            .retain(|wire| !matches!(wire, WireData::Right(i) if *i == gate));
+        composer.perm.variable_map[b.0].push(WireData::Right(gate));
+
+        let failures = composer.unsatisfied_constraints();
+        assert!(gate_kinds(&failures).is_empty());
+        assert_eq!(
+            failures,
+            [WireData::Left(other_gate), WireData::Right(other_gate)]
+                .into_iter()
+                .map(|wire| Failure::CopyConstraint {
+                    first: WireData::Right(gate),
+                    wire,
+                    expected: F::from(3u64),
+                    found: F::from(4u64),
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+
+    // Tests for Bls12_381
+    batch_test_field_params!(
+        [
+            test_satisfied_circuit,
+            test_unsatisfied_gates,
+            test_broken_copy_constraint
+        ],
+        [] => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12_377
+    batch_test_field_params!(
+        [
+            test_satisfied_circuit,
+            test_unsatisfied_gates,
+            test_broken_copy_constraint
+        ],
+        [] => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
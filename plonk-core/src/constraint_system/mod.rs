@@ -10,10 +10,15 @@
 
 mod arithmetic;
 mod boolean;
+mod custom;
 mod hash;
+mod ir;
 mod logic;
 mod lookup;
+mod mock;
+mod profile;
 mod range;
+mod witness;
 
 pub(crate) mod composer;
 pub(crate) mod helper;
@@ -22,7 +27,11 @@ pub(crate) mod variable;
 pub mod ecc;
 
 pub(crate) use hash::SBOX_ALPHA;
-pub(crate) use variable::WireData;
 
-pub use composer::StandardComposer;
-pub use variable::Variable;
+pub use composer::{Blinding, GateLayout, StandardComposer};
+pub use custom::CustomGateId;
+pub use ir::{CircuitIr, CIRCUIT_IR_VERSION};
+pub use mock::{Failure, GateKind, MockProver};
+pub use profile::{CircuitProfile, GateCounts, NamespaceProfile};
+pub use variable::{Variable, WireData};
+pub use witness::{Witness, WITNESS_VERSION};
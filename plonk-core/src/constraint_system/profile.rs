@@ -0,0 +1,539 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Namespaces and gate-level profiling of circuits.
+//!
+//! Gadgets can be wrapped in named scopes with
+//! [`StandardComposer::namespace`], and [`StandardComposer::profile`] reports
+//! the gates and variables each scope adds to the circuit, so that the cost
+//! of a circuit can be tracked down to its parts.
+
+use crate::constraint_system::StandardComposer;
+use alloc::collections::BTreeMap;
+use ark_ec::ModelParameters;
+use ark_ff::PrimeField;
+use core::fmt::{self, Write};
+use core::ops::Range;
+
+/// Gates and variables added to a composer within a call to
+/// [`StandardComposer::namespace`].
+#[derive(Clone, Debug)]
+pub(crate) struct NamespaceSpan {
+    /// Name of the namespace.
+    name: String,
+
+    /// Index of the enclosing namespace, if any.
+    parent: Option<usize>,
+
+    /// Indices of the gates added within the namespace.
+    gates: Range<usize>,
+
+    /// Number of variables before and after the namespace.
+    variables: Range<usize>,
+}
+
+/// Number of gates of a circuit, by type.
+///
+/// Every gate is counted once, under the first of its enabled selectors in
+/// the order of the fields.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GateCounts {
+    /// Lookup gates
+    pub lookup: usize,
+    /// Range gates
+    pub range: usize,
+    /// Logic gates
+    pub logic: usize,
+    /// Fixed base group addition gates
+    pub fixed_group_add: usize,
+    /// Variable base group addition gates
+    pub variable_group_add: usize,
+    /// Custom gates, by name
+    pub custom: BTreeMap<&'static str, usize>,
+    /// Poseidon affine transformation gates, that is arithmetic gates raising
+    /// their wires to the power of the S-box
+    pub poseidon_affine: usize,
+    /// Arithmetic gates
+    pub arithmetic: usize,
+    /// Gates with every selector turned off, which only hold wires
+    pub other: usize,
+}
+
+impl GateCounts {
+    /// Returns the total number of gates.
+    pub fn total(&self) -> usize {
+        self.lookup
+            + self.range
+            + self.logic
+            + self.fixed_group_add
+            + self.variable_group_add
+            + self.custom.values().sum::<usize>()
+            + self.poseidon_affine
+            + self.arithmetic
+            + self.other
+    }
+
+    /// Adds the counts of `other` to `self`.
+    fn add(&mut self, other: &Self) {
+        self.lookup += other.lookup;
+        self.range += other.range;
+        self.logic += other.logic;
+        self.fixed_group_add += other.fixed_group_add;
+        self.variable_group_add += other.variable_group_add;
+        for (name, count) in &other.custom {
+            *self.custom.entry(name).or_default() += count;
+        }
+        self.poseidon_affine += other.poseidon_affine;
+        self.arithmetic += other.arithmetic;
+        self.other += other.other;
+    }
+
+    /// Returns the non-zero counts, by name of the gate type.
+    fn entries(&self) -> Vec<(&'static str, usize)> {
+        let mut entries = vec![
+            ("lookup", self.lookup),
+            ("range", self.range),
+            ("logic", self.logic),
+            ("fixed_group_add", self.fixed_group_add),
+            ("variable_group_add", self.variable_group_add),
+        ];
+        entries.extend(self.custom.iter().map(|(&name, &count)| (name, count)));
+        entries.extend([
+            ("poseidon_affine", self.poseidon_affine),
+            ("arithmetic", self.arithmetic),
+            ("other", self.other),
+        ]);
+        entries.retain(|(_, count)| *count != 0);
+        entries
+    }
+}
+
+/// Cost of a namespace of a circuit, including the namespaces nested in it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct NamespaceProfile {
+    /// Name of the namespace
+    pub name: String,
+    /// Number of times the namespace was entered
+    pub calls: usize,
+    /// Gates added within the namespace, by type
+    pub gates: GateCounts,
+    /// Number of variables added within the namespace
+    pub variables: usize,
+    /// Namespaces entered within this one, merged by name, in the order they
+    /// were first entered
+    pub children: Vec<NamespaceProfile>,
+}
+
+impl NamespaceProfile {
+    /// Returns the child namespace named `name`, creating it if needed.
+    fn child(&mut self, name: &str) -> &mut Self {
+        let position =
+            match self.children.iter().position(|child| child.name == name) {
+                Some(position) => position,
+                None => {
+                    self.children.push(Self {
+                        name: name.to_string(),
+                        ..Default::default()
+                    });
+                    self.children.len() - 1
+                }
+            };
+        &mut self.children[position]
+    }
+
+    /// Returns the namespace at `path` below this one, if any.
+    pub fn get(&self, path: &[&str]) -> Option<&Self> {
+        match path.split_first() {
+            Some((name, rest)) => self
+                .children
+                .iter()
+                .find(|child| child.name == *name)?
+                .get(rest),
+            None => Some(self),
+        }
+    }
+
+    /// Writes the namespace and its children as lines of a tree, each one
+    /// prefixed with `prefix`.
+    fn write_tree(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        prefix: &str,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "{} gates={} variables={}",
+            self.name,
+            self.gates.total(),
+            self.variables
+        )?;
+        if self.calls > 1 {
+            write!(f, " calls={}", self.calls)?;
+        }
+        let entries = self.gates.entries();
+        if !entries.is_empty() {
+            let entries = entries
+                .iter()
+                .map(|(name, count)| format!("{}={}", name, count))
+                .collect::<Vec<_>>();
+            write!(f, " [{}]", entries.join(" "))?;
+        }
+        writeln!(f)?;
+
+        for (i, child) in self.children.iter().enumerate() {
+            let (branch, indent) = if i + 1 == self.children.len() {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            write!(f, "{}{}", prefix, branch)?;
+            child.write_tree(f, &format!("{}{}", prefix, indent))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the namespace and its children as a JSON object.
+    fn write_json(&self, out: &mut String) {
+        out.push_str("{\"name\":");
+        write_json_string(out, &self.name);
+        let _ = write!(
+            out,
+            ",\"calls\":{},\"gates\":{},\"variables\":{},\"gate_types\":{{",
+            self.calls,
+            self.gates.total(),
+            self.variables
+        );
+        for (i, (name, count)) in self.gates.entries().iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            write_json_string(out, name);
+            let _ = write!(out, ":{}", count);
+        }
+        out.push_str("},\"children\":[");
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            child.write_json(out);
+        }
+        out.push_str("]}");
+    }
+}
+
+/// Gate and variable counts of a circuit, by namespace and gate type.
+///
+/// The report is rendered as a tree by its [`Display`](fmt::Display)
+/// implementation and as JSON by [`CircuitProfile::to_json`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CircuitProfile {
+    /// The whole circuit, with the namespaces entered at its top level
+    pub root: NamespaceProfile,
+    /// Size the circuit is padded to, see
+    /// [`StandardComposer::circuit_bound`]
+    pub circuit_bound: usize,
+    /// Number of rows of the lookup table
+    pub lookup_table_rows: usize,
+}
+
+impl CircuitProfile {
+    /// Returns the JSON representation of the report.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "{{\"circuit_bound\":{},\"lookup_table_rows\":{},\"root\":",
+            self.circuit_bound, self.lookup_table_rows
+        );
+        self.root.write_json(&mut out);
+        out.push('}');
+        out
+    }
+}
+
+impl fmt::Display for CircuitProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.write_tree(f, "")?;
+        writeln!(
+            f,
+            "circuit bound: {}, lookup table: {} rows, {} queries",
+            self.circuit_bound, self.lookup_table_rows, self.root.gates.lookup
+        )
+    }
+}
+
+/// Writes `value` to `out` as a JSON string.
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: ModelParameters<BaseField = F>,
+{
+    /// Runs `gadget` within the namespace `name`, so that the gates and
+    /// variables it adds are reported under `name` by
+    /// [`StandardComposer::profile`].
+    ///
+    /// Namespaces can be nested, and the namespaces entered several times
+    /// with the same name under the same parent are merged in the report.
+    /// They do not change the circuit.
+    pub fn namespace<N, R>(
+        &mut self,
+        name: N,
+        gadget: impl FnOnce(&mut Self) -> R,
+    ) -> R
+    where
+        N: Into<String>,
+    {
+        let parent = self.current_namespace;
+        let id = self.namespaces.len();
+        let variables = self.perm.variable_map.len();
+        self.namespaces.push(NamespaceSpan {
+            name: name.into(),
+            parent,
+            gates: self.n..self.n,
+            variables: variables..variables,
+        });
+
+        self.current_namespace = Some(id);
+        let result = gadget(self);
+        self.current_namespace = parent;
+
+        let span = &mut self.namespaces[id];
+        span.gates.end = self.n;
+        span.variables.end = self.perm.variable_map.len();
+        result
+    }
+
+    /// Returns the number of gates and variables of the circuit, by
+    /// namespace and gate type.
+    pub fn profile(&self) -> CircuitProfile {
+        let mut root = NamespaceProfile {
+            name: "circuit".to_string(),
+            calls: 1,
+            gates: self.gate_counts(0..self.n),
+            variables: self.perm.variable_map.len(),
+            children: Vec::new(),
+        };
+
+        for (id, span) in self.namespaces.iter().enumerate() {
+            let mut path = vec![span.name.as_str()];
+            let mut parent = span.parent;
+            while let Some(index) = parent {
+                path.push(self.namespaces[index].name.as_str());
+                parent = self.namespaces[index].parent;
+            }
+
+            // Namespaces still being run when profiling end at the current
+            // size of the circuit.
+            let (gates_end, variables_end) = if self.is_open(id) {
+                (self.n, self.perm.variable_map.len())
+            } else {
+                (span.gates.end, span.variables.end)
+            };
+
+            let node = path
+                .iter()
+                .rev()
+                .fold(&mut root, |node, name| node.child(name));
+            node.calls += 1;
+            node.gates
+                .add(&self.gate_counts(span.gates.start..gates_end));
+            node.variables += variables_end - span.variables.start;
+        }
+
+        CircuitProfile {
+            root,
+            circuit_bound: self.circuit_bound(),
+            lookup_table_rows: self.lookup_table.size(),
+        }
+    }
+
+    /// Returns whether the namespace `id` is being run.
+    fn is_open(&self, id: usize) -> bool {
+        let mut current = self.current_namespace;
+        while let Some(index) = current {
+            if index == id {
+                return true;
+            }
+            current = self.namespaces[index].parent;
+        }
+        false
+    }
+
+    /// Counts the gates in `gates` by type.
+    fn gate_counts(&self, gates: Range<usize>) -> GateCounts {
+        let mut counts = GateCounts::default();
+        for i in gates {
+            if !self.q_lookup[i].is_zero() {
+                counts.lookup += 1;
+            } else if !self.q_range[i].is_zero() {
+                counts.range += 1;
+            } else if !self.q_logic[i].is_zero() {
+                counts.logic += 1;
+            } else if !self.q_fixed_group_add[i].is_zero() {
+                counts.fixed_group_add += 1;
+            } else if !self.q_variable_group_add[i].is_zero() {
+                counts.variable_group_add += 1;
+            } else if let Some(gate) = self
+                .custom_gates
+                .iter()
+                .zip(&self.q_custom)
+                .find(|(_, q_custom)| {
+                    q_custom.get(i).map_or(false, |q| !q.is_zero())
+                })
+                .map(|(gate, _)| gate)
+            {
+                *counts.custom.entry(gate.name()).or_default() += 1;
+            } else if !self.q_arith[i].is_zero()
+                && !(self.q_hl[i].is_zero()
+                    && self.q_hr[i].is_zero()
+                    && self.q_h4[i].is_zero())
+            {
+                counts.poseidon_affine += 1;
+            } else if !self.q_arith[i].is_zero() {
+                counts.arithmetic += 1;
+            } else {
+                counts.other += 1;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{batch_test_field_params, lookup::LookupTable};
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+
+    fn test_namespaces<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        composer.lookup_table = LookupTable::xor_table(0, 4);
+        let zero = composer.zero_var();
+
+        let sum = composer.namespace("sums", |composer| {
+            let mut sum = zero;
+            for i in 0..3u64 {
+                sum = composer.namespace("add", |composer| {
+                    let value = composer.add_input(F::from(i));
+                    composer.arithmetic_gate(|gate| {
+                        gate.witness(sum, value, None).add(F::one(), F::one())
+                    })
+                });
+            }
+            sum
+        });
+        composer.namespace("checks", |composer| {
+            composer.range_gate(sum, 4);
+            composer.namespace("xor", |composer| {
+                let negative_one = composer.add_input(-F::one());
+                let xor = composer.add_input(F::zero());
+                composer.lookup_gate(sum, sum, xor, Some(negative_one), None);
+            });
+        });
+
+        let profile = composer.profile();
+        assert_eq!(profile.root.calls, 1);
+        assert_eq!(profile.root.gates.total(), composer.n);
+        assert_eq!(profile.root.variables, composer.perm.variable_map.len());
+        assert_eq!(profile.lookup_table_rows, 256);
+        assert_eq!(profile.circuit_bound, composer.circuit_bound());
+
+        let sums = profile.root.get(&["sums"]).unwrap();
+        let add = profile.root.get(&["sums", "add"]).unwrap();
+        assert_eq!(add.calls, 3);
+        assert_eq!(add.gates.arithmetic, 3);
+        assert_eq!(add.gates.total(), 3);
+        // Each addition adds its input and its output.
+        assert_eq!(add.variables, 6);
+        assert_eq!(sums.gates, add.gates);
+
+        let checks = profile.root.get(&["checks"]).unwrap();
+        let xor = profile.root.get(&["checks", "xor"]).unwrap();
+        assert_eq!(xor.gates.lookup, 1);
+        assert_eq!(xor.variables, 2);
+        assert_eq!(checks.gates.lookup, 1);
+        assert!(checks.gates.range > 0);
+        assert!(profile.root.get(&["xor"]).is_none());
+
+        let tree = profile.to_string();
+        assert!(tree.starts_with("circuit gates="));
+        assert!(tree.contains("├── sums gates=3 variables=6"));
+        assert!(tree.contains("│   └── add gates=3 variables=6 calls=3"));
+        assert!(tree.contains("    └── xor gates=1 variables=2 [lookup=1]"));
+
+        let json = profile.to_json();
+        assert!(json.starts_with(&format!(
+            "{{\"circuit_bound\":{},\"lookup_table_rows\":256,\"root\":",
+            composer.circuit_bound()
+        )));
+        assert!(json.contains(
+            "{\"name\":\"add\",\"calls\":3,\"gates\":3,\"variables\":6,\
+             \"gate_types\":{\"arithmetic\":3},\"children\":[]}"
+        ));
+    }
+
+    fn test_open_namespace<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        composer.namespace("outer", |composer| {
+            let one = composer.add_input(F::one());
+            composer.constrain_to_constant(one, F::one(), None);
+
+            let profile = composer.profile();
+            let outer = profile.root.get(&["outer"]).unwrap();
+            assert_eq!(outer.gates.arithmetic, 1);
+            assert_eq!(outer.variables, 1);
+        });
+    }
+
+    // Tests for Bls12_381
+    batch_test_field_params!(
+        [
+            test_namespaces,
+            test_open_namespace
+        ],
+        [] => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12_377
+    batch_test_field_params!(
+        [
+            test_namespaces,
+            test_open_namespace
+        ],
+        [] => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
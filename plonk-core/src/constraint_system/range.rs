@@ -63,7 +63,7 @@ where
         assert!(num_bits % 2 == 0);
 
         // Convert witness to bit representation and reverse
-        let bits = self.variables[&witness].into_repr().to_bits_le();
+        let bits = self.value_of_var(witness).into_repr().to_bits_le();
 
         // For a width-4 program, one gate will contain 4 accumulators
         // Each accumulator proves that a single quad is a base-4 digit.
@@ -188,9 +188,18 @@ where
         // wire, which will be used in the gate before it
         // Furthermore, we set the left, right and output wires to zero
         *self.q_range.last_mut().unwrap() = F::zero();
+        // The wires of the last gate are copies of the zero variable, like
+        // the wires of every other gate, so that each wire belongs to a
+        // permutation cycle and the exported IR lists all of them.
         self.w_l.push(self.zero_var);
         self.w_r.push(self.zero_var);
         self.w_o.push(self.zero_var);
+        self.perm
+            .add_variable_to_map(self.zero_var, WireData::Left(self.n - 1));
+        self.perm
+            .add_variable_to_map(self.zero_var, WireData::Right(self.n - 1));
+        self.perm
+            .add_variable_to_map(self.zero_var, WireData::Output(self.n - 1));
 
         // Lastly, we must link the last accumulator value to the initial
         // witness This last constraint will pass as long as
@@ -205,7 +214,7 @@ where
 mod test {
     use super::*;
     use crate::{
-        batch_test, commitment::HomomorphicCommitment,
+        batch_test, batch_test_field_params, commitment::HomomorphicCommitment,
         constraint_system::helper::*,
     };
     use ark_bls12_377::Bls12_377;
@@ -267,6 +276,43 @@ mod test {
         );
     }
 
+    fn test_range_gate_maps_every_wire<F, P>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+    {
+        let mut composer = StandardComposer::<F, P>::new();
+        let witness = composer.add_input(F::from(2u64.pow(34) - 1));
+        let first_gate = composer.n;
+        composer.range_gate(witness, 34);
+
+        let mapped = composer
+            .perm
+            .variable_map
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        for gate in first_gate..composer.n {
+            for wire in [
+                WireData::Left(gate),
+                WireData::Right(gate),
+                WireData::Output(gate),
+                WireData::Fourth(gate),
+            ] {
+                assert!(mapped.contains(&wire), "{:?} is not mapped", wire);
+            }
+        }
+    }
+
+    batch_test_field_params!(
+        [test_range_gate_maps_every_wire],
+        []
+        => (
+            Bls12_381, ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
     // Test on Bls12-381
     batch_test!(
         [test_range_constraint],
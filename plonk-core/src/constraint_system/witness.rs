@@ -0,0 +1,135 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Serializable witness of circuits.
+//!
+//! A [`Witness`] holds the values assigned to the variables of a circuit and
+//! its public inputs. It is exported from the composer the gadget ran in with
+//! [`StandardComposer::export_witness`] and assigned to the composer rebuilt
+//! from the [`CircuitIr`] of the circuit with
+//! [`StandardComposer::assign_witness`], so witnesses can be generated and
+//! proven in different processes.
+//!
+//! [`CircuitIr`]: crate::constraint_system::CircuitIr
+
+use crate::{
+    constraint_system::StandardComposer,
+    error::Error,
+    proof_system::{pi::PublicInputs, CircuitId},
+};
+use ark_ec::ModelParameters;
+use ark_ff::PrimeField;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
+
+/// Version of the serialization format of [`Witness`].
+pub const WITNESS_VERSION: u32 = 1;
+
+/// Assignment of the variables and public inputs of a circuit.
+///
+/// The serialized form starts with [`WITNESS_VERSION`], and witnesses
+/// written with a different version fail to deserialize.
+#[derive(derivative::Derivative)]
+#[derivative(Clone, Debug, Eq, PartialEq)]
+pub struct Witness<F>
+where
+    F: PrimeField,
+{
+    /// Identifier of the circuit the witness was generated for.
+    pub circuit_id: CircuitId,
+
+    /// Values of the variables, indexed by
+    /// [`Variable`](crate::constraint_system::Variable).
+    pub values: Vec<F>,
+
+    /// Public inputs of the circuit.
+    pub public_inputs: PublicInputs<F>,
+}
+
+impl<F> CanonicalSerialize for Witness<F>
+where
+    F: PrimeField,
+{
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        WITNESS_VERSION.serialize(&mut writer)?;
+        self.circuit_id.serialize(&mut writer)?;
+        self.values.serialize(&mut writer)?;
+        self.public_inputs.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        WITNESS_VERSION.serialized_size()
+            + self.circuit_id.serialized_size()
+            + self.values.serialized_size()
+            + self.public_inputs.serialized_size()
+    }
+}
+
+impl<F> CanonicalDeserialize for Witness<F>
+where
+    F: PrimeField,
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        if u32::deserialize(&mut reader)? != WITNESS_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(Self {
+            circuit_id: CircuitId::deserialize(&mut reader)?,
+            values: Vec::deserialize(&mut reader)?,
+            public_inputs: PublicInputs::deserialize(&mut reader)?,
+        })
+    }
+}
+
+impl<F, P> StandardComposer<F, P>
+where
+    F: PrimeField,
+    P: ModelParameters<BaseField = F>,
+{
+    /// Returns the [`Witness`] of the circuit built so far.
+    ///
+    /// Fails with [`Error::InvalidWitness`] if the composer is setup-only and
+    /// therefore holds no witness.
+    pub fn export_witness(&self) -> Result<Witness<F>, Error> {
+        if self.variables.len() != self.perm.variable_map.len() {
+            return Err(Error::InvalidWitness);
+        }
+        Ok(Witness {
+            circuit_id: self.circuit_id(),
+            values: self.variables.clone(),
+            public_inputs: self.public_inputs.clone(),
+        })
+    }
+
+    /// Assigns `witness` to the variables and public inputs of the circuit,
+    /// replacing any previous assignment and leaving setup-only mode.
+    ///
+    /// Fails with [`Error::InvalidWitness`] if the witness was generated for
+    /// a different circuit, does not assign every variable or sets public
+    /// inputs at positions the circuit does not reserve for them.
+    pub fn assign_witness(
+        &mut self,
+        witness: &Witness<F>,
+    ) -> Result<(), Error> {
+        if witness.circuit_id != self.circuit_id()
+            || witness.values.len() != self.perm.variable_map.len()
+            || !witness
+                .public_inputs
+                .get_pos()
+                .all(|pos| self.intended_pi_pos.contains(pos))
+        {
+            return Err(Error::InvalidWitness);
+        }
+        self.variables = witness.values.clone();
+        self.public_inputs = witness.public_inputs.clone();
+        self.setup_only = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,461 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Versioned and validated on-disk format of keys and proofs.
+//!
+//! The canonical serialization of keys and proofs carries no information
+//! about what it encodes, so a key read with the wrong curve, commitment
+//! scheme or library version either fails to deserialize with an unhelpful
+//! error or, worse, deserializes and makes verification fail silently. A
+//! container prefixes the canonical serialization of a value with a header:
+//!
+//! | Field             | Size    | Contents                                |
+//! |-------------------|---------|-----------------------------------------|
+//! | magic             | 4       | [`CONTAINER_MAGIC`]                     |
+//! | version           | 1       | [`CONTAINER_VERSION`]                   |
+//! | kind              | 1       | [`ContainerKind`] of the value          |
+//! | curve             | 8       | hash of the scalar field modulus        |
+//! | commitment scheme | 1 + len | [`HomomorphicCommitment::name`]         |
+//! | length            | 8       | length of the contents, little-endian   |
+//! | hash              | 32      | Blake2b hash of the contents, truncated |
+//! | contents          | length  | canonical serialization of the value    |
+//!
+//! Reading a container checks every field of the header against the type
+//! read and the hash against the contents before deserializing them. The
+//! contents are deserialized with the checked deserialization of arkworks,
+//! which rejects points off the curve or outside of its prime order subgroup.
+
+use crate::{
+    circuit::VerifierData,
+    commitment::HomomorphicCommitment,
+    error::Error,
+    proof_system::{Proof, ProverKey, VerifierKey},
+};
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
+use blake2::{Blake2b, Digest};
+
+/// Magic every container starts with.
+pub const CONTAINER_MAGIC: [u8; 4] = *b"PLNK";
+
+/// Version of the format of the containers.
+pub const CONTAINER_VERSION: u8 = 2;
+
+/// Kinds of values stored in containers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContainerKind {
+    /// [`ProverKey`].
+    ProverKey = 1,
+    /// [`VerifierKey`].
+    VerifierKey = 2,
+    /// [`Proof`].
+    Proof = 3,
+    /// [`VerifierData`].
+    VerifierData = 4,
+}
+
+impl ContainerKind {
+    /// Returns the kind tagged by `tag`, if any.
+    fn from_tag(tag: u8) -> Option<Self> {
+        [
+            Self::ProverKey,
+            Self::VerifierKey,
+            Self::Proof,
+            Self::VerifierData,
+        ]
+        .into_iter()
+        .find(|kind| *kind as u8 == tag)
+    }
+}
+
+/// Returns the tag of the curve with scalar field `F`, which is the start of
+/// the Blake2b hash of the modulus of `F`.
+fn curve_tag<F: PrimeField>() -> [u8; 8] {
+    let mut tag = [0; 8];
+    tag.copy_from_slice(
+        &Blake2b::digest(&F::Params::MODULUS.to_bytes_le())[..8],
+    );
+    tag
+}
+
+/// Returns the hash of the contents of a container.
+fn content_hash(contents: &[u8]) -> [u8; 32] {
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&Blake2b::digest(contents)[..32]);
+    hash
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Checks the `field` of the header of a container.
+fn check(
+    field: &'static str,
+    expected: impl ToString,
+    found: impl ToString,
+) -> Result<(), Error> {
+    let (expected, found) = (expected.to_string(), found.to_string());
+    if expected != found {
+        return Err(Error::ContainerMismatch {
+            field,
+            expected,
+            found,
+        });
+    }
+    Ok(())
+}
+
+/// Reads exactly `buf.len()` bytes of a container.
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), Error> {
+    reader
+        .read_exact(buf)
+        .map_err(|_| Error::CorruptedContainer)
+}
+
+/// Values stored in containers.
+pub trait Containable: CanonicalSerialize + CanonicalDeserialize {
+    /// Kind of the value.
+    const KIND: ContainerKind;
+
+    /// Returns the name of the commitment scheme of the value, empty if the
+    /// value does not depend on the scheme.
+    fn scheme() -> String;
+
+    /// Scalar field of the value, which determines its curve.
+    type Field: PrimeField;
+
+    /// Writes the value in a container.
+    fn write_container<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        let mut contents = Vec::new();
+        self.serialize(&mut contents)?;
+        writer.write_all(&CONTAINER_MAGIC)?;
+        writer.write_all(&[CONTAINER_VERSION, Self::KIND as u8])?;
+        writer.write_all(&curve_tag::<Self::Field>())?;
+        let scheme = Self::scheme();
+        writer.write_all(&[scheme.len() as u8])?;
+        writer.write_all(scheme.as_bytes())?;
+        writer.write_all(&(contents.len() as u64).to_le_bytes())?;
+        writer.write_all(&content_hash(&contents))?;
+        writer.write_all(&contents)?;
+        Ok(())
+    }
+
+    /// Reads a value written with [`Containable::write_container`].
+    ///
+    /// Fails with [`Error::ContainerMismatch`] if the container holds a value
+    /// of another kind, curve or commitment scheme, or was written with
+    /// another version of the format, with [`Error::CorruptedContainer`] if
+    /// its contents are truncated or do not match their hash, and with
+    /// [`Error::InvalidContainerContents`] if they cannot be deserialized.
+    fn read_container<R: Read>(mut reader: R) -> Result<Self, Error> {
+        let mut magic = [0; 4];
+        read_exact(&mut reader, &mut magic)?;
+        check("magic", hex(&CONTAINER_MAGIC), hex(&magic))?;
+        let mut tags = [0; 2];
+        read_exact(&mut reader, &mut tags)?;
+        check("version", CONTAINER_VERSION, tags[0])?;
+        check(
+            "kind",
+            format!("{:?}", Self::KIND),
+            ContainerKind::from_tag(tags[1]).map_or_else(
+                || tags[1].to_string(),
+                |kind| format!("{:?}", kind),
+            ),
+        )?;
+        let mut curve = [0; 8];
+        read_exact(&mut reader, &mut curve)?;
+        check("curve", hex(&curve_tag::<Self::Field>()), hex(&curve))?;
+        let mut scheme_len = [0];
+        read_exact(&mut reader, &mut scheme_len)?;
+        let mut scheme = vec![0; scheme_len[0] as usize];
+        read_exact(&mut reader, &mut scheme)?;
+        check(
+            "commitment scheme",
+            Self::scheme(),
+            String::from_utf8_lossy(&scheme),
+        )?;
+
+        let mut len = [0; 8];
+        read_exact(&mut reader, &mut len)?;
+        let len = u64::from_le_bytes(len);
+        let mut hash = [0; 32];
+        read_exact(&mut reader, &mut hash)?;
+        // The length is not trusted to allocate the contents up front.
+        let mut contents = Vec::new();
+        reader
+            .take(len)
+            .read_to_end(&mut contents)
+            .map_err(|_| Error::CorruptedContainer)?;
+        if contents.len() as u64 != len || content_hash(&contents) != hash {
+            return Err(Error::CorruptedContainer);
+        }
+
+        let mut contents = &contents[..];
+        let value = Self::deserialize(&mut contents)
+            .map_err(|_| Error::InvalidContainerContents)?;
+        if !contents.is_empty() {
+            return Err(Error::InvalidContainerContents);
+        }
+        Ok(value)
+    }
+}
+
+impl<F> Containable for ProverKey<F>
+where
+    F: PrimeField,
+{
+    const KIND: ContainerKind = ContainerKind::ProverKey;
+    type Field = F;
+
+    fn scheme() -> String {
+        String::new()
+    }
+}
+
+impl<F, PC> Containable for VerifierKey<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    const KIND: ContainerKind = ContainerKind::VerifierKey;
+    type Field = F;
+
+    fn scheme() -> String {
+        PC::name()
+    }
+}
+
+impl<F, PC> Containable for Proof<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    const KIND: ContainerKind = ContainerKind::Proof;
+    type Field = F;
+
+    fn scheme() -> String {
+        PC::name()
+    }
+}
+
+impl<F, PC> Containable for VerifierData<F, PC>
+where
+    F: PrimeField,
+    PC: HomomorphicCommitment<F>,
+{
+    const KIND: ContainerKind = ContainerKind::VerifierData;
+    type Field = F;
+
+    fn scheme() -> String {
+        PC::name()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        circuit::trim_params,
+        commitment::{IPA, KZG10},
+        constraint_system::helper::dummy_gadget,
+        proof_system::{Prover, Verifier},
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::{AffineCurve, PairingEngine, TEModelParameters};
+    use ark_ff::One;
+    use rand_core::OsRng;
+
+    /// Returns the keys of a circuit and a proof of it.
+    fn keys_and_proof<F, P, PC>() -> (
+        ProverKey<F>,
+        VerifierKey<F, PC>,
+        Proof<F, PC>,
+        VerifierData<F, PC>,
+    )
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut prover = Prover::<F, P, PC>::new(b"container");
+        dummy_gadget(10, prover.mut_cs());
+        let pp =
+            PC::setup(2 * prover.circuit_bound(), None, &mut OsRng).unwrap();
+        let (ck, _) =
+            trim_params::<F, PC>(&pp, prover.circuit_bound()).unwrap();
+        prover.preprocess(&ck).unwrap();
+        let pk = prover.prover_key.clone().unwrap();
+        let pi = prover.cs.get_pi().clone();
+        let proof = prover.prove(&ck, &mut OsRng).unwrap();
+
+        let mut verifier = Verifier::<F, P, PC>::new(b"container");
+        dummy_gadget(10, verifier.mut_cs());
+        verifier.preprocess(&ck).unwrap();
+        let vk = verifier.verifier_key.unwrap();
+        (pk, vk.clone(), proof, VerifierData::new(vk, pi))
+    }
+
+    fn container<T: Containable>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value.write_container(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Checks that `value` is read back from its container.
+    fn assert_round_trip<T: Containable>(value: &T) {
+        let read = T::read_container(&container(value)[..]).unwrap();
+        let (mut bytes, mut read_bytes) = (Vec::new(), Vec::new());
+        value.serialize(&mut bytes).unwrap();
+        read.serialize(&mut read_bytes).unwrap();
+        assert_eq!(bytes, read_bytes);
+    }
+
+    fn test_container_round_trip<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let (pk, vk, proof, verifier_data) = keys_and_proof::<F, P, PC>();
+        assert_round_trip(&pk);
+        assert_round_trip(&vk);
+        assert_round_trip(&proof);
+        assert_round_trip(&verifier_data);
+    }
+
+    fn test_corrupted_container<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let (_, vk, proof, _) = keys_and_proof::<F, P, PC>();
+        let bytes = container(&proof);
+
+        assert!(matches!(
+            VerifierKey::<F, PC>::read_container(&bytes[..]),
+            Err(Error::ContainerMismatch { field: "kind", .. })
+        ));
+        assert!(matches!(
+            Proof::<F, PC>::read_container(&container(&vk)[..]),
+            Err(Error::ContainerMismatch { field: "kind", .. })
+        ));
+
+        let mut tampered = bytes.clone();
+        tampered[4] += 1;
+        assert!(matches!(
+            Proof::<F, PC>::read_container(&tampered[..]),
+            Err(Error::ContainerMismatch {
+                field: "version",
+                ..
+            })
+        ));
+
+        let mut tampered = bytes.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            Proof::<F, PC>::read_container(&tampered[..]),
+            Err(Error::CorruptedContainer)
+        ));
+        assert!(matches!(
+            Proof::<F, PC>::read_container(&bytes[..bytes.len() - 1]),
+            Err(Error::CorruptedContainer)
+        ));
+    }
+
+    type Fr = <Bls12_381 as PairingEngine>::Fr;
+    type P = ark_ed_on_bls12_381::EdwardsParameters;
+    type Kzg = KZG10<Bls12_381>;
+    type Ipa = IPA<<Bls12_381 as PairingEngine>::G1Affine, blake2::Blake2s>;
+
+    /// Tests that values are rejected when read with another curve or
+    /// commitment scheme.
+    #[test]
+    fn test_container_mismatch() {
+        let (pk, vk, proof, _) = keys_and_proof::<Fr, P, Kzg>();
+        assert!(matches!(
+            VerifierKey::<Fr, Ipa>::read_container(&container(&vk)[..]),
+            Err(Error::ContainerMismatch {
+                field: "commitment scheme",
+                ..
+            })
+        ));
+
+        // The IPA schemes over different digests are told apart.
+        let (_, vk, _, _) = keys_and_proof::<Fr, P, Ipa>();
+        assert!(matches!(
+            VerifierKey::<
+                Fr,
+                IPA<<Bls12_381 as PairingEngine>::G1Affine, blake2::Blake2b>,
+            >::read_container(&container(&vk)[..]),
+            Err(Error::ContainerMismatch {
+                field: "commitment scheme",
+                ..
+            })
+        ));
+        assert!(matches!(
+            Proof::<
+                <Bls12_377 as PairingEngine>::Fr,
+                KZG10<Bls12_377>,
+            >::read_container(&container(&proof)[..]),
+            Err(Error::ContainerMismatch { field: "curve", .. })
+        ));
+        assert!(matches!(
+            ProverKey::<<Bls12_377 as PairingEngine>::Fr>::read_container(
+                &container(&pk)[..]
+            ),
+            Err(Error::ContainerMismatch { field: "curve", .. })
+        ));
+    }
+
+    /// Tests that points outside of the prime order subgroup are rejected.
+    #[test]
+    fn test_container_subgroup_check() {
+        let (_, _, mut proof, _) = keys_and_proof::<Fr, P, Kzg>();
+        let mut x = <Bls12_381 as PairingEngine>::Fq::one();
+        let point = loop {
+            if let Some(point) =
+                ark_bls12_381::G1Affine::get_point_from_x(x, false)
+            {
+                if !point.is_in_correct_subgroup_assuming_on_curve() {
+                    break point;
+                }
+            }
+            x += <Bls12_381 as PairingEngine>::Fq::one();
+        };
+        proof.a_comm = ark_poly_commit::kzg10::Commitment(point);
+        assert!(matches!(
+            Proof::<Fr, Kzg>::read_container(&container(&proof)[..]),
+            Err(Error::InvalidContainerContents)
+        ));
+    }
+
+    // Tests for Bls12_381
+    batch_test!(
+        [test_container_round_trip, test_corrupted_container],
+        [] => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12_377
+    batch_test!(
+        [test_container_round_trip, test_corrupted_container],
+        [] => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -22,6 +22,8 @@ pub enum Error {
     // Prover/Verifier errors
     /// This error occurs when a proof verification fails.
     ProofVerificationError,
+    /// This error occurs when a batch of proofs to verify is empty.
+    EmptyBatch,
     /// This error occurs when the circuit is not provided with all of the
     /// required inputs.
     CircuitInputsNotFound,
@@ -32,14 +34,75 @@ pub enum Error {
     InvalidPublicInputBytes,
     /// PublicInput value conversion error
     InvalidPublicInputValue,
+    /// This error occurs when a public input position is repeated, falls
+    /// outside of the evaluation domain of the circuit or is not one of the
+    /// positions the circuit reserves for public inputs.
+    InvalidPublicInputPosition {
+        /// Offending position
+        position: usize,
+    },
     /// This error occurs when the Prover structure already contains a
     /// preprocessed circuit inside, but you call preprocess again.
     CircuitAlreadyPreprocessed,
+    /// This error occurs when a key is used with a circuit other than the
+    /// one it was preprocessed from.
+    CircuitIdMismatch,
+    /// This error occurs when a setup-only composer, which holds no
+    /// witnesses, is used to prove.
+    SetupOnlyComposer,
+    /// This error occurs when the commitments and evaluations of a proof do
+    /// not follow the gate layout of the key it is verified against.
+    ProofLayoutMismatch,
+    /// This error occurs when a circuit declares a padded size smaller than
+    /// the size it needs.
+    CircuitSizeTooSmall {
+        /// Size declared by the circuit
+        declared: usize,
+        /// Size of the circuit
+        required: usize,
+    },
+    /// This error occurs when the public parameters do not support the
+    /// degree needed to prove or verify a circuit.
+    SRSTooSmall {
+        /// Degree needed by the circuit
+        required: usize,
+        /// Maximum degree supported by the public parameters
+        supported: usize,
+    },
+
+    // Setup errors
+    /// This error occurs when the output of a setup ceremony cannot be
+    /// parsed.
+    MalformedSetup {
+        /// Description of the problem
+        reason: String,
+    },
+    /// This error occurs when the powers of a setup ceremony are not powers
+    /// of the same secret.
+    InconsistentSetup,
 
     // Preprocessing errors
     /// This error occurs when an error triggers during the preprocessing
     /// stage.
     MismatchedPolyLen,
+    /// This error occurs when a [`CircuitIr`] does not describe a
+    /// well-formed circuit.
+    ///
+    /// [`CircuitIr`]: crate::constraint_system::CircuitIr
+    InvalidCircuitIr,
+    /// This error occurs when a [`Witness`] is missing or does not fit the
+    /// circuit it is assigned to.
+    ///
+    /// [`Witness`]: crate::constraint_system::Witness
+    InvalidWitness,
+    /// This error occurs when the file of a memory-mapped [`ProverKey`] is
+    /// malformed or does not match the field of the key.
+    ///
+    /// [`ProverKey`]: crate::proof_system::ProverKey
+    InvalidMappedProverKey {
+        /// Reason the file was rejected.
+        reason: String,
+    },
 
     /// Polynomial Commitment errors
     PCError {
@@ -76,12 +139,72 @@ pub enum Error {
     /// This error occurs when a malformed scalar is decoded from a byte
     /// array.
     ScalarMalformed,
+    /// This error occurs when an encoded proof does not have the expected
+    /// length.
+    InvalidProofLength {
+        /// Expected length in bytes
+        expected: usize,
+        /// Actual length in bytes
+        found: usize,
+    },
+    /// This error occurs when a key or proof left out some gate types, which
+    /// the EVM verifier does not support.
+    UnsupportedGateLayout,
+    /// This error occurs when an opening proof holds a zero blinding
+    /// evaluation, which the EVM encoding reserves for a missing one.
+    ZeroBlindingEvaluation,
+    /// This error occurs when the header of a container does not match the
+    /// value read from it, such as a container of another kind, curve,
+    /// commitment scheme or format version.
+    ContainerMismatch {
+        /// Field of the header
+        field: &'static str,
+        /// Expected value of the field
+        expected: String,
+        /// Value of the field in the container
+        found: String,
+    },
+    /// This error occurs when a container is truncated or its contents do not
+    /// match its hash.
+    CorruptedContainer,
+    /// This error occurs when the contents of a container cannot be
+    /// deserialized, such as points off the curve or outside of its prime
+    /// order subgroup.
+    InvalidContainerContents,
 
     // Plonkup errors
     /// Query element not found in lookup table
     ElementNotIndexed,
     /// Cannot commit to table column polynomial
     TablePreProcessingError,
+
+    // Custom gate errors
+    /// This error occurs when a key refers to a custom gate that has not been
+    /// registered with the [`StandardComposer`] used to prove or verify.
+    ///
+    /// [`StandardComposer`]: crate::constraint_system::StandardComposer
+    CustomGateNotRegistered {
+        /// Name of the missing custom gate
+        name: String,
+    },
+    /// This error occurs when a custom gate is registered under the name of
+    /// another registered custom gate.
+    CustomGateNameConflict {
+        /// Name shared by the two custom gates
+        name: String,
+    },
+    /// This error occurs when a gate reads a custom evaluation which is not
+    /// part of the proof.
+    MissingCustomEvaluation {
+        /// Label of the missing evaluation
+        label: String,
+    },
+    /// This error occurs when a key with custom gates is used with a verifier
+    /// which only supports the built-in gates, such as the EVM verifier.
+    UnsupportedCustomGate {
+        /// Name of the unsupported custom gate
+        name: String,
+    },
 }
 
 impl From<ark_poly_commit::error::Error> for Error {
@@ -121,6 +244,7 @@ impl std::fmt::Display for Error {
             Self::ProofVerificationError => {
                 write!(f, "proof verification failed")
             }
+            Self::EmptyBatch => write!(f, "batch of proofs is empty"),
             Self::CircuitInputsNotFound => {
                 write!(f, "circuit inputs not found")
             }
@@ -133,15 +257,55 @@ impl std::fmt::Display for Error {
             Self::InvalidPublicInputValue => {
                 write!(f, "public input value conversion error")
             }
+            Self::InvalidPublicInputPosition { position } => {
+                write!(f, "invalid public input position {}", position)
+            }
+            Self::MalformedSetup { reason } => {
+                write!(f, "malformed setup: {}", reason)
+            }
+            Self::InconsistentSetup => {
+                write!(f, "the powers of the setup are not consistent")
+            }
             Self::MismatchedPolyLen => {
                 write!(f, "the length of the wires is not the same")
             }
+            Self::InvalidCircuitIr => {
+                write!(f, "the circuit representation is malformed")
+            }
+            Self::InvalidWitness => {
+                write!(f, "the witness does not match the circuit")
+            }
+            Self::InvalidMappedProverKey { reason } => {
+                write!(f, "invalid mapped prover key: {}", reason)
+            }
             Self::PCError { error } => {
                 write!(f, "{:?}", error)
             }
             Self::CircuitAlreadyPreprocessed => {
                 write!(f, "circuit has already been preprocessed")
             }
+            Self::CircuitIdMismatch => {
+                write!(f, "key was preprocessed from a different circuit")
+            }
+            Self::SetupOnlyComposer => {
+                write!(f, "a setup-only composer cannot be used to prove")
+            }
+            Self::ProofLayoutMismatch => {
+                write!(f, "proof does not follow the gate layout of the key")
+            }
+            Self::CircuitSizeTooSmall { declared, required } => write!(
+                f,
+                "declared circuit size {} is smaller than the circuit size {}",
+                declared, required
+            ),
+            Self::SRSTooSmall {
+                required,
+                supported,
+            } => write!(
+                f,
+                "public parameters support degree {} but the circuit needs {}",
+                supported, required
+            ),
             Self::DegreeIsZero => {
                 write!(f, "cannot create PublicParameters with max degree 0")
             }
@@ -163,12 +327,50 @@ impl std::fmt::Display for Error {
             Self::NotEnoughBytes => write!(f, "not enough bytes left to read"),
             Self::PointMalformed => write!(f, "point bytes malformed"),
             Self::ScalarMalformed => write!(f, "scalar bytes malformed"),
+            Self::InvalidProofLength { expected, found } => write!(
+                f,
+                "proof must be {} bytes long, found {} bytes",
+                expected, found
+            ),
+            Self::UnsupportedGateLayout => {
+                write!(f, "key or proof does not hold every gate type")
+            }
+            Self::ZeroBlindingEvaluation => {
+                write!(f, "blinding evaluation of an opening proof is zero")
+            }
+            Self::ContainerMismatch {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "container {} mismatch: expected {}, found {}",
+                field, expected, found
+            ),
+            Self::CorruptedContainer => write!(f, "container is corrupted"),
+            Self::InvalidContainerContents => {
+                write!(f, "container contents are invalid")
+            }
             Self::ElementNotIndexed => {
                 write!(f, "element not found in lookup table")
             }
             Self::TablePreProcessingError => {
                 write!(f, "lookup table not preprocessed correctly")
             }
+            Self::CustomGateNotRegistered { name } => {
+                write!(f, "custom gate `{}` has not been registered", name)
+            }
+            Self::CustomGateNameConflict { name } => write!(
+                f,
+                "another custom gate is registered under the name `{}`",
+                name
+            ),
+            Self::MissingCustomEvaluation { label } => {
+                write!(f, "custom evaluation `{}` not found", label)
+            }
+            Self::UnsupportedCustomGate { name } => {
+                write!(f, "custom gate `{}` is not supported", name)
+            }
         }
     }
 }
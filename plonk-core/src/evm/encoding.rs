@@ -0,0 +1,393 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! EVM encoding of [`Proof`]s and [`PublicInputs`] over BN254.
+//!
+//! Every value is encoded as 32 byte big-endian words: scalars take one word
+//! and `G1` points take two, their `x` and `y` coordinates, with the point at
+//! infinity encoded as `(0, 0)`. A proof is the concatenation of the following
+//! words, which is also the layout the generated contract reads:
+//!
+//! | words    | content                                                  |
+//! |----------|----------------------------------------------------------|
+//! | `0..34`  | `a`, `b`, `c`, `d`, `z`, `f`, `h_1`, `h_2`, `z_2` and    |
+//! |          | `t_1` to `t_8` commitments                               |
+//! | `34..40` | witness and blinding evaluation of the openings at `z`   |
+//! |          | and at `z * omega`                                       |
+//! | `40..56` | `a`, `b`, `c`, `d`, `left_sigma`, `right_sigma`,         |
+//! |          | `out_sigma`, `permutation`, `q_lookup`, `z2_next`, `h1`, |
+//! |          | `h1_next`, `h2`, `f`, `table` and `table_next`           |
+//! |          | evaluations                                              |
+//! | `56..66` | `q_arith`, `q_c`, `q_l`, `q_r`, `q_hl`, `q_hr`, `q_h4`,  |
+//! |          | `a_next`, `b_next` and `d_next` evaluations              |
+//!
+//! A missing blinding evaluation is encoded as zero, so a proof whose blinding
+//! evaluation is zero cannot be encoded: it would decode as another proof.
+//! Only the proofs of
+//! circuits using [`GateLayout::Full`] can be encoded: the proofs of
+//! [`GateLayout::Compact`], the default, leave out commitments and
+//! evaluations the contract reads.
+//!
+//! [`GateLayout::Compact`]: crate::constraint_system::GateLayout::Compact
+//! [`GateLayout::Full`]: crate::constraint_system::GateLayout::Full
+
+use crate::{
+    commitment::KZG10,
+    error::Error,
+    proof_system::{
+        linearisation_poly::{
+            CustomEvaluations, LookupEvaluations, PermutationEvaluations,
+            ProofEvaluations, WireEvaluations,
+        },
+        pi::PublicInputs,
+        Proof,
+    },
+};
+use ark_bn254::{Bn254, Fq, Fr, G1Affine};
+use ark_ff::{BigInteger, FromBytes, PrimeField, Zero};
+use ark_poly_commit::kzg10;
+use hashbrown::HashMap;
+
+/// Size in bytes of an EVM word.
+pub(crate) const WORD_SIZE: usize = 32;
+
+/// Number of words of an encoded [`Proof`].
+pub(crate) const PROOF_WORDS: usize = 66;
+
+/// Size in bytes of an encoded [`Proof`].
+pub const PROOF_LENGTH: usize = PROOF_WORDS * WORD_SIZE;
+
+/// Labels of the custom evaluations of an encoded [`Proof`], in order.
+const CUSTOM_EVALUATION_LABELS: [&str; 10] = [
+    "q_arith_eval",
+    "q_c_eval",
+    "q_l_eval",
+    "q_r_eval",
+    "q_hl_eval",
+    "q_hr_eval",
+    "q_h4_eval",
+    "a_next_eval",
+    "b_next_eval",
+    "d_next_eval",
+];
+
+/// Encodes `scalar` as a big-endian word.
+pub(crate) fn scalar_to_word<F>(scalar: &F) -> [u8; WORD_SIZE]
+where
+    F: PrimeField,
+{
+    let bytes = scalar.into_repr().to_bytes_be();
+    let mut word = [0u8; WORD_SIZE];
+    word[WORD_SIZE - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+/// Decodes a big-endian word into a scalar, failing if it is not smaller than
+/// the modulus.
+fn scalar_from_word<F>(word: &[u8]) -> Option<F>
+where
+    F: PrimeField,
+{
+    let mut bytes = word.to_vec();
+    bytes.reverse();
+    F::from_repr(F::BigInt::read(bytes.as_slice()).ok()?)
+}
+
+/// Encodes `point` as two big-endian words.
+pub(crate) fn point_to_words(point: &G1Affine) -> [u8; 2 * WORD_SIZE] {
+    let mut words = [0u8; 2 * WORD_SIZE];
+    if !point.infinity {
+        words[..WORD_SIZE].copy_from_slice(&scalar_to_word(&point.x));
+        words[WORD_SIZE..].copy_from_slice(&scalar_to_word(&point.y));
+    }
+    words
+}
+
+/// Decodes two big-endian words into a point, failing if the coordinates are
+/// not canonical or the point is not on the curve.
+fn point_from_words(words: &[u8]) -> Result<G1Affine, Error> {
+    let x = scalar_from_word::<Fq>(&words[..WORD_SIZE])
+        .ok_or(Error::PointMalformed)?;
+    let y = scalar_from_word::<Fq>(&words[WORD_SIZE..])
+        .ok_or(Error::PointMalformed)?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new(x, y, false);
+    if !point.is_on_curve() {
+        return Err(Error::PointMalformed);
+    }
+    Ok(point)
+}
+
+/// Encodes `proof` in the EVM format read by the generated contract.
+///
+/// Fails with [`Error::UnsupportedGateLayout`] if the proof leaves out some
+/// gate types, as the proofs of [`GateLayout::Compact`] do, and with
+/// [`Error::ZeroBlindingEvaluation`] if an opening proof holds a zero blinding
+/// evaluation.
+///
+/// [`GateLayout::Compact`]: crate::constraint_system::GateLayout::Compact
+pub fn encode_proof(proof: &Proof<Fr, KZG10<Bn254>>) -> Result<Vec<u8>, Error> {
+    let lookup_comm = |commitment: &Option<kzg10::Commitment<Bn254>>| {
+        commitment.ok_or(Error::UnsupportedGateLayout)
+    };
+    let mut bytes = Vec::with_capacity(PROOF_LENGTH);
+    for commitment in [
+        proof.a_comm,
+        proof.b_comm,
+        proof.c_comm,
+        proof.d_comm,
+        proof.z_comm,
+        lookup_comm(&proof.f_comm)?,
+        lookup_comm(&proof.h_1_comm)?,
+        lookup_comm(&proof.h_2_comm)?,
+        lookup_comm(&proof.z_2_comm)?,
+        proof.t_1_comm,
+        proof.t_2_comm,
+        proof.t_3_comm,
+        proof.t_4_comm,
+        proof.t_5_comm,
+        proof.t_6_comm,
+        proof.t_7_comm,
+        proof.t_8_comm,
+    ] {
+        bytes.extend_from_slice(&point_to_words(&commitment.0));
+    }
+    for opening in [&proof.aw_opening, &proof.saw_opening] {
+        if opening
+            .random_v
+            .map_or(false, |random_v| random_v.is_zero())
+        {
+            return Err(Error::ZeroBlindingEvaluation);
+        }
+        bytes.extend_from_slice(&point_to_words(&opening.w));
+        bytes.extend_from_slice(&scalar_to_word(
+            &opening.random_v.unwrap_or_else(Fr::zero),
+        ));
+    }
+
+    let evaluations = &proof.evaluations;
+    let wire = &evaluations.wire_evals;
+    let perm = &evaluations.perm_evals;
+    let lookup = evaluations
+        .lookup_evals
+        .as_ref()
+        .ok_or(Error::UnsupportedGateLayout)?;
+    for evaluation in [
+        wire.a_eval,
+        wire.b_eval,
+        wire.c_eval,
+        wire.d_eval,
+        perm.left_sigma_eval,
+        perm.right_sigma_eval,
+        perm.out_sigma_eval,
+        perm.permutation_eval,
+        lookup.q_lookup_eval,
+        lookup.z2_next_eval,
+        lookup.h1_eval,
+        lookup.h1_next_eval,
+        lookup.h2_eval,
+        lookup.f_eval,
+        lookup.table_eval,
+        lookup.table_next_eval,
+    ] {
+        bytes.extend_from_slice(&scalar_to_word(&evaluation));
+    }
+    for label in CUSTOM_EVALUATION_LABELS {
+        let evaluation = evaluations
+            .custom_evals
+            .get(label)
+            .map_err(|_| Error::UnsupportedGateLayout)?;
+        bytes.extend_from_slice(&scalar_to_word(&evaluation));
+    }
+    Ok(bytes)
+}
+
+/// Decodes a proof encoded with [`encode_proof`], checking that every point is
+/// on the curve and every scalar is canonical.
+pub fn decode_proof(bytes: &[u8]) -> Result<Proof<Fr, KZG10<Bn254>>, Error> {
+    if bytes.len() != PROOF_LENGTH {
+        return Err(Error::InvalidProofLength {
+            expected: PROOF_LENGTH,
+            found: bytes.len(),
+        });
+    }
+    let mut reader = WordReader(bytes);
+    Ok(Proof {
+        a_comm: reader.commitment()?,
+        b_comm: reader.commitment()?,
+        c_comm: reader.commitment()?,
+        d_comm: reader.commitment()?,
+        z_comm: reader.commitment()?,
+        f_comm: Some(reader.commitment()?),
+        h_1_comm: Some(reader.commitment()?),
+        h_2_comm: Some(reader.commitment()?),
+        z_2_comm: Some(reader.commitment()?),
+        t_1_comm: reader.commitment()?,
+        t_2_comm: reader.commitment()?,
+        t_3_comm: reader.commitment()?,
+        t_4_comm: reader.commitment()?,
+        t_5_comm: reader.commitment()?,
+        t_6_comm: reader.commitment()?,
+        t_7_comm: reader.commitment()?,
+        t_8_comm: reader.commitment()?,
+        aw_opening: reader.opening()?,
+        saw_opening: reader.opening()?,
+        evaluations: ProofEvaluations {
+            wire_evals: WireEvaluations {
+                a_eval: reader.scalar()?,
+                b_eval: reader.scalar()?,
+                c_eval: reader.scalar()?,
+                d_eval: reader.scalar()?,
+            },
+            perm_evals: PermutationEvaluations {
+                left_sigma_eval: reader.scalar()?,
+                right_sigma_eval: reader.scalar()?,
+                out_sigma_eval: reader.scalar()?,
+                permutation_eval: reader.scalar()?,
+            },
+            lookup_evals: Some(LookupEvaluations {
+                q_lookup_eval: reader.scalar()?,
+                z2_next_eval: reader.scalar()?,
+                h1_eval: reader.scalar()?,
+                h1_next_eval: reader.scalar()?,
+                h2_eval: reader.scalar()?,
+                f_eval: reader.scalar()?,
+                table_eval: reader.scalar()?,
+                table_next_eval: reader.scalar()?,
+            }),
+            custom_evals: CustomEvaluations {
+                vals: CUSTOM_EVALUATION_LABELS
+                    .iter()
+                    .map(|label| Ok((label.to_string(), reader.scalar()?)))
+                    .collect::<Result<_, Error>>()?,
+            },
+        },
+    })
+}
+
+/// Encodes the `public_inputs` of a circuit with public inputs at `pi_pos` as
+/// one word per position, in the order of `pi_pos`.
+pub fn encode_public_inputs(
+    public_inputs: &PublicInputs<Fr>,
+    pi_pos: &[usize],
+) -> Vec<u8> {
+    let values = public_inputs
+        .get_pos()
+        .zip(public_inputs.get_vals())
+        .collect::<HashMap<_, _>>();
+    pi_pos
+        .iter()
+        .flat_map(|pos| {
+            scalar_to_word(&values.get(pos).map_or(Fr::zero(), |value| **value))
+        })
+        .collect()
+}
+
+/// Decodes public inputs encoded with [`encode_public_inputs`].
+pub fn decode_public_inputs(
+    bytes: &[u8],
+    pi_pos: &[usize],
+) -> Result<PublicInputs<Fr>, Error> {
+    if bytes.len() != pi_pos.len() * WORD_SIZE {
+        return Err(Error::InvalidPublicInputBytes);
+    }
+    let values = bytes
+        .chunks(WORD_SIZE)
+        .map(|word| {
+            scalar_from_word::<Fr>(word).ok_or(Error::InvalidPublicInputValue)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    PublicInputs::from_val_pos(pi_pos, &values)
+}
+
+/// Reads consecutive values out of EVM words.
+struct WordReader<'a>(&'a [u8]);
+
+impl<'a> WordReader<'a> {
+    /// Reads the next `count` words.
+    fn words(&mut self, count: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < count * WORD_SIZE {
+            return Err(Error::NotEnoughBytes);
+        }
+        let (words, rest) = self.0.split_at(count * WORD_SIZE);
+        self.0 = rest;
+        Ok(words)
+    }
+
+    /// Reads a scalar.
+    fn scalar(&mut self) -> Result<Fr, Error> {
+        scalar_from_word(self.words(1)?).ok_or(Error::ScalarMalformed)
+    }
+
+    /// Reads a commitment.
+    fn commitment(&mut self) -> Result<kzg10::Commitment<Bn254>, Error> {
+        point_from_words(self.words(2)?).map(kzg10::Commitment)
+    }
+
+    /// Reads an opening proof, a missing blinding evaluation being encoded as
+    /// zero.
+    fn opening(&mut self) -> Result<kzg10::Proof<Bn254>, Error> {
+        let w = point_from_words(self.words(2)?)?;
+        let random_v = self.scalar()?;
+        Ok(kzg10::Proof {
+            w,
+            random_v: (!random_v.is_zero()).then(|| random_v),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bn254::G1Projective;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{Field, UniformRand};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_point_round_trip() {
+        let mut rng = test_rng();
+        for point in
+            [G1Projective::rand(&mut rng).into_affine(), G1Affine::zero()]
+        {
+            let words = point_to_words(&point);
+            assert_eq!(point_from_words(&words).unwrap(), point);
+        }
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_values() {
+        let modulus = Fr::characteristic();
+        let mut word = [0u8; WORD_SIZE];
+        for (byte, limb) in word.chunks_mut(8).rev().zip(modulus) {
+            byte.copy_from_slice(&limb.to_be_bytes());
+        }
+        assert!(scalar_from_word::<Fr>(&word).is_none());
+
+        let mut words = point_to_words(&G1Affine::prime_subgroup_generator());
+        words[2 * WORD_SIZE - 1] ^= 1;
+        assert!(point_from_words(&words).is_err());
+    }
+
+    #[test]
+    fn test_public_inputs_round_trip() {
+        let pi_pos = [3, 0, 7];
+        let public_inputs = PublicInputs::from_val_pos(
+            &pi_pos,
+            &[Fr::from(5u64), Fr::zero(), -Fr::from(2u64)],
+        )
+        .unwrap();
+        let bytes = encode_public_inputs(&public_inputs, &pi_pos);
+        assert_eq!(bytes.len(), pi_pos.len() * WORD_SIZE);
+        assert_eq!(
+            decode_public_inputs(&bytes, &pi_pos).unwrap(),
+            public_inputs
+        );
+        assert!(decode_public_inputs(&bytes[1..], &pi_pos).is_err());
+    }
+}
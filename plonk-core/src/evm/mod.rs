@@ -0,0 +1,41 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! On-chain verification of PLONK proofs over BN254.
+//!
+//! This module is only available with the `evm` feature, which pulls in the
+//! BN254 curve and the Keccak-256 hash.
+//!
+//! Proofs which are meant to be verified by an Ethereum contract are created
+//! with the [`KeccakTranscript`], which hashes scalars and points in the
+//! encoding the EVM precompiles of [EIP-196] and [EIP-197] use. The
+//! [`EvmVerifier`] of a circuit generates the Solidity source of the verifier
+//! contract and checks proofs in the byte format the contract takes as
+//! calldata.
+//!
+//! The tests of this module deploy the contract of a test circuit in the EVM of
+//! [Foundry] and check that it accepts the proof the [`EvmVerifier`] accepts
+//! and rejects the tampered proofs it rejects. They need `forge` to be
+//! installed, so they are ignored unless run with
+//! `cargo test --features evm -- --ignored evm::`, as the CI does. The
+//! contract of any other circuit should still be tested against proofs of
+//! that circuit before being deployed.
+//!
+//! [Foundry]: https://book.getfoundry.sh
+//! [EIP-196]: https://eips.ethereum.org/EIPS/eip-196
+//! [EIP-197]: https://eips.ethereum.org/EIPS/eip-197
+
+mod encoding;
+mod solidity;
+mod transcript;
+mod verifier;
+
+pub use encoding::{
+    decode_proof, decode_public_inputs, encode_proof, encode_public_inputs,
+    PROOF_LENGTH,
+};
+pub use transcript::KeccakTranscript;
+pub use verifier::EvmVerifier;
@@ -0,0 +1,224 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Generation of the Solidity source of the verifier contract.
+
+use crate::{
+    evm::{
+        encoding::{point_to_words, scalar_to_word},
+        transcript::KeccakTranscript,
+        verifier::EvmVerifier,
+    },
+    transcript::TranscriptProtocol,
+    util::EvaluationDomainExt,
+};
+use ark_bn254::{Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::TEModelParameters;
+use ark_ff::{Field, PrimeField};
+use ark_poly::EvaluationDomain;
+use core::fmt::Write;
+
+/// Template of the verifier contract.
+const TEMPLATE: &str = include_str!("verifier.sol");
+
+/// Renders the verifier contract of `verifier` as `contract_name`.
+pub(super) fn render<P>(
+    verifier: &EvmVerifier<P>,
+    contract_name: &str,
+) -> String
+where
+    P: TEModelParameters<BaseField = Fr>,
+{
+    TEMPLATE
+        .replace("{{CONTRACT_NAME}}", contract_name)
+        .replace("{{CONSTANTS}}", &constants(verifier))
+        .replace("{{ABSORB_PUBLIC_INPUTS}}", &absorb_public_inputs(verifier))
+        .replace(
+            "{{EVALUATE_PUBLIC_INPUTS}}",
+            &evaluate_public_inputs(verifier),
+        )
+}
+
+/// Renders the constants which depend on the circuit and on the SRS.
+fn constants<P>(verifier: &EvmVerifier<P>) -> String
+where
+    P: TEModelParameters<BaseField = Fr>,
+{
+    let domain = &verifier.domain;
+    let vk = &verifier.verifier_key;
+    let pc_vk = &verifier.pc_verifier_key;
+    let mut out = String::new();
+
+    out.push_str("    // Evaluation domain.\n");
+    uint(&mut out, "N", &domain.size().to_string());
+    uint(&mut out, "LOG_N", &domain.log_size_of_group().to_string());
+    uint(&mut out, "N_INV", &scalar(&domain.size_inv()));
+    uint(&mut out, "OMEGA", &scalar(&domain.group_gen()));
+
+    out.push_str("\n    // Embedded curve of the circuit.\n");
+    uint(&mut out, "COEFF_A", &scalar(&P::COEFF_A));
+    uint(&mut out, "COEFF_D", &scalar(&P::COEFF_D));
+
+    out.push_str("\n    // Public inputs and transcript.\n");
+    uint(
+        &mut out,
+        "NUM_PUBLIC_INPUTS",
+        &verifier.pi_pos.len().to_string(),
+    );
+    // The circuit digest is a constant of the contract, so it is absorbed
+    // into the initial state of the transcript.
+    let mut transcript = KeccakTranscript::new(verifier.transcript_init);
+    vk.circuit_digest().seed_transcript(&mut transcript);
+    writeln!(
+        out,
+        "    bytes32 internal constant TRANSCRIPT_INIT = {};",
+        hex(&transcript.state())
+    )
+    .unwrap();
+
+    out.push_str("\n    // Commitments of the verifier key.\n");
+    let arithmetic = &vk.arithmetic;
+    let permutation = &vk.permutation;
+    let lookup = held(&vk.lookup);
+    for (name, commitment) in [
+        ("Q_M", &arithmetic.q_m),
+        ("Q_L", &arithmetic.q_l),
+        ("Q_R", &arithmetic.q_r),
+        ("Q_O", &arithmetic.q_o),
+        ("Q_4", &arithmetic.q_4),
+        ("Q_C", &arithmetic.q_c),
+        ("Q_HL", &arithmetic.q_hl),
+        ("Q_HR", &arithmetic.q_hr),
+        ("Q_H4", &arithmetic.q_h4),
+        ("Q_RANGE", held(&vk.range_selector_commitment)),
+        ("Q_LOGIC", held(&vk.logic_selector_commitment)),
+        (
+            "Q_FIXED_GROUP_ADD",
+            held(&vk.fixed_group_add_selector_commitment),
+        ),
+        (
+            "Q_VARIABLE_GROUP_ADD",
+            held(&vk.variable_group_add_selector_commitment),
+        ),
+        ("Q_LOOKUP", &lookup.q_lookup),
+        ("LEFT_SIGMA", &permutation.left_sigma),
+        ("RIGHT_SIGMA", &permutation.right_sigma),
+        ("OUT_SIGMA", &permutation.out_sigma),
+        ("FOURTH_SIGMA", &permutation.fourth_sigma),
+        ("TABLE_1", &lookup.table_1),
+        ("TABLE_2", &lookup.table_2),
+        ("TABLE_3", &lookup.table_3),
+        ("TABLE_4", &lookup.table_4),
+    ] {
+        g1(&mut out, name, &commitment.0);
+    }
+
+    out.push_str("\n    // Structured reference string.\n");
+    g1(&mut out, "G", &pc_vk.g);
+    g1(&mut out, "GAMMA_G", &pc_vk.gamma_g);
+    g2(&mut out, "H", &pc_vk.h);
+    g2(&mut out, "BETA_H", &pc_vk.beta_h);
+    out
+}
+
+/// Renders the serialization of the non-zero public inputs, in increasing
+/// order of their positions.
+fn absorb_public_inputs<P>(verifier: &EvmVerifier<P>) -> String
+where
+    P: TEModelParameters<BaseField = Fr>,
+{
+    let mut out = String::new();
+    for (index, pos) in sorted_positions(&verifier.pi_pos) {
+        writeln!(
+            out,
+            "        if (publicInputs[{index}] != 0) {{\n            \
+             encoded = abi.encodePacked(encoded, hex\"{pos}\", \
+             reverseBytes(publicInputs[{index}]));\n            \
+             count++;\n        }}",
+            index = index,
+            pos = hex(&(pos as u64).to_le_bytes()).trim_start_matches("0x"),
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Renders the barycentric evaluation of the public input polynomial at `z`.
+fn evaluate_public_inputs<P>(verifier: &EvmVerifier<P>) -> String
+where
+    P: TEModelParameters<BaseField = Fr>,
+{
+    let group_gen_inv = verifier.domain.group_gen_inv();
+    let mut out = String::new();
+    for (index, pos) in sorted_positions(&verifier.pi_pos) {
+        writeln!(
+            out,
+            "        if (publicInputs[{index}] != 0) {{\n            \
+             sum = addmod(sum, mulmod(publicInputs[{index}], \
+             inverse(submod(mulmod({root}, z, R_MOD), 1)), R_MOD), R_MOD);\n        \
+             }}",
+            index = index,
+            root = scalar(&group_gen_inv.pow([pos as u64])),
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Returns the indices of `pi_pos` together with the positions, sorted by
+/// position.
+fn sorted_positions(pi_pos: &[usize]) -> Vec<(usize, usize)> {
+    let mut positions = pi_pos.iter().copied().enumerate().collect::<Vec<_>>();
+    positions.sort_unstable_by_key(|(_, pos)| *pos);
+    positions
+}
+
+/// Unwraps a part of the verifier key, which holds every gate type since
+/// [`EvmVerifier::new`] rejects the other keys.
+fn held<T>(part: &Option<T>) -> &T {
+    part.as_ref().expect("key holds every gate type")
+}
+
+/// Writes a `uint256` constant.
+fn uint(out: &mut String, name: &str, value: &str) {
+    writeln!(out, "    uint256 internal constant {} = {};", name, value)
+        .unwrap();
+}
+
+/// Writes the coordinates of a `G1` point as `{name}_X` and `{name}_Y`.
+fn g1(out: &mut String, name: &str, point: &G1Affine) {
+    let words = point_to_words(point);
+    uint(out, &format!("{}_X", name), &hex(&words[..32]));
+    uint(out, &format!("{}_Y", name), &hex(&words[32..]));
+}
+
+/// Writes the coordinates of a `G2` point in the order of the EIP-197
+/// precompile.
+fn g2(out: &mut String, name: &str, point: &G2Affine) {
+    let coordinate = |suffix: &str, value: &Fq2, out: &mut String| {
+        uint(out, &format!("{}_{}_C1", name, suffix), &scalar(&value.c1));
+        uint(out, &format!("{}_{}_C0", name, suffix), &scalar(&value.c0));
+    };
+    coordinate("X", &point.x, out);
+    coordinate("Y", &point.y, out);
+}
+
+/// Formats a scalar as a hexadecimal literal.
+fn scalar<F>(value: &F) -> String
+where
+    F: PrimeField,
+{
+    hex(&scalar_to_word(value))
+}
+
+/// Formats bytes as a hexadecimal literal.
+pub(super) fn hex(bytes: &[u8]) -> String {
+    let mut out = String::from("0x");
+    for byte in bytes {
+        write!(out, "{:02x}", byte).unwrap();
+    }
+    out
+}
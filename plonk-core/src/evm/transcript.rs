@@ -0,0 +1,210 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Keccak-256 transcript which can be replayed by the EVM.
+
+use crate::{
+    commitment::HomomorphicCommitment,
+    evm::encoding::point_to_words,
+    transcript::{challenge_len, TranscriptProtocol, TRANSCRIPT_DOMAIN_TAG},
+};
+use ark_bn254::G1Affine;
+use ark_ec::AffineCurve;
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use core::any::Any;
+use sha3::{Digest, Keccak256};
+
+/// Fiat-Shamir transcript over a running Keccak-256 state.
+///
+/// The state starts as `keccak256(label)`, to which the
+/// [`TRANSCRIPT_DOMAIN_TAG`] is appended. Appending a message sets it to
+/// `keccak256(state || label || message)` and squeezing a challenge sets it to
+/// `keccak256(state || label)`. The challenge is the new state followed by as
+/// many successive hashes of it as [`challenge_len`] requires, read as a
+/// big-endian integer reduced modulo the scalar field. Over BN254, this is
+/// `state || keccak256(state)`.
+///
+/// Scalars appended with [`append_scalar`] are encoded as big-endian words and
+/// BN254 `G1` points appended with [`append_point`] or [`append_commitment`]
+/// as their two big-endian coordinates, with the point at infinity encoded as
+/// `(0, 0)`. Every other item is appended in its uncompressed arkworks
+/// serialization.
+///
+/// [`append_scalar`]: TranscriptProtocol::append_scalar
+/// [`append_point`]: TranscriptProtocol::append_point
+/// [`append_commitment`]: TranscriptProtocol::append_commitment
+#[derive(Clone, Debug)]
+pub struct KeccakTranscript {
+    /// Running state of the transcript.
+    state: [u8; 32],
+}
+
+impl KeccakTranscript {
+    /// Returns the current state of the transcript.
+    pub fn state(&self) -> [u8; 32] {
+        self.state
+    }
+
+    /// Hashes the state followed by `parts` into the new state.
+    fn absorb(&mut self, parts: &[&[u8]]) {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.state);
+        for part in parts {
+            hasher.update(part);
+        }
+        self.state.copy_from_slice(&hasher.finalize());
+    }
+}
+
+impl TranscriptProtocol for KeccakTranscript {
+    fn new(label: &'static [u8]) -> Self {
+        let mut state = [0u8; 32];
+        state.copy_from_slice(&Keccak256::digest(label));
+        let mut transcript = Self { state };
+        transcript.append_message(b"dom-sep", TRANSCRIPT_DOMAIN_TAG);
+        transcript
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.absorb(&[label, message]);
+    }
+
+    fn append(&mut self, label: &'static [u8], item: &impl CanonicalSerialize) {
+        let mut bytes = Vec::new();
+        item.serialize_uncompressed(&mut bytes).unwrap();
+        self.append_message(label, &bytes)
+    }
+
+    fn append_scalar<F>(&mut self, label: &'static [u8], scalar: &F)
+    where
+        F: PrimeField,
+    {
+        self.append_message(label, &scalar.into_repr().to_bytes_be())
+    }
+
+    /// Appends BN254 `G1` points as their two big-endian coordinates, and
+    /// points of any other curve in their uncompressed serialization.
+    fn append_point<G>(&mut self, label: &'static [u8], point: &G)
+    where
+        G: AffineCurve,
+    {
+        match (point as &dyn Any).downcast_ref::<G1Affine>() {
+            Some(point) => self.append_message(label, &point_to_words(point)),
+            None => self.append(label, point),
+        }
+    }
+
+    fn append_commitment<F, PC>(
+        &mut self,
+        label: &'static [u8],
+        commitment: &PC::Commitment,
+    ) where
+        F: PrimeField,
+        PC: HomomorphicCommitment<F>,
+    {
+        self.append_point(label, &PC::commitment_point(commitment))
+    }
+
+    fn challenge_scalar<F>(&mut self, label: &'static [u8]) -> F
+    where
+        F: PrimeField,
+    {
+        self.absorb(&[label]);
+        let mut bytes = self.state.to_vec();
+        let mut block = self.state;
+        while bytes.len() < challenge_len::<F>() {
+            block.copy_from_slice(&Keccak256::digest(block));
+            bytes.extend_from_slice(&block);
+        }
+        F::from_be_bytes_mod_order(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::commitment::KZG10;
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::Zero;
+    use ark_poly_commit::kzg10;
+
+    #[test]
+    fn test_scalars_are_appended_big_endian() {
+        let scalar = Fr::from(0x0102u64);
+        let mut transcript = KeccakTranscript::new(b"test");
+        let mut expected = transcript.clone();
+        transcript.append_scalar(b"scalar", &scalar);
+        expected.append_message(b"scalar", &scalar.into_repr().to_bytes_be());
+        assert_eq!(transcript.state(), expected.state());
+    }
+
+    #[test]
+    fn test_points_are_appended_as_evm_words() {
+        let generator = G1Affine::prime_subgroup_generator();
+        let mut transcript = KeccakTranscript::new(b"test");
+        let mut expected = transcript.clone();
+        transcript.append_point(b"point", &generator);
+        expected.append_message(
+            b"point",
+            &[
+                generator.x.into_repr().to_bytes_be(),
+                generator.y.into_repr().to_bytes_be(),
+            ]
+            .concat(),
+        );
+        assert_eq!(transcript.state(), expected.state());
+
+        transcript.append_commitment::<Fr, KZG10<Bn254>>(
+            b"commitment",
+            &kzg10::Commitment(generator),
+        );
+        expected.append_point(b"commitment", &generator);
+        assert_eq!(transcript.state(), expected.state());
+
+        transcript.append_point(b"infinity", &G1Affine::zero());
+        expected.append_message(b"infinity", &[0; 64]);
+        assert_eq!(transcript.state(), expected.state());
+    }
+
+    #[test]
+    fn test_other_items_are_not_reencoded() {
+        // Items are encoded by the method they are appended with, not by the
+        // length of their serialization.
+        let scalar = Fr::from(0x0102u64);
+        let mut transcript = KeccakTranscript::new(b"test");
+        let mut expected = transcript.clone();
+        transcript.append(b"item", &scalar);
+        let mut bytes = Vec::new();
+        scalar.serialize_uncompressed(&mut bytes).unwrap();
+        expected.append_message(b"item", &bytes);
+        assert_eq!(transcript.state(), expected.state());
+    }
+
+    #[test]
+    fn test_challenges_are_wide_reductions() {
+        let mut transcript = KeccakTranscript::new(b"test");
+        let challenge: Fr = transcript.challenge_scalar(b"challenge");
+        let state = transcript.state();
+        assert_eq!(
+            challenge,
+            Fr::from_be_bytes_mod_order(
+                &[state.as_slice(), &Keccak256::digest(state)].concat()
+            )
+        );
+    }
+
+    #[test]
+    fn test_transcripts_are_keyed_with_the_domain_tag() {
+        let transcript = KeccakTranscript::new(b"test");
+        let mut state = [0u8; 32];
+        state.copy_from_slice(&Keccak256::digest(b"test"));
+        let mut untagged = KeccakTranscript { state };
+        assert_ne!(transcript.state(), untagged.state());
+        untagged.append_message(b"dom-sep", TRANSCRIPT_DOMAIN_TAG);
+        assert_eq!(transcript.state(), untagged.state());
+    }
+}
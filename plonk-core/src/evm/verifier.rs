@@ -0,0 +1,505 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Verifier of proofs in the format of the generated Solidity contract.
+
+use crate::{
+    circuit::trim_params,
+    commitment::KZG10,
+    error::Error,
+    evm::{
+        encoding::{decode_proof, decode_public_inputs},
+        solidity,
+        transcript::KeccakTranscript,
+    },
+    proof_system::{ActiveGates, Verifier, VerifierKey},
+};
+use ark_bn254::{Bn254, Fr};
+use ark_ec::TEModelParameters;
+use ark_ff::{FftField, FftParameters};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
+};
+use ark_poly_commit::{
+    sonic_pc::VerifierKey as SonicVerifierKey, PolynomialCommitment,
+};
+use core::marker::PhantomData;
+use hashbrown::HashSet;
+
+/// Verifier of the proofs of a single circuit over BN254 in the format of
+/// the EVM.
+///
+/// Proofs have to be created with the [`KeccakTranscript`], initialized with
+/// the same label as the `EvmVerifier`, and are passed encoded with
+/// [`encode_proof`] together with public inputs encoded with
+/// [`encode_public_inputs`], the format the contract generated by
+/// [`EvmVerifier::solidity`] takes.
+///
+/// Circuits with custom gates are not supported.
+///
+/// [`encode_proof`]: crate::evm::encode_proof
+/// [`encode_public_inputs`]: crate::evm::encode_public_inputs
+pub struct EvmVerifier<P>
+where
+    P: TEModelParameters<BaseField = Fr>,
+{
+    /// Verifier key of the circuit.
+    pub(super) verifier_key: VerifierKey<Fr, KZG10<Bn254>>,
+
+    /// Verifier key of the commitment scheme, trimmed to the circuit size.
+    pub(super) pc_verifier_key: SonicVerifierKey<Bn254>,
+
+    /// Evaluation domain of the circuit.
+    pub(super) domain: GeneralEvaluationDomain<Fr>,
+
+    /// Positions of the public inputs, in the order they are encoded in.
+    pub(super) pi_pos: Vec<usize>,
+
+    /// Label the transcript is initialized with.
+    pub(super) transcript_init: &'static [u8],
+
+    /// Embedded curve of the circuit.
+    _marker: PhantomData<P>,
+}
+
+impl<P> EvmVerifier<P>
+where
+    P: TEModelParameters<BaseField = Fr>,
+{
+    /// Creates the verifier of the circuit with `verifier_key` and public
+    /// inputs at `pi_pos`.
+    ///
+    /// Fails if the circuit uses custom gates or a layout other than
+    /// [`GateLayout::Full`], or if `pi_pos` contains duplicates or positions
+    /// the circuit does not reserve for public inputs.
+    ///
+    /// [`GateLayout::Full`]: crate::constraint_system::GateLayout::Full
+    pub fn new(
+        u_params: &<KZG10<Bn254> as PolynomialCommitment<
+            Fr,
+            DensePolynomial<Fr>,
+        >>::UniversalParams,
+        verifier_key: VerifierKey<Fr, KZG10<Bn254>>,
+        pi_pos: Vec<usize>,
+        transcript_init: &'static [u8],
+    ) -> Result<Self, Error> {
+        if let Some(name) = verifier_key.custom_gate_names().next() {
+            return Err(Error::UnsupportedCustomGate {
+                name: name.to_string(),
+            });
+        }
+
+        if verifier_key.active_gates() != ActiveGates::ALL {
+            return Err(Error::UnsupportedGateLayout);
+        }
+
+        let domain =
+            GeneralEvaluationDomain::<Fr>::new(verifier_key.n)
+                .ok_or(Error::InvalidEvalDomainSize {
+                log_size_of_group: verifier_key.n.trailing_zeros(),
+                adicity:
+                    <<Fr as FftField>::FftParams as FftParameters>::TWO_ADICITY,
+            })?;
+
+        let mut positions = HashSet::new();
+        if let Some(&position) = pi_pos.iter().find(|&&pos| {
+            pos >= domain.size()
+                || !verifier_key.pi_pos().contains(&pos)
+                || !positions.insert(pos)
+        }) {
+            return Err(Error::InvalidPublicInputPosition { position });
+        }
+
+        let (_, pc_verifier_key) =
+            trim_params::<Fr, KZG10<Bn254>>(u_params, domain.size())?;
+
+        Ok(Self {
+            verifier_key,
+            pc_verifier_key,
+            domain,
+            pi_pos,
+            transcript_init,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the positions of the public inputs, in the order they are
+    /// encoded in.
+    pub fn pi_pos(&self) -> &[usize] {
+        &self.pi_pos
+    }
+
+    /// Verifies the encoded `proof` against the encoded `public_inputs`.
+    pub fn verify(
+        &self,
+        proof: &[u8],
+        public_inputs: &[u8],
+    ) -> Result<(), Error> {
+        let proof = decode_proof(proof)?;
+        let public_inputs = decode_public_inputs(public_inputs, &self.pi_pos)?;
+
+        let mut verifier =
+            Verifier::<Fr, P, KZG10<Bn254>, KeccakTranscript>::new(
+                self.transcript_init,
+            );
+        verifier.verifier_key = Some(self.verifier_key.clone());
+        let accumulator = verifier.verify_deferred(
+            &proof,
+            &self.pc_verifier_key,
+            &public_inputs,
+        )?;
+        if accumulator.check(&self.pc_verifier_key) {
+            Ok(())
+        } else {
+            Err(Error::PairingCheckFailure)
+        }
+    }
+
+    /// Returns the Solidity source of a contract named `contract_name` which
+    /// verifies the proofs of the circuit.
+    ///
+    /// The contract exposes `verify(bytes proof, uint256[] publicInputs)`,
+    /// which takes the same encoded proof as [`EvmVerifier::verify`] and the
+    /// public inputs as one word each, in the order of
+    /// [`EvmVerifier::pi_pos`].
+    pub fn solidity(&self, contract_name: &str) -> String {
+        solidity::render(self, contract_name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::Circuit,
+        constraint_system::{GateLayout, StandardComposer},
+        error::to_pc_error,
+        evm::{
+            encode_proof, encode_public_inputs, solidity::hex, PROOF_LENGTH,
+        },
+    };
+    use ark_ed_on_bn254::EdwardsParameters;
+    use ark_ff::{One, Zero};
+    use core::fmt::Write;
+    use rand_core::OsRng;
+    use std::{fs, process::Command};
+    use tempdir::TempDir;
+
+    // Implements a circuit that checks:
+    // 1) a + b = c where c is a PI
+    // 2) a and b are 8 bit values
+    // 3) a xor b = d where d is a PI
+    #[derive(Default)]
+    struct TestCircuit {
+        a: Fr,
+        b: Fr,
+        c: Fr,
+        d: Fr,
+    }
+
+    impl Circuit<Fr, EdwardsParameters> for TestCircuit {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+        const GATE_LAYOUT: GateLayout = GateLayout::Full;
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<Fr, EdwardsParameters>,
+        ) -> Result<(), Error> {
+            let a = composer.add_input(self.a);
+            let b = composer.add_input(self.b);
+            let zero = composer.zero_var();
+
+            composer.arithmetic_gate(|gate| {
+                gate.witness(a, b, Some(zero))
+                    .add(Fr::one(), Fr::one())
+                    .pi(-self.c)
+            });
+            composer.range_gate(a, 8);
+            composer.range_gate(b, 8);
+            let xor = composer.xor_gate(a, b, 8);
+            composer.constrain_to_constant(xor, Fr::from(0u64), Some(-self.d));
+            Ok(())
+        }
+    }
+
+    type Setup = (EvmVerifier<EdwardsParameters>, Vec<u8>, Vec<u8>);
+
+    fn setup() -> Result<Setup, Error> {
+        let pp = KZG10::<Bn254>::setup(1 << 7, None, &mut OsRng)
+            .map_err(to_pc_error::<Fr, KZG10<Bn254>>)?;
+        let (pk, (vk, pi_pos)) =
+            TestCircuit::default().compile::<KZG10<Bn254>>(&pp, &mut OsRng)?;
+
+        let mut circuit = TestCircuit {
+            a: Fr::from(25u64),
+            b: Fr::from(100u64),
+            c: Fr::from(125u64),
+            d: Fr::from(25u64 ^ 100),
+        };
+        let (proof, pi) = circuit
+            .gen_proof_with_transcript::<KZG10<Bn254>, KeccakTranscript>(
+                &pp, pk, b"Test", &mut OsRng,
+            )?;
+
+        let verifier = EvmVerifier::new(&pp, vk, pi_pos, b"Test")?;
+        let public_inputs = encode_public_inputs(&pi, verifier.pi_pos());
+        Ok((verifier, encode_proof(&proof)?, public_inputs))
+    }
+
+    #[test]
+    fn test_verify() -> Result<(), Error> {
+        let (verifier, proof, public_inputs) = setup()?;
+        assert_eq!(proof.len(), PROOF_LENGTH);
+        verifier.verify(&proof, &public_inputs)
+    }
+
+    #[test]
+    fn test_encoding_round_trip() -> Result<(), Error> {
+        let (verifier, proof, public_inputs) = setup()?;
+        assert_eq!(encode_proof(&decode_proof(&proof)?)?, proof);
+
+        // Zero encodes a missing blinding evaluation, so a zero one is
+        // rejected rather than decoded as missing.
+        let mut decoded = decode_proof(&proof)?;
+        decoded.aw_opening.random_v = Some(Fr::one());
+        assert_eq!(decode_proof(&encode_proof(&decoded)?)?, decoded);
+        decoded.aw_opening.random_v = Some(Fr::zero());
+        assert!(matches!(
+            encode_proof(&decoded),
+            Err(Error::ZeroBlindingEvaluation)
+        ));
+        assert_eq!(
+            encode_public_inputs(
+                &decode_public_inputs(&public_inputs, verifier.pi_pos())?,
+                verifier.pi_pos()
+            ),
+            public_inputs
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_tampered_proof() -> Result<(), Error> {
+        let (verifier, mut proof, public_inputs) = setup()?;
+        // Last byte of the `a` evaluation.
+        proof[41 * 32 - 1] ^= 1;
+        assert!(verifier.verify(&proof, &public_inputs).is_err());
+        assert!(matches!(
+            verifier.verify(&proof[1..], &public_inputs),
+            Err(Error::InvalidProofLength { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_wrong_public_inputs() -> Result<(), Error> {
+        let (verifier, proof, mut public_inputs) = setup()?;
+        let last = public_inputs.len() - 1;
+        public_inputs[last] ^= 1;
+        assert!(verifier.verify(&proof, &public_inputs).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solidity() -> Result<(), Error> {
+        let (verifier, _, _) = setup()?;
+        let source = verifier.solidity("TestVerifier");
+        assert!(source.contains("contract TestVerifier {"));
+        assert!(source.contains(&format!(
+            "uint256 internal constant N = {};",
+            verifier.domain.size()
+        )));
+        assert!(
+            source.contains("uint256 internal constant NUM_PUBLIC_INPUTS = 2;")
+        );
+        assert!(!source.contains("{{"));
+        Ok(())
+    }
+
+    /// Configuration of the Foundry project running the contract.
+    const FOUNDRY_CONFIG: &str = "[profile.default]
+src = \"src\"
+test = \"test\"
+out = \"out\"
+optimizer = true
+";
+
+    /// Start of the Foundry test of the contract, whose cases are appended by
+    /// [`contract_test`].
+    const CONTRACT_TEST: &str = "// SPDX-License-Identifier: MIT OR Apache-2.0
+pragma solidity ^0.8.0;
+
+import \"../src/Verifier.sol\";
+
+contract VerifierTest {
+    Verifier internal verifier;
+
+    function setUp() public {
+        verifier = new Verifier();
+    }
+
+    function check(
+        bytes memory proof,
+        uint256[] memory publicInputs,
+        bool valid
+    ) internal view {
+        bool accepted;
+        try verifier.verify(proof, publicInputs) returns (bool result) {
+            accepted = result;
+        } catch {
+            accepted = false;
+        }
+        require(accepted == valid, \"verifiers disagree\");
+    }
+";
+
+    /// Returns the Foundry test checking that the contract accepts the
+    /// proofs and public inputs of `cases` exactly when they are `valid`.
+    fn contract_test(cases: &[(Vec<u8>, Vec<u8>)], valid: &[bool]) -> String {
+        let mut out = String::from(CONTRACT_TEST);
+        for (index, ((proof, public_inputs), valid)) in
+            cases.iter().zip(valid).enumerate()
+        {
+            writeln!(out, "\n    function testCase{}() public view {{", index)
+                .unwrap();
+            writeln!(
+                out,
+                "        uint256[] memory publicInputs = new uint256[]({});",
+                public_inputs.len() / 32
+            )
+            .unwrap();
+            for (i, word) in public_inputs.chunks(32).enumerate() {
+                writeln!(out, "        publicInputs[{}] = {};", i, hex(word))
+                    .unwrap();
+            }
+            writeln!(
+                out,
+                "        check(hex\"{}\", publicInputs, {});\n    }}",
+                hex(proof).trim_start_matches("0x"),
+                valid
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Deploys the generated contract in the EVM of Foundry and checks that
+    /// it accepts the proof [`EvmVerifier::verify`] accepts and rejects the
+    /// tampered ones it rejects.
+    #[test]
+    #[ignore = "requires Foundry, run by the EVM contract job of the CI"]
+    fn test_contract_agrees_with_reference_verifier() -> Result<(), Error> {
+        let (verifier, proof, public_inputs) = setup()?;
+        let tampered = |tamper: &dyn Fn(&mut [u8])| {
+            let mut proof = proof.clone();
+            tamper(&mut proof);
+            (proof, public_inputs.clone())
+        };
+        let mut wrong_public_inputs = public_inputs.clone();
+        let last = wrong_public_inputs.len() - 1;
+        wrong_public_inputs[last] ^= 1;
+
+        let cases = vec![
+            (proof.clone(), public_inputs.clone()),
+            // Last byte of the `a` evaluation.
+            tampered(&|proof: &mut [u8]| proof[41 * 32 - 1] ^= 1),
+            // The `a` commitment replaced by the `b` commitment.
+            tampered(&|proof: &mut [u8]| proof.copy_within(2 * 32..4 * 32, 0)),
+            // The `a` commitment moved off the curve.
+            tampered(&|proof: &mut [u8]| proof[2 * 32 - 1] ^= 1),
+            // The opening at `z` replaced by the opening at `z * omega`.
+            tampered(&|proof: &mut [u8]| {
+                proof.copy_within(37 * 32..40 * 32, 34 * 32)
+            }),
+            // The `a` evaluation replaced by a non-canonical scalar.
+            tampered(&|proof: &mut [u8]| proof[40 * 32..41 * 32].fill(0xff)),
+            (proof.clone(), wrong_public_inputs),
+        ];
+        let valid = cases
+            .iter()
+            .map(|(proof, public_inputs)| {
+                verifier.verify(proof, public_inputs).is_ok()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(valid, [true, false, false, false, false, false, false]);
+
+        let project = TempDir::new("evm").unwrap();
+        let root = project.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::create_dir(root.join("test")).unwrap();
+        fs::write(root.join("foundry.toml"), FOUNDRY_CONFIG).unwrap();
+        fs::write(
+            root.join("src").join("Verifier.sol"),
+            verifier.solidity("Verifier"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("test").join("Verifier.t.sol"),
+            contract_test(&cases, &valid),
+        )
+        .unwrap();
+
+        let output = Command::new("forge")
+            .arg("test")
+            .arg("--root")
+            .arg(root)
+            .output()
+            .expect("forge is installed");
+        assert!(
+            output.status.success(),
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Ok(())
+    }
+
+    // The test circuit with the default layout, which leaves out the lookup
+    // argument the contract expects.
+    #[derive(Default)]
+    struct CompactCircuit(TestCircuit);
+
+    impl Circuit<Fr, EdwardsParameters> for CompactCircuit {
+        const CIRCUIT_ID: [u8; 32] = [0xff; 32];
+
+        fn gadget(
+            &mut self,
+            composer: &mut StandardComposer<Fr, EdwardsParameters>,
+        ) -> Result<(), Error> {
+            self.0.gadget(composer)
+        }
+    }
+
+    #[test]
+    fn test_reject_compact_layout() -> Result<(), Error> {
+        let pp = KZG10::<Bn254>::setup(1 << 7, None, &mut OsRng)
+            .map_err(to_pc_error::<Fr, KZG10<Bn254>>)?;
+        let (pk, (vk, pi_pos)) = CompactCircuit::default()
+            .compile::<KZG10<Bn254>>(&pp, &mut OsRng)?;
+
+        let mut circuit = CompactCircuit(TestCircuit {
+            a: Fr::from(25u64),
+            b: Fr::from(100u64),
+            c: Fr::from(125u64),
+            d: Fr::from(25u64 ^ 100),
+        });
+        let (proof, _) = circuit
+            .gen_proof_with_transcript::<KZG10<Bn254>, KeccakTranscript>(
+                &pp, pk, b"Test", &mut OsRng,
+            )?;
+
+        assert!(matches!(
+            encode_proof(&proof),
+            Err(Error::UnsupportedGateLayout)
+        ));
+        assert!(matches!(
+            EvmVerifier::<EdwardsParameters>::new(&pp, vk, pi_pos, b"Test"),
+            Err(Error::UnsupportedGateLayout)
+        ));
+        Ok(())
+    }
+}
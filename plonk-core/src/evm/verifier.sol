@@ -0,0 +1,798 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+//
+// This contract was generated by plonk-core. Do not edit it by hand: generate
+// it again from the verifier key of the circuit instead.
+
+pragma solidity ^0.8.0;
+
+/// @title {{CONTRACT_NAME}}
+/// @notice Verifies PLONK proofs of a single circuit over BN254, created with
+/// the Keccak-256 transcript of plonk-core.
+contract {{CONTRACT_NAME}} {
+    // Scalar field modulus.
+    uint256 internal constant R_MOD =
+        21888242871839275222246405745257275088548364400416034343698204186575808495617;
+    // 2^256 modulo the scalar field modulus.
+    uint256 internal constant TWO_256_MOD_R = (type(uint256).max % R_MOD) + 1;
+    // Base field modulus.
+    uint256 internal constant Q_MOD =
+        21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+    // Cosets of the permutation argument.
+    uint256 internal constant K1 = 7;
+    uint256 internal constant K2 = 13;
+    uint256 internal constant K3 = 17;
+
+    // Size of a proof, in 32 byte words and in bytes.
+    uint256 internal constant PROOF_WORDS = 66;
+    uint256 internal constant PROOF_LENGTH = 2112;
+
+{{CONSTANTS}}
+    // Word offsets of the commitments in the proof.
+    uint256 internal constant W_A = 0;
+    uint256 internal constant W_B = 2;
+    uint256 internal constant W_C = 4;
+    uint256 internal constant W_D = 6;
+    uint256 internal constant W_Z = 8;
+    uint256 internal constant W_F = 10;
+    uint256 internal constant W_H1 = 12;
+    uint256 internal constant W_H2 = 14;
+    uint256 internal constant W_Z2 = 16;
+    uint256 internal constant W_T1 = 18;
+    uint256 internal constant W_AW = 34;
+    uint256 internal constant W_AW_V = 36;
+    uint256 internal constant W_SAW = 37;
+    uint256 internal constant W_SAW_V = 39;
+
+    // Word offsets of the evaluations in the proof.
+    uint256 internal constant E_A = 40;
+    uint256 internal constant E_B = 41;
+    uint256 internal constant E_C = 42;
+    uint256 internal constant E_D = 43;
+    uint256 internal constant E_LEFT_SIGMA = 44;
+    uint256 internal constant E_RIGHT_SIGMA = 45;
+    uint256 internal constant E_OUT_SIGMA = 46;
+    uint256 internal constant E_PERM = 47;
+    uint256 internal constant E_Q_LOOKUP = 48;
+    uint256 internal constant E_Z2_NEXT = 49;
+    uint256 internal constant E_H1 = 50;
+    uint256 internal constant E_H1_NEXT = 51;
+    uint256 internal constant E_H2 = 52;
+    uint256 internal constant E_F = 53;
+    uint256 internal constant E_TABLE = 54;
+    uint256 internal constant E_TABLE_NEXT = 55;
+    uint256 internal constant E_Q_ARITH = 56;
+    uint256 internal constant E_Q_C = 57;
+    uint256 internal constant E_Q_L = 58;
+    uint256 internal constant E_Q_R = 59;
+    uint256 internal constant E_Q_HL = 60;
+    uint256 internal constant E_Q_HR = 61;
+    uint256 internal constant E_Q_H4 = 62;
+    uint256 internal constant E_A_NEXT = 63;
+    uint256 internal constant E_B_NEXT = 64;
+    uint256 internal constant E_D_NEXT = 65;
+
+    // Offsets of the challenges and of the values derived from them.
+    uint256 internal constant C_ZETA = 0;
+    uint256 internal constant C_BETA = 1;
+    uint256 internal constant C_GAMMA = 2;
+    uint256 internal constant C_DELTA = 3;
+    uint256 internal constant C_EPSILON = 4;
+    uint256 internal constant C_ALPHA = 5;
+    uint256 internal constant C_RANGE = 6;
+    uint256 internal constant C_LOGIC = 7;
+    uint256 internal constant C_FIXED_BASE = 8;
+    uint256 internal constant C_VARIABLE_BASE = 9;
+    uint256 internal constant C_LOOKUP = 10;
+    uint256 internal constant C_Z = 11;
+    uint256 internal constant C_AW = 12;
+    uint256 internal constant C_SAW = 13;
+    uint256 internal constant C_ACCUMULATOR = 14;
+    uint256 internal constant C_ZH = 15;
+    uint256 internal constant C_ZN = 16;
+    uint256 internal constant C_L1 = 17;
+    uint256 internal constant C_PI = 18;
+    uint256 internal constant C_R0 = 19;
+
+    /// @notice Verifies `proof` against `publicInputs`, given in the order of
+    /// the public input positions of the circuit.
+    function verify(bytes calldata proof, uint256[] calldata publicInputs)
+        external
+        view
+        returns (bool)
+    {
+        require(proof.length == PROOF_LENGTH, "invalid proof length");
+        require(
+            publicInputs.length == NUM_PUBLIC_INPUTS,
+            "invalid number of public inputs"
+        );
+        uint256[PROOF_WORDS] memory p = decodeProof(proof);
+        uint256[20] memory c;
+
+        bytes32 state = absorbPublicInputs(TRANSCRIPT_INIT, publicInputs);
+        state = absorbCommitments(state, p, c);
+        evaluateVanishing(c);
+        evaluatePublicInputs(publicInputs, c);
+        computeR0(p, c);
+        state = absorbEvaluations(state, p);
+        squeezeOpeningChallenges(state, p, c);
+
+        return checkOpenings(p, c);
+    }
+
+    // ---------------------------------------------------------------------
+    // Decoding
+    // ---------------------------------------------------------------------
+
+    function decodeProof(bytes calldata proof)
+        internal
+        pure
+        returns (uint256[PROOF_WORDS] memory p)
+    {
+        uint256 words = PROOF_WORDS;
+        assembly {
+            for {
+                let i := 0
+            } lt(i, words) {
+                i := add(i, 1)
+            } {
+                mstore(
+                    add(p, mul(i, 0x20)),
+                    calldataload(add(proof.offset, mul(i, 0x20)))
+                )
+            }
+        }
+        for (uint256 i = 0; i < W_AW_V; i += 2) {
+            requirePoint(p[i], p[i + 1]);
+        }
+        requirePoint(p[W_SAW], p[W_SAW + 1]);
+        require(p[W_AW_V] < R_MOD, "invalid scalar");
+        for (uint256 i = W_SAW_V; i < PROOF_WORDS; i++) {
+            require(p[i] < R_MOD, "invalid scalar");
+        }
+    }
+
+    function requirePoint(uint256 x, uint256 y) internal pure {
+        require(x < Q_MOD && y < Q_MOD, "invalid point");
+        if (x != 0 || y != 0) {
+            uint256 x3 = mulmod(mulmod(x, x, Q_MOD), x, Q_MOD);
+            require(
+                mulmod(y, y, Q_MOD) == addmod(x3, 3, Q_MOD),
+                "point not on curve"
+            );
+        }
+    }
+
+    // ---------------------------------------------------------------------
+    // Transcript
+    // ---------------------------------------------------------------------
+
+    function absorbPoint(bytes32 state, bytes memory label, uint256[PROOF_WORDS] memory p, uint256 offset)
+        internal
+        pure
+        returns (bytes32)
+    {
+        return keccak256(abi.encodePacked(state, label, p[offset], p[offset + 1]));
+    }
+
+    function absorbScalar(bytes32 state, bytes memory label, uint256 scalar)
+        internal
+        pure
+        returns (bytes32)
+    {
+        return keccak256(abi.encodePacked(state, label, scalar));
+    }
+
+    /// Squeezes a challenge from the 64 byte integer
+    /// `state || keccak256(state)`, which makes it statistically uniform.
+    function squeeze(bytes32 state, bytes memory label)
+        internal
+        pure
+        returns (bytes32, uint256)
+    {
+        state = keccak256(abi.encodePacked(state, label));
+        uint256 low = uint256(keccak256(abi.encodePacked(state)));
+        uint256 high = mulmod(uint256(state), TWO_256_MOD_R, R_MOD);
+        return (state, addmod(high, low, R_MOD));
+    }
+
+    /// Squeezes the challenge `c[index]` and appends it back under
+    /// `appendLabel`.
+    function squeezeAndAppend(
+        bytes32 state,
+        bytes memory label,
+        bytes memory appendLabel,
+        uint256[20] memory c,
+        uint256 index
+    ) internal pure returns (bytes32) {
+        (state, c[index]) = squeeze(state, label);
+        return absorbScalar(state, appendLabel, c[index]);
+    }
+
+    function absorbPublicInputs(bytes32 state, uint256[] calldata publicInputs)
+        internal
+        pure
+        returns (bytes32)
+    {
+        for (uint256 i = 0; i < publicInputs.length; i++) {
+            require(publicInputs[i] < R_MOD, "invalid public input");
+        }
+        // The non-zero public inputs are serialized as a map from their
+        // position to their value, with little-endian integers.
+        bytes memory encoded;
+        uint256 count = 0;
+{{ABSORB_PUBLIC_INPUTS}}
+        return
+            keccak256(
+                abi.encodePacked(
+                    state,
+                    "pi",
+                    bytes8(bytes32(reverseBytes(count))),
+                    encoded
+                )
+            );
+    }
+
+    function absorbCommitments(bytes32 state, uint256[PROOF_WORDS] memory p, uint256[20] memory c)
+        internal
+        pure
+        returns (bytes32)
+    {
+        state = absorbPoint(state, "w_l", p, W_A);
+        state = absorbPoint(state, "w_r", p, W_B);
+        state = absorbPoint(state, "w_o", p, W_C);
+        state = absorbPoint(state, "w_4", p, W_D);
+        state = squeezeAndAppend(state, "zeta", "zeta", c, C_ZETA);
+
+        state = absorbPoint(state, "f", p, W_F);
+        state = absorbPoint(state, "h1", p, W_H1);
+        state = absorbPoint(state, "h2", p, W_H2);
+        state = squeezeAndAppend(state, "beta", "beta", c, C_BETA);
+        state = squeezeAndAppend(state, "gamma", "gamma", c, C_GAMMA);
+        state = squeezeAndAppend(state, "delta", "delta", c, C_DELTA);
+        state = squeezeAndAppend(state, "epsilon", "epsilon", c, C_EPSILON);
+        require(
+            c[C_BETA] != c[C_GAMMA] &&
+                c[C_BETA] != c[C_DELTA] &&
+                c[C_BETA] != c[C_EPSILON] &&
+                c[C_GAMMA] != c[C_DELTA] &&
+                c[C_GAMMA] != c[C_EPSILON] &&
+                c[C_DELTA] != c[C_EPSILON],
+            "challenges must be different"
+        );
+
+        state = absorbPoint(state, "z", p, W_Z);
+        state = squeezeAndAppend(state, "alpha", "alpha", c, C_ALPHA);
+        state = squeezeAndAppend(
+            state,
+            "range separation challenge",
+            "range seperation challenge",
+            c,
+            C_RANGE
+        );
+        state = squeezeAndAppend(
+            state,
+            "logic separation challenge",
+            "logic seperation challenge",
+            c,
+            C_LOGIC
+        );
+        state = squeezeAndAppend(
+            state,
+            "fixed base separation challenge",
+            "fixed base separation challenge",
+            c,
+            C_FIXED_BASE
+        );
+        state = squeezeAndAppend(
+            state,
+            "variable base separation challenge",
+            "variable base separation challenge",
+            c,
+            C_VARIABLE_BASE
+        );
+        state = squeezeAndAppend(
+            state,
+            "lookup separation challenge",
+            "lookup separation challenge",
+            c,
+            C_LOOKUP
+        );
+
+        state = absorbPoint(state, "t_1", p, W_T1);
+        state = absorbPoint(state, "t_2", p, W_T1 + 2);
+        state = absorbPoint(state, "t_3", p, W_T1 + 4);
+        state = absorbPoint(state, "t_4", p, W_T1 + 6);
+        state = absorbPoint(state, "t_5", p, W_T1 + 8);
+        state = absorbPoint(state, "t_6", p, W_T1 + 10);
+        state = absorbPoint(state, "t_7", p, W_T1 + 12);
+        state = absorbPoint(state, "t_8", p, W_T1 + 14);
+        return squeezeAndAppend(state, "z", "z", c, C_Z);
+    }
+
+    function absorbEvaluations(bytes32 state, uint256[PROOF_WORDS] memory p)
+        internal
+        pure
+        returns (bytes32)
+    {
+        state = absorbScalar(state, "a_eval", p[E_A]);
+        state = absorbScalar(state, "b_eval", p[E_B]);
+        state = absorbScalar(state, "c_eval", p[E_C]);
+        state = absorbScalar(state, "d_eval", p[E_D]);
+        state = absorbScalar(state, "left_sig_eval", p[E_LEFT_SIGMA]);
+        state = absorbScalar(state, "right_sig_eval", p[E_RIGHT_SIGMA]);
+        state = absorbScalar(state, "out_sig_eval", p[E_OUT_SIGMA]);
+        state = absorbScalar(state, "perm_eval", p[E_PERM]);
+        state = absorbScalar(state, "f_eval", p[E_F]);
+        state = absorbScalar(state, "q_lookup_eval", p[E_Q_LOOKUP]);
+        state = absorbScalar(state, "lookup_perm_eval", p[E_Z2_NEXT]);
+        state = absorbScalar(state, "h_1_eval", p[E_H1]);
+        state = absorbScalar(state, "h_1_next_eval", p[E_H1_NEXT]);
+        state = absorbScalar(state, "h_2_eval", p[E_H2]);
+        state = absorbScalar(state, "q_arith_eval", p[E_Q_ARITH]);
+        state = absorbScalar(state, "q_c_eval", p[E_Q_C]);
+        state = absorbScalar(state, "q_l_eval", p[E_Q_L]);
+        state = absorbScalar(state, "q_r_eval", p[E_Q_R]);
+        state = absorbScalar(state, "q_hl_eval", p[E_Q_HL]);
+        state = absorbScalar(state, "q_hr_eval", p[E_Q_HR]);
+        state = absorbScalar(state, "q_h4_eval", p[E_Q_H4]);
+        state = absorbScalar(state, "a_next_eval", p[E_A_NEXT]);
+        state = absorbScalar(state, "b_next_eval", p[E_B_NEXT]);
+        return absorbScalar(state, "d_next_eval", p[E_D_NEXT]);
+    }
+
+    function squeezeOpeningChallenges(bytes32 state, uint256[PROOF_WORDS] memory p, uint256[20] memory c)
+        internal
+        pure
+    {
+        (state, c[C_AW]) = squeeze(state, "aggregate_witness");
+        (state, c[C_SAW]) = squeeze(state, "aggregate_witness");
+        state = absorbPoint(state, "aw_opening", p, W_AW);
+        state = absorbScalar(state, "aw_random_v", p[W_AW_V]);
+        state = absorbPoint(state, "saw_opening", p, W_SAW);
+        state = absorbScalar(state, "saw_random_v", p[W_SAW_V]);
+        (, c[C_ACCUMULATOR]) = squeeze(state, "accumulator randomizer");
+    }
+
+    // ---------------------------------------------------------------------
+    // Evaluations
+    // ---------------------------------------------------------------------
+
+    function evaluateVanishing(uint256[20] memory c) internal view {
+        uint256 zn = c[C_Z];
+        for (uint256 i = 0; i < LOG_N; i++) {
+            zn = mulmod(zn, zn, R_MOD);
+        }
+        c[C_ZN] = zn;
+        c[C_ZH] = submod(zn, 1);
+        c[C_L1] = mulmod(
+            c[C_ZH],
+            inverse(mulmod(N, submod(c[C_Z], 1), R_MOD)),
+            R_MOD
+        );
+    }
+
+    function evaluatePublicInputs(uint256[] calldata publicInputs, uint256[20] memory c)
+        internal
+        view
+    {
+        uint256 z = c[C_Z];
+        uint256 sum = 0;
+{{EVALUATE_PUBLIC_INPUTS}}
+        c[C_PI] = mulmod(mulmod(sum, c[C_ZH], R_MOD), N_INV, R_MOD);
+    }
+
+    function computeR0(uint256[PROOF_WORDS] memory p, uint256[20] memory c) internal pure {
+        uint256 beta = c[C_BETA];
+        uint256 gamma = c[C_GAMMA];
+        uint256 b = addmod(addmod(p[E_A], mulmod(beta, p[E_LEFT_SIGMA], R_MOD), R_MOD), gamma, R_MOD);
+        b = mulmod(b, addmod(addmod(p[E_B], mulmod(beta, p[E_RIGHT_SIGMA], R_MOD), R_MOD), gamma, R_MOD), R_MOD);
+        b = mulmod(b, addmod(addmod(p[E_C], mulmod(beta, p[E_OUT_SIGMA], R_MOD), R_MOD), gamma, R_MOD), R_MOD);
+        b = mulmod(b, mulmod(mulmod(addmod(p[E_D], gamma, R_MOD), p[E_PERM], R_MOD), c[C_ALPHA], R_MOD), R_MOD);
+
+        uint256 l1AlphaSq = mulmod(c[C_L1], mulmod(c[C_ALPHA], c[C_ALPHA], R_MOD), R_MOD);
+
+        uint256 sepSq = mulmod(c[C_LOOKUP], c[C_LOOKUP], R_MOD);
+        uint256 epsilonOnePlusDelta = mulmod(c[C_EPSILON], addmod(1, c[C_DELTA], R_MOD), R_MOD);
+        uint256 d = mulmod(sepSq, p[E_Z2_NEXT], R_MOD);
+        d = mulmod(d, addmod(epsilonOnePlusDelta, mulmod(c[C_DELTA], p[E_H2], R_MOD), R_MOD), R_MOD);
+        d = mulmod(
+            d,
+            addmod(addmod(epsilonOnePlusDelta, p[E_H2], R_MOD), mulmod(c[C_DELTA], p[E_H1_NEXT], R_MOD), R_MOD),
+            R_MOD
+        );
+        uint256 e = mulmod(mulmod(sepSq, c[C_LOOKUP], R_MOD), c[C_L1], R_MOD);
+
+        c[C_R0] = submod(submod(submod(submod(c[C_PI], b), l1AlphaSq), d), e);
+    }
+
+    // ---------------------------------------------------------------------
+    // Gate constraints
+    // ---------------------------------------------------------------------
+
+    function delta(uint256 f) internal pure returns (uint256) {
+        return mulmod(mulmod(f, submod(f, 1), R_MOD), mulmod(submod(f, 2), submod(f, 3), R_MOD), R_MOD);
+    }
+
+    function deltaXorAnd(uint256 a, uint256 b, uint256 w, uint256 c, uint256 qc)
+        internal
+        pure
+        returns (uint256)
+    {
+        uint256 aPlusB = addmod(a, b, R_MOD);
+        uint256 f = addmod(submod(mulmod(4, w, R_MOD), mulmod(18, aPlusB, R_MOD)), 81, R_MOD);
+        f = addmod(
+            mulmod(w, f, R_MOD),
+            mulmod(18, addmod(mulmod(a, a, R_MOD), mulmod(b, b, R_MOD), R_MOD), R_MOD),
+            R_MOD
+        );
+        f = mulmod(w, addmod(submod(f, mulmod(81, aPlusB, R_MOD)), 83, R_MOD), R_MOD);
+        uint256 e = submod(mulmod(3, addmod(aPlusB, c, R_MOD), R_MOD), mulmod(2, f, R_MOD));
+        return addmod(e, mulmod(qc, submod(mulmod(9, c, R_MOD), mulmod(3, aPlusB, R_MOD)), R_MOD), R_MOD);
+    }
+
+    function rangeScalar(uint256[PROOF_WORDS] memory p, uint256 sep) internal pure returns (uint256) {
+        uint256 kappa = mulmod(sep, sep, R_MOD);
+        uint256 sum = delta(submod(p[E_D_NEXT], mulmod(4, p[E_A], R_MOD)));
+        sum = addmod(mulmod(sum, kappa, R_MOD), delta(submod(p[E_A], mulmod(4, p[E_B], R_MOD))), R_MOD);
+        sum = addmod(mulmod(sum, kappa, R_MOD), delta(submod(p[E_B], mulmod(4, p[E_C], R_MOD))), R_MOD);
+        sum = addmod(mulmod(sum, kappa, R_MOD), delta(submod(p[E_C], mulmod(4, p[E_D], R_MOD))), R_MOD);
+        return mulmod(sum, sep, R_MOD);
+    }
+
+    function logicScalar(uint256[PROOF_WORDS] memory p, uint256 sep) internal pure returns (uint256) {
+        uint256 kappa = mulmod(sep, sep, R_MOD);
+        uint256 a = submod(p[E_A_NEXT], mulmod(4, p[E_A], R_MOD));
+        uint256 b = submod(p[E_B_NEXT], mulmod(4, p[E_B], R_MOD));
+        uint256 d = submod(p[E_D_NEXT], mulmod(4, p[E_D], R_MOD));
+        uint256 sum = deltaXorAnd(a, b, p[E_C], d, p[E_Q_C]);
+        sum = addmod(mulmod(sum, kappa, R_MOD), submod(p[E_C], mulmod(a, b, R_MOD)), R_MOD);
+        sum = addmod(mulmod(sum, kappa, R_MOD), delta(d), R_MOD);
+        sum = addmod(mulmod(sum, kappa, R_MOD), delta(b), R_MOD);
+        sum = addmod(mulmod(sum, kappa, R_MOD), delta(a), R_MOD);
+        return mulmod(sum, sep, R_MOD);
+    }
+
+    function fixedBaseScalar(uint256[PROOF_WORDS] memory p, uint256 sep) internal pure returns (uint256) {
+        uint256 kappa = mulmod(sep, sep, R_MOD);
+        uint256 bit = submod(p[E_D_NEXT], mulmod(2, p[E_D], R_MOD));
+        uint256 xAlpha = mulmod(p[E_Q_L], bit, R_MOD);
+        uint256 yAlpha = addmod(mulmod(mulmod(bit, bit, R_MOD), submod(p[E_Q_R], 1), R_MOD), 1, R_MOD);
+        uint256 xyD = mulmod(mulmod(p[E_C], p[E_A], R_MOD), mulmod(p[E_B], COEFF_D, R_MOD), R_MOD);
+
+        // y accumulator consistency
+        uint256 sum = submod(
+            submod(p[E_B_NEXT], mulmod(p[E_B_NEXT], xyD, R_MOD)),
+            submod(mulmod(yAlpha, p[E_B], R_MOD), mulmod(mulmod(COEFF_A, xAlpha, R_MOD), p[E_A], R_MOD))
+        );
+        // x accumulator consistency
+        sum = addmod(
+            mulmod(sum, kappa, R_MOD),
+            submod(
+                addmod(p[E_A_NEXT], mulmod(p[E_A_NEXT], xyD, R_MOD), R_MOD),
+                addmod(mulmod(xAlpha, p[E_B], R_MOD), mulmod(yAlpha, p[E_A], R_MOD), R_MOD)
+            ),
+            R_MOD
+        );
+        // xy_alpha consistency
+        sum = addmod(mulmod(sum, kappa, R_MOD), submod(mulmod(bit, p[E_Q_C], R_MOD), p[E_C]), R_MOD);
+        // bit consistency
+        sum = addmod(
+            mulmod(sum, kappa, R_MOD),
+            mulmod(mulmod(bit, submod(bit, 1), R_MOD), addmod(bit, 1, R_MOD), R_MOD),
+            R_MOD
+        );
+        return mulmod(sum, sep, R_MOD);
+    }
+
+    function curveAdditionScalar(uint256[PROOF_WORDS] memory p, uint256 sep) internal pure returns (uint256) {
+        uint256 kappa = mulmod(sep, sep, R_MOD);
+        uint256 y1x2 = mulmod(p[E_B], p[E_C], R_MOD);
+        uint256 dxy = mulmod(mulmod(COEFF_D, p[E_D_NEXT], R_MOD), y1x2, R_MOD);
+
+        // y_3 consistency
+        uint256 sum = submod(
+            submod(mulmod(p[E_B], p[E_D], R_MOD), mulmod(COEFF_A, mulmod(p[E_A], p[E_C], R_MOD), R_MOD)),
+            submod(p[E_B_NEXT], mulmod(p[E_B_NEXT], dxy, R_MOD))
+        );
+        // x_3 consistency
+        sum = addmod(
+            mulmod(sum, kappa, R_MOD),
+            submod(addmod(p[E_D_NEXT], y1x2, R_MOD), addmod(p[E_A_NEXT], mulmod(p[E_A_NEXT], dxy, R_MOD), R_MOD)),
+            R_MOD
+        );
+        // x_1 * y_2 consistency
+        sum = addmod(mulmod(sum, kappa, R_MOD), submod(mulmod(p[E_A], p[E_D], R_MOD), p[E_D_NEXT]), R_MOD);
+        return mulmod(sum, sep, R_MOD);
+    }
+
+    // ---------------------------------------------------------------------
+    // Opening check
+    // ---------------------------------------------------------------------
+
+    /// Folds both openings into `e(left, H) = e(right, beta * H)` and checks
+    /// it with a single pairing.
+    function checkOpenings(uint256[PROOF_WORDS] memory p, uint256[20] memory c) internal view returns (bool) {
+        uint256[2] memory left;
+        accumulateLinearisation(left, p, c);
+        (uint256 awEval, uint256 awTable) = accumulateAggregateWitness(left, p, c);
+        (uint256 sawEval, uint256 sawTable) = accumulateShiftedAggregateWitness(left, p, c);
+        accumulateTable(left, addmod(awTable, sawTable, R_MOD), c[C_ZETA]);
+
+        uint256 r = c[C_ACCUMULATOR];
+        ecMulAdd(left, p[W_AW], p[W_AW + 1], c[C_Z]);
+        ecMulAdd(left, p[W_SAW], p[W_SAW + 1], mulmod(mulmod(r, c[C_Z], R_MOD), OMEGA, R_MOD));
+        ecMulAdd(left, G_X, G_Y, submod(0, addmod(awEval, sawEval, R_MOD)));
+        ecMulAdd(left, GAMMA_G_X, GAMMA_G_Y, submod(0, addmod(p[W_AW_V], mulmod(r, p[W_SAW_V], R_MOD), R_MOD)));
+
+        uint256[2] memory right;
+        ecMulAdd(right, p[W_AW], p[W_AW + 1], 1);
+        ecMulAdd(right, p[W_SAW], p[W_SAW + 1], r);
+        return pairing(left, right);
+    }
+
+    function accumulateLinearisation(uint256[2] memory acc, uint256[PROOF_WORDS] memory p, uint256[20] memory c)
+        internal
+        view
+    {
+        uint256 qArith = p[E_Q_ARITH];
+        ecMulAdd(acc, Q_M_X, Q_M_Y, mulmod(mulmod(p[E_A], p[E_B], R_MOD), qArith, R_MOD));
+        ecMulAdd(acc, Q_L_X, Q_L_Y, mulmod(p[E_A], qArith, R_MOD));
+        ecMulAdd(acc, Q_R_X, Q_R_Y, mulmod(p[E_B], qArith, R_MOD));
+        ecMulAdd(acc, Q_4_X, Q_4_Y, mulmod(p[E_D], qArith, R_MOD));
+        ecMulAdd(acc, Q_O_X, Q_O_Y, mulmod(p[E_C], qArith, R_MOD));
+        ecMulAdd(acc, Q_HL_X, Q_HL_Y, mulmod(pow5(p[E_A]), qArith, R_MOD));
+        ecMulAdd(acc, Q_HR_X, Q_HR_Y, mulmod(pow5(p[E_B]), qArith, R_MOD));
+        ecMulAdd(acc, Q_H4_X, Q_H4_Y, mulmod(pow5(p[E_D]), qArith, R_MOD));
+        ecMulAdd(acc, Q_C_X, Q_C_Y, qArith);
+
+        ecMulAdd(acc, Q_RANGE_X, Q_RANGE_Y, rangeScalar(p, c[C_RANGE]));
+        ecMulAdd(acc, Q_LOGIC_X, Q_LOGIC_Y, logicScalar(p, c[C_LOGIC]));
+        ecMulAdd(acc, Q_FIXED_GROUP_ADD_X, Q_FIXED_GROUP_ADD_Y, fixedBaseScalar(p, c[C_FIXED_BASE]));
+        ecMulAdd(acc, Q_VARIABLE_GROUP_ADD_X, Q_VARIABLE_GROUP_ADD_Y, curveAdditionScalar(p, c[C_VARIABLE_BASE]));
+
+        accumulateLookup(acc, p, c);
+        accumulatePermutation(acc, p, c);
+
+        uint256 scalar = submod(0, c[C_ZH]);
+        for (uint256 i = 0; i < 8; i++) {
+            ecMulAdd(acc, p[W_T1 + 2 * i], p[W_T1 + 2 * i + 1], scalar);
+            scalar = mulmod(scalar, c[C_ZN], R_MOD);
+        }
+    }
+
+    function accumulateLookup(uint256[2] memory acc, uint256[PROOF_WORDS] memory p, uint256[20] memory c)
+        internal
+        view
+    {
+        uint256 sep = c[C_LOOKUP];
+        uint256 sepSq = mulmod(sep, sep, R_MOD);
+        uint256 zeta = c[C_ZETA];
+        uint256 onePlusDelta = addmod(1, c[C_DELTA], R_MOD);
+        uint256 epsilonOnePlusDelta = mulmod(c[C_EPSILON], onePlusDelta, R_MOD);
+
+        uint256 compressed = addmod(mulmod(p[E_D], zeta, R_MOD), p[E_C], R_MOD);
+        compressed = addmod(mulmod(compressed, zeta, R_MOD), p[E_B], R_MOD);
+        compressed = addmod(mulmod(compressed, zeta, R_MOD), p[E_A], R_MOD);
+        ecMulAdd(acc, Q_LOOKUP_X, Q_LOOKUP_Y, mulmod(submod(compressed, p[E_F]), sep, R_MOD));
+
+        uint256 scalar = mulmod(onePlusDelta, addmod(c[C_EPSILON], p[E_F], R_MOD), R_MOD);
+        scalar = mulmod(
+            scalar,
+            addmod(addmod(epsilonOnePlusDelta, p[E_TABLE], R_MOD), mulmod(c[C_DELTA], p[E_TABLE_NEXT], R_MOD), R_MOD),
+            R_MOD
+        );
+        scalar = addmod(mulmod(scalar, sepSq, R_MOD), mulmod(c[C_L1], mulmod(sepSq, sep, R_MOD), R_MOD), R_MOD);
+        ecMulAdd(acc, p[W_Z2], p[W_Z2 + 1], scalar);
+
+        scalar = mulmod(
+            mulmod(p[E_Z2_NEXT], sepSq, R_MOD),
+            addmod(addmod(epsilonOnePlusDelta, p[E_H2], R_MOD), mulmod(c[C_DELTA], p[E_H1_NEXT], R_MOD), R_MOD),
+            R_MOD
+        );
+        ecMulAdd(acc, p[W_H1], p[W_H1 + 1], submod(0, scalar));
+    }
+
+    function accumulatePermutation(uint256[2] memory acc, uint256[PROOF_WORDS] memory p, uint256[20] memory c)
+        internal
+        view
+    {
+        uint256 alpha = c[C_ALPHA];
+        uint256 beta = c[C_BETA];
+        uint256 gamma = c[C_GAMMA];
+        uint256 betaZ = mulmod(beta, c[C_Z], R_MOD);
+
+        uint256 scalar = addmod(addmod(p[E_A], betaZ, R_MOD), gamma, R_MOD);
+        scalar = mulmod(scalar, addmod(addmod(p[E_B], mulmod(K1, betaZ, R_MOD), R_MOD), gamma, R_MOD), R_MOD);
+        scalar = mulmod(scalar, addmod(addmod(p[E_C], mulmod(K2, betaZ, R_MOD), R_MOD), gamma, R_MOD), R_MOD);
+        scalar = mulmod(scalar, addmod(addmod(p[E_D], mulmod(K3, betaZ, R_MOD), R_MOD), gamma, R_MOD), R_MOD);
+        scalar = addmod(mulmod(scalar, alpha, R_MOD), mulmod(c[C_L1], mulmod(alpha, alpha, R_MOD), R_MOD), R_MOD);
+        ecMulAdd(acc, p[W_Z], p[W_Z + 1], scalar);
+
+        scalar = addmod(addmod(p[E_A], mulmod(beta, p[E_LEFT_SIGMA], R_MOD), R_MOD), gamma, R_MOD);
+        scalar = mulmod(
+            scalar,
+            addmod(addmod(p[E_B], mulmod(beta, p[E_RIGHT_SIGMA], R_MOD), R_MOD), gamma, R_MOD),
+            R_MOD
+        );
+        scalar = mulmod(
+            scalar,
+            addmod(addmod(p[E_C], mulmod(beta, p[E_OUT_SIGMA], R_MOD), R_MOD), gamma, R_MOD),
+            R_MOD
+        );
+        scalar = mulmod(scalar, mulmod(mulmod(beta, p[E_PERM], R_MOD), alpha, R_MOD), R_MOD);
+        ecMulAdd(acc, FOURTH_SIGMA_X, FOURTH_SIGMA_Y, submod(0, scalar));
+    }
+
+    /// Accumulates the polynomials opened at `z`, except for the linearisation
+    /// polynomial and the table. Returns their aggregated evaluation and the
+    /// weight of the table.
+    function accumulateAggregateWitness(uint256[2] memory acc, uint256[PROOF_WORDS] memory p, uint256[20] memory c)
+        internal
+        view
+        returns (uint256 sum, uint256 tableWeight)
+    {
+        uint256 u = c[C_AW];
+        uint256 weight = u;
+        sum = submod(0, c[C_R0]);
+        sum = fold(acc, LEFT_SIGMA_X, LEFT_SIGMA_Y, weight, p[E_LEFT_SIGMA], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, RIGHT_SIGMA_X, RIGHT_SIGMA_Y, weight, p[E_RIGHT_SIGMA], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, OUT_SIGMA_X, OUT_SIGMA_Y, weight, p[E_OUT_SIGMA], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, p[W_F], p[W_F + 1], weight, p[E_F], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, p[W_H2], p[W_H2 + 1], weight, p[E_H2], sum);
+        weight = mulmod(weight, u, R_MOD);
+        tableWeight = weight;
+        sum = addmod(sum, mulmod(weight, p[E_TABLE], R_MOD), R_MOD);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, p[W_A], p[W_A + 1], weight, p[E_A], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, p[W_B], p[W_B + 1], weight, p[E_B], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, p[W_C], p[W_C + 1], weight, p[E_C], sum);
+        weight = mulmod(weight, u, R_MOD);
+        sum = fold(acc, p[W_D], p[W_D + 1], weight, p[E_D], sum);
+    }
+
+    /// Accumulates the polynomials opened at `z * omega`, except for the
+    /// table, weighted by the accumulator challenge. Returns their weighted
+    /// aggregated evaluation and the weight of the table.
+    function accumulateShiftedAggregateWitness(
+        uint256[2] memory acc,
+        uint256[PROOF_WORDS] memory p,
+        uint256[20] memory c
+    ) internal view returns (uint256 sum, uint256 tableWeight) {
+        uint256 v = c[C_SAW];
+        uint256 weight = c[C_ACCUMULATOR];
+        sum = fold(acc, p[W_Z], p[W_Z + 1], weight, p[E_PERM], 0);
+        weight = mulmod(weight, v, R_MOD);
+        sum = fold(acc, p[W_A], p[W_A + 1], weight, p[E_A_NEXT], sum);
+        weight = mulmod(weight, v, R_MOD);
+        sum = fold(acc, p[W_B], p[W_B + 1], weight, p[E_B_NEXT], sum);
+        weight = mulmod(weight, v, R_MOD);
+        sum = fold(acc, p[W_D], p[W_D + 1], weight, p[E_D_NEXT], sum);
+        weight = mulmod(weight, v, R_MOD);
+        sum = fold(acc, p[W_H1], p[W_H1 + 1], weight, p[E_H1_NEXT], sum);
+        weight = mulmod(weight, v, R_MOD);
+        sum = fold(acc, p[W_Z2], p[W_Z2 + 1], weight, p[E_Z2_NEXT], sum);
+        weight = mulmod(weight, v, R_MOD);
+        tableWeight = weight;
+        sum = addmod(sum, mulmod(weight, p[E_TABLE_NEXT], R_MOD), R_MOD);
+    }
+
+    /// Accumulates the table commitment, compressed with `zeta`.
+    function accumulateTable(uint256[2] memory acc, uint256 weight, uint256 zeta) internal view {
+        ecMulAdd(acc, TABLE_1_X, TABLE_1_Y, weight);
+        weight = mulmod(weight, zeta, R_MOD);
+        ecMulAdd(acc, TABLE_2_X, TABLE_2_Y, weight);
+        weight = mulmod(weight, zeta, R_MOD);
+        ecMulAdd(acc, TABLE_3_X, TABLE_3_Y, weight);
+        weight = mulmod(weight, zeta, R_MOD);
+        ecMulAdd(acc, TABLE_4_X, TABLE_4_Y, weight);
+    }
+
+    /// Adds `weight * (x, y)` to `acc` and returns `sum + weight * eval`.
+    function fold(
+        uint256[2] memory acc,
+        uint256 x,
+        uint256 y,
+        uint256 weight,
+        uint256 eval,
+        uint256 sum
+    ) internal view returns (uint256) {
+        ecMulAdd(acc, x, y, weight);
+        return addmod(sum, mulmod(weight, eval, R_MOD), R_MOD);
+    }
+
+    // ---------------------------------------------------------------------
+    // Arithmetic
+    // ---------------------------------------------------------------------
+
+    function submod(uint256 a, uint256 b) internal pure returns (uint256) {
+        return addmod(a, R_MOD - b, R_MOD);
+    }
+
+    function pow5(uint256 a) internal pure returns (uint256) {
+        uint256 a2 = mulmod(a, a, R_MOD);
+        return mulmod(mulmod(a2, a2, R_MOD), a, R_MOD);
+    }
+
+    function inverse(uint256 a) internal view returns (uint256 result) {
+        uint256 modulus = R_MOD;
+        uint256 exponent = R_MOD - 2;
+        bool success;
+        assembly {
+            let m := mload(0x40)
+            mstore(m, 0x20)
+            mstore(add(m, 0x20), 0x20)
+            mstore(add(m, 0x40), 0x20)
+            mstore(add(m, 0x60), a)
+            mstore(add(m, 0x80), exponent)
+            mstore(add(m, 0xa0), modulus)
+            success := staticcall(gas(), 0x05, m, 0xc0, m, 0x20)
+            result := mload(m)
+        }
+        require(success, "modular inversion failed");
+    }
+
+    /// Sets `acc` to `acc + scalar * (x, y)` with the EIP-196 precompiles.
+    function ecMulAdd(uint256[2] memory acc, uint256 x, uint256 y, uint256 scalar) internal view {
+        bool success;
+        assembly {
+            let m := mload(0x40)
+            mstore(m, x)
+            mstore(add(m, 0x20), y)
+            mstore(add(m, 0x40), scalar)
+            success := staticcall(gas(), 0x07, m, 0x60, m, 0x40)
+            mstore(add(m, 0x40), mload(acc))
+            mstore(add(m, 0x60), mload(add(acc, 0x20)))
+            success := and(success, staticcall(gas(), 0x06, m, 0x80, acc, 0x40))
+        }
+        require(success, "elliptic curve operation failed");
+    }
+
+    /// Checks `e(left, H) * e(-right, beta * H) = 1` with the EIP-197
+    /// precompile.
+    function pairing(uint256[2] memory left, uint256[2] memory right) internal view returns (bool) {
+        uint256[12] memory input;
+        input[0] = left[0];
+        input[1] = left[1];
+        input[2] = H_X_C1;
+        input[3] = H_X_C0;
+        input[4] = H_Y_C1;
+        input[5] = H_Y_C0;
+        input[6] = right[0];
+        input[7] = right[1] == 0 ? 0 : Q_MOD - right[1];
+        input[8] = BETA_H_X_C1;
+        input[9] = BETA_H_X_C0;
+        input[10] = BETA_H_Y_C1;
+        input[11] = BETA_H_Y_C0;
+
+        uint256[1] memory output;
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x08, input, 0x180, output, 0x20)
+        }
+        require(success, "pairing failed");
+        return output[0] == 1;
+    }
+
+    function reverseBytes(uint256 v) internal pure returns (uint256) {
+        v =
+            ((v & 0xFF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00) >> 8) |
+            ((v & 0x00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF00FF) << 8);
+        v =
+            ((v & 0xFFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000) >> 16) |
+            ((v & 0x0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF0000FFFF) << 16);
+        v =
+            ((v & 0xFFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000) >> 32) |
+            ((v & 0x00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF00000000FFFFFFFF) << 32);
+        v =
+            ((v & 0xFFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF0000000000000000) >> 64) |
+            ((v & 0x0000000000000000FFFFFFFFFFFFFFFF0000000000000000FFFFFFFFFFFFFFFF) << 64);
+        return (v >> 128) | (v << 128);
+    }
+}
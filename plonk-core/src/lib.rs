@@ -36,16 +36,20 @@
 extern crate alloc;
 
 mod permutation;
-mod transcript;
 mod util;
 
 pub mod circuit;
 pub mod commitment;
 pub mod constraint_system;
+pub mod container;
 pub mod error;
+#[cfg(feature = "evm")]
+pub mod evm;
 pub mod lookup;
 pub mod prelude;
 pub mod proof_system;
+pub mod srs;
+pub mod transcript;
 
 #[cfg(test)]
 mod test;
@@ -16,7 +16,6 @@ use ark_poly::{
     UVPolynomial,
 };
 use constants::*;
-use hashbrown::HashMap;
 use itertools::izip;
 
 /// Permutation provides the necessary state information and functions
@@ -25,8 +24,9 @@ use itertools::izip;
 #[derive(derivative::Derivative)]
 #[derivative(Debug)]
 pub(crate) struct Permutation {
-    /// Maps a variable to the wires that it is associated to.
-    pub variable_map: HashMap<Variable, Vec<WireData>>,
+    /// Maps a variable to the wires that it is associated to, indexed by
+    /// [`Variable`].
+    pub variable_map: Vec<Vec<WireData>>,
 }
 
 impl Permutation {
@@ -38,7 +38,7 @@ impl Permutation {
     /// Creates a Permutation struct with an expected capacity of `n`.
     pub fn with_capacity(expected_size: usize) -> Self {
         Self {
-            variable_map: HashMap::with_capacity(expected_size),
+            variable_map: Vec::with_capacity(expected_size),
         }
     }
 
@@ -47,12 +47,12 @@ impl Permutation {
     /// into the system It is always allocated in the `variable_map`.
     pub fn new_variable(&mut self) -> Variable {
         // Generate the Variable
-        let var = Variable(self.variable_map.keys().len());
+        let var = Variable(self.variable_map.len());
 
         // Allocate space for the Variable on the variable_map
         // Each vector is initialised with a capacity of 16.
         // This number is a best guess estimate.
-        self.variable_map.insert(var, Vec::with_capacity(16usize));
+        self.variable_map.push(Vec::with_capacity(16usize));
 
         var
     }
@@ -60,9 +60,7 @@ impl Permutation {
     /// Checks that the [`Variable`]s are valid by determining if they have been
     /// added to the system.
     fn valid_variables(&self, variables: &[Variable]) -> bool {
-        variables
-            .iter()
-            .all(|var| self.variable_map.contains_key(var))
+        variables.iter().all(|var| var.0 < self.variable_map.len())
     }
 
     /// Maps a set of [`Variable`]s (a,b,c,d) to a set of [`Wire`](WireData)s
@@ -93,13 +91,13 @@ impl Permutation {
 
         // NOTE: Since we always allocate space for the Vec of WireData when a
         // `Variable` is added to the variable_map, this should never fail.
-        let vec_wire_data = self.variable_map.get_mut(&var).unwrap();
+        let vec_wire_data = &mut self.variable_map[var.0];
         vec_wire_data.push(wire_data);
     }
     /// Performs shift by one permutation and computes `sigma_1`, `sigma_2` and
     /// `sigma_3`, `sigma_4` permutations from the variable maps.
     pub(super) fn compute_sigma_permutations(
-        &mut self,
+        &self,
         n: usize,
     ) -> [Vec<WireData>; 4] {
         let sigma_1 = (0..n).map(WireData::Left).collect::<Vec<_>>();
@@ -109,7 +107,7 @@ impl Permutation {
 
         let mut sigmas = [sigma_1, sigma_2, sigma_3, sigma_4];
 
-        for (_, wire_data) in self.variable_map.iter() {
+        for wire_data in self.variable_map.iter() {
             // Gets the data for each wire assosciated with this variable
             for (wire_index, current_wire) in wire_data.iter().enumerate() {
                 // Fetch index of the next wire, if it is the last element
@@ -871,13 +869,13 @@ mod test {
 
         let pad = vec![F::zero(); domain.size() - cs.w_l.len()];
         let mut w_l_scalar: Vec<F> =
-            cs.w_l.iter().map(|v| cs.variables[v]).collect();
+            cs.w_l.iter().map(|v| cs.variables[v.0]).collect();
         let mut w_r_scalar: Vec<F> =
-            cs.w_r.iter().map(|v| cs.variables[v]).collect();
+            cs.w_r.iter().map(|v| cs.variables[v.0]).collect();
         let mut w_o_scalar: Vec<F> =
-            cs.w_o.iter().map(|v| cs.variables[v]).collect();
+            cs.w_o.iter().map(|v| cs.variables[v.0]).collect();
         let mut w_4_scalar: Vec<F> =
-            cs.w_4.iter().map(|v| cs.variables[v]).collect();
+            cs.w_4.iter().map(|v| cs.variables[v.0]).collect();
 
         w_l_scalar.extend(&pad);
         w_r_scalar.extend(&pad);
@@ -955,7 +953,7 @@ mod test {
         }
 
         // Check all gate_indices are valid
-        for (_, wire_data) in perm.variable_map.iter() {
+        for wire_data in perm.variable_map.iter() {
             for wire in wire_data.iter() {
                 match wire {
                     WireData::Left(index)
@@ -10,8 +10,11 @@
 //! data structures of the plonk library.
 
 pub use crate::{
-    circuit::{self, verify_proof, Circuit, VerifierData},
-    constraint_system::{ecc::Point, StandardComposer, Variable},
+    circuit::{self, verify_proof, verify_proofs_batch, Circuit, VerifierData},
+    constraint_system::{
+        ecc::Point, Blinding, GateLayout, StandardComposer, Variable,
+    },
+    container::Containable,
     error::Error,
     proof_system::{Proof, ProverKey, VerifierKey},
     util::from_embedded_curve_scalar,
@@ -12,7 +12,7 @@ use crate::{
         logic::{Logic, LogicVals},
         proof,
         range::{Range, RangeVals},
-        widget::GateConstraint,
+        widget::{lookup::LookupPolynomials, CustomGate, GateConstraint},
         CustomValues, ProverKey, WitnessValues,
     },
     util::EvaluationDomainExt,
@@ -26,6 +26,7 @@ use ark_poly::{
 use ark_serialize::{
     CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
 };
+use num_traits::Zero;
 
 /// Subset of the [`ProofEvaluations`]. Evaluations at `z` of the
 /// wire polynomials
@@ -111,6 +112,7 @@ pub struct CustomEvaluations<F>
 where
     F: Field,
 {
+    /// Labeled evaluations
     pub vals: Vec<(String, F)>,
 }
 
@@ -119,13 +121,17 @@ where
     F: Field,
 {
     /// Get the evaluation of the specified label.
-    /// This funtions panics if the requested label is not found
-    pub fn get(&self, label: &str) -> F {
-        if let Some(result) = &self.vals.iter().find(|entry| entry.0 == label) {
-            result.1
-        } else {
-            panic!("{} label not found in evaluations set", label)
-        }
+    ///
+    /// Fails with [`Error::MissingCustomEvaluation`] if the requested label
+    /// is not found.
+    pub fn get(&self, label: &str) -> Result<F, Error> {
+        self.vals
+            .iter()
+            .find(|entry| entry.0 == label)
+            .map(|entry| entry.1)
+            .ok_or_else(|| Error::MissingCustomEvaluation {
+                label: label.to_string(),
+            })
     }
 
     /// Add evaluation of poly at point if the label is not already
@@ -152,8 +158,8 @@ where
     /// Permutation and sigma polynomials evaluations
     pub perm_evals: PermutationEvaluations<F>,
 
-    /// Lookup evaluations
-    pub lookup_evals: LookupEvaluations<F>,
+    /// Lookup evaluations, if the proof holds the lookup argument
+    pub lookup_evals: Option<LookupEvaluations<F>>,
 
     /// Evaluations needed for custom gates. This includes selector polynomials
     /// and evaluations of wire polynomials at an offset
@@ -175,6 +181,8 @@ pub fn compute<F, P>(
     fixed_base_separation_challenge: &F,
     var_base_separation_challenge: &F,
     lookup_separation_challenge: &F,
+    custom_gates: &[CustomGate<F>],
+    custom_separation_challenges: &[F],
     z_challenge: &F,
     w_l_poly: &DensePolynomial<F>,
     w_r_poly: &DensePolynomial<F>,
@@ -189,11 +197,7 @@ pub fn compute<F, P>(
     t_7_poly: &DensePolynomial<F>,
     t_8_poly: &DensePolynomial<F>,
     z_poly: &DensePolynomial<F>,
-    z2_poly: &DensePolynomial<F>,
-    f_poly: &DensePolynomial<F>,
-    h1_poly: &DensePolynomial<F>,
-    h2_poly: &DensePolynomial<F>,
-    table_poly: &DensePolynomial<F>,
+    lookup_polys: Option<&LookupPolynomials<F>>,
 ) -> Result<(DensePolynomial<F>, ProofEvaluations<F>), Error>
 where
     F: PrimeField,
@@ -233,9 +237,6 @@ where
     // Arith selector evaluation
     let q_arith_eval = prover_key.arithmetic.q_arith.0.evaluate(z_challenge);
 
-    // Lookup selector evaluation
-    let q_lookup_eval = prover_key.lookup.q_lookup.0.evaluate(z_challenge);
-
     // Custom gate evaluations
     let q_c_eval = prover_key.arithmetic.q_c.0.evaluate(z_challenge);
     let q_l_eval = prover_key.arithmetic.q_l.0.evaluate(z_challenge);
@@ -249,7 +250,7 @@ where
     let q_hr_eval = prover_key.arithmetic.q_hr.0.evaluate(z_challenge);
     let q_h4_eval = prover_key.arithmetic.q_h4.0.evaluate(z_challenge);
 
-    let custom_evals = CustomEvaluations {
+    let mut custom_evals = CustomEvaluations {
         vals: vec![
             label_eval!(q_arith_eval),
             label_eval!(q_c_eval),
@@ -263,14 +264,13 @@ where
             label_eval!(d_next_eval),
         ],
     };
-
-    let z2_next_eval = z2_poly.evaluate(&shifted_z_challenge);
-    let h1_eval = h1_poly.evaluate(z_challenge);
-    let h1_next_eval = h1_poly.evaluate(&shifted_z_challenge);
-    let h2_eval = h2_poly.evaluate(z_challenge);
-    let f_eval = f_poly.evaluate(z_challenge);
-    let table_eval = table_poly.evaluate(z_challenge);
-    let table_next_eval = table_poly.evaluate(&shifted_z_challenge);
+    // Only keep the evaluations needed by the gate types of the key.
+    let labels = prover_key
+        .active_gates
+        .custom_evaluation_labels(!custom_gates.is_empty());
+    custom_evals
+        .vals
+        .retain(|(label, _)| labels.contains(&label.as_str()));
 
     // Compute the last term in the linearisation polynomial
     // (negative_quotient_term):
@@ -285,47 +285,52 @@ where
         z_challenge,
     );
 
-    let lookup_evals = LookupEvaluations {
-        q_lookup_eval,
-        z2_next_eval,
-        h1_eval,
-        h1_next_eval,
-        h2_eval,
-        f_eval,
-        table_eval,
-        table_next_eval,
-    };
+    let lookup_evals = lookup_polys.map(|polys| LookupEvaluations {
+        q_lookup_eval: prover_key.lookup.q_lookup.0.evaluate(z_challenge),
+        z2_next_eval: polys.z_2.evaluate(&shifted_z_challenge),
+        h1_eval: polys.h_1.evaluate(z_challenge),
+        h1_next_eval: polys.h_1.evaluate(&shifted_z_challenge),
+        h2_eval: polys.h_2.evaluate(z_challenge),
+        f_eval: polys.f.evaluate(z_challenge),
+        table_eval: polys.table.evaluate(z_challenge),
+        table_next_eval: polys.table.evaluate(&shifted_z_challenge),
+    });
 
     let gate_constraints = compute_gate_constraint_satisfiability::<F, P>(
         range_separation_challenge,
         logic_separation_challenge,
         fixed_base_separation_challenge,
         var_base_separation_challenge,
+        custom_gates,
+        custom_separation_challenges,
         &wire_evals,
         q_arith_eval,
         &custom_evals,
         prover_key,
-    );
+    )?;
 
-    let lookup = prover_key.lookup.compute_linearisation(
-        l1_eval,
-        a_eval,
-        b_eval,
-        c_eval,
-        d_eval,
-        f_eval,
-        table_eval,
-        table_next_eval,
-        h1_next_eval,
-        h2_eval,
-        z2_next_eval,
-        *delta,
-        *epsilon,
-        *zeta,
-        z2_poly,
-        h1_poly,
-        *lookup_separation_challenge,
-    );
+    let lookup = match (lookup_polys, &lookup_evals) {
+        (Some(polys), Some(evals)) => prover_key.lookup.compute_linearisation(
+            l1_eval,
+            a_eval,
+            b_eval,
+            c_eval,
+            d_eval,
+            evals.f_eval,
+            evals.table_eval,
+            evals.table_next_eval,
+            evals.h1_next_eval,
+            evals.h2_eval,
+            evals.z2_next_eval,
+            *delta,
+            *epsilon,
+            *zeta,
+            &polys.z_2,
+            &polys.h_1,
+            *lookup_separation_challenge,
+        ),
+        _ => DensePolynomial::zero(),
+    };
 
     let permutation = prover_key.permutation.compute_linearisation(
         n,
@@ -376,11 +381,13 @@ fn compute_gate_constraint_satisfiability<F, P>(
     logic_separation_challenge: &F,
     fixed_base_separation_challenge: &F,
     var_base_separation_challenge: &F,
+    custom_gates: &[CustomGate<F>],
+    custom_separation_challenges: &[F],
     wire_evals: &WireEvaluations<F>,
     q_arith_eval: F,
     custom_evals: &CustomEvaluations<F>,
     prover_key: &ProverKey<F>,
-) -> DensePolynomial<F>
+) -> Result<DensePolynomial<F>, Error>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
@@ -400,33 +407,75 @@ where
         q_arith_eval,
     );
 
-    let range = Range::linearisation_term(
-        &prover_key.range_selector.0,
-        *range_separation_challenge,
-        wit_vals,
-        RangeVals::from_evaluations(custom_evals),
-    );
+    // The gate types the key does not hold have no term.
+    let active_gates = prover_key.active_gates;
+
+    let range = if active_gates.range {
+        Range::linearisation_term(
+            &prover_key.range_selector.0,
+            *range_separation_challenge,
+            wit_vals,
+            RangeVals::from_evaluations(custom_evals)?,
+        )
+    } else {
+        DensePolynomial::zero()
+    };
 
-    let logic = Logic::linearisation_term(
-        &prover_key.logic_selector.0,
-        *logic_separation_challenge,
-        wit_vals,
-        LogicVals::from_evaluations(custom_evals),
-    );
+    let logic = if active_gates.logic {
+        Logic::linearisation_term(
+            &prover_key.logic_selector.0,
+            *logic_separation_challenge,
+            wit_vals,
+            LogicVals::from_evaluations(custom_evals)?,
+        )
+    } else {
+        DensePolynomial::zero()
+    };
 
-    let fixed_base_scalar_mul = FixedBaseScalarMul::<F, P>::linearisation_term(
-        &prover_key.fixed_group_add_selector.0,
-        *fixed_base_separation_challenge,
-        wit_vals,
-        FBSMVals::from_evaluations(custom_evals),
-    );
+    let fixed_base_scalar_mul = if active_gates.fixed_base {
+        FixedBaseScalarMul::<F, P>::linearisation_term(
+            &prover_key.fixed_group_add_selector.0,
+            *fixed_base_separation_challenge,
+            wit_vals,
+            FBSMVals::from_evaluations(custom_evals)?,
+        )
+    } else {
+        DensePolynomial::zero()
+    };
 
-    let curve_addition = CurveAddition::<F, P>::linearisation_term(
-        &prover_key.variable_group_add_selector.0,
-        *var_base_separation_challenge,
-        wit_vals,
-        CAVals::from_evaluations(custom_evals),
-    );
+    let curve_addition = if active_gates.variable_base {
+        CurveAddition::<F, P>::linearisation_term(
+            &prover_key.variable_group_add_selector.0,
+            *var_base_separation_challenge,
+            wit_vals,
+            CAVals::from_evaluations(custom_evals)?,
+        )
+    } else {
+        DensePolynomial::zero()
+    };
 
-    arithmetic + range + logic + fixed_base_scalar_mul + curve_addition
+    let custom = custom_gates
+        .iter()
+        .zip(custom_separation_challenges)
+        .zip(&prover_key.custom_selectors)
+        .try_fold(
+            DensePolynomial::zero(),
+            |acc, ((gate, challenge), (_, selector))| {
+                Ok::<_, Error>(
+                    acc + gate.linearisation_term(
+                        &selector.0,
+                        *challenge,
+                        wit_vals,
+                        custom_evals,
+                    )?,
+                )
+            },
+        )?;
+
+    Ok(arithmetic
+        + range
+        + logic
+        + fixed_base_scalar_mul
+        + curve_addition
+        + custom)
 }
@@ -0,0 +1,520 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Memory-mapped files of [`ProverKey`]s.
+//!
+//! The file of a key has a stable layout, with every integer in little-endian
+//! order:
+//!
+//! | Offset      | Size         | Contents                                   |
+//! |-------------|--------------|--------------------------------------------|
+//! | 0           | 8            | magic `PLNKPKEY`                           |
+//! | 8           | 4            | version of the layout, currently 2         |
+//! | 12          | 4            | size `s` of a serialized field element     |
+//! | 16          | 8            | length `h` of the header                   |
+//! | 24          | `h`          | header, the key in coefficient form        |
+//! | 24 + `h`    | 8            | number of sections                         |
+//! |             |              | table of the sections, each with the       |
+//! |             |              | length of its label as 4 bytes, its label, |
+//! |             |              | and the offset and number of elements of   |
+//! |             |              | the section as 8 bytes each                |
+//! |             |              | padding to a multiple of 8 bytes           |
+//! | section     | `s` × number | canonical serialization of the elements    |
+//!
+//! The header is the canonical serialization of the key without its coset
+//! evaluations, see [`ProverKey::into_coefficient_form`]. Every section holds
+//! the coset evaluations over the domain of size `8n` of a selector, labeled
+//! with the name of the selector, `custom:` followed by the name of the gate
+//! for custom selectors, `linear` for the polynomial `X` and `v_h` for the
+//! vanishing polynomial of the domain of the circuit.
+//!
+//! Version 2 of the layout added the active gate types of the key to the
+//! header, and leaves out the sections of the selectors of the other gate
+//! types.
+//!
+//! Opening a key only reads its header. While proving, each part of the
+//! quotient polynomial is computed over chunks of [`CHUNK_ROWS`] rows of the
+//! coset domain, and only the elements of the current chunk of the sections
+//! it needs are deserialized from the map. The rest of the file is only read
+//! by the operating system on demand, and its pages can be evicted as those
+//! of any cached file.
+//!
+//! Mapping files needs the `mmap` feature.
+
+use crate::error::Error;
+use ark_ff::PrimeField;
+use ark_poly::GeneralEvaluationDomain;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Write};
+use core::{marker::PhantomData, ops::Range};
+#[cfg(feature = "mmap")]
+use {
+    crate::proof_system::ProverKey,
+    ark_ff::Zero,
+    ark_poly::EvaluationDomain,
+    hashbrown::HashMap,
+    memmap2::Mmap,
+    std::{fs::File, path::Path, sync::Arc},
+};
+
+/// Number of rows of the coset domain whose coset evaluations are read at
+/// once from a mapped file. The circuits of the tests are small, so they use
+/// smaller chunks to be split in several of them.
+pub(crate) const CHUNK_ROWS: usize = if cfg!(test) { 1 << 6 } else { 1 << 14 };
+
+/// Magic the files of keys start with.
+#[cfg(feature = "mmap")]
+const MAGIC: &[u8; 8] = b"PLNKPKEY";
+
+/// Version of the layout of the files, see the [module](self) documentation.
+#[cfg(feature = "mmap")]
+const VERSION: u32 = 2;
+
+#[cfg(feature = "mmap")]
+fn invalid(reason: impl ToString) -> Error {
+    Error::InvalidMappedProverKey {
+        reason: reason.to_string(),
+    }
+}
+
+/// Writes `key` to `writer` in the layout of mapped keys, computing the coset
+/// evaluations the key does not hold.
+#[cfg(feature = "mmap")]
+pub(crate) fn write<F, W>(
+    key: &ProverKey<F>,
+    mut writer: W,
+) -> Result<(), Error>
+where
+    F: PrimeField,
+    W: Write,
+{
+    let domain_8n = GeneralEvaluationDomain::<F>::new(8 * key.n)
+        .ok_or_else(|| invalid("the circuit is too large"))?;
+    let element_size = F::zero().serialized_size();
+    let mut header = Vec::new();
+    key.hollow(true).serialize(&mut header).map_err(invalid)?;
+    let labels = key
+        .selectors()
+        .map(|(label, _, _)| label)
+        .chain(["linear".to_string(), "v_h".to_string()])
+        .collect::<Vec<_>>();
+
+    let table_len = 8 + labels
+        .iter()
+        .map(|label| 4 + label.len() + 16)
+        .sum::<usize>();
+    let data_start = (24 + header.len() + table_len + 7) / 8 * 8;
+    let section_len = domain_8n.size() * element_size;
+
+    let mut prefix = Vec::with_capacity(data_start);
+    prefix.extend(MAGIC);
+    prefix.extend(VERSION.to_le_bytes());
+    prefix.extend((element_size as u32).to_le_bytes());
+    prefix.extend((header.len() as u64).to_le_bytes());
+    prefix.extend(header);
+    prefix.extend((labels.len() as u64).to_le_bytes());
+    for (i, label) in labels.iter().enumerate() {
+        prefix.extend((label.len() as u32).to_le_bytes());
+        prefix.extend(label.as_bytes());
+        prefix.extend(((data_start + i * section_len) as u64).to_le_bytes());
+        prefix.extend((domain_8n.size() as u64).to_le_bytes());
+    }
+    prefix.resize(data_start, 0);
+    writer.write_all(&prefix).map_err(invalid)?;
+
+    // The sections are written one at a time, so that a key in coefficient
+    // form never holds all of its coset evaluations in memory.
+    for label in &labels {
+        let evaluations =
+            key.coset_evaluations(label, domain_8n, 0..domain_8n.size())?;
+        for element in evaluations.iter() {
+            element.serialize(&mut writer).map_err(invalid)?;
+        }
+    }
+    Ok(())
+}
+
+/// Sections of the coset evaluations of a key in a memory-mapped file.
+#[cfg(feature = "mmap")]
+pub(crate) struct Mapping<F> {
+    /// Mapped file.
+    map: Mmap,
+
+    /// Offset and number of elements of the sections, by label.
+    sections: HashMap<String, (usize, usize)>,
+
+    /// Size of a serialized field element.
+    element_size: usize,
+
+    _field: PhantomData<F>,
+}
+
+#[cfg(feature = "mmap")]
+impl<F> Mapping<F>
+where
+    F: PrimeField,
+{
+    /// Returns the coset evaluations of the rows `rows` of the section
+    /// labeled `label`.
+    fn evaluations(
+        &self,
+        label: &str,
+        domain_8n: GeneralEvaluationDomain<F>,
+        rows: Range<usize>,
+    ) -> Result<Vec<F>, Error> {
+        let (offset, len) = *self
+            .sections
+            .get(label)
+            .ok_or_else(|| invalid(format!("missing section `{}`", label)))?;
+        if len != domain_8n.size() {
+            return Err(invalid(format!("wrong length of `{}`", label)));
+        }
+        let start = offset + rows.start * self.element_size;
+        let end = offset + rows.end * self.element_size;
+        self.map[start..end]
+            .chunks(self.element_size)
+            .map(F::deserialize)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid)
+    }
+}
+
+/// Reads the little-endian integer of `N` bytes at `*offset` of `bytes`,
+/// moving `offset` past it.
+#[cfg(feature = "mmap")]
+fn read_int<const N: usize>(
+    bytes: &[u8],
+    offset: &mut usize,
+) -> Result<u64, Error> {
+    let end = offset
+        .checked_add(N)
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| invalid("truncated file"))?;
+    let mut int = [0; 8];
+    int[..N].copy_from_slice(&bytes[*offset..end]);
+    *offset = end;
+    Ok(u64::from_le_bytes(int))
+}
+
+/// Reads the slice of `len` bytes at `*offset` of `bytes`, moving `offset`
+/// past it.
+#[cfg(feature = "mmap")]
+fn read_slice<'a>(
+    bytes: &'a [u8],
+    offset: &mut usize,
+    len: u64,
+) -> Result<&'a [u8], Error> {
+    let end = usize::try_from(len)
+        .ok()
+        .and_then(|len| offset.checked_add(len))
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| invalid("truncated file"))?;
+    let slice = &bytes[*offset..end];
+    *offset = end;
+    Ok(slice)
+}
+
+/// Opens the key in the file at `path`, mapping its coset evaluations.
+#[cfg(feature = "mmap")]
+pub(crate) fn open<F>(path: &Path) -> Result<ProverKey<F>, Error>
+where
+    F: PrimeField,
+{
+    let file = File::open(path).map_err(invalid)?;
+    // SAFETY: the map is only read through slices checked against its
+    // length, and callers of `ProverKey::open_mapped` must not modify the
+    // file while the key is in use.
+    let map = unsafe { Mmap::map(&file) }.map_err(invalid)?;
+
+    let mut offset = 0;
+    if read_slice(&map, &mut offset, 8)? != MAGIC {
+        return Err(invalid("not a prover key file"));
+    }
+    if read_int::<4>(&map, &mut offset)? != VERSION as u64 {
+        return Err(invalid("unsupported version"));
+    }
+    let element_size = read_int::<4>(&map, &mut offset)? as usize;
+    if element_size != F::zero().serialized_size() {
+        return Err(invalid("the key is for a different field"));
+    }
+    let header_len = read_int::<8>(&map, &mut offset)?;
+    let mut key =
+        ProverKey::<F>::deserialize(read_slice(&map, &mut offset, header_len)?)
+            .map_err(invalid)?;
+
+    let count = read_int::<8>(&map, &mut offset)?;
+    let mut sections = HashMap::new();
+    for _ in 0..count {
+        let label_len = read_int::<4>(&map, &mut offset)?;
+        let label = String::from_utf8(
+            read_slice(&map, &mut offset, label_len)?.to_vec(),
+        )
+        .map_err(invalid)?;
+        let start = read_int::<8>(&map, &mut offset)?;
+        let len = read_int::<8>(&map, &mut offset)?;
+        let in_bounds = len
+            .checked_mul(element_size as u64)
+            .and_then(|size| size.checked_add(start))
+            .map_or(false, |end| end <= map.len() as u64);
+        if !in_bounds {
+            return Err(invalid(format!("section `{}` out of bounds", label)));
+        }
+        sections.insert(label, (start as usize, len as usize));
+    }
+    let labels = key
+        .selectors()
+        .map(|(label, _, _)| label)
+        .chain(["linear".to_string(), "v_h".to_string()]);
+    for label in labels {
+        if !sections.contains_key(&label) {
+            return Err(invalid(format!("missing section `{}`", label)));
+        }
+    }
+
+    key.mapped = MappedEvaluations(Some(Arc::new(Mapping {
+        map,
+        sections,
+        element_size,
+        _field: PhantomData,
+    })));
+    Ok(key)
+}
+
+/// Mapped file the coset evaluations of a [`ProverKey`] are read from, if
+/// any.
+///
+/// The mapping is not part of the serialization of the key, nor of its
+/// comparisons. Without the `mmap` feature, keys are never mapped.
+#[cfg(feature = "mmap")]
+pub(crate) struct MappedEvaluations<F>(Option<Arc<Mapping<F>>>);
+
+/// Mapped file the coset evaluations of a [`ProverKey`] are read from, if
+/// any.
+///
+/// The mapping is not part of the serialization of the key, nor of its
+/// comparisons. Without the `mmap` feature, keys are never mapped.
+///
+/// [`ProverKey`]: crate::proof_system::ProverKey
+#[cfg(not(feature = "mmap"))]
+pub(crate) struct MappedEvaluations<F>(PhantomData<F>);
+
+#[cfg(feature = "mmap")]
+impl<F> MappedEvaluations<F>
+where
+    F: PrimeField,
+{
+    /// Returns `true` if the coset evaluations are read from a mapped file.
+    pub(crate) fn is_mapped(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Returns the coset evaluations of the rows `rows` of the section
+    /// labeled `label` of the mapped file, if any.
+    pub(crate) fn evaluations(
+        &self,
+        label: &str,
+        domain_8n: GeneralEvaluationDomain<F>,
+        rows: Range<usize>,
+    ) -> Option<Result<Vec<F>, Error>> {
+        self.0
+            .as_ref()
+            .map(|mapping| mapping.evaluations(label, domain_8n, rows))
+    }
+}
+
+#[cfg(not(feature = "mmap"))]
+impl<F> MappedEvaluations<F>
+where
+    F: PrimeField,
+{
+    /// Returns `true` if the coset evaluations are read from a mapped file.
+    pub(crate) fn is_mapped(&self) -> bool {
+        false
+    }
+
+    /// Returns the coset evaluations of the rows `rows` of the section
+    /// labeled `label` of the mapped file, if any.
+    pub(crate) fn evaluations(
+        &self,
+        _: &str,
+        _: GeneralEvaluationDomain<F>,
+        _: Range<usize>,
+    ) -> Option<Result<Vec<F>, Error>> {
+        None
+    }
+}
+
+impl<F> Clone for MappedEvaluations<F> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<F> Default for MappedEvaluations<F> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<F> core::fmt::Debug for MappedEvaluations<F>
+where
+    F: PrimeField,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "MappedEvaluations({})", self.is_mapped())
+    }
+}
+
+impl<F> PartialEq for MappedEvaluations<F> {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<F> Eq for MappedEvaluations<F> {}
+
+impl<F> CanonicalSerialize for MappedEvaluations<F> {
+    fn serialize<W: Write>(
+        &self,
+        _: W,
+    ) -> Result<(), ark_serialize::SerializationError> {
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        0
+    }
+}
+
+impl<F> CanonicalDeserialize for MappedEvaluations<F> {
+    fn deserialize<R: ark_serialize::Read>(
+        _: R,
+    ) -> Result<Self, ark_serialize::SerializationError> {
+        Ok(Self::default())
+    }
+}
+
+#[cfg(all(test, feature = "mmap"))]
+mod test {
+    use super::*;
+    use crate::{
+        batch_test,
+        circuit::trim_params,
+        commitment::HomomorphicCommitment,
+        constraint_system::helper::dummy_gadget,
+        proof_system::{widget::QuotientStage, Prover, Verifier},
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::TEModelParameters;
+    use rand_core::OsRng;
+    use std::io::BufWriter;
+    use tempdir::TempDir;
+
+    /// Tests that proofs made with a key in coefficient form or mapped from a
+    /// file verify, and that malformed files are rejected.
+    fn test_mapped_prover_key<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+    {
+        let mut prover = Prover::<F, P, PC>::new(b"mapped");
+        dummy_gadget(10, prover.mut_cs());
+        let pp =
+            PC::setup(2 * prover.circuit_bound(), None, &mut OsRng).unwrap();
+        let (ck, vk) =
+            trim_params::<F, PC>(&pp, prover.circuit_bound()).unwrap();
+        prover.preprocess(&ck).unwrap();
+        let key = prover.prover_key.clone().unwrap();
+        let public_inputs = prover.cs.get_pi().clone();
+
+        let mut verifier = Verifier::<F, P, PC>::new(b"mapped");
+        dummy_gadget(10, verifier.mut_cs());
+        verifier.preprocess(&ck).unwrap();
+
+        let dir = TempDir::new("mapped").unwrap();
+        let path = dir.path().join("key");
+        let mut writer = BufWriter::new(File::create(&path).unwrap());
+        key.write_mapped(&mut writer).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+
+        let coefficient_form = key.clone().into_coefficient_form();
+        assert!(!coefficient_form.has_coset_evaluations());
+        let mapped = ProverKey::<F>::open_mapped(&path).unwrap();
+        assert!(!mapped.has_coset_evaluations());
+        assert_eq!(mapped, coefficient_form);
+
+        // The views of the mapped key share its lookup table.
+        let domain_8n =
+            GeneralEvaluationDomain::<F>::new(8 * mapped.padded_circuit_size())
+                .unwrap();
+        for stage in [
+            QuotientStage::Gates,
+            QuotientStage::Permutation,
+            QuotientStage::Lookup,
+        ] {
+            for rows in mapped.quotient_chunks(domain_8n) {
+                let view = mapped.coset_view(domain_8n, stage, rows).unwrap();
+                for (table, view_table) in [
+                    (&mapped.lookup.table_1, &view.lookup.table_1),
+                    (&mapped.lookup.table_2, &view.lookup.table_2),
+                    (&mapped.lookup.table_3, &view.lookup.table_3),
+                    (&mapped.lookup.table_4, &view.lookup.table_4),
+                ] {
+                    assert!(Arc::ptr_eq(table, view_table));
+                }
+            }
+        }
+
+        for key in [&key, &coefficient_form, &mapped] {
+            let proof = prover
+                .prove_with_preprocessed(&ck, key, PhantomData, &mut OsRng)
+                .unwrap();
+            verifier.verify(&proof, &vk, &public_inputs).unwrap();
+        }
+
+        // A key in coefficient form is written with the same evaluations.
+        let other_path = dir.path().join("other_key");
+        let mut writer = BufWriter::new(File::create(&other_path).unwrap());
+        coefficient_form.write_mapped(&mut writer).unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes, std::fs::read(&other_path).unwrap());
+
+        let mut tampered = bytes.clone();
+        tampered[0] ^= 1;
+        std::fs::write(&other_path, &tampered).unwrap();
+        assert!(matches!(
+            ProverKey::<F>::open_mapped(&other_path),
+            Err(Error::InvalidMappedProverKey { .. })
+        ));
+        std::fs::write(&other_path, &bytes[..bytes.len() - 1]).unwrap();
+        assert!(matches!(
+            ProverKey::<F>::open_mapped(&other_path),
+            Err(Error::InvalidMappedProverKey { .. })
+        ));
+    }
+
+    // Tests for Bls12_381
+    batch_test!(
+        [test_mapped_prover_key],
+        [] => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    // Tests for Bls12_377
+    batch_test!(
+        [test_mapped_prover_key],
+        [] => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
+}
@@ -6,7 +6,8 @@
 
 //! PLONK Proving System
 
-mod linearisation_poly;
+pub(crate) mod linearisation_poly;
+mod mapped;
 mod permutation;
 mod preprocess;
 mod quotient_poly;
@@ -17,6 +18,7 @@ pub mod proof;
 pub mod prover;
 pub mod verifier;
 
+pub use linearisation_poly::CustomEvaluations;
 pub use proof::*;
 pub use prover::Prover;
 pub use verifier::Verifier;
@@ -8,20 +8,19 @@
 
 use crate::{
     commitment::HomomorphicCommitment,
-    constraint_system::StandardComposer,
+    constraint_system::{GateLayout, StandardComposer},
     error::{to_pc_error, Error},
     label_polynomial,
     lookup::PreprocessedLookupTable,
-    proof_system::{widget, ProverKey},
+    proof_system::{widget, ActiveGates, ProverKey},
 };
 use ark_ec::TEModelParameters;
-use ark_ff::{FftField, PrimeField};
+use ark_ff::{FftField, PrimeField, Zero};
 use ark_poly::{
     polynomial::univariate::DensePolynomial, EvaluationDomain, Evaluations,
     GeneralEvaluationDomain, UVPolynomial,
 };
 use core::marker::PhantomData;
-use merlin::Transcript;
 
 /// Struct that contains all of the selector and permutation [`Polynomial`]s in
 /// PLONK.
@@ -50,6 +49,7 @@ where
     right_sigma: DensePolynomial<F>,
     out_sigma: DensePolynomial<F>,
     fourth_sigma: DensePolynomial<F>,
+    q_custom: Vec<(String, DensePolynomial<F>)>,
 }
 
 impl<F, P> StandardComposer<F, P>
@@ -87,6 +87,9 @@ where
         self.q_lookup.extend(zeroes_scalar.iter());
         self.q_fixed_group_add.extend(zeroes_scalar.iter());
         self.q_variable_group_add.extend(zeroes_scalar.iter());
+        self.q_custom
+            .iter_mut()
+            .for_each(|q_custom| q_custom.resize(self.n + diff, zero_scalar));
 
         self.w_l.extend(zeroes_var.iter());
         self.w_r.extend(zeroes_var.iter());
@@ -119,6 +122,7 @@ where
             && self.w_r.len() == k
             && self.w_o.len() == k
             && self.w_4.len() == k
+            && self.q_custom.iter().all(|q_custom| q_custom.len() <= k)
         {
             Ok(())
         } else {
@@ -157,19 +161,37 @@ where
 {
     /// These are the parts of preprocessing that the prover must compute
     /// Although the prover does not need the verification key, he must compute
-    /// the commitments in order to seed the transcript, allowing both the
-    /// prover and verifier to have the same view
+    /// the commitments in order to derive the circuit digest, allowing both
+    /// the prover and verifier to have the same view
     pub fn preprocess_prover<PC>(
         &mut self,
         commit_key: &PC::CommitterKey,
-        transcript: &mut Transcript,
         _pc: PhantomData<PC>,
     ) -> Result<ProverKey<F>, Error>
     where
         PC: HomomorphicCommitment<F>,
     {
-        let (_, selectors, domain, preprocessed_table) =
-            self.preprocess_shared(commit_key, transcript, _pc)?;
+        let (prover_key, _) = self.preprocess(commit_key, _pc)?;
+        Ok(prover_key)
+    }
+
+    /// Preprocesses the circuit into both its [`ProverKey`] and its
+    /// [`VerifierKey`](widget::VerifierKey).
+    ///
+    /// The selector and permutation polynomials are only interpolated and
+    /// committed to once, so this is cheaper than calling
+    /// [`StandardComposer::preprocess_prover`] and
+    /// [`StandardComposer::preprocess_verifier`] on two copies of the circuit.
+    pub fn preprocess<PC>(
+        &mut self,
+        commit_key: &PC::CommitterKey,
+        _pc: PhantomData<PC>,
+    ) -> Result<(ProverKey<F>, widget::VerifierKey<F, PC>), Error>
+    where
+        PC: HomomorphicCommitment<F>,
+    {
+        let (verifier_key, selectors, domain, preprocessed_table) =
+            self.preprocess_shared(commit_key, _pc)?;
 
         let domain_8n =
             GeneralEvaluationDomain::new(8 * domain.size()).ok_or(Error::InvalidEvalDomainSize {
@@ -177,6 +199,17 @@ where
                 adicity:
                     <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
             })?;
+
+        // The selectors of the gate types left out of the keys are zero, so
+        // their coset evaluations are not needed.
+        let active_gates = verifier_key.active_gates();
+        let coset_fft_if = |active: bool, polynomial: &DensePolynomial<F>| {
+            if active {
+                domain_8n.coset_fft(polynomial)
+            } else {
+                Vec::new()
+            }
+        };
         let q_m_eval_8n = Evaluations::from_vec_and_domain(
             domain_8n.coset_fft(&selectors.q_m),
             domain_8n,
@@ -218,23 +251,26 @@ where
             domain_8n,
         );
         let q_range_eval_8n = Evaluations::from_vec_and_domain(
-            domain_8n.coset_fft(&selectors.q_range),
+            coset_fft_if(active_gates.range, &selectors.q_range),
             domain_8n,
         );
         let q_logic_eval_8n = Evaluations::from_vec_and_domain(
-            domain_8n.coset_fft(&selectors.q_logic),
+            coset_fft_if(active_gates.logic, &selectors.q_logic),
             domain_8n,
         );
         let q_lookup_eval_8n = Evaluations::from_vec_and_domain(
-            domain_8n.coset_fft(&selectors.q_lookup),
+            coset_fft_if(active_gates.lookup, &selectors.q_lookup),
             domain_8n,
         );
         let q_fixed_group_add_eval_8n = Evaluations::from_vec_and_domain(
-            domain_8n.coset_fft(&selectors.q_fixed_group_add),
+            coset_fft_if(active_gates.fixed_base, &selectors.q_fixed_group_add),
             domain_8n,
         );
         let q_variable_group_add_eval_8n = Evaluations::from_vec_and_domain(
-            domain_8n.coset_fft(&selectors.q_variable_group_add),
+            coset_fft_if(
+                active_gates.variable_base,
+                &selectors.q_variable_group_add,
+            ),
             domain_8n,
         );
         let left_sigma_eval_8n = Evaluations::from_vec_and_domain(
@@ -253,6 +289,17 @@ where
             domain_8n.coset_fft(&selectors.fourth_sigma),
             domain_8n,
         );
+        let q_custom_evals_8n = selectors
+            .q_custom
+            .into_iter()
+            .map(|(name, q_custom)| {
+                let q_custom_eval_8n = Evaluations::from_vec_and_domain(
+                    domain_8n.coset_fft(&q_custom),
+                    domain_8n,
+                );
+                (name, (q_custom, q_custom_eval_8n))
+            })
+            .collect();
         // XXX: Remove this and compute it on the fly
         let linear_eval_8n = Evaluations::from_vec_and_domain(
             domain_8n.coset_fft(&[F::zero(), F::one()]),
@@ -263,8 +310,10 @@ where
         let v_h_coset_8n =
             compute_vanishing_poly_over_coset(domain_8n, domain.size() as u64);
 
-        Ok(ProverKey::from_polynomials_and_evals(
+        let prover_key = ProverKey::from_polynomials_and_evals(
             domain.size(),
+            verifier_key.circuit_id,
+            active_gates,
             (selectors.q_m, q_m_eval_8n),
             (selectors.q_l, q_l_eval_8n),
             (selectors.q_r, q_r_eval_8n),
@@ -290,7 +339,10 @@ where
             preprocessed_table.t[1].0.clone(),
             preprocessed_table.t[2].0.clone(),
             preprocessed_table.t[3].0.clone(),
-        ))
+            q_custom_evals_8n,
+            verifier_key.circuit_digest(),
+        );
+        Ok((prover_key, verifier_key))
     }
 
     /// The verifier only requires the commitments in order to verify a
@@ -299,26 +351,24 @@ where
     pub fn preprocess_verifier<PC>(
         &mut self,
         commit_key: &PC::CommitterKey,
-        transcript: &mut Transcript,
         _pc: PhantomData<PC>,
     ) -> Result<widget::VerifierKey<F, PC>, Error>
     where
         PC: HomomorphicCommitment<F>,
     {
         let (verifier_key, _, _, _) =
-            self.preprocess_shared(commit_key, transcript, _pc)?;
+            self.preprocess_shared(commit_key, _pc)?;
         Ok(verifier_key)
     }
 
     /// Both the [`Prover`](super::Prover) and [`Verifier`](super::Verifier)
     /// must perform IFFTs on the selector polynomials and permutation
-    /// polynomials in order to commit to them and have the same transcript
-    /// view.
+    /// polynomials in order to commit to them and agree on the circuit
+    /// digest.
     #[allow(clippy::type_complexity)] // FIXME: Add struct for prover side (last two tuple items).
     fn preprocess_shared<PC>(
         &mut self,
         commit_key: &PC::CommitterKey,
-        transcript: &mut Transcript,
         _pc: PhantomData<PC>,
     ) -> Result<
         (
@@ -348,6 +398,21 @@ where
         // Check that the length of the wires is consistent.
         self.check_poly_same_len()?;
 
+        let circuit_id = self.circuit_id();
+
+        // Gate types held by the keys.
+        let is_used = |selector: &[F]| selector.iter().any(|q| !q.is_zero());
+        let active_gates = match self.gate_layout {
+            GateLayout::Compact => ActiveGates {
+                range: is_used(&self.q_range),
+                logic: is_used(&self.q_logic),
+                fixed_base: is_used(&self.q_fixed_group_add),
+                variable_base: is_used(&self.q_variable_group_add),
+                lookup: is_used(&self.q_lookup),
+            },
+            GateLayout::Full => ActiveGates::ALL,
+        };
+
         // 1. Pad circuit to a power of two
         self.pad(domain.size() - self.n);
 
@@ -400,6 +465,20 @@ where
                 domain.ifft(&self.q_variable_group_add),
             );
 
+        let q_custom_polys: Vec<(String, DensePolynomial<F>)> = self
+            .custom_gates
+            .iter()
+            .zip(self.q_custom.iter())
+            .map(|(gate, q_custom)| {
+                (
+                    gate.name().to_string(),
+                    DensePolynomial::from_coefficients_vec(
+                        domain.ifft(q_custom),
+                    ),
+                )
+            })
+            .collect();
+
         // 2. Compute the sigma polynomials
         let (
             left_sigma_poly,
@@ -436,8 +515,25 @@ where
         )
         .map_err(to_pc_error::<F, PC>)?;
 
-        let verifier_key = widget::VerifierKey::from_polynomial_commitments(
+        let labeled_q_custom_polys = q_custom_polys
+            .iter()
+            .map(|(name, q_custom)| {
+                ark_poly_commit::LabeledPolynomial::new(
+                    name.clone(),
+                    q_custom.clone(),
+                    None,
+                    None,
+                )
+            })
+            .collect::<Vec<_>>();
+        let (q_custom_commitments, _) =
+            PC::commit(commit_key, labeled_q_custom_polys.iter(), None)
+                .map_err(to_pc_error::<F, PC>)?;
+
+        let mut verifier_key = widget::VerifierKey::from_polynomial_commitments(
             self.n,
+            circuit_id,
+            active_gates,
             commitments[0].commitment().clone(), // q_m
             commitments[1].commitment().clone(), // q_l
             commitments[2].commitment().clone(), // q_r
@@ -461,7 +557,19 @@ where
             preprocessed_table.t[1].1.clone(),
             preprocessed_table.t[2].1.clone(),
             preprocessed_table.t[3].1.clone(),
+            q_custom_commitments
+                .into_iter()
+                .map(|commitment| {
+                    (
+                        commitment.label().clone(),
+                        commitment.commitment().clone(),
+                    )
+                })
+                .collect(),
+            self.intended_pi_pos.clone(),
         );
+        verifier_key.attached_gates =
+            widget::AttachedGates(self.custom_gates.clone());
 
         let selectors = SelectorPolynomials {
             q_m: q_m_poly,
@@ -483,11 +591,9 @@ where
             right_sigma: right_sigma_poly,
             out_sigma: out_sigma_poly,
             fourth_sigma: fourth_sigma_poly,
+            q_custom: q_custom_polys,
         };
 
-        // Add the circuit description to the transcript
-        verifier_key.seed_transcript(transcript);
-
         Ok((verifier_key, selectors, domain, preprocessed_table))
     }
 }
@@ -522,9 +628,13 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{batch_test_field_params, constraint_system::helper::*};
+    use crate::{
+        batch_test, batch_test_field_params, constraint_system::helper::*,
+        proof_system::prover::MAX_BLINDER_DEGREE,
+    };
     use ark_bls12_377::Bls12_377;
     use ark_bls12_381::Bls12_381;
+    use rand_core::OsRng;
 
     /// Tests that the circuit gets padded to the correct length.
     // FIXME: We can do this test without dummy_gadget method.
@@ -561,6 +671,43 @@ mod test {
         assert_eq!(composer.w_o.len(), size);
     }
 
+    /// Tests that preprocessing both keys at once gives the same keys as
+    /// preprocessing them separately.
+    fn test_preprocess<F, P, PC>()
+    where
+        F: PrimeField,
+        P: TEModelParameters<BaseField = F>,
+        PC: HomomorphicCommitment<F>,
+        widget::VerifierKey<F, PC>: PartialEq,
+    {
+        let u_params = PC::setup(2 * 30, None, &mut OsRng).unwrap();
+        let circuit = || {
+            let mut composer: StandardComposer<F, P> = StandardComposer::new();
+            dummy_gadget(10, &mut composer);
+            composer
+        };
+        let (ck, _) = PC::trim(
+            &u_params,
+            circuit().circuit_bound() + MAX_BLINDER_DEGREE,
+            0,
+            None,
+        )
+        .unwrap();
+
+        let (prover_key, verifier_key) =
+            circuit().preprocess(&ck, PhantomData::<PC>).unwrap();
+        assert_eq!(
+            prover_key,
+            circuit().preprocess_prover(&ck, PhantomData::<PC>).unwrap()
+        );
+        assert!(
+            verifier_key
+                == circuit()
+                    .preprocess_verifier(&ck, PhantomData::<PC>)
+                    .unwrap()
+        );
+    }
+
     // Bls12-381 tests
     batch_test_field_params!(
         [test_pad],
@@ -578,4 +725,20 @@ mod test {
             ark_ed_on_bls12_377::EdwardsParameters
         )
     );
+
+    batch_test!(
+        [test_preprocess],
+        [] => (
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters
+        )
+    );
+
+    batch_test!(
+        [test_preprocess],
+        [] => (
+            Bls12_377,
+            ark_ed_on_bls12_377::EdwardsParameters
+        )
+    );
 }
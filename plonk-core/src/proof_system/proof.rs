@@ -11,15 +11,16 @@
 //! structure and it's methods.
 
 use crate::{
-    commitment::HomomorphicCommitment,
+    commitment::{HomomorphicCommitment, OpeningClaim},
     error::Error,
     label_commitment,
     proof_system::{
         ecc::{CurveAddition, FixedBaseScalarMul},
-        linearisation_poly::ProofEvaluations,
+        linearisation_poly::{LookupEvaluations, ProofEvaluations},
         logic::Logic,
         range::Range,
-        GateConstraint, VerifierKey as PlonkVerifierKey,
+        ActiveGates, CustomGate, GateConstraint,
+        VerifierKey as PlonkVerifierKey,
     },
     transcript::TranscriptProtocol,
     util::EvaluationDomainExt,
@@ -31,7 +32,6 @@ use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_serialize::{
     CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
 };
-use merlin::Transcript;
 
 use super::pi::PublicInputs;
 
@@ -68,17 +68,21 @@ where
     /// Commitment to the permutation polynomial.
     pub(crate) z_comm: PC::Commitment,
 
-    /// Commitment to the lookup query polynomial.
-    pub(crate) f_comm: PC::Commitment,
+    /// Commitment to the lookup query polynomial, if the proof holds the
+    /// lookup argument.
+    pub(crate) f_comm: Option<PC::Commitment>,
 
-    /// Commitment to first half of sorted polynomial
-    pub(crate) h_1_comm: PC::Commitment,
+    /// Commitment to first half of sorted polynomial, if the proof holds the
+    /// lookup argument.
+    pub(crate) h_1_comm: Option<PC::Commitment>,
 
-    /// Commitment to second half of sorted polynomial
-    pub(crate) h_2_comm: PC::Commitment,
+    /// Commitment to second half of sorted polynomial, if the proof holds
+    /// the lookup argument.
+    pub(crate) h_2_comm: Option<PC::Commitment>,
 
-    /// Commitment to the lookup permutation polynomial.
-    pub(crate) z_2_comm: PC::Commitment,
+    /// Commitment to the lookup permutation polynomial, if the proof holds
+    /// the lookup argument.
+    pub(crate) z_2_comm: Option<PC::Commitment>,
 
     /// Commitment to the quotient polynomial.
     pub(crate) t_1_comm: PC::Commitment,
@@ -120,22 +124,78 @@ where
     PC: HomomorphicCommitment<F>,
 {
     /// Performs the verification of a [`Proof`] returning a boolean result.
-    pub(crate) fn verify<P>(
+    pub(crate) fn verify<P, T>(
         &self,
         plonk_verifier_key: &PlonkVerifierKey<F, PC>,
-        transcript: &mut Transcript,
+        transcript: &mut T,
         verifier_key: &PC::VerifierKey,
         pub_inputs: &PublicInputs<F>,
+        custom_gates: &[CustomGate<F>],
     ) -> Result<(), Error>
     where
         P: TEModelParameters<BaseField = F>,
+        T: TranscriptProtocol,
     {
+        let [aw_claim, saw_claim] = self.opening_claims::<P, T>(
+            plonk_verifier_key,
+            transcript,
+            pub_inputs,
+            custom_gates,
+        )?;
+
+        match aw_claim.check(verifier_key) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::ProofVerificationError),
+            Err(e) => panic!("{:?}", e),
+        }
+        .and_then(|_| match saw_claim.check(verifier_key) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::ProofVerificationError),
+            Err(e) => panic!("{:?}", e),
+        })
+    }
+
+    /// Replays the transcript of the [`Proof`] and returns the two opening
+    /// claims which have to be checked against the commitment scheme for the
+    /// proof to be valid: the aggregated witness opened at `z` and the shifted
+    /// aggregated witness opened at `z * omega`.
+    pub(crate) fn opening_claims<P, T>(
+        &self,
+        plonk_verifier_key: &PlonkVerifierKey<F, PC>,
+        transcript: &mut T,
+        pub_inputs: &PublicInputs<F>,
+        custom_gates: &[CustomGate<F>],
+    ) -> Result<[OpeningClaim<F, PC>; 2], Error>
+    where
+        P: TEModelParameters<BaseField = F>,
+        T: TranscriptProtocol,
+    {
+        let custom_gates = plonk_verifier_key.custom_gates(custom_gates)?;
+
+        // The proof must hold the commitments and evaluations of the gate
+        // types of the key, and only those.
+        let active_gates = plonk_verifier_key.active_gates();
+        self.check_layout(active_gates, !custom_gates.is_empty())?;
+
         let domain =
             GeneralEvaluationDomain::<F>::new(plonk_verifier_key.n).ok_or(Error::InvalidEvalDomainSize {
                 log_size_of_group: plonk_verifier_key.n.trailing_zeros(),
                 adicity: <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
             })?;
 
+        // Public inputs are only accepted at the positions the circuit
+        // reserved for them.
+        if let Some(&position) = pub_inputs
+            .get_pos()
+            .find(|pos| !plonk_verifier_key.pi_pos.contains(pos))
+        {
+            return Err(Error::InvalidPublicInputPosition { position });
+        }
+
+        // Bind the transcript to the circuit before anything else, so the
+        // proof cannot be replayed against a different circuit.
+        plonk_verifier_key.seed_transcript(transcript);
+
         // Append Public Inputs to the transcript
         transcript.append(b"pi", pub_inputs);
 
@@ -149,39 +209,43 @@ where
         // same challenges
         //
         // Add commitment to witness polynomials to transcript
-        transcript.append(b"w_l", &self.a_comm);
-        transcript.append(b"w_r", &self.b_comm);
-        transcript.append(b"w_o", &self.c_comm);
-        transcript.append(b"w_4", &self.d_comm);
+        transcript.append_commitment::<F, PC>(b"w_l", &self.a_comm);
+        transcript.append_commitment::<F, PC>(b"w_r", &self.b_comm);
+        transcript.append_commitment::<F, PC>(b"w_o", &self.c_comm);
+        transcript.append_commitment::<F, PC>(b"w_4", &self.d_comm);
 
         // Compute table compression challenge `zeta`.
         let zeta = transcript.challenge_scalar(b"zeta");
-        transcript.append(b"zeta", &zeta);
+        transcript.append_scalar(b"zeta", &zeta);
 
-        // Add f_poly commitment to transcript
-        transcript.append(b"f", &self.f_comm);
+        if let (Some(f_comm), Some(h_1_comm), Some(h_2_comm)) =
+            (&self.f_comm, &self.h_1_comm, &self.h_2_comm)
+        {
+            // Add f_poly commitment to transcript
+            transcript.append_commitment::<F, PC>(b"f", f_comm);
 
-        // Add h polynomials to transcript
-        transcript.append(b"h1", &self.h_1_comm);
-        transcript.append(b"h2", &self.h_2_comm);
+            // Add h polynomials to transcript
+            transcript.append_commitment::<F, PC>(b"h1", h_1_comm);
+            transcript.append_commitment::<F, PC>(b"h2", h_2_comm);
+        }
 
         // Compute permutation challenges and add them to transcript
 
         // Compute permutation challenge `beta`.
         let beta = transcript.challenge_scalar(b"beta");
-        transcript.append(b"beta", &beta);
+        transcript.append_scalar(b"beta", &beta);
 
         // Compute permutation challenge `gamma`.
         let gamma = transcript.challenge_scalar(b"gamma");
-        transcript.append(b"gamma", &gamma);
+        transcript.append_scalar(b"gamma", &gamma);
 
         // Compute permutation challenge `delta`.
         let delta = transcript.challenge_scalar(b"delta");
-        transcript.append(b"delta", &delta);
+        transcript.append_scalar(b"delta", &delta);
 
         // Compute permutation challenge `epsilon`.
         let epsilon = transcript.challenge_scalar(b"epsilon");
-        transcript.append(b"epsilon", &epsilon);
+        transcript.append_scalar(b"epsilon", &epsilon);
 
         // Challenges must be different
         assert!(beta != gamma, "challenges must be different");
@@ -192,51 +256,68 @@ where
         assert!(delta != epsilon, "challenges must be different");
 
         // Add commitment to permutation polynomial to transcript
-        transcript.append(b"z", &self.z_comm);
+        transcript.append_commitment::<F, PC>(b"z", &self.z_comm);
 
         // Compute quotient challenge
         let alpha = transcript.challenge_scalar(b"alpha");
-        transcript.append(b"alpha", &alpha);
+        transcript.append_scalar(b"alpha", &alpha);
         let range_sep_challenge =
             transcript.challenge_scalar(b"range separation challenge");
-        transcript.append(b"range seperation challenge", &range_sep_challenge);
+        transcript
+            .append_scalar(b"range seperation challenge", &range_sep_challenge);
 
         let logic_sep_challenge =
             transcript.challenge_scalar(b"logic separation challenge");
-        transcript.append(b"logic seperation challenge", &logic_sep_challenge);
+        transcript
+            .append_scalar(b"logic seperation challenge", &logic_sep_challenge);
 
         let fixed_base_sep_challenge =
             transcript.challenge_scalar(b"fixed base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"fixed base separation challenge",
             &fixed_base_sep_challenge,
         );
 
         let var_base_sep_challenge =
             transcript.challenge_scalar(b"variable base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"variable base separation challenge",
             &var_base_sep_challenge,
         );
 
         let lookup_sep_challenge =
             transcript.challenge_scalar(b"lookup separation challenge");
-        transcript
-            .append(b"lookup separation challenge", &lookup_sep_challenge);
+        transcript.append_scalar(
+            b"lookup separation challenge",
+            &lookup_sep_challenge,
+        );
+
+        let custom_sep_challenges = custom_gates
+            .iter()
+            .map(|_| {
+                let challenge: F = transcript
+                    .challenge_scalar(b"custom gate separation challenge");
+                transcript.append_scalar(
+                    b"custom gate separation challenge",
+                    &challenge,
+                );
+                challenge
+            })
+            .collect::<Vec<F>>();
 
         // Add commitment to quotient polynomial to transcript
-        transcript.append(b"t_1", &self.t_1_comm);
-        transcript.append(b"t_2", &self.t_2_comm);
-        transcript.append(b"t_3", &self.t_3_comm);
-        transcript.append(b"t_4", &self.t_4_comm);
-        transcript.append(b"t_5", &self.t_5_comm);
-        transcript.append(b"t_6", &self.t_6_comm);
-        transcript.append(b"t_7", &self.t_7_comm);
-        transcript.append(b"t_8", &self.t_8_comm);
+        transcript.append_commitment::<F, PC>(b"t_1", &self.t_1_comm);
+        transcript.append_commitment::<F, PC>(b"t_2", &self.t_2_comm);
+        transcript.append_commitment::<F, PC>(b"t_3", &self.t_3_comm);
+        transcript.append_commitment::<F, PC>(b"t_4", &self.t_4_comm);
+        transcript.append_commitment::<F, PC>(b"t_5", &self.t_5_comm);
+        transcript.append_commitment::<F, PC>(b"t_6", &self.t_6_comm);
+        transcript.append_commitment::<F, PC>(b"t_7", &self.t_7_comm);
+        transcript.append_commitment::<F, PC>(b"t_8", &self.t_8_comm);
 
         // Compute evaluation point challenge
         let z_challenge = transcript.challenge_scalar(b"z");
-        transcript.append(b"z", &z_challenge);
+        transcript.append_scalar(b"z", &z_challenge);
 
         // Compute zero polynomial evaluated at `z_challenge`
         let z_h_eval = domain.evaluate_vanishing_polynomial(z_challenge);
@@ -256,50 +337,48 @@ where
             z_challenge,
             l1_eval,
             self.evaluations.perm_evals.permutation_eval,
-            self.evaluations.lookup_evals.z2_next_eval,
-            self.evaluations.lookup_evals.h1_next_eval,
-            self.evaluations.lookup_evals.h2_eval,
+            self.evaluations.lookup_evals.as_ref(),
             lookup_sep_challenge,
         );
 
         // Add evaluations to transcript
-        transcript.append(b"a_eval", &self.evaluations.wire_evals.a_eval);
-        transcript.append(b"b_eval", &self.evaluations.wire_evals.b_eval);
-        transcript.append(b"c_eval", &self.evaluations.wire_evals.c_eval);
-        transcript.append(b"d_eval", &self.evaluations.wire_evals.d_eval);
+        transcript
+            .append_scalar(b"a_eval", &self.evaluations.wire_evals.a_eval);
+        transcript
+            .append_scalar(b"b_eval", &self.evaluations.wire_evals.b_eval);
+        transcript
+            .append_scalar(b"c_eval", &self.evaluations.wire_evals.c_eval);
+        transcript
+            .append_scalar(b"d_eval", &self.evaluations.wire_evals.d_eval);
 
-        transcript.append(
+        transcript.append_scalar(
             b"left_sig_eval",
             &self.evaluations.perm_evals.left_sigma_eval,
         );
-        transcript.append(
+        transcript.append_scalar(
             b"right_sig_eval",
             &self.evaluations.perm_evals.right_sigma_eval,
         );
-        transcript.append(
+        transcript.append_scalar(
             b"out_sig_eval",
             &self.evaluations.perm_evals.out_sigma_eval,
         );
-        transcript.append(
+        transcript.append_scalar(
             b"perm_eval",
             &self.evaluations.perm_evals.permutation_eval,
         );
 
-        transcript.append(b"f_eval", &self.evaluations.lookup_evals.f_eval);
-        transcript.append(
-            b"q_lookup_eval",
-            &self.evaluations.lookup_evals.q_lookup_eval,
-        );
-        transcript.append(
-            b"lookup_perm_eval",
-            &self.evaluations.lookup_evals.z2_next_eval,
-        );
-        transcript.append(b"h_1_eval", &self.evaluations.lookup_evals.h1_eval);
-        transcript.append(
-            b"h_1_next_eval",
-            &self.evaluations.lookup_evals.h1_next_eval,
-        );
-        transcript.append(b"h_2_eval", &self.evaluations.lookup_evals.h2_eval);
+        if let Some(lookup_evals) = &self.evaluations.lookup_evals {
+            transcript.append_scalar(b"f_eval", &lookup_evals.f_eval);
+            transcript
+                .append_scalar(b"q_lookup_eval", &lookup_evals.q_lookup_eval);
+            transcript
+                .append_scalar(b"lookup_perm_eval", &lookup_evals.z2_next_eval);
+            transcript.append_scalar(b"h_1_eval", &lookup_evals.h1_eval);
+            transcript
+                .append_scalar(b"h_1_next_eval", &lookup_evals.h1_next_eval);
+            transcript.append_scalar(b"h_2_eval", &lookup_evals.h2_eval);
+        }
 
         self.evaluations
             .custom_evals
@@ -307,7 +386,7 @@ where
             .iter()
             .for_each(|(label, eval)| {
                 let static_label = Box::leak(label.to_owned().into_boxed_str());
-                transcript.append(static_label.as_bytes(), eval);
+                transcript.append_scalar(static_label.as_bytes(), eval);
             });
 
         // Compute linearisation commitment
@@ -324,21 +403,12 @@ where
             fixed_base_sep_challenge,
             var_base_sep_challenge,
             lookup_sep_challenge,
+            &custom_gates,
+            &custom_sep_challenges,
             z_challenge,
             l1_eval,
             plonk_verifier_key,
-        );
-
-        let zeta_sq = zeta.square();
-        let table_comm = PC::multi_scalar_mul(
-            &[
-                plonk_verifier_key.lookup.table_1.clone(),
-                plonk_verifier_key.lookup.table_2.clone(),
-                plonk_verifier_key.lookup.table_3.clone(),
-                plonk_verifier_key.lookup.table_4.clone(),
-            ],
-            &[F::one(), zeta, zeta_sq, zeta_sq * zeta],
-        );
+        )?;
 
         // Commitment Scheme
         // Now we delegate computation to the commitment scheme by batch
@@ -355,90 +425,146 @@ where
 
         // Reconstruct the Aggregated Proof commitments and evals
         // The proof consists of the witness commitment with no blinder
-
-        // Compute aggregate witness to polynomials evaluated at the evaluation
-        // challenge `z`
-        let aw_challenge: F = transcript.challenge_scalar(b"aggregate_witness");
-
-        let aw_commits = [
+        let mut aw_commits = vec![
             label_commitment!(lin_comm),
             label_commitment!(plonk_verifier_key.permutation.left_sigma),
             label_commitment!(plonk_verifier_key.permutation.right_sigma),
             label_commitment!(plonk_verifier_key.permutation.out_sigma),
-            label_commitment!(self.f_comm),
-            label_commitment!(self.h_2_comm),
-            label_commitment!(table_comm),
-            label_commitment!(self.a_comm),
-            label_commitment!(self.b_comm),
-            label_commitment!(self.c_comm),
-            label_commitment!(self.d_comm),
         ];
-
-        let aw_evals = [
+        let mut aw_evals = vec![
             -r0,
             self.evaluations.perm_evals.left_sigma_eval,
             self.evaluations.perm_evals.right_sigma_eval,
             self.evaluations.perm_evals.out_sigma_eval,
-            self.evaluations.lookup_evals.f_eval,
-            self.evaluations.lookup_evals.h2_eval,
-            self.evaluations.lookup_evals.table_eval,
+        ];
+
+        let mut saw_commits = vec![label_commitment!(self.z_comm)];
+        let mut saw_evals = vec![self.evaluations.perm_evals.permutation_eval];
+        if active_gates.shifted_wires(!custom_gates.is_empty()) {
+            saw_commits.extend([
+                label_commitment!(self.a_comm),
+                label_commitment!(self.b_comm),
+                label_commitment!(self.d_comm),
+            ]);
+            saw_evals.extend([
+                self.evaluations.custom_evals.get("a_next_eval")?,
+                self.evaluations.custom_evals.get("b_next_eval")?,
+                self.evaluations.custom_evals.get("d_next_eval")?,
+            ]);
+        }
+
+        if let (
+            Some(lookup_key),
+            Some(lookup_evals),
+            Some(f_comm),
+            Some(h_1_comm),
+            Some(h_2_comm),
+            Some(z_2_comm),
+        ) = (
+            &plonk_verifier_key.lookup,
+            &self.evaluations.lookup_evals,
+            &self.f_comm,
+            &self.h_1_comm,
+            &self.h_2_comm,
+            &self.z_2_comm,
+        ) {
+            let zeta_sq = zeta.square();
+            let table_comm = PC::multi_scalar_mul(
+                &[
+                    lookup_key.table_1.clone(),
+                    lookup_key.table_2.clone(),
+                    lookup_key.table_3.clone(),
+                    lookup_key.table_4.clone(),
+                ],
+                &[F::one(), zeta, zeta_sq, zeta_sq * zeta],
+            );
+
+            aw_commits.extend([
+                label_commitment!(f_comm),
+                label_commitment!(h_2_comm),
+                label_commitment!(table_comm),
+            ]);
+            aw_evals.extend([
+                lookup_evals.f_eval,
+                lookup_evals.h2_eval,
+                lookup_evals.table_eval,
+            ]);
+
+            saw_commits.extend([
+                label_commitment!(h_1_comm),
+                label_commitment!(z_2_comm),
+                label_commitment!(table_comm),
+            ]);
+            saw_evals.extend([
+                lookup_evals.h1_next_eval,
+                lookup_evals.z2_next_eval,
+                lookup_evals.table_next_eval,
+            ]);
+        }
+
+        aw_commits.extend([
+            label_commitment!(self.a_comm),
+            label_commitment!(self.b_comm),
+            label_commitment!(self.c_comm),
+            label_commitment!(self.d_comm),
+        ]);
+        aw_evals.extend([
             self.evaluations.wire_evals.a_eval,
             self.evaluations.wire_evals.b_eval,
             self.evaluations.wire_evals.c_eval,
             self.evaluations.wire_evals.d_eval,
-        ];
+        ]);
 
+        // Compute aggregate witness to polynomials evaluated at the evaluation
+        // challenge `z`
+        let aw_challenge: F = transcript.challenge_scalar(b"aggregate_witness");
         let saw_challenge: F =
             transcript.challenge_scalar(b"aggregate_witness");
 
-        let saw_commits = [
-            label_commitment!(self.z_comm),
-            label_commitment!(self.a_comm),
-            label_commitment!(self.b_comm),
-            label_commitment!(self.d_comm),
-            label_commitment!(self.h_1_comm),
-            label_commitment!(self.z_2_comm),
-            label_commitment!(table_comm),
-        ];
-
-        let saw_evals = [
-            self.evaluations.perm_evals.permutation_eval,
-            self.evaluations.custom_evals.get("a_next_eval"),
-            self.evaluations.custom_evals.get("b_next_eval"),
-            self.evaluations.custom_evals.get("d_next_eval"),
-            self.evaluations.lookup_evals.h1_next_eval,
-            self.evaluations.lookup_evals.z2_next_eval,
-            self.evaluations.lookup_evals.table_next_eval,
-        ];
+        Ok([
+            OpeningClaim {
+                commitments: aw_commits,
+                point: z_challenge,
+                evals: aw_evals,
+                proof: self.aw_opening.clone(),
+                challenge: aw_challenge,
+            },
+            OpeningClaim {
+                commitments: saw_commits,
+                point: z_challenge * domain.element(1),
+                evals: saw_evals,
+                proof: self.saw_opening.clone(),
+                challenge: saw_challenge,
+            },
+        ])
+    }
 
-        match PC::check(
-            verifier_key,
-            &aw_commits,
-            &z_challenge,
-            aw_evals,
-            &self.aw_opening,
-            aw_challenge,
-            None,
-        ) {
-            Ok(true) => Ok(()),
-            Ok(false) => Err(Error::ProofVerificationError),
-            Err(e) => panic!("{:?}", e),
+    /// Checks that the proof holds the commitments and evaluations of the
+    /// gate types of a key with `active_gates`, with or without
+    /// `custom_gates`, and only those.
+    fn check_layout(
+        &self,
+        active_gates: ActiveGates,
+        custom_gates: bool,
+    ) -> Result<(), Error> {
+        let lookup_comms =
+            [&self.f_comm, &self.h_1_comm, &self.h_2_comm, &self.z_2_comm];
+        let lookup_held = lookup_comms
+            .iter()
+            .all(|comm| comm.is_some() == active_gates.lookup)
+            && self.evaluations.lookup_evals.is_some() == active_gates.lookup;
+        let labels_held = self
+            .evaluations
+            .custom_evals
+            .vals
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .eq(active_gates.custom_evaluation_labels(custom_gates));
+        if lookup_held && labels_held {
+            Ok(())
+        } else {
+            Err(Error::ProofLayoutMismatch)
         }
-        .and_then(|_| {
-            match PC::check(
-                verifier_key,
-                &saw_commits,
-                &(z_challenge * domain.element(1)),
-                saw_evals,
-                &self.saw_opening,
-                saw_challenge,
-                None,
-            ) {
-                Ok(true) => Ok(()),
-                Ok(false) => Err(Error::ProofVerificationError),
-                Err(e) => panic!("{:?}", e),
-            }
-        })
     }
 
     fn compute_r0(
@@ -453,9 +579,7 @@ where
         z_challenge: F,
         l1_eval: F,
         z_hat_eval: F,
-        z2_next_eval: F,
-        h1_next_eval: F,
-        h2_eval: F,
+        lookup_evals: Option<&LookupEvaluations<F>>,
         lookup_sep_challenge: F,
     ) -> F {
         // Compute the public input polynomial evaluated at `z_challenge`
@@ -463,10 +587,6 @@ where
 
         let alpha_sq = alpha.square();
 
-        let lookup_sep_challenge_sq = lookup_sep_challenge.square();
-        let lookup_sep_challenge_cu =
-            lookup_sep_challenge_sq * lookup_sep_challenge;
-
         // a + beta * sigma_1 + gamma
         let beta_sig1 = beta * self.evaluations.perm_evals.left_sigma_eval;
         let b_0 = self.evaluations.wire_evals.a_eval + beta_sig1 + gamma;
@@ -488,17 +608,31 @@ where
         // l_1(z) * alpha^2
         let c = l1_eval * alpha_sq;
 
+        let r0 = pi_eval - b - c;
+
+        // A proof without lookup argument has no lookup terms
+        let lookup_evals = match lookup_evals {
+            Some(lookup_evals) => lookup_evals,
+            None => return r0,
+        };
+
+        let lookup_sep_challenge_sq = lookup_sep_challenge.square();
+        let lookup_sep_challenge_cu =
+            lookup_sep_challenge_sq * lookup_sep_challenge;
+
         let epsilon_one_plus_delta = epsilon * (F::one() + delta);
 
-        let d_0 = lookup_sep_challenge_sq * z2_next_eval;
-        let d_1 = epsilon_one_plus_delta + delta * h2_eval;
-        let d_2 = epsilon_one_plus_delta + h2_eval + delta * h1_next_eval;
+        let d_0 = lookup_sep_challenge_sq * lookup_evals.z2_next_eval;
+        let d_1 = epsilon_one_plus_delta + delta * lookup_evals.h2_eval;
+        let d_2 = epsilon_one_plus_delta
+            + lookup_evals.h2_eval
+            + delta * lookup_evals.h1_next_eval;
         let d = d_0 * d_1 * d_2;
 
         let e = lookup_sep_challenge_cu * l1_eval;
 
         // Return r_0
-        pi_eval - b - c - d - e
+        r0 - d - e
     }
 
     /// Computes the commitment to `[r]_1`.
@@ -516,10 +650,12 @@ where
         fixed_base_sep_challenge: F,
         var_base_sep_challenge: F,
         lookup_sep_challenge: F,
+        custom_gates: &[CustomGate<F>],
+        custom_sep_challenges: &[F],
         z_challenge: F,
         l1_eval: F,
         plonk_verifier_key: &PlonkVerifierKey<F, PC>,
-    ) -> PC::Commitment
+    ) -> Result<PC::Commitment, Error>
     where
         P: TEModelParameters<BaseField = F>,
     {
@@ -531,10 +667,11 @@ where
         // +  3 for lookups
         // +  2 for permutation
         // +  8 for each piece of the quotient poly
-        // = 26 total scalars and points
+        // +  1 for each custom gate
+        // = 26 + #custom gates total scalars and points
 
-        let mut scalars = Vec::with_capacity(26);
-        let mut points = Vec::with_capacity(26);
+        let mut scalars = Vec::with_capacity(26 + custom_gates.len());
+        let mut points = Vec::with_capacity(26 + custom_gates.len());
 
         plonk_verifier_key
             .arithmetic
@@ -542,47 +679,89 @@ where
                 &mut scalars,
                 &mut points,
                 &self.evaluations,
-            );
-        Range::extend_linearisation_commitment::<PC>(
-            &plonk_verifier_key.range_selector_commitment,
-            range_sep_challenge,
-            &self.evaluations,
-            &mut scalars,
-            &mut points,
-        );
+            )?;
+        // The gate types the key does not hold have no term.
+        if let Some(commitment) = &plonk_verifier_key.range_selector_commitment
+        {
+            Range::extend_linearisation_commitment::<PC>(
+                commitment,
+                range_sep_challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            )?;
+        }
 
-        Logic::extend_linearisation_commitment::<PC>(
-            &plonk_verifier_key.logic_selector_commitment,
-            logic_sep_challenge,
-            &self.evaluations,
-            &mut scalars,
-            &mut points,
-        );
+        if let Some(commitment) = &plonk_verifier_key.logic_selector_commitment
+        {
+            Logic::extend_linearisation_commitment::<PC>(
+                commitment,
+                logic_sep_challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            )?;
+        }
 
-        FixedBaseScalarMul::<_, P>::extend_linearisation_commitment::<PC>(
-            &plonk_verifier_key.fixed_group_add_selector_commitment,
-            fixed_base_sep_challenge,
-            &self.evaluations,
-            &mut scalars,
-            &mut points,
-        );
-        CurveAddition::<_, P>::extend_linearisation_commitment::<PC>(
-            &plonk_verifier_key.variable_group_add_selector_commitment,
-            var_base_sep_challenge,
-            &self.evaluations,
-            &mut scalars,
-            &mut points,
-        );
-        plonk_verifier_key.lookup.compute_linearisation_commitment(
-            &mut scalars,
-            &mut points,
-            &self.evaluations,
-            (delta, epsilon, zeta),
-            lookup_sep_challenge,
-            l1_eval,
-            self.z_2_comm.clone(),
-            self.h_1_comm.clone(),
-        );
+        if let Some(commitment) =
+            &plonk_verifier_key.fixed_group_add_selector_commitment
+        {
+            FixedBaseScalarMul::<_, P>::extend_linearisation_commitment::<PC>(
+                commitment,
+                fixed_base_sep_challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            )?;
+        }
+
+        if let Some(commitment) =
+            &plonk_verifier_key.variable_group_add_selector_commitment
+        {
+            CurveAddition::<_, P>::extend_linearisation_commitment::<PC>(
+                commitment,
+                var_base_sep_challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            )?;
+        }
+        for ((gate, challenge), (_, selector_commitment)) in custom_gates
+            .iter()
+            .zip(custom_sep_challenges)
+            .zip(&plonk_verifier_key.custom_selector_commitments)
+        {
+            gate.extend_linearisation_commitment::<PC>(
+                selector_commitment,
+                *challenge,
+                &self.evaluations,
+                &mut scalars,
+                &mut points,
+            )?;
+        }
+        if let (
+            Some(lookup_key),
+            Some(lookup_evals),
+            Some(z_2_comm),
+            Some(h_1_comm),
+        ) = (
+            &plonk_verifier_key.lookup,
+            &self.evaluations.lookup_evals,
+            &self.z_2_comm,
+            &self.h_1_comm,
+        ) {
+            lookup_key.compute_linearisation_commitment(
+                &mut scalars,
+                &mut points,
+                &self.evaluations,
+                lookup_evals,
+                (delta, epsilon, zeta),
+                lookup_sep_challenge,
+                l1_eval,
+                z_2_comm.clone(),
+                h_1_comm.clone(),
+            );
+        }
         plonk_verifier_key
             .permutation
             .compute_linearisation_commitment(
@@ -624,7 +803,7 @@ where
             self.t_8_comm.clone(),
         ]);
 
-        PC::multi_scalar_mul(&points, &scalars)
+        Ok(PC::multi_scalar_mul(&points, &scalars))
     }
 }
 
@@ -9,11 +9,14 @@
 use crate::lookup::MultiSet;
 use crate::{
     commitment::HomomorphicCommitment,
-    constraint_system::{StandardComposer, Variable},
+    constraint_system::{
+        Blinding, CircuitIr, StandardComposer, Variable, Witness,
+    },
     error::{to_pc_error, Error},
     label_polynomial,
     proof_system::{
-        linearisation_poly, proof::Proof, quotient_poly, ProverKey,
+        linearisation_poly, lookup::LookupPolynomials, proof::Proof,
+        quotient_poly, CustomGate, ProverKey,
     },
     transcript::TranscriptProtocol,
 };
@@ -26,14 +29,27 @@ use ark_poly::{
 use core::marker::PhantomData;
 use itertools::izip;
 use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+/// Largest degree of the blinders the [`Prover`] adds to the polynomials it
+/// commits to. Blinded polynomials have a degree of up to the size of the
+/// domain plus this degree, which the commit key has to support.
+pub(crate) const MAX_BLINDER_DEGREE: usize = 2;
 
 /// Abstraction structure designed to construct a circuit and generate
 /// [`Proof`]s for it.
-pub struct Prover<F, P, PC>
+///
+/// The Fiat-Shamir transcript backend `T` defaults to the Merlin
+/// [`Transcript`]. Proofs have to be verified with a [`Verifier`] using the
+/// same backend.
+///
+/// [`Verifier`]: crate::proof_system::Verifier
+pub struct Prover<F, P, PC, T = Transcript>
 where
     F: PrimeField,
     P: ModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol,
 {
     /// Proving Key which is used to create proofs about a specific PLONK
     /// circuit.
@@ -45,22 +61,23 @@ where
     /// Store the messages exchanged during the preprocessing stage.
     ///
     /// This is copied each time, we make a proof.
-    pub preprocessed_transcript: Transcript,
+    pub preprocessed_transcript: T,
 
     _phantom: PhantomData<PC>,
 }
-impl<F, P, PC> Prover<F, P, PC>
+impl<F, P, PC, T> Prover<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol,
 {
     /// Creates a new `Prover` instance.
     pub fn new(label: &'static [u8]) -> Self {
         Self {
             prover_key: None,
             cs: StandardComposer::new(),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
             _phantom: PhantomData::<PC>,
         }
     }
@@ -70,11 +87,55 @@ where
         Self {
             prover_key: None,
             cs: StandardComposer::with_expected_size(size),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
+            _phantom: PhantomData::<PC>,
+        }
+    }
+
+    /// Creates a new `Prover` instance for a circuit made zero-knowledge with
+    /// the given [`Blinding`] strategy, which has to match the one of the
+    /// [`Verifier`](crate::proof_system::Verifier).
+    ///
+    /// See [`StandardComposer::with_blinding`] for the use of `rng`.
+    pub fn with_blinding<R>(
+        label: &'static [u8],
+        blinding: Blinding,
+        rng: &mut R,
+    ) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        Self {
+            prover_key: None,
+            cs: StandardComposer::with_blinding(0, blinding, rng),
+            preprocessed_transcript: T::new(label),
             _phantom: PhantomData::<PC>,
         }
     }
 
+    /// Creates a new `Prover` instance for the circuit described by `ir`,
+    /// with the `witness` exported from the composer the gadget ran in.
+    ///
+    /// The custom gates of the circuit have to be passed in `custom_gates`,
+    /// see [`StandardComposer::from_ir`]. A [`ProverKey`] of the circuit can
+    /// be set in [`Prover::prover_key`] to skip preprocessing, but the `ir`
+    /// is always needed since the key does not hold the wiring.
+    pub fn with_witness(
+        label: &'static [u8],
+        ir: &CircuitIr<F>,
+        custom_gates: &[CustomGate<F>],
+        witness: &Witness<F>,
+    ) -> Result<Self, Error> {
+        let mut cs = StandardComposer::from_ir(ir, custom_gates)?;
+        cs.assign_witness(witness)?;
+        Ok(Self {
+            prover_key: None,
+            cs,
+            preprocessed_transcript: T::new(label),
+            _phantom: PhantomData::<PC>,
+        })
+    }
+
     /// Returns a mutable copy of the underlying [`StandardComposer`].
     pub fn mut_cs(&mut self) -> &mut StandardComposer<F, P> {
         &mut self.cs
@@ -85,6 +146,13 @@ where
         self.cs.circuit_bound()
     }
 
+    /// Returns the degree the commit key has to be trimmed to in order to
+    /// prove the circuit, which accounts for the blinders added to the
+    /// committed polynomials.
+    pub fn commit_key_degree(&self) -> usize {
+        self.circuit_bound() + MAX_BLINDER_DEGREE
+    }
+
     /// Preprocesses the underlying constraint system.
     pub fn preprocess(
         &mut self,
@@ -93,11 +161,7 @@ where
         if self.prover_key.is_some() {
             return Err(Error::CircuitAlreadyPreprocessed);
         }
-        let pk = self.cs.preprocess_prover(
-            commit_key,
-            &mut self.preprocessed_transcript,
-            PhantomData::<PC>,
-        )?;
+        let pk = self.cs.preprocess_prover(commit_key, PhantomData::<PC>)?;
         self.prover_key = Some(pk);
         Ok(())
     }
@@ -126,32 +190,79 @@ where
 
     /// Convert variables to their actual witness values.
     fn to_scalars(&self, vars: &[Variable]) -> Vec<F> {
-        vars.iter().map(|var| self.cs.variables[var]).collect()
+        vars.iter().map(|var| self.cs.variables[var.0]).collect()
+    }
+
+    /// Adds a random multiple of the vanishing polynomial of `domain` to
+    /// `poly`, with a blinder of degree `degree`.
+    ///
+    /// The values of `poly` over `domain`, and therefore every constraint
+    /// checked by the quotient polynomial, are left unchanged. Up to
+    /// `degree + 1` evaluations of the blinded polynomial outside of `domain`
+    /// are uniformly random.
+    fn add_blinder<R>(
+        poly: &DensePolynomial<F>,
+        domain: &GeneralEvaluationDomain<F>,
+        degree: usize,
+        rng: &mut R,
+    ) -> DensePolynomial<F>
+    where
+        R: CryptoRng + RngCore,
+    {
+        debug_assert!(degree <= MAX_BLINDER_DEGREE);
+        let blinder = DensePolynomial::rand(degree, rng);
+        poly + &blinder.mul_by_vanishing_poly(*domain)
+    }
+
+    /// Blinds a wire or permutation polynomial with [`Self::add_blinder`]
+    /// when the circuit uses [`Blinding::Polynomials`]. With
+    /// [`Blinding::DummyGates`] the circuit itself blinds them and `poly` is
+    /// returned untouched.
+    fn blind_witness<R>(
+        &self,
+        poly: DensePolynomial<F>,
+        domain: &GeneralEvaluationDomain<F>,
+        degree: usize,
+        rng: &mut R,
+    ) -> DensePolynomial<F>
+    where
+        R: CryptoRng + RngCore,
+    {
+        match self.cs.blinding {
+            Blinding::Polynomials => {
+                Self::add_blinder(&poly, domain, degree, rng)
+            }
+            Blinding::DummyGates => poly,
+        }
     }
 
     /// Resets the witnesses in the prover object.
     ///
     /// This function is used when the user wants to make multiple proofs with
-    /// the same circuit.
-    pub fn clear_witness(&mut self) {
-        self.cs = StandardComposer::new();
+    /// the same circuit. See [`StandardComposer::with_blinding`] for the use
+    /// of `rng`.
+    pub fn clear_witness<R>(&mut self, rng: &mut R)
+    where
+        R: CryptoRng + RngCore,
+    {
+        self.cs = StandardComposer::with_blinding(0, self.cs.blinding, rng);
     }
 
     /// Clears all data in the [`Prover`] instance.
     ///
     /// This function is used when the user wants to use the same `Prover` to
     /// make a [`Proof`] regarding a different circuit.
-    pub fn clear(&mut self) {
-        self.clear_witness();
+    pub fn clear<R>(&mut self, rng: &mut R)
+    where
+        R: CryptoRng + RngCore,
+    {
+        self.clear_witness(rng);
         self.prover_key = None;
-        self.preprocessed_transcript = Transcript::new(b"plonk");
+        self.preprocessed_transcript = T::new(b"plonk");
     }
 
-    /// Keys the [`Transcript`] with additional seed information
-    /// Wrapper around [`Transcript::append_message`].
-    ///
-    /// [`Transcript`]: merlin::Transcript
-    /// [`Transcript::append_message`]: merlin::Transcript::append_message
+    /// Keys the transcript with additional seed information
+    /// Wrapper around [`TranscriptProtocol::append_message`].
     pub fn key_transcript(&mut self, label: &'static [u8], message: &[u8]) {
         self.preprocessed_transcript.append_message(label, message);
     }
@@ -162,12 +273,28 @@ where
     /// after calling this method, the user should then call
     /// [`Prover::clear_witness`].
     /// This is automatically done when [`Prover::prove`] is called.
-    pub fn prove_with_preprocessed(
+    ///
+    /// The blinders of the committed polynomials are sampled from `rng`.
+    ///
+    /// Fails with [`Error::CircuitIdMismatch`] if `prover_key` was
+    /// preprocessed from a different circuit than the one in the composer,
+    /// and with [`Error::SetupOnlyComposer`] if the composer is a setup-only
+    /// one.
+    pub fn prove_with_preprocessed<R>(
         &self,
         commit_key: &PC::CommitterKey,
         prover_key: &ProverKey<F>,
         _data: PhantomData<PC>,
-    ) -> Result<Proof<F, PC>, Error> {
+        rng: &mut R,
+    ) -> Result<Proof<F, PC>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        // The witnesses of a setup-only composer are unassigned.
+        if self.cs.is_setup_only() {
+            return Err(Error::SetupOnlyComposer);
+        }
+
         let domain =
             GeneralEvaluationDomain::new(self.cs.circuit_bound()).ok_or(Error::InvalidEvalDomainSize {
                 log_size_of_group: self.cs.circuit_bound().trailing_zeros(),
@@ -175,10 +302,19 @@ where
             })?;
         let n = domain.size();
 
-        // Since the caller is passing a pre-processed circuit
-        // We assume that the Transcript has been seeded with the preprocessed
-        // Commitments
+        // The key must have been preprocessed from the circuit being proven.
+        if prover_key.circuit_id != self.cs.circuit_id() {
+            return Err(Error::CircuitIdMismatch);
+        }
+
+        // Match the custom gates of the key with the ones registered in the
+        // composer.
+        let custom_gates = prover_key.custom_gates(&self.cs.custom_gates)?;
+
+        // Bind the transcript to the circuit before anything else, so the
+        // proof cannot be replayed against a different circuit.
         let mut transcript = self.preprocessed_transcript.clone();
+        prover_key.circuit_digest.seed_transcript(&mut transcript);
 
         // Append Public Inputs to the transcript
         transcript.append(b"pi", self.cs.get_pi());
@@ -204,6 +340,13 @@ where
         let w_4_poly =
             DensePolynomial::from_coefficients_vec(domain.ifft(w_4_scalar));
 
+        // Add blinders to witness polynomials. The output wire is only
+        // evaluated at `z`, the rest of them at both `z` and `z * omega`.
+        let w_l_poly = self.blind_witness(w_l_poly, &domain, 2, rng);
+        let w_r_poly = self.blind_witness(w_r_poly, &domain, 2, rng);
+        let w_o_poly = self.blind_witness(w_o_poly, &domain, 1, rng);
+        let w_4_poly = self.blind_witness(w_4_poly, &domain, 2, rng);
+
         let w_polys = [
             label_polynomial!(w_l_poly),
             label_polynomial!(w_r_poly),
@@ -216,127 +359,163 @@ where
             .map_err(to_pc_error::<F, PC>)?;
 
         // Add witness polynomial commitments to transcript.
-        transcript.append(b"w_l", w_commits[0].commitment());
-        transcript.append(b"w_r", w_commits[1].commitment());
-        transcript.append(b"w_o", w_commits[2].commitment());
-        transcript.append(b"w_4", w_commits[3].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"w_l", w_commits[0].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"w_r", w_commits[1].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"w_o", w_commits[2].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"w_4", w_commits[3].commitment());
 
         // 2. Derive lookup polynomials
+        //
+        // A circuit without lookup gates skips the lookup argument, whose
+        // polynomials are left out of the proof.
 
         // Generate table compression factor
         let zeta = transcript.challenge_scalar(b"zeta");
-        transcript.append(b"zeta", &zeta);
-
-        // Compress lookup table into vector of single elements
-        let compressed_t_multiset = MultiSet::compress(
-            &[
-                prover_key.lookup.table_1.clone(),
-                prover_key.lookup.table_2.clone(),
-                prover_key.lookup.table_3.clone(),
-                prover_key.lookup.table_4.clone(),
-            ],
-            zeta,
-        );
-
-        // Compute table poly
-        let table_poly = DensePolynomial::from_coefficients_vec(
-            domain.ifft(&compressed_t_multiset.0),
-        );
-
-        // Compute query table f
-        // When q_lookup[i] is zero the wire value is replaced with a dummy
-        //   value currently set as the first row of the public table
-        // If q_lookup[i] is one the wire values are preserved
-        // This ensures the ith element of the compressed query table
-        //   is an element of the compressed lookup table even when
-        //   q_lookup[i] is 0 so the lookup check will pass
-
-        let q_lookup_pad = vec![F::zero(); n - self.cs.q_lookup.len()];
-        let padded_q_lookup =
-            &[self.cs.q_lookup.as_slice(), q_lookup_pad.as_slice()].concat();
-
-        let mut f_scalars: Vec<MultiSet<F>> =
-            vec![MultiSet::with_capacity(w_l_scalar.len()); 4];
-
-        for (q_lookup, w_l, w_r, w_o, w_4) in izip!(
-            padded_q_lookup,
-            w_l_scalar,
-            w_r_scalar,
-            w_o_scalar,
-            w_4_scalar,
-        ) {
-            if q_lookup.is_zero() {
-                f_scalars[0].push(compressed_t_multiset.0[0]);
-                f_scalars.iter_mut().skip(1).for_each(|f| f.push(F::zero()));
-            } else {
-                f_scalars[0].push(*w_l);
-                f_scalars[1].push(*w_r);
-                f_scalars[2].push(*w_o);
-                f_scalars[3].push(*w_4);
+        transcript.append_scalar(b"zeta", &zeta);
+
+        let lookup_query = if prover_key.active_gates.lookup {
+            // Compress lookup table into vector of single elements
+            let compressed_t_multiset = MultiSet::compress(
+                &[
+                    (*prover_key.lookup.table_1).clone(),
+                    (*prover_key.lookup.table_2).clone(),
+                    (*prover_key.lookup.table_3).clone(),
+                    (*prover_key.lookup.table_4).clone(),
+                ],
+                zeta,
+            );
+
+            // Compute table poly
+            let table_poly = DensePolynomial::from_coefficients_vec(
+                domain.ifft(&compressed_t_multiset.0),
+            );
+
+            // Compute query table f
+            // When q_lookup[i] is zero the wire value is replaced with a dummy
+            //   value currently set as the first row of the public table
+            // If q_lookup[i] is one the wire values are preserved
+            // This ensures the ith element of the compressed query table
+            //   is an element of the compressed lookup table even when
+            //   q_lookup[i] is 0 so the lookup check will pass
+
+            let q_lookup_pad = vec![F::zero(); n - self.cs.q_lookup.len()];
+            let padded_q_lookup =
+                &[self.cs.q_lookup.as_slice(), q_lookup_pad.as_slice()]
+                    .concat();
+
+            let mut f_scalars: Vec<MultiSet<F>> =
+                vec![MultiSet::with_capacity(w_l_scalar.len()); 4];
+
+            for (q_lookup, w_l, w_r, w_o, w_4) in izip!(
+                padded_q_lookup,
+                w_l_scalar,
+                w_r_scalar,
+                w_o_scalar,
+                w_4_scalar,
+            ) {
+                if q_lookup.is_zero() {
+                    f_scalars[0].push(compressed_t_multiset.0[0]);
+                    f_scalars
+                        .iter_mut()
+                        .skip(1)
+                        .for_each(|f| f.push(F::zero()));
+                } else {
+                    f_scalars[0].push(*w_l);
+                    f_scalars[1].push(*w_r);
+                    f_scalars[2].push(*w_o);
+                    f_scalars[3].push(*w_4);
+                }
             }
-        }
-
-        // Compress all wires into a single vector
-        let compressed_f_multiset = MultiSet::compress(&f_scalars, zeta);
-
-        // Compute query poly
-        let f_poly = DensePolynomial::from_coefficients_vec(
-            domain.ifft(&compressed_f_multiset.0),
-        );
 
-        // Add blinders to query polynomials
-        // let f_poly = Self::add_blinder(&f_poly, n, 1);
-
-        // Commit to query polynomial
-        let (f_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(f_poly)], None)
-                .map_err(to_pc_error::<F, PC>)?;
-
-        // Add f_poly commitment to transcript
-        transcript.append(b"f", f_poly_commit[0].commitment());
-
-        // Compute s, as the sorted and concatenated version of f and t
-        let (h_1, h_2) = compressed_t_multiset
-            .combine_split(&compressed_f_multiset)
-            .unwrap();
-
-        // Compute h polys
-        let h_1_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&h_1.0));
-        let h_2_poly =
-            DensePolynomial::from_coefficients_vec(domain.ifft(&h_2.0));
-
-        // Add blinders to h polynomials
-        // let h_1_poly = Self::add_blinder(&h_1_poly, n, 1);
-        // let h_2_poly = Self::add_blinder(&h_2_poly, n, 1);
-
-        // Commit to h polys
-        let (h_1_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(h_1_poly)], None)
-                .map_err(to_pc_error::<F, PC>)?;
-        let (h_2_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(h_2_poly)], None)
-                .map_err(to_pc_error::<F, PC>)?;
-
-        // Add h polynomials to transcript
-        transcript.append(b"h1", h_1_poly_commit[0].commitment());
-        transcript.append(b"h2", h_2_poly_commit[0].commitment());
+            // Compress all wires into a single vector
+            let compressed_f_multiset = MultiSet::compress(&f_scalars, zeta);
+
+            // Compute query poly
+            let f_poly = DensePolynomial::from_coefficients_vec(
+                domain.ifft(&compressed_f_multiset.0),
+            );
+
+            // Add blinders to query polynomials. The query polynomial is only
+            // evaluated at `z`.
+            let f_poly = Self::add_blinder(&f_poly, &domain, 1, rng);
+
+            // Commit to query polynomial
+            let (f_poly_commit, _) =
+                PC::commit(commit_key, &[label_polynomial!(f_poly)], None)
+                    .map_err(to_pc_error::<F, PC>)?;
+
+            // Add f_poly commitment to transcript
+            transcript.append_commitment::<F, PC>(
+                b"f",
+                f_poly_commit[0].commitment(),
+            );
+
+            // Compute s, as the sorted and concatenated version of f and t
+            let (h_1, h_2) = compressed_t_multiset
+                .combine_split(&compressed_f_multiset)
+                .unwrap();
+
+            // Compute h polys
+            let h_1_poly =
+                DensePolynomial::from_coefficients_vec(domain.ifft(&h_1.0));
+            let h_2_poly =
+                DensePolynomial::from_coefficients_vec(domain.ifft(&h_2.0));
+
+            // Add blinders to h polynomials. `h_1` is evaluated at both `z` and
+            // `z * omega`, `h_2` only at `z`.
+            let h_1_poly = Self::add_blinder(&h_1_poly, &domain, 2, rng);
+            let h_2_poly = Self::add_blinder(&h_2_poly, &domain, 1, rng);
+
+            // Commit to h polys
+            let (h_1_poly_commit, _) =
+                PC::commit(commit_key, &[label_polynomial!(h_1_poly)], None)
+                    .map_err(to_pc_error::<F, PC>)?;
+            let (h_2_poly_commit, _) =
+                PC::commit(commit_key, &[label_polynomial!(h_2_poly)], None)
+                    .map_err(to_pc_error::<F, PC>)?;
+
+            // Add h polynomials to transcript
+            transcript.append_commitment::<F, PC>(
+                b"h1",
+                h_1_poly_commit[0].commitment(),
+            );
+            transcript.append_commitment::<F, PC>(
+                b"h2",
+                h_2_poly_commit[0].commitment(),
+            );
+
+            Some((
+                [compressed_t_multiset, compressed_f_multiset, h_1, h_2],
+                [f_poly, table_poly, h_1_poly, h_2_poly],
+                [
+                    f_poly_commit[0].commitment().clone(),
+                    h_1_poly_commit[0].commitment().clone(),
+                    h_2_poly_commit[0].commitment().clone(),
+                ],
+            ))
+        } else {
+            None
+        };
 
         // 3. Compute permutation polynomial
         //
         // Compute permutation challenge `beta`.
         let beta = transcript.challenge_scalar(b"beta");
-        transcript.append(b"beta", &beta);
+        transcript.append_scalar(b"beta", &beta);
         // Compute permutation challenge `gamma`.
         let gamma = transcript.challenge_scalar(b"gamma");
-        transcript.append(b"gamma", &gamma);
+        transcript.append_scalar(b"gamma", &gamma);
         // Compute permutation challenge `delta`.
         let delta = transcript.challenge_scalar(b"delta");
-        transcript.append(b"delta", &delta);
+        transcript.append_scalar(b"delta", &delta);
 
         // Compute permutation challenge `epsilon`.
         let epsilon = transcript.challenge_scalar(b"epsilon");
-        transcript.append(b"epsilon", &epsilon);
+        transcript.append_scalar(b"epsilon", &epsilon);
 
         // Challenges must be different
         assert!(beta != gamma, "challenges must be different");
@@ -359,36 +538,63 @@ where
             ),
         );
 
+        // Add blinder to permutation polynomial.
+        let z_poly = self.blind_witness(z_poly, &domain, 2, rng);
+
         // Commit to permutation polynomial.
         let (z_poly_commit, _) =
             PC::commit(commit_key, &[label_polynomial!(z_poly)], None)
                 .map_err(to_pc_error::<F, PC>)?;
 
         // Add permutation polynomial commitment to transcript.
-        transcript.append(b"z", z_poly_commit[0].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"z", z_poly_commit[0].commitment());
 
         // Compute mega permutation polynomial.
         // Compute lookup permutation poly
-        let z_2_poly = DensePolynomial::from_coefficients_slice(
-            &self.cs.perm.compute_lookup_permutation_poly(
-                &domain,
-                &compressed_f_multiset.0,
-                &compressed_t_multiset.0,
-                &h_1.0,
-                &h_2.0,
-                delta,
-                epsilon,
-            ),
-        );
-
-        // TODO: Find strategy for blinding lookups
-        // Add blinder for lookup permutation poly
-        // z_2_poly = Self::add_blinder(&z_2_poly, n, 2);
-
-        // Commit to lookup permutation polynomial.
-        let (z_2_poly_commit, _) =
-            PC::commit(commit_key, &[label_polynomial!(z_2_poly)], None)
-                .map_err(to_pc_error::<F, PC>)?;
+        let mut lookup_polys = None;
+        let mut lookup_comms = None;
+        if let Some((multisets, polys, comms)) = lookup_query {
+            let [compressed_t_multiset, compressed_f_multiset, h_1, h_2] =
+                multisets;
+            let [f_poly, table_poly, h_1_poly, h_2_poly] = polys;
+            let [f_comm, h_1_comm, h_2_comm] = comms;
+
+            let z_2_poly = DensePolynomial::from_coefficients_slice(
+                &self.cs.perm.compute_lookup_permutation_poly(
+                    &domain,
+                    &compressed_f_multiset.0,
+                    &compressed_t_multiset.0,
+                    &h_1.0,
+                    &h_2.0,
+                    delta,
+                    epsilon,
+                ),
+            );
+
+            // Add blinder for lookup permutation poly. It is only evaluated
+            // at `z * omega`.
+            let z_2_poly = Self::add_blinder(&z_2_poly, &domain, 1, rng);
+
+            // Commit to lookup permutation polynomial.
+            let (z_2_poly_commit, _) =
+                PC::commit(commit_key, &[label_polynomial!(z_2_poly)], None)
+                    .map_err(to_pc_error::<F, PC>)?;
+
+            lookup_comms = Some([
+                f_comm,
+                h_1_comm,
+                h_2_comm,
+                z_2_poly_commit[0].commitment().clone(),
+            ]);
+            lookup_polys = Some(LookupPolynomials {
+                f: f_poly,
+                table: table_poly,
+                h_1: h_1_poly,
+                h_2: h_2_poly,
+                z_2: z_2_poly,
+            });
+        }
 
         // 3. Compute public inputs polynomial.
         let pi_poly = self.cs.get_pi().into_dense_poly(n);
@@ -398,49 +604,62 @@ where
         // Compute quotient challenge; `alpha`, and gate-specific separation
         // challenges.
         let alpha = transcript.challenge_scalar(b"alpha");
-        transcript.append(b"alpha", &alpha);
+        transcript.append_scalar(b"alpha", &alpha);
 
         let range_sep_challenge =
             transcript.challenge_scalar(b"range separation challenge");
-        transcript.append(b"range seperation challenge", &range_sep_challenge);
+        transcript
+            .append_scalar(b"range seperation challenge", &range_sep_challenge);
 
         let logic_sep_challenge =
             transcript.challenge_scalar(b"logic separation challenge");
-        transcript.append(b"logic seperation challenge", &logic_sep_challenge);
+        transcript
+            .append_scalar(b"logic seperation challenge", &logic_sep_challenge);
 
         let fixed_base_sep_challenge =
             transcript.challenge_scalar(b"fixed base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"fixed base separation challenge",
             &fixed_base_sep_challenge,
         );
 
         let var_base_sep_challenge =
             transcript.challenge_scalar(b"variable base separation challenge");
-        transcript.append(
+        transcript.append_scalar(
             b"variable base separation challenge",
             &var_base_sep_challenge,
         );
 
         let lookup_sep_challenge =
             transcript.challenge_scalar(b"lookup separation challenge");
-        transcript
-            .append(b"lookup separation challenge", &lookup_sep_challenge);
+        transcript.append_scalar(
+            b"lookup separation challenge",
+            &lookup_sep_challenge,
+        );
+
+        let custom_sep_challenges = custom_gates
+            .iter()
+            .map(|_| {
+                let challenge: F = transcript
+                    .challenge_scalar(b"custom gate separation challenge");
+                transcript.append_scalar(
+                    b"custom gate separation challenge",
+                    &challenge,
+                );
+                challenge
+            })
+            .collect::<Vec<F>>();
 
         let t_poly = quotient_poly::compute::<F, P>(
             &domain,
             prover_key,
             &z_poly,
-            &z_2_poly,
             &w_l_poly,
             &w_r_poly,
             &w_o_poly,
             &w_4_poly,
             &pi_poly,
-            &f_poly,
-            &table_poly,
-            &h_1_poly,
-            &h_2_poly,
+            lookup_polys.as_ref(),
             &alpha,
             &beta,
             &gamma,
@@ -452,6 +671,8 @@ where
             &fixed_base_sep_challenge,
             &var_base_sep_challenge,
             &lookup_sep_challenge,
+            &custom_gates,
+            &custom_sep_challenges,
         )?;
 
         let t_i_polys = self.split_tx_poly(n, &t_poly);
@@ -473,20 +694,28 @@ where
         .map_err(to_pc_error::<F, PC>)?;
 
         // Add quotient polynomial commitments to transcript
-        transcript.append(b"t_1", t_commits[0].commitment());
-        transcript.append(b"t_2", t_commits[1].commitment());
-        transcript.append(b"t_3", t_commits[2].commitment());
-        transcript.append(b"t_4", t_commits[3].commitment());
-        transcript.append(b"t_5", t_commits[4].commitment());
-        transcript.append(b"t_6", t_commits[5].commitment());
-        transcript.append(b"t_7", t_commits[6].commitment());
-        transcript.append(b"t_8", t_commits[7].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_1", t_commits[0].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_2", t_commits[1].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_3", t_commits[2].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_4", t_commits[3].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_5", t_commits[4].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_6", t_commits[5].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_7", t_commits[6].commitment());
+        transcript
+            .append_commitment::<F, PC>(b"t_8", t_commits[7].commitment());
 
         // 4. Compute linearisation polynomial
         //
         // Compute evaluation challenge; `z`.
         let z_challenge = transcript.challenge_scalar(b"z");
-        transcript.append(b"z", &z_challenge);
+        transcript.append_scalar(b"z", &z_challenge);
 
         let (lin_poly, evaluations) = linearisation_poly::compute::<F, P>(
             &domain,
@@ -502,6 +731,8 @@ where
             &fixed_base_sep_challenge,
             &var_base_sep_challenge,
             &lookup_sep_challenge,
+            &custom_gates,
+            &custom_sep_challenges,
             &z_challenge,
             &w_l_poly,
             &w_r_poly,
@@ -516,44 +747,46 @@ where
             &t_i_polys[6],
             &t_i_polys[7],
             &z_poly,
-            &z_2_poly,
-            &f_poly,
-            &h_1_poly,
-            &h_2_poly,
-            &table_poly,
+            lookup_polys.as_ref(),
         )?;
 
         // Add evaluations to transcript.
         // First wire evals
-        transcript.append(b"a_eval", &evaluations.wire_evals.a_eval);
-        transcript.append(b"b_eval", &evaluations.wire_evals.b_eval);
-        transcript.append(b"c_eval", &evaluations.wire_evals.c_eval);
-        transcript.append(b"d_eval", &evaluations.wire_evals.d_eval);
+        transcript.append_scalar(b"a_eval", &evaluations.wire_evals.a_eval);
+        transcript.append_scalar(b"b_eval", &evaluations.wire_evals.b_eval);
+        transcript.append_scalar(b"c_eval", &evaluations.wire_evals.c_eval);
+        transcript.append_scalar(b"d_eval", &evaluations.wire_evals.d_eval);
 
         // Second permutation evals
-        transcript
-            .append(b"left_sig_eval", &evaluations.perm_evals.left_sigma_eval);
-        transcript.append(
+        transcript.append_scalar(
+            b"left_sig_eval",
+            &evaluations.perm_evals.left_sigma_eval,
+        );
+        transcript.append_scalar(
             b"right_sig_eval",
             &evaluations.perm_evals.right_sigma_eval,
         );
-        transcript
-            .append(b"out_sig_eval", &evaluations.perm_evals.out_sigma_eval);
-        transcript
-            .append(b"perm_eval", &evaluations.perm_evals.permutation_eval);
+        transcript.append_scalar(
+            b"out_sig_eval",
+            &evaluations.perm_evals.out_sigma_eval,
+        );
+        transcript.append_scalar(
+            b"perm_eval",
+            &evaluations.perm_evals.permutation_eval,
+        );
 
         // Third lookup evals
-        transcript.append(b"f_eval", &evaluations.lookup_evals.f_eval);
-        transcript
-            .append(b"q_lookup_eval", &evaluations.lookup_evals.q_lookup_eval);
-        transcript.append(
-            b"lookup_perm_eval",
-            &evaluations.lookup_evals.z2_next_eval,
-        );
-        transcript.append(b"h_1_eval", &evaluations.lookup_evals.h1_eval);
-        transcript
-            .append(b"h_1_next_eval", &evaluations.lookup_evals.h1_next_eval);
-        transcript.append(b"h_2_eval", &evaluations.lookup_evals.h2_eval);
+        if let Some(lookup_evals) = &evaluations.lookup_evals {
+            transcript.append_scalar(b"f_eval", &lookup_evals.f_eval);
+            transcript
+                .append_scalar(b"q_lookup_eval", &lookup_evals.q_lookup_eval);
+            transcript
+                .append_scalar(b"lookup_perm_eval", &lookup_evals.z2_next_eval);
+            transcript.append_scalar(b"h_1_eval", &lookup_evals.h1_eval);
+            transcript
+                .append_scalar(b"h_1_next_eval", &lookup_evals.h1_next_eval);
+            transcript.append_scalar(b"h_2_eval", &lookup_evals.h2_eval);
+        }
 
         // Third, all evals needed for custom gates
         evaluations
@@ -562,7 +795,7 @@ where
             .iter()
             .for_each(|(label, eval)| {
                 let static_label = Box::leak(label.to_owned().into_boxed_str());
-                transcript.append(static_label.as_bytes(), eval);
+                transcript.append_scalar(static_label.as_bytes(), eval);
             });
 
         // 5. Compute Openings using KZG10
@@ -578,15 +811,19 @@ where
         // opening poly. It is being left in for now but it may not
         // be necessary. Warrants further investigation.
         // Ditto with the out_sigma poly.
-        let aw_polys = [
+        let mut aw_polys = vec![
             label_polynomial!(lin_poly),
             label_polynomial!(prover_key.permutation.left_sigma.0.clone()),
             label_polynomial!(prover_key.permutation.right_sigma.0.clone()),
             label_polynomial!(prover_key.permutation.out_sigma.0.clone()),
-            label_polynomial!(f_poly),
-            label_polynomial!(h_2_poly),
-            label_polynomial!(table_poly),
         ];
+        if let Some(lookup_polys) = &lookup_polys {
+            aw_polys.extend([
+                label_polynomial!(lookup_polys.f),
+                label_polynomial!(lookup_polys.h_2),
+                label_polynomial!(lookup_polys.table),
+            ]);
+        }
 
         let (aw_commits, aw_rands) = PC::commit(commit_key, &aw_polys, None)
             .map_err(to_pc_error::<F, PC>)?;
@@ -605,15 +842,26 @@ where
         let saw_challenge: F =
             transcript.challenge_scalar(b"aggregate_witness");
 
-        let saw_polys = [
-            label_polynomial!(z_poly),
-            label_polynomial!(w_l_poly),
-            label_polynomial!(w_r_poly),
-            label_polynomial!(w_4_poly),
-            label_polynomial!(h_1_poly),
-            label_polynomial!(z_2_poly),
-            label_polynomial!(table_poly),
-        ];
+        // The wires are only opened at `z * omega` for the gate types which
+        // need it.
+        let mut saw_polys = vec![label_polynomial!(z_poly)];
+        if prover_key
+            .active_gates
+            .shifted_wires(!custom_gates.is_empty())
+        {
+            saw_polys.extend([
+                label_polynomial!(w_l_poly),
+                label_polynomial!(w_r_poly),
+                label_polynomial!(w_4_poly),
+            ]);
+        }
+        if let Some(lookup_polys) = &lookup_polys {
+            saw_polys.extend([
+                label_polynomial!(lookup_polys.h_1),
+                label_polynomial!(lookup_polys.z_2),
+                label_polynomial!(lookup_polys.table),
+            ]);
+        }
 
         let (saw_commits, saw_rands) = PC::commit(commit_key, &saw_polys, None)
             .map_err(to_pc_error::<F, PC>)?;
@@ -629,16 +877,21 @@ where
         )
         .map_err(to_pc_error::<F, PC>)?;
 
+        let [f_comm, h_1_comm, h_2_comm, z_2_comm] = match lookup_comms {
+            Some(comms) => comms.map(Some),
+            None => [None, None, None, None],
+        };
+
         Ok(Proof {
             a_comm: w_commits[0].commitment().clone(),
             b_comm: w_commits[1].commitment().clone(),
             c_comm: w_commits[2].commitment().clone(),
             d_comm: w_commits[3].commitment().clone(),
             z_comm: saw_commits[0].commitment().clone(),
-            f_comm: f_poly_commit[0].commitment().clone(),
-            h_1_comm: h_1_poly_commit[0].commitment().clone(),
-            h_2_comm: h_2_poly_commit[0].commitment().clone(),
-            z_2_comm: z_2_poly_commit[0].commitment().clone(),
+            f_comm,
+            h_1_comm,
+            h_2_comm,
+            z_2_comm,
             t_1_comm: t_commits[0].commitment().clone(),
             t_2_comm: t_commits[1].commitment().clone(),
             t_3_comm: t_commits[2].commitment().clone(),
@@ -656,18 +909,21 @@ where
     /// Proves a circuit is satisfied, then clears the witness variables
     /// If the circuit is not pre-processed, then the preprocessed circuit will
     /// also be computed.
-    pub fn prove(
+    ///
+    /// All of the randomness of the proof is sampled from `rng`.
+    pub fn prove<R>(
         &mut self,
         commit_key: &PC::CommitterKey,
-    ) -> Result<Proof<F, PC>, Error> {
+        rng: &mut R,
+    ) -> Result<Proof<F, PC>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
         if self.prover_key.is_none() {
             // Preprocess circuit and store preprocessed circuit and transcript
             // in the Prover.
-            self.prover_key = Some(self.cs.preprocess_prover(
-                commit_key,
-                &mut self.preprocessed_transcript,
-                PhantomData::<PC>,
-            )?);
+            self.prover_key =
+                Some(self.cs.preprocess_prover(commit_key, PhantomData::<PC>)?);
         }
 
         let prover_key = self.prover_key.as_ref().unwrap();
@@ -675,20 +931,22 @@ where
             commit_key,
             prover_key,
             PhantomData::<PC>,
+            rng,
         )?;
 
         // Clear witness and reset composer variables
-        self.clear_witness();
+        self.clear_witness(rng);
 
         Ok(proof)
     }
 }
 
-impl<F, P, PC> Default for Prover<F, P, PC>
+impl<F, P, PC, T> Default for Prover<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol,
 {
     #[inline]
     fn default() -> Self {
@@ -10,7 +10,10 @@ use crate::{
         ecc::{CurveAddition, FixedBaseScalarMul},
         logic::Logic,
         range::Range,
-        widget::GateConstraint,
+        widget::{
+            lookup::LookupPolynomials, CustomGate, GateConstraint,
+            QuotientStage,
+        },
         ProverKey,
     },
 };
@@ -20,6 +23,7 @@ use ark_poly::{
     univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain,
     UVPolynomial,
 };
+use itertools::izip;
 
 use super::{
     ecc::{CAVals, FBSMVals},
@@ -35,16 +39,12 @@ pub fn compute<F, P>(
     domain: &GeneralEvaluationDomain<F>,
     prover_key: &ProverKey<F>,
     z_poly: &DensePolynomial<F>,
-    z2_poly: &DensePolynomial<F>,
     w_l_poly: &DensePolynomial<F>,
     w_r_poly: &DensePolynomial<F>,
     w_o_poly: &DensePolynomial<F>,
     w_4_poly: &DensePolynomial<F>,
     public_inputs_poly: &DensePolynomial<F>,
-    f_poly: &DensePolynomial<F>,
-    table_poly: &DensePolynomial<F>,
-    h1_poly: &DensePolynomial<F>,
-    h2_poly: &DensePolynomial<F>,
+    lookup_polys: Option<&LookupPolynomials<F>>,
     alpha: &F,
     beta: &F,
     gamma: &F,
@@ -56,6 +56,8 @@ pub fn compute<F, P>(
     fixed_base_challenge: &F,
     var_base_challenge: &F,
     lookup_challenge: &F,
+    custom_gates: &[CustomGate<F>],
+    custom_challenges: &[F],
 ) -> Result<DensePolynomial<F>, Error>
 where
     F: PrimeField,
@@ -68,9 +70,6 @@ where
             <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
     })?;
 
-    let l1_poly = compute_first_lagrange_poly_scaled(domain, F::one());
-    let l1_eval_8n = domain_8n.coset_fft(&l1_poly);
-
     let mut z_eval_8n = domain_8n.coset_fft(z_poly);
     z_eval_8n.push(z_eval_8n[0]);
     z_eval_8n.push(z_eval_8n[1]);
@@ -113,46 +112,17 @@ where
     w4_eval_8n.push(w4_eval_8n[6]);
     w4_eval_8n.push(w4_eval_8n[7]);
 
-    let mut z2_eval_8n = domain_8n.coset_fft(z2_poly);
-    z2_eval_8n.push(z2_eval_8n[0]);
-    z2_eval_8n.push(z2_eval_8n[1]);
-    z2_eval_8n.push(z2_eval_8n[2]);
-    z2_eval_8n.push(z2_eval_8n[3]);
-    z2_eval_8n.push(z2_eval_8n[4]);
-    z2_eval_8n.push(z2_eval_8n[5]);
-    z2_eval_8n.push(z2_eval_8n[6]);
-    z2_eval_8n.push(z2_eval_8n[7]);
-
-    let f_eval_8n = domain_8n.coset_fft(f_poly);
-
-    let mut table_eval_8n = domain_8n.coset_fft(table_poly);
-    table_eval_8n.push(table_eval_8n[0]);
-    table_eval_8n.push(table_eval_8n[1]);
-    table_eval_8n.push(table_eval_8n[2]);
-    table_eval_8n.push(table_eval_8n[3]);
-    table_eval_8n.push(table_eval_8n[4]);
-    table_eval_8n.push(table_eval_8n[5]);
-    table_eval_8n.push(table_eval_8n[6]);
-    table_eval_8n.push(table_eval_8n[7]);
-
-    let mut h1_eval_8n = domain_8n.coset_fft(h1_poly);
-    h1_eval_8n.push(h1_eval_8n[0]);
-    h1_eval_8n.push(h1_eval_8n[1]);
-    h1_eval_8n.push(h1_eval_8n[2]);
-    h1_eval_8n.push(h1_eval_8n[3]);
-    h1_eval_8n.push(h1_eval_8n[4]);
-    h1_eval_8n.push(h1_eval_8n[5]);
-    h1_eval_8n.push(h1_eval_8n[6]);
-    h1_eval_8n.push(h1_eval_8n[7]);
-
-    let h2_eval_8n = domain_8n.coset_fft(h2_poly);
-
+    // A key without coset evaluations only materializes those of one part of
+    // the quotient and one chunk of rows at a time, see
+    // `ProverKey::coset_view`.
     let gate_constraints = compute_gate_constraint_satisfiability::<F, P>(
         domain,
         *range_challenge,
         *logic_challenge,
         *fixed_base_challenge,
         *var_base_challenge,
+        custom_gates,
+        custom_challenges,
         prover_key,
         &wl_eval_8n,
         &wr_eval_8n,
@@ -174,37 +144,127 @@ where
         *gamma,
     )?;
 
-    let lookup = prover_key.lookup.compute_lookup_quotient_term(
-        domain,
-        &wl_eval_8n,
-        &wr_eval_8n,
-        &wo_eval_8n,
-        &w4_eval_8n,
-        &f_eval_8n,
-        &table_eval_8n,
-        &h1_eval_8n,
-        &h2_eval_8n,
-        &z2_eval_8n,
-        &l1_eval_8n,
-        *delta,
-        *epsilon,
-        *zeta,
-        *lookup_challenge,
-    )?;
+    // A circuit without lookup gates has no lookup argument.
+    let lookup = match lookup_polys {
+        Some(lookup_polys) => compute_lookup_checks(
+            domain,
+            domain_8n,
+            prover_key,
+            lookup_polys,
+            &wl_eval_8n,
+            &wr_eval_8n,
+            &wo_eval_8n,
+            &w4_eval_8n,
+            *delta,
+            *epsilon,
+            *zeta,
+            *lookup_challenge,
+        )?,
+        None => vec![F::zero(); domain_8n.size()],
+    };
 
-    let quotient = (0..domain_8n.size())
-        .map(|i| {
-            let numerator = gate_constraints[i] + permutation[i] + lookup[i];
-            let denominator = prover_key.v_h_coset_8n()[i];
-            numerator * denominator.inverse().unwrap()
-        })
-        .collect::<Vec<_>>();
+    let mut quotient = Vec::with_capacity(domain_8n.size());
+    for rows in prover_key.quotient_chunks(domain_8n) {
+        let v_h_coset_8n =
+            prover_key.coset_evaluations("v_h", domain_8n, rows.clone())?;
+        quotient.extend(rows.zip(v_h_coset_8n.iter()).map(
+            |(i, denominator)| {
+                let numerator =
+                    gate_constraints[i] + permutation[i] + lookup[i];
+                numerator * denominator.inverse().unwrap()
+            },
+        ));
+    }
 
     Ok(DensePolynomial::from_coefficients_vec(
         domain_8n.coset_ifft(&quotient),
     ))
 }
 
+/// Computes the lookup contribution to the quotient polynomial over
+/// `domain`.
+fn compute_lookup_checks<F>(
+    domain: &GeneralEvaluationDomain<F>,
+    domain_8n: GeneralEvaluationDomain<F>,
+    prover_key: &ProverKey<F>,
+    lookup_polys: &LookupPolynomials<F>,
+    wl_eval_8n: &[F],
+    wr_eval_8n: &[F],
+    wo_eval_8n: &[F],
+    w4_eval_8n: &[F],
+    delta: F,
+    epsilon: F,
+    zeta: F,
+    lookup_challenge: F,
+) -> Result<Vec<F>, Error>
+where
+    F: PrimeField,
+{
+    let l1_poly = compute_first_lagrange_poly_scaled(domain, F::one());
+    let l1_eval_8n = domain_8n.coset_fft(&l1_poly);
+
+    let mut z2_eval_8n = domain_8n.coset_fft(&lookup_polys.z_2);
+    z2_eval_8n.push(z2_eval_8n[0]);
+    z2_eval_8n.push(z2_eval_8n[1]);
+    z2_eval_8n.push(z2_eval_8n[2]);
+    z2_eval_8n.push(z2_eval_8n[3]);
+    z2_eval_8n.push(z2_eval_8n[4]);
+    z2_eval_8n.push(z2_eval_8n[5]);
+    z2_eval_8n.push(z2_eval_8n[6]);
+    z2_eval_8n.push(z2_eval_8n[7]);
+
+    let f_eval_8n = domain_8n.coset_fft(&lookup_polys.f);
+
+    let mut table_eval_8n = domain_8n.coset_fft(&lookup_polys.table);
+    table_eval_8n.push(table_eval_8n[0]);
+    table_eval_8n.push(table_eval_8n[1]);
+    table_eval_8n.push(table_eval_8n[2]);
+    table_eval_8n.push(table_eval_8n[3]);
+    table_eval_8n.push(table_eval_8n[4]);
+    table_eval_8n.push(table_eval_8n[5]);
+    table_eval_8n.push(table_eval_8n[6]);
+    table_eval_8n.push(table_eval_8n[7]);
+
+    let mut h1_eval_8n = domain_8n.coset_fft(&lookup_polys.h_1);
+    h1_eval_8n.push(h1_eval_8n[0]);
+    h1_eval_8n.push(h1_eval_8n[1]);
+    h1_eval_8n.push(h1_eval_8n[2]);
+    h1_eval_8n.push(h1_eval_8n[3]);
+    h1_eval_8n.push(h1_eval_8n[4]);
+    h1_eval_8n.push(h1_eval_8n[5]);
+    h1_eval_8n.push(h1_eval_8n[6]);
+    h1_eval_8n.push(h1_eval_8n[7]);
+
+    let h2_eval_8n = domain_8n.coset_fft(&lookup_polys.h_2);
+
+    let mut lookup = Vec::with_capacity(domain_8n.size());
+    for rows in prover_key.quotient_chunks(domain_8n) {
+        let lookup_key = prover_key.coset_view(
+            domain_8n,
+            QuotientStage::Lookup,
+            rows.clone(),
+        )?;
+        lookup.extend(lookup_key.lookup.compute_lookup_quotient_term(
+            rows,
+            wl_eval_8n,
+            wr_eval_8n,
+            wo_eval_8n,
+            w4_eval_8n,
+            &f_eval_8n,
+            &table_eval_8n,
+            &h1_eval_8n,
+            &h2_eval_8n,
+            &z2_eval_8n,
+            &l1_eval_8n,
+            delta,
+            epsilon,
+            zeta,
+            lookup_challenge,
+        ));
+    }
+    Ok(lookup)
+}
+
 /// Computes contribution to the quotient polynomial that ensures
 /// the gate constraints are satisfied.
 fn compute_gate_constraint_satisfiability<F, P>(
@@ -213,6 +273,8 @@ fn compute_gate_constraint_satisfiability<F, P>(
     logic_challenge: F,
     fixed_base_challenge: F,
     var_base_challenge: F,
+    custom_gates: &[CustomGate<F>],
+    custom_challenges: &[F],
     prover_key: &ProverKey<F>,
     wl_eval_8n: &[F],
     wr_eval_8n: &[F],
@@ -232,9 +294,19 @@ where
     })?;
     let pi_eval_8n = domain_8n.coset_fft(pi_poly);
 
-    // TODO Eliminate contribution of unused gates
-    Ok((0..domain_8n.size())
-        .map(|i| {
+    // The gate types the key does not hold have no coset evaluations, and no
+    // contribution.
+    let active_gates = prover_key.active_gates;
+    let mut constraints = Vec::with_capacity(domain_8n.size());
+    for rows in prover_key.quotient_chunks(domain_8n) {
+        let key = prover_key.coset_view(
+            domain_8n,
+            QuotientStage::Gates,
+            rows.clone(),
+        )?;
+        for i in rows.clone() {
+            // Index of the row in the coset evaluations of `key`.
+            let j = i - rows.start;
             let wit_vals = WitnessValues {
                 a_val: wl_eval_8n[i],
                 b_val: wr_eval_8n[i],
@@ -247,55 +319,72 @@ where
                     ("a_next_eval".to_string(), wl_eval_8n[i + 8]),
                     ("b_next_eval".to_string(), wr_eval_8n[i + 8]),
                     ("d_next_eval".to_string(), w4_eval_8n[i + 8]),
-                    ("q_l_eval".to_string(), prover_key.arithmetic.q_l.1[i]),
-                    ("q_r_eval".to_string(), prover_key.arithmetic.q_r.1[i]),
-                    ("q_c_eval".to_string(), prover_key.arithmetic.q_c.1[i]),
+                    ("q_l_eval".to_string(), key.arithmetic.q_l.1[j]),
+                    ("q_r_eval".to_string(), key.arithmetic.q_r.1[j]),
+                    ("q_c_eval".to_string(), key.arithmetic.q_c.1[j]),
                     // prob don't need the follow but nonetheless...
-                    ("q_hl_eval".to_string(), prover_key.arithmetic.q_hl.1[i]),
-                    ("q_hr_eval".to_string(), prover_key.arithmetic.q_hr.1[i]),
-                    ("q_h4_eval".to_string(), prover_key.arithmetic.q_hr.1[i]),
+                    ("q_hl_eval".to_string(), key.arithmetic.q_hl.1[j]),
+                    ("q_hr_eval".to_string(), key.arithmetic.q_hr.1[j]),
+                    ("q_h4_eval".to_string(), key.arithmetic.q_h4.1[j]),
                 ],
             };
 
-            let arithmetic =
-                prover_key.arithmetic.compute_quotient_i(i, wit_vals);
-
-            let range = Range::quotient_term(
-                prover_key.range_selector.1[i],
-                range_challenge,
-                wit_vals,
-                RangeVals::from_evaluations(&custom_vals),
-            );
-
-            let logic = Logic::quotient_term(
-                prover_key.logic_selector.1[i],
-                logic_challenge,
-                wit_vals,
-                LogicVals::from_evaluations(&custom_vals),
-            );
-
-            let fixed_base_scalar_mul =
-                FixedBaseScalarMul::<_, P>::quotient_term(
-                    prover_key.fixed_group_add_selector.1[i],
+            let arithmetic = key.arithmetic.compute_quotient_i(j, wit_vals);
+
+            let mut gates = arithmetic + pi_eval_8n[i];
+
+            if active_gates.range {
+                gates += Range::quotient_term(
+                    key.range_selector.1[j],
+                    range_challenge,
+                    wit_vals,
+                    RangeVals::from_evaluations(&custom_vals)?,
+                );
+            }
+
+            if active_gates.logic {
+                gates += Logic::quotient_term(
+                    key.logic_selector.1[j],
+                    logic_challenge,
+                    wit_vals,
+                    LogicVals::from_evaluations(&custom_vals)?,
+                );
+            }
+
+            if active_gates.fixed_base {
+                gates += FixedBaseScalarMul::<_, P>::quotient_term(
+                    key.fixed_group_add_selector.1[j],
                     fixed_base_challenge,
                     wit_vals,
-                    FBSMVals::from_evaluations(&custom_vals),
+                    FBSMVals::from_evaluations(&custom_vals)?,
                 );
+            }
+
+            if active_gates.variable_base {
+                gates += CurveAddition::<_, P>::quotient_term(
+                    key.variable_group_add_selector.1[j],
+                    var_base_challenge,
+                    wit_vals,
+                    CAVals::from_evaluations(&custom_vals)?,
+                );
+            }
+
+            let custom =
+                izip!(custom_gates, custom_challenges, &key.custom_selectors)
+                    .map(|(gate, challenge, (_, selector))| {
+                        gate.quotient_term(
+                            selector.1[j],
+                            *challenge,
+                            wit_vals,
+                            &custom_vals,
+                        )
+                    })
+                    .sum::<Result<F, Error>>()?;
 
-            let curve_addition = CurveAddition::<_, P>::quotient_term(
-                prover_key.variable_group_add_selector.1[i],
-                var_base_challenge,
-                wit_vals,
-                CAVals::from_evaluations(&custom_vals),
-            );
-
-            (arithmetic + pi_eval_8n[i])
-                + range
-                + logic
-                + fixed_base_scalar_mul
-                + curve_addition
-        })
-        .collect())
+            constraints.push(gates + custom);
+        }
+    }
+    Ok(constraints)
 }
 
 /// Computes the permutation contribution to the quotient polynomial over
@@ -324,10 +413,16 @@ where
     let l1_poly_alpha =
         compute_first_lagrange_poly_scaled(domain, alpha.square());
     let l1_alpha_sq_evals = domain_8n.coset_fft(&l1_poly_alpha.coeffs);
-    Ok((0..domain_8n.size())
-        .map(|i| {
-            prover_key.permutation.compute_quotient_i(
-                i,
+    let mut permutation = Vec::with_capacity(domain_8n.size());
+    for rows in prover_key.quotient_chunks(domain_8n) {
+        let key = prover_key.coset_view(
+            domain_8n,
+            QuotientStage::Permutation,
+            rows.clone(),
+        )?;
+        permutation.extend(rows.clone().map(|i| {
+            key.permutation.compute_quotient_i(
+                i - rows.start,
                 wl_eval_8n[i],
                 wr_eval_8n[i],
                 wo_eval_8n[i],
@@ -339,8 +434,9 @@ where
                 beta,
                 gamma,
             )
-        })
-        .collect())
+        }));
+    }
+    Ok(permutation)
 }
 
 /// Computes the first lagrange polynomial with the given `scale` over `domain`.
@@ -8,24 +8,38 @@
 
 //use crate::circuit::EmbeddedCurve;
 use crate::{
-    commitment::HomomorphicCommitment,
-    constraint_system::StandardComposer,
-    error::Error,
-    proof_system::{widget::VerifierKey as PlonkVerifierKey, Proof},
+    commitment::{HomomorphicCommitment, PairingAccumulator, KZG10},
+    constraint_system::{Blinding, StandardComposer},
+    error::{to_pc_error, Error},
+    proof_system::{
+        prover::MAX_BLINDER_DEGREE, widget::VerifierKey as PlonkVerifierKey,
+        Proof,
+    },
+    transcript::TranscriptProtocol,
 };
-use ark_ec::TEModelParameters;
-use ark_ff::PrimeField;
+use ark_ec::{PairingEngine, TEModelParameters};
+use ark_ff::{PrimeField, Zero};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::PolynomialCommitment;
 use core::marker::PhantomData;
 use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
 
 use super::pi::PublicInputs;
 
 /// Abstraction structure designed verify [`Proof`]s.
-pub struct Verifier<F, P, PC>
+///
+/// The Fiat-Shamir transcript backend `T` defaults to the Merlin
+/// [`Transcript`] and has to match the one of the [`Prover`] which created the
+/// proofs.
+///
+/// [`Prover`]: crate::proof_system::Prover
+pub struct Verifier<F, P, PC, T = Transcript>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol,
 {
     /// VerificationKey which is used to verify a specific PLONK circuit
     pub verifier_key: Option<PlonkVerifierKey<F, PC>>,
@@ -39,21 +53,22 @@ where
     /// verifier to verify multiple proofs from the same circuit. If this is
     /// not copied, then the verification procedure will modify the transcript,
     /// making it unusable for future proofs.
-    pub preprocessed_transcript: Transcript,
+    pub preprocessed_transcript: T,
 }
 
-impl<F, P, PC> Verifier<F, P, PC>
+impl<F, P, PC, T> Verifier<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol,
 {
     /// Creates a new `Verifier` instance.
     pub fn new(label: &'static [u8]) -> Self {
         Self {
             verifier_key: None,
             cs: StandardComposer::new(),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
         }
     }
 
@@ -62,7 +77,28 @@ where
         Self {
             verifier_key: None,
             cs: StandardComposer::with_expected_size(size),
-            preprocessed_transcript: Transcript::new(label),
+            preprocessed_transcript: T::new(label),
+        }
+    }
+
+    /// Creates a new `Verifier` instance for a circuit made zero-knowledge
+    /// with the given [`Blinding`] strategy, which has to match the one of the
+    /// [`Prover`](crate::proof_system::Prover).
+    ///
+    /// `rng` only fills the witnesses of the dummy gates of
+    /// [`Blinding::DummyGates`], which the verifier never uses.
+    pub fn with_blinding<R>(
+        label: &'static [u8],
+        blinding: Blinding,
+        rng: &mut R,
+    ) -> Self
+    where
+        R: CryptoRng + RngCore,
+    {
+        Self {
+            verifier_key: None,
+            cs: StandardComposer::with_blinding(0, blinding, rng),
+            preprocessed_transcript: T::new(label),
         }
     }
 
@@ -71,6 +107,14 @@ where
         self.cs.circuit_bound()
     }
 
+    /// Returns the degree the commit key has to be trimmed to in order to
+    /// verify proofs of the circuit. It matches the one of the [`Prover`].
+    ///
+    /// [`Prover`]: crate::proof_system::Prover
+    pub fn commit_key_degree(&self) -> usize {
+        self.circuit_bound() + MAX_BLINDER_DEGREE
+    }
+
     /// Returns a mutable copy of the underlying composer.
     pub fn mut_cs(&mut self) -> &mut StandardComposer<F, P> {
         &mut self.cs
@@ -83,21 +127,14 @@ where
         &mut self,
         commit_key: &PC::CommitterKey,
     ) -> Result<(), Error> {
-        let vk = self.cs.preprocess_verifier(
-            commit_key,
-            &mut self.preprocessed_transcript,
-            PhantomData::<PC>,
-        )?;
+        let vk = self.cs.preprocess_verifier(commit_key, PhantomData::<PC>)?;
 
         self.verifier_key = Some(vk);
         Ok(())
     }
 
-    /// Keys the [`Transcript`] with additional seed information
-    /// Wrapper around [`Transcript::append_message`].
-    ///
-    /// [`Transcript`]: merlin::Transcript
-    /// [`Transcript::append_message`]: merlin::Transcript::append_message
+    /// Keys the transcript with additional seed information
+    /// Wrapper around [`TranscriptProtocol::append_message`].
     pub fn key_transcript(&mut self, label: &'static [u8], message: &[u8]) {
         self.preprocessed_transcript.append_message(label, message);
     }
@@ -109,23 +146,126 @@ where
         pc_verifier_key: &PC::VerifierKey,
         public_inputs: &PublicInputs<F>,
     ) -> Result<(), Error> {
-        proof.verify::<P>(
+        proof.verify::<P, T>(
             self.verifier_key.as_ref().unwrap(),
             &mut self.preprocessed_transcript.clone(),
             pc_verifier_key,
             public_inputs,
+            &self.cs.custom_gates,
         )
     }
+
+    /// Verifies a batch of [`Proof`]s for the same circuit together with
+    /// their `public_inputs`.
+    ///
+    /// All of the opening claims of the proofs are combined with a random
+    /// linear combination and handed to the commitment scheme at once, which
+    /// for [`KZG10`](crate::commitment::KZG10) means a single pairing check
+    /// for the whole batch. The result is the same as verifying every proof
+    /// with [`Verifier::verify`], but the batch only tells whether all of the
+    /// proofs are valid, not which one is not.
+    ///
+    /// An empty batch is rejected with [`Error::EmptyBatch`].
+    pub fn verify_batch(
+        &self,
+        proofs: &[(Proof<F, PC>, PublicInputs<F>)],
+        pc_verifier_key: &PC::VerifierKey,
+    ) -> Result<(), Error> {
+        if proofs.is_empty() {
+            return Err(Error::EmptyBatch);
+        }
+        let plonk_verifier_key = self.verifier_key.as_ref().unwrap();
+
+        let mut batch_transcript = self.preprocessed_transcript.clone();
+        batch_transcript.append_message(b"dom-sep", b"batch_verification");
+        batch_transcript.append(b"batch_size", &(proofs.len() as u64));
+
+        let mut claims = Vec::with_capacity(2 * proofs.len());
+        for (proof, public_inputs) in proofs {
+            claims.extend(proof.opening_claims::<P, T>(
+                plonk_verifier_key,
+                &mut self.preprocessed_transcript.clone(),
+                public_inputs,
+                &self.cs.custom_gates,
+            )?);
+            batch_transcript.append(b"proof", proof);
+            batch_transcript.append(b"pi", public_inputs);
+        }
+        let randomizer: F =
+            batch_transcript.challenge_scalar(b"batch verification");
+
+        match PC::batch_check_openings(pc_verifier_key, &claims, randomizer) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::ProofVerificationError),
+            Err(e) => Err(to_pc_error::<F, PC>(e)),
+        }
+    }
+}
+
+impl<E, P, T> Verifier<E::Fr, P, KZG10<E>, T>
+where
+    E: PairingEngine,
+    P: TEModelParameters<BaseField = E::Fr>,
+    T: TranscriptProtocol,
+{
+    /// Runs every check of [`Verifier::verify`] except for the final pairing
+    /// check, and returns the [`PairingAccumulator`] that it would be
+    /// performed on.
+    ///
+    /// The [`Proof`] is valid if and only if the accumulator passes
+    /// [`PairingAccumulator::check`], which callers can defer, for instance to
+    /// combine the accumulators of several proofs first.
+    pub fn verify_deferred(
+        &self,
+        proof: &Proof<E::Fr, KZG10<E>>,
+        pc_verifier_key: &<KZG10<E> as PolynomialCommitment<
+            E::Fr,
+            DensePolynomial<E::Fr>,
+        >>::VerifierKey,
+        public_inputs: &PublicInputs<E::Fr>,
+    ) -> Result<PairingAccumulator<E>, Error> {
+        let mut transcript = self.preprocessed_transcript.clone();
+        let claims = proof.opening_claims::<P, T>(
+            self.verifier_key.as_ref().unwrap(),
+            &mut transcript,
+            public_inputs,
+            &self.cs.custom_gates,
+        )?;
+
+        // Both openings are folded into the same accumulator, so they are
+        // weighted by a challenge that depends on them. The witness and the
+        // blinding evaluation of each opening are appended as a point and a
+        // scalar.
+        transcript.append_point(b"aw_opening", &proof.aw_opening.w);
+        transcript.append_scalar(
+            b"aw_random_v",
+            &proof.aw_opening.random_v.unwrap_or_else(E::Fr::zero),
+        );
+        transcript.append_point(b"saw_opening", &proof.saw_opening.w);
+        transcript.append_scalar(
+            b"saw_random_v",
+            &proof.saw_opening.random_v.unwrap_or_else(E::Fr::zero),
+        );
+        let randomizer: E::Fr =
+            transcript.challenge_scalar(b"accumulator randomizer");
+
+        Ok(PairingAccumulator::from_claims(
+            pc_verifier_key,
+            &claims,
+            randomizer,
+        ))
+    }
 }
 
-impl<F, P, PC> Default for Verifier<F, P, PC>
+impl<F, P, PC, T> Default for Verifier<F, P, PC, T>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>,
     PC: HomomorphicCommitment<F>,
+    T: TranscriptProtocol,
 {
     #[inline]
-    fn default() -> Verifier<F, P, PC> {
+    fn default() -> Verifier<F, P, PC, T> {
         Verifier::new(b"plonk")
     }
 }
@@ -8,7 +8,7 @@
 
 use crate::proof_system::WitnessValues;
 use crate::{
-    constraint_system::SBOX_ALPHA,
+    constraint_system::SBOX_ALPHA, error::Error,
     proof_system::linearisation_poly::ProofEvaluations,
 };
 use ark_ff::{FftField, PrimeField};
@@ -157,8 +157,8 @@ where
         scalars: &mut Vec<F>,
         points: &mut Vec<PC::Commitment>,
         evaluations: &ProofEvaluations<F>,
-    ) {
-        let q_arith_eval = evaluations.custom_evals.get("q_arith_eval");
+    ) -> Result<(), Error> {
+        let q_arith_eval = evaluations.custom_evals.get("q_arith_eval")?;
 
         scalars.push(
             evaluations.wire_evals.a_eval
@@ -196,5 +196,6 @@ where
 
         scalars.push(q_arith_eval);
         points.push(self.q_c.clone());
+        Ok(())
     }
 }
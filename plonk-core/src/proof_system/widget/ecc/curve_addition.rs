@@ -6,10 +6,13 @@
 
 //! Elliptic Curve Point Addition Gate
 
-use crate::proof_system::{
-    linearisation_poly::CustomEvaluations,
-    widget::{GateConstraint, WitnessValues},
-    CustomValues,
+use crate::{
+    error::Error,
+    proof_system::{
+        linearisation_poly::CustomEvaluations,
+        widget::{GateConstraint, WitnessValues},
+        CustomValues,
+    },
 };
 use ark_ec::{ModelParameters, TEModelParameters};
 use ark_ff::PrimeField;
@@ -32,15 +35,17 @@ impl<F> CustomValues<F> for CAVals<F>
 where
     F: PrimeField,
 {
-    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
-        let a_next_val = custom_evals.get("a_next_eval");
-        let b_next_val = custom_evals.get("b_next_eval");
-        let d_next_val = custom_evals.get("d_next_eval");
-        CAVals {
+    fn from_evaluations(
+        custom_evals: &CustomEvaluations<F>,
+    ) -> Result<Self, Error> {
+        let a_next_val = custom_evals.get("a_next_eval")?;
+        let b_next_val = custom_evals.get("b_next_eval")?;
+        let d_next_val = custom_evals.get("d_next_eval")?;
+        Ok(CAVals {
             a_next_val,
             b_next_val,
             d_next_val,
-        }
+        })
     }
 }
 
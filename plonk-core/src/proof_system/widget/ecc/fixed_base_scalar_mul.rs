@@ -18,10 +18,13 @@
 //! Bits are accumulated in base2. So we use d(Xw) - 2d(X) to extract the
 //! base2 bit.
 
-use crate::proof_system::{
-    linearisation_poly::CustomEvaluations,
-    widget::{GateConstraint, WitnessValues},
-    CustomValues,
+use crate::{
+    error::Error,
+    proof_system::{
+        linearisation_poly::CustomEvaluations,
+        widget::{GateConstraint, WitnessValues},
+        CustomValues,
+    },
 };
 use ark_ec::{ModelParameters, TEModelParameters};
 use ark_ff::PrimeField;
@@ -51,21 +54,23 @@ impl<F> CustomValues<F> for FBSMVals<F>
 where
     F: PrimeField,
 {
-    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
-        let a_next_val = custom_evals.get("a_next_eval");
-        let b_next_val = custom_evals.get("b_next_eval");
-        let d_next_val = custom_evals.get("d_next_eval");
-        let q_l_val = custom_evals.get("q_l_eval");
-        let q_r_val = custom_evals.get("q_r_eval");
-        let q_c_val = custom_evals.get("q_c_eval");
-        FBSMVals {
+    fn from_evaluations(
+        custom_evals: &CustomEvaluations<F>,
+    ) -> Result<Self, Error> {
+        let a_next_val = custom_evals.get("a_next_eval")?;
+        let b_next_val = custom_evals.get("b_next_eval")?;
+        let d_next_val = custom_evals.get("d_next_eval")?;
+        let q_l_val = custom_evals.get("q_l_eval")?;
+        let q_r_val = custom_evals.get("q_r_eval")?;
+        let q_c_val = custom_evals.get("q_c_eval")?;
+        Ok(FBSMVals {
             a_next_val,
             b_next_val,
             d_next_val,
             q_l_val,
             q_r_val,
             q_c_val,
-        }
+        })
     }
 }
 
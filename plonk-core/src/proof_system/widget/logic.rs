@@ -6,10 +6,13 @@
 
 //! Logic Gates
 
-use crate::proof_system::{
-    linearisation_poly::CustomEvaluations,
-    widget::{GateConstraint, WitnessValues},
-    CustomValues,
+use crate::{
+    error::Error,
+    proof_system::{
+        linearisation_poly::CustomEvaluations,
+        widget::{GateConstraint, WitnessValues},
+        CustomValues,
+    },
 };
 use ark_ff::PrimeField;
 use core::marker::PhantomData;
@@ -33,18 +36,20 @@ impl<F> CustomValues<F> for LogicVals<F>
 where
     F: PrimeField,
 {
-    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self {
+    fn from_evaluations(
+        custom_evals: &CustomEvaluations<F>,
+    ) -> Result<Self, Error> {
         // TODO: Subsitute labels
-        let a_next_val = custom_evals.get("a_next_eval");
-        let b_next_val = custom_evals.get("b_next_eval");
-        let d_next_val = custom_evals.get("d_next_eval");
-        let q_c_val = custom_evals.get("q_c_eval");
-        LogicVals {
+        let a_next_val = custom_evals.get("a_next_eval")?;
+        let b_next_val = custom_evals.get("b_next_eval")?;
+        let d_next_val = custom_evals.get("d_next_eval")?;
+        let q_c_val = custom_evals.get("q_c_eval")?;
+        Ok(LogicVals {
             a_next_val,
             b_next_val,
             d_next_val,
             q_c_val,
-        }
+        })
     }
 }
 
@@ -5,18 +5,25 @@
 // Copyright (c) ZK-Garage. All rights reserved.
 //! Lookup gates
 
-use crate::error::Error;
 use crate::lookup::multiset::MultiSet;
-use crate::proof_system::linearisation_poly::ProofEvaluations;
+use crate::proof_system::linearisation_poly::{
+    LookupEvaluations, ProofEvaluations,
+};
 use crate::util::lc;
-use ark_ff::{FftField, PrimeField};
+use alloc::sync::Arc;
+use ark_ff::PrimeField;
 use ark_poly::polynomial::univariate::DensePolynomial;
-use ark_poly::{EvaluationDomain, Evaluations, GeneralEvaluationDomain};
+use ark_poly::Evaluations;
 use ark_poly_commit::PolynomialCommitment;
 use ark_serialize::*;
+use core::ops::Range;
 
 /// Lookup Gates Prover Key
-#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+///
+/// The columns of the lookup table are shared by the clones of the key, such
+/// as the views of a mapped key computing the quotient polynomial a chunk at
+/// a time.
+#[derive(derivative::Derivative)]
 #[derivative(Clone, Debug, Eq, PartialEq)]
 pub struct ProverKey<F>
 where
@@ -25,23 +32,89 @@ where
     /// Lookup selector
     pub q_lookup: (DensePolynomial<F>, Evaluations<F>),
     /// Column 1 of lookup table
-    pub table_1: MultiSet<F>,
+    pub table_1: Arc<MultiSet<F>>,
     /// Column 2 of lookup table
-    pub table_2: MultiSet<F>,
+    pub table_2: Arc<MultiSet<F>>,
     /// Column 3 of lookup table
-    pub table_3: MultiSet<F>,
+    pub table_3: Arc<MultiSet<F>>,
     /// Column 4 of lookup table
-    pub table_4: MultiSet<F>,
+    pub table_4: Arc<MultiSet<F>>,
+}
+
+// The key is serialized like its fields, with the columns of the table in
+// place of the pointers to them.
+impl<F> CanonicalSerialize for ProverKey<F>
+where
+    F: PrimeField,
+{
+    fn serialize<W: Write>(
+        &self,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        self.q_lookup.serialize(&mut writer)?;
+        self.table_1.serialize(&mut writer)?;
+        self.table_2.serialize(&mut writer)?;
+        self.table_3.serialize(&mut writer)?;
+        self.table_4.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        self.q_lookup.serialized_size()
+            + self.table_1.serialized_size()
+            + self.table_2.serialized_size()
+            + self.table_3.serialized_size()
+            + self.table_4.serialized_size()
+    }
+}
+
+impl<F> CanonicalDeserialize for ProverKey<F>
+where
+    F: PrimeField,
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        Ok(Self {
+            q_lookup: CanonicalDeserialize::deserialize(&mut reader)?,
+            table_1: Arc::new(MultiSet::deserialize(&mut reader)?),
+            table_2: Arc::new(MultiSet::deserialize(&mut reader)?),
+            table_3: Arc::new(MultiSet::deserialize(&mut reader)?),
+            table_4: Arc::new(MultiSet::deserialize(&mut reader)?),
+        })
+    }
+}
+
+/// Polynomials of the lookup argument of a proof, computed by the prover
+/// unless the circuit has no lookup gates.
+pub(crate) struct LookupPolynomials<F>
+where
+    F: PrimeField,
+{
+    /// Query polynomial
+    pub(crate) f: DensePolynomial<F>,
+    /// Compressed table polynomial
+    pub(crate) table: DensePolynomial<F>,
+    /// First half of the sorted polynomial
+    pub(crate) h_1: DensePolynomial<F>,
+    /// Second half of the sorted polynomial
+    pub(crate) h_2: DensePolynomial<F>,
+    /// Lookup permutation polynomial
+    pub(crate) z_2: DensePolynomial<F>,
 }
 
 impl<F> ProverKey<F>
 where
     F: PrimeField,
 {
-    /// Compute lookup portion of quotient polynomial
+    /// Compute lookup portion of quotient polynomial over the rows `rows` of
+    /// the coset domain, whose evaluations in the key are indexed from the
+    /// start of `rows`
+    ///
+    /// The query, `h_1`, `h_2` and lookup permutation polynomials are blinded
+    /// by the prover with multiples of the vanishing polynomial of the domain
+    /// of the circuit. This leaves their values over it, and therefore the
+    /// identities checked here, unchanged.
     pub fn compute_lookup_quotient_term(
         &self,
-        domain: &GeneralEvaluationDomain<F>,
+        rows: Range<usize>,
         wl_eval_8n: &[F],
         wr_eval_8n: &[F],
         wo_eval_8n: &[F],
@@ -56,21 +129,14 @@ where
         epsilon: F,
         zeta: F,
         lookup_sep: F,
-    ) -> Result<Vec<F>, Error>
+    ) -> Vec<F>
     where
         F: PrimeField,
     {
-        let domain_8n = GeneralEvaluationDomain::<F>::new(8 * domain.size())
-        .ok_or(Error::InvalidEvalDomainSize {
-        log_size_of_group: (8 * domain.size()).trailing_zeros(),
-        adicity:
-            <<F as FftField>::FftParams as ark_ff::FftParameters>::TWO_ADICITY,
-    })?;
-
-        Ok((0..domain_8n.size())
+        rows.clone()
             .map(|i| {
                 self.compute_quotient_i(
-                    i,
+                    i - rows.start,
                     wl_eval_8n[i],
                     wr_eval_8n[i],
                     wo_eval_8n[i],
@@ -90,7 +156,7 @@ where
                     lookup_sep,
                 )
             })
-            .collect())
+            .collect()
     }
 
     /// Compute evals of lookup portion of quotient polynomial
@@ -240,6 +306,7 @@ where
         scalars: &mut Vec<F>,
         points: &mut Vec<PC::Commitment>,
         evaluations: &ProofEvaluations<F>,
+        lookup_evals: &LookupEvaluations<F>,
         (delta, epsilon, zeta): (F, F, F),
         lookup_sep: F,
         l1_eval: F,
@@ -262,7 +329,7 @@ where
                 &zeta,
             );
 
-            let a_0 = compressed_eval - evaluations.lookup_evals.f_eval;
+            let a_0 = compressed_eval - lookup_evals.f_eval;
             a_0 * lookup_sep
         };
 
@@ -271,10 +338,10 @@ where
 
         // (1 + δ) * (ε + f_bar) * (ε(1+δ) + t_bar + δ*tω_bar) *  lookup_sep^2
         let b = {
-            let b_0 = epsilon + evaluations.lookup_evals.f_eval;
+            let b_0 = epsilon + lookup_evals.f_eval;
             let b_1 = epsilon_one_plus_delta
-                + evaluations.lookup_evals.table_eval
-                + delta * evaluations.lookup_evals.table_next_eval;
+                + lookup_evals.table_eval
+                + delta * lookup_evals.table_next_eval;
             let b_2 = l1_eval * lookup_sep_cu;
             one_plus_delta * b_0 * b_1 * lookup_sep_sq + b_2
         };
@@ -283,10 +350,10 @@ where
         points.push(z2_comm);
 
         let c = {
-            let c_0 = -evaluations.lookup_evals.z2_next_eval * lookup_sep_sq;
+            let c_0 = -lookup_evals.z2_next_eval * lookup_sep_sq;
             let c_1 = epsilon_one_plus_delta
-                + evaluations.lookup_evals.h2_eval
-                + delta * evaluations.lookup_evals.h1_next_eval;
+                + lookup_evals.h2_eval
+                + delta * lookup_evals.h1_next_eval;
             c_0 * c_1
         };
         scalars.push(c);
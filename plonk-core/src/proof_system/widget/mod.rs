@@ -14,25 +14,39 @@ pub mod range;
 
 use crate::{
     commitment::HomomorphicCommitment,
+    error::Error,
     lookup::MultiSet,
     proof_system::{
         linearisation_poly::CustomEvaluations,
-        linearisation_poly::ProofEvaluations, permutation,
+        linearisation_poly::ProofEvaluations,
+        mapped::{self, MappedEvaluations},
+        permutation,
+        preprocess::compute_vanishing_poly_over_coset,
     },
     transcript::TranscriptProtocol,
 };
-use ark_ff::PrimeField;
-use ark_poly::{univariate::DensePolynomial, Evaluations};
+use alloc::{borrow::Cow, sync::Arc};
+use ark_ff::{One, PrimeField, Zero};
+use ark_poly::{
+    univariate::DensePolynomial, EvaluationDomain, Evaluations,
+    GeneralEvaluationDomain,
+};
 use ark_serialize::*;
+use blake2::{digest::Digest, Blake2s};
+use core::{any::TypeId, ops::Range};
+#[cfg(feature = "mmap")]
+use std::path::Path;
 
 /// Set of values needed for a custom gate
-pub trait CustomValues<F>
+pub trait CustomValues<F>: Sized
 where
     F: PrimeField,
 {
     /// Constructs gate-specific values struct from the set of evaluations
-    /// `CustomEvaluations`
-    fn from_evaluations(custom_evals: &CustomEvaluations<F>) -> Self;
+    /// `CustomEvaluations`, failing if one of them is missing.
+    fn from_evaluations(
+        custom_evals: &CustomEvaluations<F>,
+    ) -> Result<Self, Error>;
 }
 
 /// Witness Values
@@ -112,7 +126,8 @@ where
         evaluations: &ProofEvaluations<F>,
         scalars: &mut Vec<F>,
         points: &mut Vec<PC::Commitment>,
-    ) where
+    ) -> Result<(), Error>
+    where
         PC: HomomorphicCommitment<F>,
     {
         let coefficient = Self::constraints(
@@ -123,10 +138,391 @@ where
                 c_val: evaluations.wire_evals.c_eval,
                 d_val: evaluations.wire_evals.d_eval,
             },
-            Self::CustomVals::from_evaluations(&evaluations.custom_evals),
+            Self::CustomVals::from_evaluations(&evaluations.custom_evals)?,
         );
         scalars.push(coefficient);
         points.push(selector_commitment.clone());
+        Ok(())
+    }
+}
+
+/// Type-erased handle to a user-defined [`GateConstraint`].
+///
+/// Custom gates are registered with a
+/// [`StandardComposer`](crate::constraint_system::StandardComposer) under a
+/// unique `name`. The [`VerifierKey`] produced by preprocessing carries the
+/// implementation of its custom gates, but their code is not part of its
+/// serialization: a deserialized key only stores the selector of each custom
+/// gate together with its name, so the same gates have to be attached again
+/// with [`VerifierKey::attach_custom_gates`] before verifying.
+#[derive(derivative::Derivative)]
+#[derivative(Clone(bound = ""), Copy(bound = ""), Debug(bound = ""))]
+pub struct CustomGate<F>
+where
+    F: PrimeField,
+{
+    /// Name identifying the gate in the keys and the transcript.
+    name: &'static str,
+
+    /// Type implementing the gate.
+    type_id: TypeId,
+
+    /// Constraint function of the gate.
+    #[derivative(Debug = "ignore")]
+    constraints:
+        fn(F, WitnessValues<F>, &CustomEvaluations<F>) -> Result<F, Error>,
+}
+
+impl<F> CustomGate<F>
+where
+    F: PrimeField,
+{
+    /// Builds a [`CustomGate`] named `name` out of the [`GateConstraint`]
+    /// implementation `G`.
+    pub fn new<G>(name: &'static str) -> Self
+    where
+        G: GateConstraint<F> + 'static,
+    {
+        Self {
+            name,
+            type_id: TypeId::of::<G>(),
+            constraints: erased_constraints::<F, G>,
+        }
+    }
+
+    /// Returns the name of the gate.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns `true` if the gate is implemented by `G`.
+    pub(crate) fn is<G>(&self) -> bool
+    where
+        G: 'static,
+    {
+        self.type_id == TypeId::of::<G>()
+    }
+
+    /// Type-erased version of [`GateConstraint::quotient_term`].
+    pub(crate) fn quotient_term(
+        &self,
+        selector: F,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_evals: &CustomEvaluations<F>,
+    ) -> Result<F, Error> {
+        Ok(selector
+            * (self.constraints)(separation_challenge, wit_vals, custom_evals)?)
+    }
+
+    /// Type-erased version of [`GateConstraint::linearisation_term`].
+    pub(crate) fn linearisation_term(
+        &self,
+        selector_polynomial: &DensePolynomial<F>,
+        separation_challenge: F,
+        wit_vals: WitnessValues<F>,
+        custom_evals: &CustomEvaluations<F>,
+    ) -> Result<DensePolynomial<F>, Error> {
+        Ok(selector_polynomial
+            * (self.constraints)(separation_challenge, wit_vals, custom_evals)?)
+    }
+
+    /// Type-erased version of
+    /// [`GateConstraint::extend_linearisation_commitment`].
+    pub(crate) fn extend_linearisation_commitment<PC>(
+        &self,
+        selector_commitment: &PC::Commitment,
+        separation_challenge: F,
+        evaluations: &ProofEvaluations<F>,
+        scalars: &mut Vec<F>,
+        points: &mut Vec<PC::Commitment>,
+    ) -> Result<(), Error>
+    where
+        PC: HomomorphicCommitment<F>,
+    {
+        let coefficient = (self.constraints)(
+            separation_challenge,
+            WitnessValues {
+                a_val: evaluations.wire_evals.a_eval,
+                b_val: evaluations.wire_evals.b_eval,
+                c_val: evaluations.wire_evals.c_eval,
+                d_val: evaluations.wire_evals.d_eval,
+            },
+            &evaluations.custom_evals,
+        )?;
+        scalars.push(coefficient);
+        points.push(selector_commitment.clone());
+        Ok(())
+    }
+}
+
+/// Evaluates the constraints of `G` building its custom values from
+/// `custom_evals`.
+fn erased_constraints<F, G>(
+    separation_challenge: F,
+    wit_vals: WitnessValues<F>,
+    custom_evals: &CustomEvaluations<F>,
+) -> Result<F, Error>
+where
+    F: PrimeField,
+    G: GateConstraint<F>,
+{
+    Ok(G::constraints(
+        separation_challenge,
+        wit_vals,
+        G::CustomVals::from_evaluations(custom_evals)?,
+    ))
+}
+
+/// Returns, in key order, the registered implementation of every custom gate
+/// named in `names`.
+fn resolve_custom_gates<'a, F, I>(
+    names: I,
+    registered: &[CustomGate<F>],
+) -> Result<Vec<CustomGate<F>>, Error>
+where
+    F: PrimeField,
+    I: IntoIterator<Item = &'a String>,
+{
+    names
+        .into_iter()
+        .map(|name| {
+            registered
+                .iter()
+                .find(|gate| gate.name == name)
+                .copied()
+                .ok_or_else(|| Error::CustomGateNotRegistered {
+                    name: name.clone(),
+                })
+        })
+        .collect()
+}
+
+/// Implementations of the custom gates of a [`VerifierKey`].
+///
+/// The implementations are not part of the serialization of the key, nor of
+/// its comparisons.
+pub(crate) struct AttachedGates<F>(pub(crate) Vec<CustomGate<F>>)
+where
+    F: PrimeField;
+
+impl<F> Clone for AttachedGates<F>
+where
+    F: PrimeField,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<F> Default for AttachedGates<F>
+where
+    F: PrimeField,
+{
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<F> core::fmt::Debug for AttachedGates<F>
+where
+    F: PrimeField,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(CustomGate::name))
+            .finish()
+    }
+}
+
+impl<F> PartialEq for AttachedGates<F>
+where
+    F: PrimeField,
+{
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<F> Eq for AttachedGates<F> where F: PrimeField {}
+
+impl<F> CanonicalSerialize for AttachedGates<F>
+where
+    F: PrimeField,
+{
+    fn serialize<W: Write>(&self, _: W) -> Result<(), SerializationError> {
+        Ok(())
+    }
+
+    fn serialized_size(&self) -> usize {
+        0
+    }
+}
+
+impl<F> CanonicalDeserialize for AttachedGates<F>
+where
+    F: PrimeField,
+{
+    fn deserialize<R: Read>(_: R) -> Result<Self, SerializationError> {
+        Ok(Self::default())
+    }
+}
+
+/// Implements the arkworks serialization of a newtype over 32 bytes.
+macro_rules! impl_canonical_bytes {
+    ($name:ident) => {
+        impl CanonicalSerialize for $name {
+            fn serialize<W: Write>(
+                &self,
+                mut writer: W,
+            ) -> Result<(), SerializationError> {
+                Ok(writer.write_all(&self.0)?)
+            }
+
+            fn serialized_size(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        impl CanonicalDeserialize for $name {
+            fn deserialize<R: Read>(
+                mut reader: R,
+            ) -> Result<Self, SerializationError> {
+                let mut bytes = [0; 32];
+                reader.read_exact(&mut bytes)?;
+                Ok(Self(bytes))
+            }
+        }
+    };
+}
+
+/// Identifier of a circuit.
+///
+/// The identifier is derived by
+/// [`StandardComposer::circuit_id`](crate::constraint_system::StandardComposer::circuit_id)
+/// from the description of the circuit alone, so two circuits share it only
+/// if they have the same gates, wiring, lookup table and public input layout.
+/// It is stored in the [`ProverKey`] and [`VerifierKey`] of the circuit.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CircuitId(pub(crate) [u8; 32]);
+
+impl CircuitId {
+    /// Returns the bytes of the identifier.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl_canonical_bytes!(CircuitId);
+
+/// Domain tag of the [`CircuitDigest`], to be bumped whenever the contents
+/// of the digest change.
+const CIRCUIT_DIGEST_DOMAIN_TAG: &[u8] = b"plonk-circuit-digest-v1";
+
+/// Canonical digest of a circuit.
+///
+/// The digest is the Blake2s hash of the serialized [`VerifierKey`], which
+/// includes every commitment of the circuit and its public input layout. It
+/// is the first message absorbed by the transcript of both the prover and
+/// the verifier, so a proof is bound to the exact circuit it was created
+/// for.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CircuitDigest([u8; 32]);
+
+impl CircuitDigest {
+    /// Returns the bytes of the digest.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Adds the digest to the transcript.
+    pub(crate) fn seed_transcript<T>(&self, transcript: &mut T)
+    where
+        T: TranscriptProtocol,
+    {
+        transcript.append_message(b"circuit_digest", &self.0);
+    }
+}
+
+impl_canonical_bytes!(CircuitDigest);
+
+/// Gate types held by the keys and proofs of a circuit.
+///
+/// With [`GateLayout::Compact`] the keys only hold the gate types the circuit
+/// uses, with [`GateLayout::Full`] they hold all of them. The arithmetic
+/// gate, the custom gates and the permutation argument are always held.
+///
+/// [`GateLayout::Compact`]: crate::constraint_system::GateLayout::Compact
+/// [`GateLayout::Full`]: crate::constraint_system::GateLayout::Full
+#[derive(
+    CanonicalDeserialize, CanonicalSerialize, Clone, Copy, Debug, Eq, PartialEq,
+)]
+pub struct ActiveGates {
+    /// Range gates
+    pub range: bool,
+
+    /// Logic gates
+    pub logic: bool,
+
+    /// Fixed base scalar multiplication gates
+    pub fixed_base: bool,
+
+    /// Variable base curve addition gates
+    pub variable_base: bool,
+
+    /// Lookup gates, along with the whole lookup argument
+    pub lookup: bool,
+}
+
+impl ActiveGates {
+    /// Every gate type, as held by the keys of
+    /// [`GateLayout::Full`](crate::constraint_system::GateLayout::Full).
+    pub const ALL: Self = Self {
+        range: true,
+        logic: true,
+        fixed_base: true,
+        variable_base: true,
+        lookup: true,
+    };
+
+    /// Returns whether the proofs hold the evaluations of the wires at
+    /// `z * omega`, for a circuit with or without `custom_gates`.
+    pub(crate) fn shifted_wires(&self, custom_gates: bool) -> bool {
+        custom_gates
+            || self.range
+            || self.logic
+            || self.fixed_base
+            || self.variable_base
+    }
+
+    /// Returns, in proof order, the labels of the [`CustomEvaluations`] held
+    /// by the proofs of a circuit with or without `custom_gates`.
+    ///
+    /// Custom gates may read any of the evaluations, so a circuit with custom
+    /// gates holds all of them.
+    pub(crate) fn custom_evaluation_labels(
+        &self,
+        custom_gates: bool,
+    ) -> Vec<&'static str> {
+        let shifted = self.shifted_wires(custom_gates);
+        let q_c = custom_gates || self.logic || self.fixed_base;
+        let q_l_q_r = custom_gates || self.fixed_base;
+        [
+            ("q_arith_eval", true),
+            ("q_c_eval", q_c),
+            ("q_l_eval", q_l_q_r),
+            ("q_r_eval", q_l_q_r),
+            ("q_hl_eval", true),
+            ("q_hr_eval", true),
+            ("q_h4_eval", true),
+            ("a_next_eval", shifted),
+            ("b_next_eval", shifted),
+            ("d_next_eval", shifted),
+        ]
+        .into_iter()
+        .filter(|(_, held)| *held)
+        .map(|(label, _)| label)
+        .collect()
     }
 }
 
@@ -153,26 +549,41 @@ where
     /// Circuit size (not padded to a power of two).
     pub(crate) n: usize,
 
+    /// Identifier of the circuit
+    pub(crate) circuit_id: CircuitId,
+
     /// Arithmetic Verifier Key
     pub(crate) arithmetic: arithmetic::VerifierKey<F, PC>,
 
-    /// Range Gate Selector Commitment
-    pub(crate) range_selector_commitment: PC::Commitment,
+    /// Range Gate Selector Commitment, if the key holds range gates
+    pub(crate) range_selector_commitment: Option<PC::Commitment>,
 
-    /// Logic Gate Selector Commitment
-    pub(crate) logic_selector_commitment: PC::Commitment,
+    /// Logic Gate Selector Commitment, if the key holds logic gates
+    pub(crate) logic_selector_commitment: Option<PC::Commitment>,
 
-    /// Fixed Group Addition Selector Commitment
-    pub(crate) fixed_group_add_selector_commitment: PC::Commitment,
+    /// Fixed Group Addition Selector Commitment, if the key holds fixed
+    /// base scalar multiplication gates
+    pub(crate) fixed_group_add_selector_commitment: Option<PC::Commitment>,
 
-    /// Variable Group Addition Selector Commitment
-    pub(crate) variable_group_add_selector_commitment: PC::Commitment,
+    /// Variable Group Addition Selector Commitment, if the key holds
+    /// variable base curve addition gates
+    pub(crate) variable_group_add_selector_commitment: Option<PC::Commitment>,
 
     /// VerifierKey for permutation checks
     pub(crate) permutation: permutation::VerifierKey<PC::Commitment>,
 
-    /// VerifierKey for Lookup Gate
-    pub(crate) lookup: lookup::VerifierKey<F, PC>,
+    /// VerifierKey for Lookup Gate, if the key holds lookup gates
+    pub(crate) lookup: Option<lookup::VerifierKey<F, PC>>,
+
+    /// Custom Gate Selector Commitments, labeled with the gate name
+    pub(crate) custom_selector_commitments: Vec<(String, PC::Commitment)>,
+
+    /// Implementations of the custom gates, attached by preprocessing or
+    /// with [`VerifierKey::attach_custom_gates`]
+    pub(crate) attached_gates: AttachedGates<F>,
+
+    /// Positions intended for the public inputs
+    pub(crate) pi_pos: Vec<usize>,
 }
 
 impl<F, PC> VerifierKey<F, PC>
@@ -183,8 +594,13 @@ where
     /// Constructs a [`VerifierKey`] from the widget VerifierKey's that are
     /// constructed based on the selector polynomial commitments and the
     /// sigma polynomial commitments.
+    ///
+    /// The commitments of the gate types which are not `active_gates` are
+    /// left out of the key.
     pub(crate) fn from_polynomial_commitments(
         n: usize,
+        circuit_id: CircuitId,
+        active_gates: ActiveGates,
         q_m: PC::Commitment,
         q_l: PC::Commitment,
         q_r: PC::Commitment,
@@ -208,9 +624,12 @@ where
         table_2: PC::Commitment,
         table_3: PC::Commitment,
         table_4: PC::Commitment,
+        custom_selector_commitments: Vec<(String, PC::Commitment)>,
+        pi_pos: Vec<usize>,
     ) -> Self {
         Self {
             n,
+            circuit_id,
             arithmetic: arithmetic::VerifierKey {
                 q_m,
                 q_l,
@@ -223,23 +642,30 @@ where
                 q_h4,
                 q_arith,
             },
-            range_selector_commitment: q_range,
-            logic_selector_commitment: q_logic,
-            fixed_group_add_selector_commitment: q_fixed_group_add,
-            variable_group_add_selector_commitment: q_variable_group_add,
+            range_selector_commitment: active_gates.range.then_some(q_range),
+            logic_selector_commitment: active_gates.logic.then_some(q_logic),
+            fixed_group_add_selector_commitment: active_gates
+                .fixed_base
+                .then_some(q_fixed_group_add),
+            variable_group_add_selector_commitment: active_gates
+                .variable_base
+                .then_some(q_variable_group_add),
             permutation: permutation::VerifierKey {
                 left_sigma,
                 right_sigma,
                 out_sigma,
                 fourth_sigma,
             },
-            lookup: lookup::VerifierKey {
+            lookup: active_gates.lookup.then_some(lookup::VerifierKey {
                 q_lookup,
                 table_1,
                 table_2,
                 table_3,
                 table_4,
-            },
+            }),
+            custom_selector_commitments,
+            attached_gates: AttachedGates::default(),
+            pi_pos,
         }
     }
 
@@ -247,6 +673,90 @@ where
     pub fn padded_circuit_size(&self) -> usize {
         self.n.next_power_of_two()
     }
+
+    /// Returns the names of the custom gates this key was preprocessed with.
+    pub fn custom_gate_names(&self) -> impl Iterator<Item = &str> {
+        self.custom_selector_commitments
+            .iter()
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the [`CircuitId`] of the circuit this key was preprocessed
+    /// from.
+    pub fn circuit_id(&self) -> CircuitId {
+        self.circuit_id
+    }
+
+    /// Returns the positions intended for the public inputs of the circuit.
+    pub fn pi_pos(&self) -> &[usize] {
+        &self.pi_pos
+    }
+
+    /// Returns the [`ActiveGates`] of the key.
+    pub fn active_gates(&self) -> ActiveGates {
+        ActiveGates {
+            range: self.range_selector_commitment.is_some(),
+            logic: self.logic_selector_commitment.is_some(),
+            fixed_base: self.fixed_group_add_selector_commitment.is_some(),
+            variable_base: self
+                .variable_group_add_selector_commitment
+                .is_some(),
+            lookup: self.lookup.is_some(),
+        }
+    }
+
+    /// Returns the [`CircuitDigest`] of the circuit this key was
+    /// preprocessed from.
+    pub fn circuit_digest(&self) -> CircuitDigest {
+        let mut bytes = Vec::new();
+        self.serialize(&mut bytes)
+            .expect("serializing into a vector does not fail");
+        let mut hasher = Blake2s::new();
+        hasher.update(CIRCUIT_DIGEST_DOMAIN_TAG);
+        hasher.update(&bytes);
+        let mut digest = [0; 32];
+        digest.copy_from_slice(&hasher.finalize());
+        CircuitDigest(digest)
+    }
+
+    /// Attaches the implementations of the custom gates of the key, taken
+    /// by name out of `custom_gates`, so that it can be verified without
+    /// registering them again.
+    ///
+    /// The keys returned by preprocessing already carry them, but the
+    /// implementations are not part of the serialization of the key.
+    pub fn attach_custom_gates(
+        &mut self,
+        custom_gates: &[CustomGate<F>],
+    ) -> Result<(), Error> {
+        self.attached_gates = AttachedGates(resolve_custom_gates(
+            self.custom_selector_commitments
+                .iter()
+                .map(|(name, _)| name),
+            custom_gates,
+        )?);
+        Ok(())
+    }
+
+    /// Matches the custom gates of this key with their `registered`
+    /// implementations, falling back on the implementations attached to the
+    /// key.
+    pub(crate) fn custom_gates(
+        &self,
+        registered: &[CustomGate<F>],
+    ) -> Result<Vec<CustomGate<F>>, Error> {
+        let known = registered
+            .iter()
+            .chain(&self.attached_gates.0)
+            .copied()
+            .collect::<Vec<_>>();
+        resolve_custom_gates(
+            self.custom_selector_commitments
+                .iter()
+                .map(|(name, _)| name),
+            &known,
+        )
+    }
 }
 
 impl<F, PC> VerifierKey<F, PC>
@@ -255,35 +765,13 @@ where
     PC: HomomorphicCommitment<F>,
 {
     /// Adds the circuit description to the transcript.
+    ///
+    /// The whole key is bound through its [`CircuitDigest`].
     pub(crate) fn seed_transcript<T>(&self, transcript: &mut T)
     where
         T: TranscriptProtocol,
     {
-        transcript.append(b"q_m", &self.arithmetic.q_m);
-        transcript.append(b"q_l", &self.arithmetic.q_l);
-        transcript.append(b"q_r", &self.arithmetic.q_r);
-        transcript.append(b"q_o", &self.arithmetic.q_o);
-        transcript.append(b"q_c", &self.arithmetic.q_c);
-        transcript.append(b"q_4", &self.arithmetic.q_4);
-        transcript.append(b"q_hl", &self.arithmetic.q_hl);
-        transcript.append(b"q_hr", &self.arithmetic.q_hr);
-        transcript.append(b"q_h4", &self.arithmetic.q_h4);
-        transcript.append(b"q_arith", &self.arithmetic.q_arith);
-        transcript.append(b"q_range", &self.range_selector_commitment);
-        transcript.append(b"q_logic", &self.logic_selector_commitment);
-        transcript.append(
-            b"q_variable_group_add",
-            &self.variable_group_add_selector_commitment,
-        );
-        transcript.append(
-            b"q_fixed_group_add",
-            &self.fixed_group_add_selector_commitment,
-        );
-        transcript.append(b"left_sigma", &self.permutation.left_sigma);
-        transcript.append(b"right_sigma", &self.permutation.right_sigma);
-        transcript.append(b"out_sigma", &self.permutation.out_sigma);
-        transcript.append(b"fourth_sigma", &self.permutation.fourth_sigma);
-        transcript.circuit_domain_sep(self.n as u64);
+        self.circuit_digest().seed_transcript(transcript);
     }
 }
 
@@ -305,6 +793,13 @@ where
     /// Circuit size
     pub(crate) n: usize,
 
+    /// Identifier of the circuit
+    pub(crate) circuit_id: CircuitId,
+
+    /// Gate types held by the key. The selectors of the other gate types are
+    /// zero and have no coset evaluations.
+    pub(crate) active_gates: ActiveGates,
+
     /// Arithmetic Prover Key
     pub(crate) arithmetic: arithmetic::ProverKey<F>,
 
@@ -327,6 +822,10 @@ where
     /// ProverKey for permutation checks
     pub(crate) permutation: permutation::ProverKey<F>,
 
+    /// Custom Gate Selectors, labeled with the gate name
+    pub(crate) custom_selectors:
+        Vec<(String, (DensePolynomial<F>, Evaluations<F>))>,
+
     /// Pre-processes the 8n Evaluations for the vanishing polynomial, so
     /// they do not need to be computed at the proving stage.
     ///
@@ -334,14 +833,401 @@ where
     /// in their evaluation phase and divide by the quotient
     /// polynomial without having to perform IFFT
     pub(crate) v_h_coset_8n: Evaluations<F>,
+
+    /// Digest of the circuit, absorbed first by the prover's transcript
+    pub(crate) circuit_digest: CircuitDigest,
+
+    /// Mapped file the coset evaluations are read from, if the key was
+    /// opened with [`ProverKey::open_mapped`]
+    pub(crate) mapped: MappedEvaluations<F>,
+}
+
+/// Parts of the quotient polynomial, each of which needs the coset
+/// evaluations of some of the selectors of a [`ProverKey`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum QuotientStage {
+    /// Gate constraints.
+    Gates,
+    /// Permutation checks.
+    Permutation,
+    /// Lookup checks.
+    Lookup,
+}
+
+/// Returns the selectors held by `$key` with their labels and the stage of
+/// the quotient polynomial which needs their coset evaluations, borrowing
+/// them with `$iter` and the references `& $($mutability)*`.
+///
+/// The selectors of the gate types the key does not hold are skipped.
+macro_rules! selectors {
+    ($key:expr, $iter:ident, $($mutability:tt)*) => {{
+        let active = $key.active_gates;
+        [
+            (
+                "q_m",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_m,
+            ),
+            (
+                "q_l",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_l,
+            ),
+            (
+                "q_r",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_r,
+            ),
+            (
+                "q_o",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_o,
+            ),
+            (
+                "q_4",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_4,
+            ),
+            (
+                "q_c",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_c,
+            ),
+            (
+                "q_hl",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_hl,
+            ),
+            (
+                "q_hr",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_hr,
+            ),
+            (
+                "q_h4",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_h4,
+            ),
+            (
+                "q_arith",
+                QuotientStage::Gates,
+                true,
+                & $($mutability)* $key.arithmetic.q_arith,
+            ),
+            (
+                "q_range",
+                QuotientStage::Gates,
+                active.range,
+                & $($mutability)* $key.range_selector,
+            ),
+            (
+                "q_logic",
+                QuotientStage::Gates,
+                active.logic,
+                & $($mutability)* $key.logic_selector,
+            ),
+            (
+                "q_fixed_group_add",
+                QuotientStage::Gates,
+                active.fixed_base,
+                & $($mutability)* $key.fixed_group_add_selector,
+            ),
+            (
+                "q_variable_group_add",
+                QuotientStage::Gates,
+                active.variable_base,
+                & $($mutability)* $key.variable_group_add_selector,
+            ),
+            (
+                "q_lookup",
+                QuotientStage::Lookup,
+                active.lookup,
+                & $($mutability)* $key.lookup.q_lookup,
+            ),
+            (
+                "left_sigma",
+                QuotientStage::Permutation,
+                true,
+                & $($mutability)* $key.permutation.left_sigma,
+            ),
+            (
+                "right_sigma",
+                QuotientStage::Permutation,
+                true,
+                & $($mutability)* $key.permutation.right_sigma,
+            ),
+            (
+                "out_sigma",
+                QuotientStage::Permutation,
+                true,
+                & $($mutability)* $key.permutation.out_sigma,
+            ),
+            (
+                "fourth_sigma",
+                QuotientStage::Permutation,
+                true,
+                & $($mutability)* $key.permutation.fourth_sigma,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, _, held, _)| *held)
+        .map(|(label, stage, _, selector)| {
+            (label.to_string(), stage, selector)
+        })
+        .chain($key.custom_selectors.$iter().map(|(name, selector)| {
+            (format!("custom:{}", name), QuotientStage::Gates, selector)
+        }))
+    }};
 }
 
 impl<F> ProverKey<F>
 where
     F: PrimeField,
 {
-    pub(crate) fn v_h_coset_8n(&self) -> &Evaluations<F> {
-        &self.v_h_coset_8n
+    /// Returns the selectors held by the key with their labels, and the stage
+    /// of the quotient polynomial which needs their coset evaluations.
+    pub(crate) fn selectors(
+        &self,
+    ) -> impl Iterator<
+        Item = (String, QuotientStage, &(DensePolynomial<F>, Evaluations<F>)),
+    > {
+        selectors!(self, iter,)
+    }
+
+    /// Mutable version of [`ProverKey::selectors`].
+    fn selectors_mut(
+        &mut self,
+    ) -> impl Iterator<
+        Item = (
+            String,
+            QuotientStage,
+            &mut (DensePolynomial<F>, Evaluations<F>),
+        ),
+    > {
+        selectors!(self, iter_mut, mut)
+    }
+
+    /// Returns `true` if the key holds the coset evaluations of its
+    /// selectors, which is the case unless it is in coefficient form or
+    /// mapped.
+    pub fn has_coset_evaluations(&self) -> bool {
+        !self.v_h_coset_8n.evals.is_empty()
+    }
+
+    /// Returns the key in coefficient form, without the coset evaluations of
+    /// its selectors.
+    ///
+    /// The coset evaluations take eight times the memory of the selectors, so
+    /// a key in coefficient form is about nine times smaller. The prover then
+    /// recomputes the coset evaluations with FFTs, one part of the quotient
+    /// polynomial at a time, trading proving time for memory.
+    pub fn into_coefficient_form(mut self) -> Self {
+        let domain = self.v_h_coset_8n.domain();
+        let empty = || Evaluations::from_vec_and_domain(Vec::new(), domain);
+        for (_, _, selector) in self.selectors_mut() {
+            selector.1 = empty();
+        }
+        self.permutation.linear_evaluations = empty();
+        self.v_h_coset_8n = empty();
+        self.mapped = MappedEvaluations::default();
+        self
+    }
+
+    /// Writes the key to `writer` in the stable layout of memory-mapped
+    /// keys, to be opened with [`ProverKey::open_mapped`].
+    ///
+    /// The coset evaluations the key does not hold are recomputed one
+    /// selector at a time. As the key is written in many small pieces,
+    /// `writer` should be buffered.
+    #[cfg(feature = "mmap")]
+    pub fn write_mapped<W: Write>(&self, writer: W) -> Result<(), Error> {
+        mapped::write(self, writer)
+    }
+
+    /// Opens the key written with [`ProverKey::write_mapped`] in the file at
+    /// `path`.
+    ///
+    /// Only the selectors of the key are read in memory. Its coset
+    /// evaluations stay in the memory-mapped file, from which the prover
+    /// reads them one chunk of rows at a time. The file must not be modified
+    /// while the key is in use.
+    #[cfg(feature = "mmap")]
+    pub fn open_mapped<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        mapped::open(path.as_ref())
+    }
+
+    /// Returns the coset evaluations of the rows `rows` labeled `label`, see
+    /// [`ProverKey::selectors`], which are held by the key, read from its
+    /// mapped file or recomputed.
+    pub(crate) fn coset_evaluations(
+        &self,
+        label: &str,
+        domain_8n: GeneralEvaluationDomain<F>,
+        rows: Range<usize>,
+    ) -> Result<Cow<'_, [F]>, Error> {
+        let (held, polynomial) = match label {
+            "linear" => (&self.permutation.linear_evaluations, None),
+            "v_h" => (&self.v_h_coset_8n, None),
+            _ => {
+                let (_, _, selector) = self
+                    .selectors()
+                    .find(|(name, _, _)| name == label)
+                    .expect("labels are the ones of the selectors");
+                (&selector.1, Some(&selector.0))
+            }
+        };
+        if !held.evals.is_empty() {
+            return Ok(Cow::Borrowed(&held.evals[rows]));
+        }
+        if let Some(evaluations) =
+            self.mapped.evaluations(label, domain_8n, rows.clone())
+        {
+            return evaluations.map(Cow::Owned);
+        }
+        let mut evaluations = match (label, polynomial) {
+            ("v_h", _) => {
+                compute_vanishing_poly_over_coset(domain_8n, self.n as u64)
+                    .evals
+            }
+            (_, Some(polynomial)) => domain_8n.coset_fft(polynomial),
+            _ => domain_8n.coset_fft(&[F::zero(), F::one()]),
+        };
+        evaluations.truncate(rows.end);
+        evaluations.drain(..rows.start);
+        Ok(Cow::Owned(evaluations))
+    }
+
+    /// Returns the chunks of rows of `domain_8n` over which the parts of the
+    /// quotient polynomial are computed.
+    ///
+    /// The coset evaluations of a mapped key are read from its file
+    /// [`CHUNK_ROWS`](mapped::CHUNK_ROWS) rows at a time. Those held by the
+    /// key or recomputed are used over the whole domain at once.
+    pub(crate) fn quotient_chunks(
+        &self,
+        domain_8n: GeneralEvaluationDomain<F>,
+    ) -> impl Iterator<Item = Range<usize>> {
+        let size = domain_8n.size();
+        let chunk = if self.mapped.is_mapped() {
+            mapped::CHUNK_ROWS
+        } else {
+            size
+        };
+        (0..size)
+            .step_by(chunk)
+            .map(move |start| start..size.min(start + chunk))
+    }
+
+    /// Returns a copy of the key without coset evaluations nor mapped file,
+    /// and with its selector polynomials if `with_polynomials` is set. The
+    /// columns of the lookup table are shared with `self`.
+    pub(crate) fn hollow(&self, with_polynomials: bool) -> Self {
+        let domain = self.v_h_coset_8n.domain();
+        let empty = || Evaluations::from_vec_and_domain(Vec::new(), domain);
+        let hollow = |selector: &(DensePolynomial<F>, Evaluations<F>)| {
+            let polynomial = if with_polynomials {
+                selector.0.clone()
+            } else {
+                DensePolynomial::zero()
+            };
+            (polynomial, empty())
+        };
+        Self {
+            n: self.n,
+            circuit_id: self.circuit_id,
+            active_gates: self.active_gates,
+            arithmetic: arithmetic::ProverKey {
+                q_m: hollow(&self.arithmetic.q_m),
+                q_l: hollow(&self.arithmetic.q_l),
+                q_r: hollow(&self.arithmetic.q_r),
+                q_o: hollow(&self.arithmetic.q_o),
+                q_4: hollow(&self.arithmetic.q_4),
+                q_c: hollow(&self.arithmetic.q_c),
+                q_hl: hollow(&self.arithmetic.q_hl),
+                q_hr: hollow(&self.arithmetic.q_hr),
+                q_h4: hollow(&self.arithmetic.q_h4),
+                q_arith: hollow(&self.arithmetic.q_arith),
+            },
+            range_selector: hollow(&self.range_selector),
+            logic_selector: hollow(&self.logic_selector),
+            lookup: lookup::ProverKey {
+                q_lookup: hollow(&self.lookup.q_lookup),
+                table_1: self.lookup.table_1.clone(),
+                table_2: self.lookup.table_2.clone(),
+                table_3: self.lookup.table_3.clone(),
+                table_4: self.lookup.table_4.clone(),
+            },
+            fixed_group_add_selector: hollow(&self.fixed_group_add_selector),
+            variable_group_add_selector: hollow(
+                &self.variable_group_add_selector,
+            ),
+            permutation: permutation::ProverKey {
+                left_sigma: hollow(&self.permutation.left_sigma),
+                right_sigma: hollow(&self.permutation.right_sigma),
+                out_sigma: hollow(&self.permutation.out_sigma),
+                fourth_sigma: hollow(&self.permutation.fourth_sigma),
+                linear_evaluations: empty(),
+            },
+            custom_selectors: self
+                .custom_selectors
+                .iter()
+                .map(|(name, selector)| (name.clone(), hollow(selector)))
+                .collect(),
+            v_h_coset_8n: empty(),
+            circuit_digest: self.circuit_digest,
+            mapped: MappedEvaluations::default(),
+        }
+    }
+
+    /// Returns the key with the coset evaluations of the rows `rows`, one of
+    /// the [`ProverKey::quotient_chunks`], needed by `stage` of the quotient
+    /// polynomial. The evaluations of the returned key are indexed from the
+    /// start of `rows`.
+    ///
+    /// A key which holds its coset evaluations is returned as is. Otherwise,
+    /// the returned key only holds the coset evaluations of `stage`, read
+    /// from the mapped file of the key or recomputed, and no polynomials.
+    pub(crate) fn coset_view(
+        &self,
+        domain_8n: GeneralEvaluationDomain<F>,
+        stage: QuotientStage,
+        rows: Range<usize>,
+    ) -> Result<Cow<'_, Self>, Error> {
+        if self.has_coset_evaluations() {
+            // Such a key is not mapped, so `rows` is the whole domain.
+            return Ok(Cow::Borrowed(self));
+        }
+        let evaluations = |label: &str| {
+            self.coset_evaluations(label, domain_8n, rows.clone()).map(
+                |evals| {
+                    Evaluations::from_vec_and_domain(
+                        evals.into_owned(),
+                        domain_8n,
+                    )
+                },
+            )
+        };
+        let mut view = self.hollow(false);
+        for ((label, selector_stage, _), (_, _, view_selector)) in
+            self.selectors().zip(view.selectors_mut())
+        {
+            if selector_stage == stage {
+                view_selector.1 = evaluations(&label)?;
+            }
+        }
+        if stage == QuotientStage::Permutation {
+            view.permutation.linear_evaluations = evaluations("linear")?;
+        }
+        Ok(Cow::Owned(view))
     }
 
     /// Constructs a [`ProverKey`] from the widget ProverKey's that are
@@ -349,6 +1235,8 @@ where
     /// sigma polynomials and it's evaluations.
     pub(crate) fn from_polynomials_and_evals(
         n: usize,
+        circuit_id: CircuitId,
+        active_gates: ActiveGates,
         q_m: (DensePolynomial<F>, Evaluations<F>),
         q_l: (DensePolynomial<F>, Evaluations<F>),
         q_r: (DensePolynomial<F>, Evaluations<F>),
@@ -374,9 +1262,13 @@ where
         table_2: MultiSet<F>,
         table_3: MultiSet<F>,
         table_4: MultiSet<F>,
+        custom_selectors: Vec<(String, (DensePolynomial<F>, Evaluations<F>))>,
+        circuit_digest: CircuitDigest,
     ) -> Self {
         Self {
             n,
+            circuit_id,
+            active_gates,
             arithmetic: arithmetic::ProverKey {
                 q_m,
                 q_l,
@@ -395,10 +1287,10 @@ where
             variable_group_add_selector: q_variable_group_add,
             lookup: lookup::ProverKey {
                 q_lookup,
-                table_1,
-                table_2,
-                table_3,
-                table_4,
+                table_1: Arc::new(table_1),
+                table_2: Arc::new(table_2),
+                table_3: Arc::new(table_3),
+                table_4: Arc::new(table_4),
             },
             permutation: permutation::ProverKey {
                 left_sigma,
@@ -407,9 +1299,51 @@ where
                 fourth_sigma,
                 linear_evaluations,
             },
+            custom_selectors,
             v_h_coset_8n,
+            circuit_digest,
+            mapped: MappedEvaluations::default(),
         }
     }
+
+    /// Returns the Circuit size padded to the next power of two.
+    pub fn padded_circuit_size(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the [`ActiveGates`] of the key.
+    pub fn active_gates(&self) -> ActiveGates {
+        self.active_gates
+    }
+
+    /// Returns the names of the custom gates this key was preprocessed with.
+    pub fn custom_gate_names(&self) -> impl Iterator<Item = &str> {
+        self.custom_selectors.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the [`CircuitId`] of the circuit this key was preprocessed
+    /// from.
+    pub fn circuit_id(&self) -> CircuitId {
+        self.circuit_id
+    }
+
+    /// Returns the [`CircuitDigest`] of the circuit this key was
+    /// preprocessed from.
+    pub fn circuit_digest(&self) -> CircuitDigest {
+        self.circuit_digest
+    }
+
+    /// Matches the custom gates of this key with their `registered`
+    /// implementations.
+    pub(crate) fn custom_gates(
+        &self,
+        registered: &[CustomGate<F>],
+    ) -> Result<Vec<CustomGate<F>>, Error> {
+        resolve_custom_gates(
+            self.custom_selectors.iter().map(|(name, _)| name),
+            registered,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -482,9 +1416,13 @@ mod test {
         let table_2 = rand_multiset(n);
         let table_3 = rand_multiset(n);
         let table_4 = rand_multiset(n);
+        let custom_selectors = vec![("custom".to_string(), rand_poly_eval(n))];
+        let circuit_digest = CircuitDigest([7; 32]);
 
         let prover_key = ProverKey::from_polynomials_and_evals(
             n,
+            CircuitId([1; 32]),
+            ActiveGates::ALL,
             q_m,
             q_l,
             q_r,
@@ -510,6 +1448,8 @@ mod test {
             table_2,
             table_3,
             table_4,
+            custom_selectors,
+            circuit_digest,
         );
 
         let mut prover_key_bytes = vec![];
@@ -562,8 +1502,13 @@ mod test {
         let table_3 = PC::Commitment::default();
         let table_4 = PC::Commitment::default();
 
+        let custom_selector_commitments =
+            vec![("custom".to_string(), PC::Commitment::default())];
+
         let verifier_key = VerifierKey::<F, PC>::from_polynomial_commitments(
             n,
+            CircuitId([1; 32]),
+            ActiveGates::ALL,
             q_m,
             q_l,
             q_r,
@@ -587,6 +1532,8 @@ mod test {
             table_2,
             table_3,
             table_4,
+            custom_selector_commitments,
+            vec![0, 3],
         );
 
         let mut verifier_key_bytes = vec![];
@@ -599,6 +1546,17 @@ mod test {
                 .unwrap();
 
         assert!(verifier_key == obtained_vk);
+        assert_eq!(verifier_key.circuit_digest(), obtained_vk.circuit_digest());
+
+        // The digest depends on the gate types held by the key.
+        let mut compact = verifier_key.clone();
+        compact.range_selector_commitment = None;
+        assert_ne!(verifier_key.circuit_digest(), compact.circuit_digest());
+
+        // The digest depends on the public input layout.
+        let mut moved_pi = obtained_vk;
+        moved_pi.pi_pos = vec![0, 4];
+        assert_ne!(verifier_key.circuit_digest(), moved_pi.circuit_digest());
     }
 
     // Test for Bls12_381
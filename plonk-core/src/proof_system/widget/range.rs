@@ -6,9 +6,12 @@
 
 //! Range Gate
 
-use crate::proof_system::{
-    linearisation_poly::CustomEvaluations, CustomValues, GateConstraint,
-    WitnessValues,
+use crate::{
+    error::Error,
+    proof_system::{
+        linearisation_poly::CustomEvaluations, CustomValues, GateConstraint,
+        WitnessValues,
+    },
 };
 use ark_ff::PrimeField;
 use core::marker::PhantomData;
@@ -26,9 +29,11 @@ impl<F> CustomValues<F> for RangeVals<F>
 where
     F: PrimeField,
 {
-    fn from_evaluations(custom_vals: &CustomEvaluations<F>) -> Self {
-        let d_next_val = custom_vals.get("d_next_eval");
-        RangeVals { d_next_val }
+    fn from_evaluations(
+        custom_vals: &CustomEvaluations<F>,
+    ) -> Result<Self, Error> {
+        let d_next_val = custom_vals.get("d_next_eval")?;
+        Ok(RangeVals { d_next_val })
     }
 }
 /// Range Gate
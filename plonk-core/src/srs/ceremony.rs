@@ -0,0 +1,303 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Multi-party ceremony computing powers of tau.
+//!
+//! The transcript of a ceremony starts from the powers of `tau = 1`. Each
+//! participant multiplies `tau` by a secret `s` they sample and then forget,
+//! and appends a [`Contribution`] proving they know `s` and that the updated
+//! powers are the previous ones raised to `s`. The final `tau` is the product
+//! of the secrets of all the participants, so the parameters are secure as
+//! long as one of them is honest.
+
+use super::{params_from_powers, SetupEngine};
+use crate::error::Error;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_poly_commit::kzg10::UniversalParams;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write,
+};
+use blake2::{Blake2b, Digest};
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain tag of the challenges of the proofs of knowledge.
+const CONTRIBUTION_DOMAIN_TAG: &[u8] = b"plonk-kzg-ceremony-v1";
+
+/// Contribution of a participant to a [`Ceremony`].
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct Contribution<E>
+where
+    E: PairingEngine,
+{
+    /// `tau * G` after the contribution.
+    pub tau_g: E::G1Affine,
+
+    /// `s * G`, for the secret `s` of the participant.
+    pub s_g: E::G1Affine,
+
+    /// `s * H`.
+    pub s_h: E::G2Affine,
+
+    /// Commitment of the Schnorr proof of knowledge of `s`.
+    commitment: E::G1Affine,
+
+    /// Response of the Schnorr proof of knowledge of `s`.
+    response: E::Fr,
+}
+
+impl<E> Contribution<E>
+where
+    E: PairingEngine,
+{
+    /// Returns the challenge of the proof of knowledge of the secret of the
+    /// contribution applied to the powers with `previous_tau_g`, so that it
+    /// cannot be replayed in another position of a transcript.
+    fn challenge(
+        previous_tau_g: &E::G1Affine,
+        s_g: &E::G1Affine,
+        commitment: &E::G1Affine,
+    ) -> E::Fr {
+        let mut bytes = CONTRIBUTION_DOMAIN_TAG.to_vec();
+        // Serializing to a `Vec` cannot fail.
+        previous_tau_g.serialize(&mut bytes).unwrap();
+        s_g.serialize(&mut bytes).unwrap();
+        commitment.serialize(&mut bytes).unwrap();
+        E::Fr::from_le_bytes_mod_order(&Blake2b::digest(&bytes))
+    }
+
+    /// Checks the contribution against the previous `tau * G` of the
+    /// transcript.
+    fn verify(&self, previous_tau_g: &E::G1Affine) -> Result<(), Error> {
+        let g = E::G1Affine::prime_subgroup_generator();
+        let h = E::G2Affine::prime_subgroup_generator();
+        let challenge =
+            Self::challenge(previous_tau_g, &self.s_g, &self.commitment);
+        let knows_s = g.mul(self.response)
+            == self.commitment.into_projective() + self.s_g.mul(challenge);
+        let same_s = E::pairing(self.s_g, h) == E::pairing(g, self.s_h);
+        let updates_tau =
+            E::pairing(self.tau_g, h) == E::pairing(*previous_tau_g, self.s_h);
+        if self.s_g.is_zero() || !knows_s || !same_s || !updates_tau {
+            return Err(Error::InconsistentSetup);
+        }
+        Ok(())
+    }
+}
+
+/// Transcript of a multi-party ceremony computing the powers of tau of
+/// [`KZG10`] universal parameters.
+///
+/// [`KZG10`]: crate::commitment::KZG10
+#[derive(CanonicalDeserialize, CanonicalSerialize, derivative::Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct Ceremony<E>
+where
+    E: PairingEngine,
+{
+    /// Powers `tau^i * G` for `i` up to the maximum degree.
+    powers_of_g: Vec<E::G1Affine>,
+
+    /// `H` and `tau * H`.
+    powers_of_h: Vec<E::G2Affine>,
+
+    /// Contributions of the participants, in order.
+    contributions: Vec<Contribution<E>>,
+}
+
+impl<E> Ceremony<E>
+where
+    E: SetupEngine,
+{
+    /// Starts a ceremony computing the powers of tau of universal parameters
+    /// supporting polynomials of degree up to `max_degree`.
+    pub fn new(max_degree: usize) -> Result<Self, Error> {
+        if max_degree == 0 {
+            return Err(Error::DegreeIsZero);
+        }
+        Ok(Self {
+            powers_of_g: vec![
+                E::G1Affine::prime_subgroup_generator();
+                max_degree + 1
+            ],
+            powers_of_h: vec![E::G2Affine::prime_subgroup_generator(); 2],
+            contributions: Vec::new(),
+        })
+    }
+
+    /// Returns the maximum degree supported by the parameters of the
+    /// ceremony.
+    pub fn max_degree(&self) -> usize {
+        self.powers_of_g.len().saturating_sub(1)
+    }
+
+    /// Returns the contributions to the ceremony, in order.
+    pub fn contributions(&self) -> &[Contribution<E>] {
+        &self.contributions
+    }
+
+    /// Adds a contribution with a secret sampled from `rng`, which is
+    /// forgotten once the contribution is made.
+    pub fn contribute<R>(&mut self, rng: &mut R) -> &Contribution<E>
+    where
+        R: CryptoRng + RngCore,
+    {
+        let g = E::G1Affine::prime_subgroup_generator();
+        let h = E::G2Affine::prime_subgroup_generator();
+        let s = loop {
+            let s = E::Fr::rand(rng);
+            if !s.is_zero() {
+                break s;
+            }
+        };
+
+        let previous_tau_g = self.powers_of_g[1];
+        let mut power = E::Fr::one();
+        let powers_of_g = self
+            .powers_of_g
+            .iter()
+            .map(|point| {
+                let point = point.mul(power);
+                power *= s;
+                point
+            })
+            .collect::<Vec<_>>();
+        self.powers_of_g =
+            E::G1Projective::batch_normalization_into_affine(&powers_of_g);
+        self.powers_of_h[1] = self.powers_of_h[1].mul(s).into_affine();
+
+        let k = E::Fr::rand(rng);
+        let s_g = g.mul(s).into_affine();
+        let commitment = g.mul(k).into_affine();
+        let challenge =
+            Contribution::<E>::challenge(&previous_tau_g, &s_g, &commitment);
+        self.contributions.push(Contribution {
+            tau_g: self.powers_of_g[1],
+            s_g,
+            s_h: h.mul(s).into_affine(),
+            commitment,
+            response: k + challenge * s,
+        });
+        self.contributions.last().unwrap()
+    }
+
+    /// Verifies the chain of contributions of the ceremony and the
+    /// consistency of its powers with pairings, weighted by scalars sampled
+    /// from `rng`, and returns the universal parameters of the ceremony.
+    ///
+    /// Fails with [`Error::InconsistentSetup`] if a contribution is invalid or
+    /// the powers are not those of the contributions, and with
+    /// [`Error::MalformedSetup`] if the ceremony has no contribution.
+    pub fn verify<R>(&self, rng: &mut R) -> Result<UniversalParams<E>, Error>
+    where
+        R: CryptoRng + RngCore,
+    {
+        if self.contributions.is_empty() {
+            return Err(Error::MalformedSetup {
+                reason: "the ceremony has no contribution".to_string(),
+            });
+        }
+        if self.powers_of_g.len() < 2
+            || self.powers_of_h.len() != 2
+            || self.powers_of_g[0] != E::G1Affine::prime_subgroup_generator()
+            || self.powers_of_h[0] != E::G2Affine::prime_subgroup_generator()
+        {
+            return Err(Error::InconsistentSetup);
+        }
+
+        let mut tau_g = E::G1Affine::prime_subgroup_generator();
+        for contribution in &self.contributions {
+            contribution.verify(&tau_g)?;
+            tau_g = contribution.tau_g;
+        }
+        if tau_g != self.powers_of_g[1] {
+            return Err(Error::InconsistentSetup);
+        }
+        params_from_powers::<E, R>(
+            self.powers_of_g.clone(),
+            &self.powers_of_h,
+            rng,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srs::test::prove_with_params;
+    use ark_bls12_381::Bls12_381;
+    #[cfg(feature = "evm")]
+    use ark_bn254::Bn254;
+    use ark_ec::TEModelParameters;
+    use rand_core::OsRng;
+
+    fn test_ceremony<E, P>()
+    where
+        E: SetupEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        crate::commitment::KZG10<E>:
+            crate::commitment::HomomorphicCommitment<E::Fr>,
+    {
+        let mut ceremony = Ceremony::<E>::new(64).unwrap();
+        assert!(matches!(
+            ceremony.verify(&mut OsRng),
+            Err(Error::MalformedSetup { .. })
+        ));
+        for _ in 0..3 {
+            ceremony.contribute(&mut OsRng);
+        }
+
+        // The transcript is passed between participants as a file.
+        let mut bytes = Vec::new();
+        ceremony.serialize(&mut bytes).unwrap();
+        let ceremony = Ceremony::<E>::deserialize(&bytes[..]).unwrap();
+        assert_eq!(ceremony.contributions().len(), 3);
+        let params = ceremony.verify(&mut OsRng).unwrap();
+        prove_with_params::<E, P>(&params);
+
+        // Contribution replayed at another position.
+        let mut replayed = ceremony.clone();
+        replayed
+            .contributions
+            .push(ceremony.contributions[2].clone());
+        assert!(matches!(
+            replayed.verify(&mut OsRng),
+            Err(Error::InconsistentSetup)
+        ));
+
+        // Contribution without the knowledge of its secret.
+        let mut forged = ceremony.clone();
+        forged.contributions[1].response += E::Fr::one();
+        assert!(matches!(
+            forged.verify(&mut OsRng),
+            Err(Error::InconsistentSetup)
+        ));
+
+        // Powers of another tau.
+        let mut other = Ceremony::<E>::new(64).unwrap();
+        other.contribute(&mut OsRng);
+        let mut tampered = ceremony;
+        tampered.powers_of_g[5] = other.powers_of_g[5];
+        assert!(matches!(
+            tampered.verify(&mut OsRng),
+            Err(Error::InconsistentSetup)
+        ));
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_ceremony_on_Bn254() {
+        test_ceremony::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_ceremony_on_Bls12_381() {
+        test_ceremony::<Bls12_381, ark_ed_on_bls12_381::EdwardsParameters>()
+    }
+}
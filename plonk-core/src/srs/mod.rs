@@ -0,0 +1,320 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Universal parameters of the [`KZG10`] commitment scheme from external
+//! setups.
+//!
+//! `PC::setup` samples the secret of the parameters itself, so they are only
+//! fit for tests. The parameters returned by this module are built from the
+//! powers of tau of a trusted setup ceremony instead, either read from a
+//! `.ptau` file of the Perpetual Powers of Tau or snarkjs with [`read_ptau`]
+//! or given as raw lists of powers to [`params_from_powers`]. They can also
+//! be computed by a local multi-party [`Ceremony`], whose transcript is passed
+//! from one participant to the next. The powers are checked to be consistent
+//! with pairings before being used.
+//!
+//! Parameters can be loaded for BLS12-381, and for BN254 with the `evm`
+//! feature.
+//!
+//! The parameters do not support hiding commitments nor degree bounds, which
+//! the proof system does not use. Hiding commitments would need the powers of
+//! a second generator `gamma * G`, which only the ceremony could compute, so
+//! the parameters hold points derived by hashing in their place.
+//!
+//! [`KZG10`]: crate::commitment::KZG10
+
+mod ceremony;
+mod ptau;
+
+pub use ceremony::{Ceremony, Contribution};
+pub use ptau::read_ptau;
+
+use crate::error::Error;
+use ark_ec::{msm::VariableBaseMSM, PairingEngine, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_poly_commit::kzg10::UniversalParams;
+use blake2::{Blake2b, Digest};
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain tag of the points standing in for the powers of `gamma * G`.
+const GAMMA_G_DOMAIN_TAG: &[u8] = b"plonk-kzg-gamma-g-v1";
+
+/// Pairing engines whose universal parameters can be loaded from an external
+/// setup.
+pub trait SetupEngine: PairingEngine {
+    /// Returns the point of G1 with affine coordinates `(x, y)`, or `None` if
+    /// it is not in the prime order subgroup.
+    fn g1_from_coordinates(x: Self::Fq, y: Self::Fq) -> Option<Self::G1Affine>;
+
+    /// Returns the point of G2 with affine coordinates `(x, y)`, each given by
+    /// its two coefficients over the base field, or `None` if it is not in the
+    /// prime order subgroup.
+    fn g2_from_coordinates(
+        x: [Self::Fq; 2],
+        y: [Self::Fq; 2],
+    ) -> Option<Self::G2Affine>;
+
+    /// Returns a point of G1 derived from `seed`, whose discrete logarithm is
+    /// not known to anyone.
+    fn hash_to_g1(seed: &[u8]) -> Self::G1Affine;
+}
+
+macro_rules! impl_setup_engine {
+    ($engine:ty, $curve:ident) => {
+        impl SetupEngine for $engine {
+            fn g1_from_coordinates(
+                x: Self::Fq,
+                y: Self::Fq,
+            ) -> Option<Self::G1Affine> {
+                let point = $curve::G1Affine::new(x, y, false);
+                (point.is_on_curve()
+                    && point.is_in_correct_subgroup_assuming_on_curve())
+                .then_some(point)
+            }
+
+            fn g2_from_coordinates(
+                x: [Self::Fq; 2],
+                y: [Self::Fq; 2],
+            ) -> Option<Self::G2Affine> {
+                let point = $curve::G2Affine::new(
+                    $curve::Fq2::new(x[0], x[1]),
+                    $curve::Fq2::new(y[0], y[1]),
+                    false,
+                );
+                (point.is_on_curve()
+                    && point.is_in_correct_subgroup_assuming_on_curve())
+                .then_some(point)
+            }
+
+            fn hash_to_g1(seed: &[u8]) -> Self::G1Affine {
+                // Try-and-increment over the x coordinate.
+                (0u64..)
+                    .find_map(|counter| {
+                        let mut hasher = Blake2b::new();
+                        hasher.update(seed);
+                        hasher.update(counter.to_le_bytes());
+                        let x = $curve::Fq::from_le_bytes_mod_order(
+                            &hasher.finalize(),
+                        );
+                        $curve::G1Affine::get_point_from_x(x, false)
+                            .map(|point| {
+                                point.scale_by_cofactor().into_affine()
+                            })
+                            .filter(|point| !point.is_zero())
+                    })
+                    .expect("half of the x coordinates are on the curve")
+            }
+        }
+    };
+}
+
+#[cfg(feature = "evm")]
+impl_setup_engine!(ark_bn254::Bn254, ark_bn254);
+impl_setup_engine!(ark_bls12_381::Bls12_381, ark_bls12_381);
+
+/// Returns the universal parameters of the powers of tau `powers_of_g`, of
+/// the form `tau^i * G`, and `powers_of_h`, of the form `tau^i * H`.
+///
+/// Both lists need at least two powers, and every power of `powers_of_g` is
+/// kept, so the parameters support polynomials of degree up to
+/// `powers_of_g.len() - 1`. Fails with [`Error::InconsistentSetup`] if the
+/// powers are not powers of the same `tau`, which is checked with pairings
+/// weighted by scalars sampled from `rng`.
+pub fn params_from_powers<E, R>(
+    powers_of_g: Vec<E::G1Affine>,
+    powers_of_h: &[E::G2Affine],
+    rng: &mut R,
+) -> Result<UniversalParams<E>, Error>
+where
+    E: SetupEngine,
+    R: CryptoRng + RngCore,
+{
+    if powers_of_g.len() < 2 || powers_of_h.len() < 2 {
+        return Err(Error::MalformedSetup {
+            reason: "at least two powers of G1 and G2 are needed".to_string(),
+        });
+    }
+    if powers_of_g[0].is_zero() || powers_of_h[0].is_zero() {
+        return Err(Error::InconsistentSetup);
+    }
+
+    // With random weights `r_i`, the powers of G1 are consistent when
+    // e(sum r_i tau^(i + 1) G, H) = e(sum r_i tau^i G, tau H), and likewise
+    // for the powers of G2.
+    let weights = |len: usize, rng: &mut R| {
+        (0..len)
+            .map(|_| E::Fr::rand(rng).into_repr())
+            .collect::<Vec<_>>()
+    };
+    let g_weights = weights(powers_of_g.len() - 1, rng);
+    let h_weights = weights(powers_of_h.len() - 1, rng);
+    let g_consistent = E::pairing(
+        VariableBaseMSM::multi_scalar_mul(&powers_of_g[1..], &g_weights),
+        powers_of_h[0],
+    ) == E::pairing(
+        VariableBaseMSM::multi_scalar_mul(
+            &powers_of_g[..powers_of_g.len() - 1],
+            &g_weights,
+        ),
+        powers_of_h[1],
+    );
+    let h_consistent = E::pairing(
+        powers_of_g[0],
+        VariableBaseMSM::multi_scalar_mul(&powers_of_h[1..], &h_weights),
+    ) == E::pairing(
+        powers_of_g[1],
+        VariableBaseMSM::multi_scalar_mul(
+            &powers_of_h[..powers_of_h.len() - 1],
+            &h_weights,
+        ),
+    );
+    if !g_consistent || !h_consistent {
+        return Err(Error::InconsistentSetup);
+    }
+
+    let powers_of_gamma_g = (0..2)
+        .map(|i: u8| {
+            (
+                i as usize,
+                E::hash_to_g1(&[GAMMA_G_DOMAIN_TAG, &[i]].concat()),
+            )
+        })
+        .collect();
+    let (h, beta_h) = (powers_of_h[0], powers_of_h[1]);
+    Ok(UniversalParams {
+        powers_of_g,
+        powers_of_gamma_g,
+        h,
+        beta_h,
+        neg_powers_of_h: Default::default(),
+        prepared_h: h.into(),
+        prepared_beta_h: beta_h.into(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        commitment::{HomomorphicCommitment, KZG10},
+        constraint_system::helper::dummy_gadget,
+        proof_system::{Prover, Verifier},
+    };
+    use ark_bls12_381::Bls12_381;
+    #[cfg(feature = "evm")]
+    use ark_bn254::Bn254;
+    use ark_ec::TEModelParameters;
+    use ark_ff::One;
+    use ark_poly_commit::PolynomialCommitment;
+    use rand_core::OsRng;
+
+    /// Returns `degree + 1` powers of tau in G1 and `2` in G2, for a random
+    /// tau.
+    pub(super) fn powers_of_tau<E>(
+        degree: usize,
+    ) -> (Vec<E::G1Affine>, Vec<E::G2Affine>)
+    where
+        E: PairingEngine,
+    {
+        let tau = E::Fr::rand(&mut OsRng);
+        let g = E::G1Projective::prime_subgroup_generator();
+        let h = E::G2Projective::prime_subgroup_generator();
+        let mut power = E::Fr::one();
+        let powers_of_g = (0..=degree)
+            .map(|_| {
+                let point = g.mul(power.into_repr()).into_affine();
+                power *= tau;
+                point
+            })
+            .collect();
+        (
+            powers_of_g,
+            vec![h.into_affine(), h.mul(tau.into_repr()).into_affine()],
+        )
+    }
+
+    /// Tests that the parameters of a setup prove and verify circuits.
+    pub(super) fn prove_with_params<E, P>(params: &UniversalParams<E>)
+    where
+        E: PairingEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        KZG10<E>: HomomorphicCommitment<E::Fr>,
+    {
+        let mut prover = Prover::<E::Fr, P, KZG10<E>>::new(b"srs");
+        dummy_gadget(10, prover.mut_cs());
+        let (ck, _) =
+            KZG10::<E>::trim(params, prover.commit_key_degree(), 0, None)
+                .unwrap();
+        let public_inputs = prover.cs.get_pi().clone();
+        let proof = prover.prove(&ck, &mut OsRng).unwrap();
+
+        let mut verifier = Verifier::<E::Fr, P, KZG10<E>>::new(b"srs");
+        dummy_gadget(10, verifier.mut_cs());
+        let (ck, vk) =
+            KZG10::<E>::trim(params, verifier.commit_key_degree(), 0, None)
+                .unwrap();
+        verifier.preprocess(&ck).unwrap();
+        verifier.verify(&proof, &vk, &public_inputs).unwrap();
+    }
+
+    fn test_params_from_powers<E, P>()
+    where
+        E: SetupEngine,
+        P: TEModelParameters<BaseField = E::Fr>,
+        KZG10<E>: HomomorphicCommitment<E::Fr>,
+    {
+        let (powers_of_g, powers_of_h) = powers_of_tau::<E>(64);
+        let params = params_from_powers::<E, _>(
+            powers_of_g.clone(),
+            &powers_of_h,
+            &mut OsRng,
+        )
+        .unwrap();
+        prove_with_params::<E, P>(&params);
+
+        // Powers of different taus.
+        let (mut tampered, _) = powers_of_tau::<E>(64);
+        tampered.truncate(10);
+        tampered.extend_from_slice(&powers_of_g[10..]);
+        assert!(matches!(
+            params_from_powers::<E, _>(tampered, &powers_of_h, &mut OsRng),
+            Err(Error::InconsistentSetup)
+        ));
+        let (_, other_powers_of_h) = powers_of_tau::<E>(1);
+        assert!(matches!(
+            params_from_powers::<E, _>(
+                powers_of_g.clone(),
+                &other_powers_of_h,
+                &mut OsRng
+            ),
+            Err(Error::InconsistentSetup)
+        ));
+        assert!(matches!(
+            params_from_powers::<E, _>(
+                powers_of_g[..1].to_vec(),
+                &powers_of_h,
+                &mut OsRng
+            ),
+            Err(Error::MalformedSetup { .. })
+        ));
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_params_from_powers_on_Bn254() {
+        test_params_from_powers::<Bn254, ark_ed_on_bn254::EdwardsParameters>()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_params_from_powers_on_Bls12_381() {
+        test_params_from_powers::<
+            Bls12_381,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >()
+    }
+}
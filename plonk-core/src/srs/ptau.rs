@@ -0,0 +1,446 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-Garage. All rights reserved.
+
+//! Reader of the `.ptau` files of the Perpetual Powers of Tau and snarkjs.
+//!
+//! A `.ptau` file starts with the magic `ptau`, a version and a number of
+//! sections, each made of a type, a length in bytes and its contents. The
+//! header section holds the byte length `n8` and the modulus of the base
+//! field, and the power of two of the number of powers. Points are stored as
+//! their affine coordinates, each in little-endian Montgomery form over `n8`
+//! bytes, with the two coefficients of the coordinates of G2 one after the
+//! other.
+
+use super::{params_from_powers, SetupEngine};
+use crate::error::Error;
+use ark_ff::{BigInteger, Field, FpParameters, PrimeField};
+use ark_poly_commit::kzg10::UniversalParams;
+use rand_core::{CryptoRng, RngCore};
+use std::io::{self, Read};
+
+/// Magic the files start with.
+const MAGIC: &[u8; 4] = b"ptau";
+
+/// Version of the format.
+const VERSION: u32 = 1;
+
+/// Type of the header section.
+const HEADER_SECTION: u32 = 1;
+
+/// Type of the section of the powers of tau in G1.
+const TAU_G1_SECTION: u32 = 2;
+
+/// Type of the section of the powers of tau in G2.
+const TAU_G2_SECTION: u32 = 3;
+
+fn malformed(reason: &str) -> Error {
+    Error::MalformedSetup {
+        reason: reason.to_string(),
+    }
+}
+
+fn io_error(error: io::Error) -> Error {
+    Error::MalformedSetup {
+        reason: error.to_string(),
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes).map_err(io_error)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes).map_err(io_error)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Skips the next `len` bytes of `reader`.
+fn skip<R: Read>(reader: &mut R, len: u64) -> Result<(), Error> {
+    if io::copy(&mut reader.take(len), &mut io::sink()).map_err(io_error)?
+        != len
+    {
+        return Err(malformed("truncated section"));
+    }
+    Ok(())
+}
+
+/// Reader of the elements of the base field of `E`.
+struct FieldReader<E>
+where
+    E: SetupEngine,
+{
+    /// Inverse of the Montgomery constant `R`.
+    r_inv: E::Fq,
+}
+
+impl<E> FieldReader<E>
+where
+    E: SetupEngine,
+{
+    /// Byte length of the elements.
+    fn n8() -> usize {
+        <E::Fq as PrimeField>::BigInt::default().as_ref().len() * 8
+    }
+
+    /// Checks the header section of a file, returning the power of two of its
+    /// number of powers.
+    fn read_header<R: Read>(reader: &mut R, len: u64) -> Result<u32, Error> {
+        let n8 = read_u32(reader)? as usize;
+        if n8 != Self::n8() {
+            return Err(malformed("the file is for a different curve"));
+        }
+        let mut modulus = vec![0; n8];
+        reader.read_exact(&mut modulus).map_err(io_error)?;
+        if modulus != <E::Fq as PrimeField>::Params::MODULUS.to_bytes_le()[..n8]
+        {
+            return Err(malformed("the file is for a different curve"));
+        }
+        let power = read_u32(reader)?;
+        if power >= usize::BITS - 1 {
+            return Err(malformed("too many powers"));
+        }
+        // The remaining fields, such as the power of the ceremony, are not
+        // needed.
+        let read = 4 + n8 as u64 + 4;
+        skip(
+            reader,
+            len.checked_sub(read)
+                .ok_or_else(|| malformed("short header"))?,
+        )?;
+        Ok(power)
+    }
+
+    fn new() -> Self {
+        let r = E::Fq::from_repr(<E::Fq as PrimeField>::Params::R)
+            .expect("R is reduced");
+        Self {
+            r_inv: r.inverse().expect("R is not zero"),
+        }
+    }
+
+    fn read<R: Read>(&self, reader: &mut R) -> Result<E::Fq, Error> {
+        let mut bytes = vec![0; Self::n8()];
+        reader.read_exact(&mut bytes).map_err(io_error)?;
+        let mut repr = <E::Fq as PrimeField>::BigInt::default();
+        for (limb, chunk) in repr.as_mut().iter_mut().zip(bytes.chunks(8)) {
+            let mut limb_bytes = [0; 8];
+            limb_bytes.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(limb_bytes);
+        }
+        // The stored value is `x * R`.
+        E::Fq::from_repr(repr)
+            .map(|value| value * self.r_inv)
+            .ok_or_else(|| malformed("coordinate out of range"))
+    }
+
+    fn read_g1<R: Read>(&self, reader: &mut R) -> Result<E::G1Affine, Error> {
+        let x = self.read(reader)?;
+        let y = self.read(reader)?;
+        E::g1_from_coordinates(x, y).ok_or_else(|| malformed("invalid point"))
+    }
+
+    fn read_g2<R: Read>(&self, reader: &mut R) -> Result<E::G2Affine, Error> {
+        let x = [self.read(reader)?, self.read(reader)?];
+        let y = [self.read(reader)?, self.read(reader)?];
+        E::g2_from_coordinates(x, y).ok_or_else(|| malformed("invalid point"))
+    }
+}
+
+/// Reads the universal parameters of degree `max_degree` from the `.ptau`
+/// file in `reader`, for BN254 or BLS12-381.
+///
+/// Only the first `max_degree + 1` powers of tau in G1 and the first two in
+/// G2 are read. Fails with [`Error::SRSTooSmall`] if the file holds fewer
+/// powers, with [`Error::MalformedSetup`] if it cannot be parsed or is for
+/// another curve, and with [`Error::InconsistentSetup`] if the powers are not
+/// consistent, see [`params_from_powers`].
+pub fn read_ptau<E, R, G>(
+    mut reader: R,
+    max_degree: usize,
+    rng: &mut G,
+) -> Result<UniversalParams<E>, Error>
+where
+    E: SetupEngine,
+    R: Read,
+    G: CryptoRng + RngCore,
+{
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic).map_err(io_error)?;
+    if &magic != MAGIC {
+        return Err(malformed("not a ptau file"));
+    }
+    if read_u32(&mut reader)? != VERSION {
+        return Err(malformed("unsupported version"));
+    }
+    let sections = read_u32(&mut reader)?;
+
+    let fields = FieldReader::<E>::new();
+    let point_len = 2 * FieldReader::<E>::n8() as u64;
+    let mut power = None;
+    let mut powers_of_g = None;
+    let mut powers_of_h = None;
+    for _ in 0..sections {
+        let section = read_u32(&mut reader)?;
+        let len = read_u64(&mut reader)?;
+        match section {
+            HEADER_SECTION => {
+                power = Some(FieldReader::<E>::read_header(&mut reader, len)?);
+            }
+            TAU_G1_SECTION | TAU_G2_SECTION => {
+                let power = power
+                    .ok_or_else(|| malformed("powers before the header"))?;
+                // The file holds `2^(power + 1) - 1` powers in G1 and
+                // `2^power` in G2, of which the first two are needed.
+                let (available, needed, stored_len) =
+                    if section == TAU_G1_SECTION {
+                        ((2usize << power) - 1, max_degree + 1, point_len)
+                    } else {
+                        (1usize << power, 2, 2 * point_len)
+                    };
+                if needed > available {
+                    return Err(Error::SRSTooSmall {
+                        required: needed - 1,
+                        supported: available - 1,
+                    });
+                }
+                let read = needed as u64 * stored_len;
+                if len < read {
+                    return Err(malformed("truncated section"));
+                }
+                if section == TAU_G1_SECTION {
+                    powers_of_g = Some(
+                        (0..needed)
+                            .map(|_| fields.read_g1(&mut reader))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    );
+                } else {
+                    powers_of_h = Some(
+                        (0..needed)
+                            .map(|_| fields.read_g2(&mut reader))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    );
+                }
+                skip(&mut reader, len - read)?;
+            }
+            _ => skip(&mut reader, len)?,
+        }
+        if powers_of_g.is_some() && powers_of_h.is_some() {
+            break;
+        }
+    }
+
+    match (powers_of_g, powers_of_h) {
+        (Some(powers_of_g), Some(powers_of_h)) => {
+            params_from_powers::<E, G>(powers_of_g, &powers_of_h, rng)
+        }
+        _ => Err(malformed("missing powers of tau")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srs::test::{powers_of_tau, prove_with_params};
+    use ark_bls12_381::Bls12_381;
+    #[cfg(feature = "evm")]
+    use ark_bn254::Bn254;
+    use ark_ec::{
+        short_weierstrass_jacobian::GroupAffine, SWModelParameters,
+        TEModelParameters,
+    };
+    #[cfg(feature = "evm")]
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    #[cfg(feature = "evm")]
+    use ark_ff::One;
+    use ark_ff::{Fp2, Fp2Parameters};
+    use rand_core::OsRng;
+
+    /// Writes `value` in little-endian Montgomery form.
+    fn write_fq<F: PrimeField>(bytes: &mut Vec<u8>, value: F) {
+        let r = F::from_repr(F::Params::R).unwrap();
+        bytes.extend((value * r).into_repr().to_bytes_le());
+    }
+
+    fn write_section(bytes: &mut Vec<u8>, section: u32, contents: &[u8]) {
+        bytes.extend(section.to_le_bytes());
+        bytes.extend((contents.len() as u64).to_le_bytes());
+        bytes.extend(contents);
+    }
+
+    /// Returns a `.ptau` file of `2^power` powers of a random tau, whose
+    /// sections other than the powers of tau are empty.
+    fn ptau_file<E, P1, P2, Q>(power: u32) -> Vec<u8>
+    where
+        E: SetupEngine<G1Affine = GroupAffine<P1>, G2Affine = GroupAffine<P2>>,
+        P1: SWModelParameters<BaseField = E::Fq>,
+        P2: SWModelParameters<BaseField = Fp2<Q>>,
+        Q: Fp2Parameters<Fp = E::Fq>,
+    {
+        let n8 = FieldReader::<E>::n8();
+        let (powers_of_g, powers_of_h) =
+            powers_of_tau::<E>((2usize << power) - 2);
+
+        let mut header = Vec::new();
+        header.extend((n8 as u32).to_le_bytes());
+        header.extend(<E::Fq as PrimeField>::Params::MODULUS.to_bytes_le());
+        header.extend(power.to_le_bytes());
+        header.extend(power.to_le_bytes());
+
+        let mut tau_g1 = Vec::new();
+        for point in powers_of_g {
+            write_fq(&mut tau_g1, point.x);
+            write_fq(&mut tau_g1, point.y);
+        }
+
+        // Only the first two powers in G2 are read, the others are left as
+        // zeroes.
+        let mut tau_g2 = Vec::new();
+        for point in powers_of_h {
+            for coordinate in [point.x, point.y] {
+                write_fq(&mut tau_g2, coordinate.c0);
+                write_fq(&mut tau_g2, coordinate.c1);
+            }
+        }
+        tau_g2.resize((1 << power) * 4 * n8, 0);
+
+        let mut bytes = Vec::new();
+        bytes.extend(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        bytes.extend(5u32.to_le_bytes());
+        write_section(&mut bytes, HEADER_SECTION, &header);
+        write_section(&mut bytes, TAU_G1_SECTION, &tau_g1);
+        write_section(&mut bytes, TAU_G2_SECTION, &tau_g2);
+        write_section(&mut bytes, 4, &[]);
+        write_section(&mut bytes, 5, &[]);
+        bytes
+    }
+
+    fn test_read_ptau<E, P1, P2, Q, P>()
+    where
+        E: SetupEngine<G1Affine = GroupAffine<P1>, G2Affine = GroupAffine<P2>>,
+        P1: SWModelParameters<BaseField = E::Fq>,
+        P2: SWModelParameters<BaseField = Fp2<Q>>,
+        Q: Fp2Parameters<Fp = E::Fq>,
+        P: TEModelParameters<BaseField = E::Fr>,
+        crate::commitment::KZG10<E>:
+            crate::commitment::HomomorphicCommitment<E::Fr>,
+    {
+        let file = ptau_file::<E, P1, P2, Q>(6);
+        let params = read_ptau::<E, _, _>(&file[..], 64, &mut OsRng).unwrap();
+        assert_eq!(params.powers_of_g.len(), 65);
+        prove_with_params::<E, P>(&params);
+
+        // Every power of the file.
+        let params = read_ptau::<E, _, _>(&file[..], 126, &mut OsRng).unwrap();
+        assert_eq!(params.powers_of_g.len(), 127);
+
+        assert!(matches!(
+            read_ptau::<E, _, _>(&file[..], 127, &mut OsRng),
+            Err(Error::SRSTooSmall {
+                required: 127,
+                supported: 126,
+            })
+        ));
+
+        // A single power in G2.
+        assert!(matches!(
+            read_ptau::<E, _, _>(
+                &ptau_file::<E, P1, P2, Q>(0)[..],
+                0,
+                &mut OsRng
+            ),
+            Err(Error::SRSTooSmall {
+                required: 1,
+                supported: 0,
+            })
+        ));
+
+        let mut wrong_magic = file.clone();
+        wrong_magic[0] = b'q';
+        assert!(matches!(
+            read_ptau::<E, _, _>(&wrong_magic[..], 64, &mut OsRng),
+            Err(Error::MalformedSetup { .. })
+        ));
+
+        let truncated = &file[..file.len() / 2];
+        assert!(matches!(
+            read_ptau::<E, _, _>(truncated, 64, &mut OsRng),
+            Err(Error::MalformedSetup { .. })
+        ));
+
+        // The first two powers in G1 swapped.
+        let n8 = FieldReader::<E>::n8();
+        let tau_g1 = 12 + 12 + (4 + n8 + 4 + 4) + 12;
+        let mut tampered = file;
+        let (first, second) = tampered[tau_g1..].split_at_mut(2 * n8);
+        first.swap_with_slice(&mut second[..2 * n8]);
+        assert!(matches!(
+            read_ptau::<E, _, _>(&tampered[..], 64, &mut OsRng),
+            Err(Error::InconsistentSetup)
+        ));
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_read_ptau_on_Bn254() {
+        test_read_ptau::<
+            Bn254,
+            ark_bn254::g1::Parameters,
+            ark_bn254::g2::Parameters,
+            ark_bn254::Fq2Parameters,
+            ark_ed_on_bn254::EdwardsParameters,
+        >()
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn test_read_ptau_on_Bls12_381() {
+        test_read_ptau::<
+            Bls12_381,
+            ark_bls12_381::g1::Parameters,
+            ark_bls12_381::g2::Parameters,
+            ark_bls12_381::Fq2Parameters,
+            ark_ed_on_bls12_381::EdwardsParameters,
+        >()
+    }
+
+    /// `.ptau` file over BN254 with `2^2` powers of `tau = 5`, with every
+    /// section of the files of snarkjs and no contributions. Its points were
+    /// computed and encoded without arkworks.
+    #[cfg(feature = "evm")]
+    const BN254_PTAU: &[u8] = include_bytes!("test_vectors/bn254_power_2.ptau");
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn test_read_ptau_known_answer() {
+        let params =
+            read_ptau::<Bn254, _, _>(BN254_PTAU, 6, &mut OsRng).unwrap();
+
+        let tau = ark_bn254::Fr::from(5u64);
+        let g = ark_bn254::G1Affine::prime_subgroup_generator();
+        let h = ark_bn254::G2Affine::prime_subgroup_generator();
+        let mut power = ark_bn254::Fr::one();
+        assert_eq!(params.powers_of_g.len(), 7);
+        for point in &params.powers_of_g {
+            assert_eq!(*point, g.mul(power.into_repr()).into_affine());
+            power *= tau;
+        }
+        assert_eq!(params.h, h);
+        assert_eq!(params.beta_h, h.mul(tau.into_repr()).into_affine());
+
+        assert!(matches!(
+            read_ptau::<Bn254, _, _>(BN254_PTAU, 7, &mut OsRng),
+            Err(Error::SRSTooSmall {
+                required: 7,
+                supported: 6,
+            })
+        ));
+    }
+}
@@ -4,47 +4,147 @@
 //
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
-//! This is an extension over the [Merlin Transcript](Transcript) which adds a
-//! few extra functionalities.
+//! Fiat-Shamir transcripts used by the [`Prover`] and the [`Verifier`].
+//!
+//! The proof system is generic over the [`TranscriptProtocol`] trait, which is
+//! implemented here for the [Merlin Transcript](Transcript). Other backends,
+//! such as algebraic sponges which are cheap to re-derive inside a circuit,
+//! can be plugged in by implementing the trait.
+//!
+//! [`Prover`]: crate::proof_system::Prover
+//! [`Verifier`]: crate::proof_system::Verifier
 
+use crate::commitment::HomomorphicCommitment;
+use ark_ec::AffineCurve;
 use ark_ff::PrimeField;
 use ark_serialize::CanonicalSerialize;
 use merlin::Transcript;
 
-/// Transcript adds an abstraction over the Merlin transcript
-/// For convenience
-pub(crate) trait TranscriptProtocol {
-    /// Append an `item` with the given `label`.
-    fn append(&mut self, label: &'static [u8], item: &impl CanonicalSerialize);
+/// Versioned domain tag every transcript is keyed with on creation.
+///
+/// It is bumped whenever the way challenges are derived changes, so that
+/// proofs created with different derivations never share challenges.
+pub const TRANSCRIPT_DOMAIN_TAG: &[u8] = b"plonk-transcript-v1";
 
-    /// Compute a `label`ed challenge variable.
-    fn challenge_scalar<F: PrimeField>(&mut self, label: &'static [u8]) -> F;
+/// Statistical security parameter of the challenge derivation, in bits.
+const CHALLENGE_SECURITY_BITS: usize = 128;
 
-    /// Append domain separator for the circuit size.
-    fn circuit_domain_sep(&mut self, n: u64);
+/// Returns the number of random bytes reduced into a challenge of `F`.
+///
+/// Following RFC 9380, this is `ceil((ceil(log2(p)) + k) / 8)` for a security
+/// parameter `k`, which makes the distribution of the challenges
+/// statistically indistinguishable from uniform, with a distance of at most
+/// `2^-k`.
+pub fn challenge_len<F>() -> usize
+where
+    F: PrimeField,
+{
+    (F::size_in_bits() + CHALLENGE_SECURITY_BITS + 7) / 8
 }
 
-impl TranscriptProtocol for Transcript {
+/// Transcript adds an abstraction over the Fiat-Shamir transcript used by the
+/// proof system.
+///
+/// The prover and the verifier must use the same backend, since they have to
+/// derive the same challenges from the same messages.
+pub trait TranscriptProtocol: Clone {
+    /// Creates a new transcript with the domain separation `label`.
+    fn new(label: &'static [u8]) -> Self;
+
+    /// Append the raw `message` bytes with the given `label`.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+
+    /// Append an `item` with the given `label`.
     fn append(&mut self, label: &'static [u8], item: &impl CanonicalSerialize) {
         let mut bytes = Vec::new();
         item.serialize(&mut bytes).unwrap();
         self.append_message(label, &bytes)
     }
 
+    /// Append a `scalar` with the given `label`.
+    fn append_scalar<F>(&mut self, label: &'static [u8], scalar: &F)
+    where
+        F: PrimeField,
+    {
+        self.append(label, scalar)
+    }
+
+    /// Append a curve `point` with the given `label`.
+    fn append_point<G>(&mut self, label: &'static [u8], point: &G)
+    where
+        G: AffineCurve,
+    {
+        self.append(label, point)
+    }
+
+    /// Append a `commitment` of the scheme `PC` with the given `label`.
+    fn append_commitment<F, PC>(
+        &mut self,
+        label: &'static [u8],
+        commitment: &PC::Commitment,
+    ) where
+        F: PrimeField,
+        PC: HomomorphicCommitment<F>,
+    {
+        self.append(label, commitment)
+    }
+
+    /// Compute a `label`ed challenge variable.
+    ///
+    /// Challenges must be statistically uniform in any prime field, which the
+    /// Merlin [`Transcript`] achieves by reducing [`challenge_len`] random
+    /// bytes.
+    fn challenge_scalar<F: PrimeField>(&mut self, label: &'static [u8]) -> F;
+}
+
+impl TranscriptProtocol for Transcript {
+    fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Transcript::new(label);
+        transcript.append_message(b"dom-sep", TRANSCRIPT_DOMAIN_TAG);
+        transcript
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        Transcript::append_message(self, label, message)
+    }
+
     fn challenge_scalar<F>(&mut self, label: &'static [u8]) -> F
     where
         F: PrimeField,
     {
-        // XXX: review this: assure from_random_bytes returnes a valid Field
-        // element
-        let size = F::size_in_bits() / 8;
-        let mut buf = vec![0u8; size];
+        let mut buf = vec![0u8; challenge_len::<F>()];
         self.challenge_bytes(label, &mut buf);
-        F::from_random_bytes(&buf).unwrap()
+        F::from_le_bytes_mod_order(&buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_381::{Fq, Fr};
+
+    #[test]
+    fn test_challenge_len() {
+        assert_eq!(challenge_len::<Fr>(), 48);
+        assert_eq!(challenge_len::<Fq>(), 64);
+    }
+
+    #[test]
+    fn test_challenges_are_wide_reductions() {
+        let mut transcript = <Transcript as TranscriptProtocol>::new(b"test");
+        let mut expected = transcript.clone();
+
+        let challenge: Fq = transcript.challenge_scalar(b"challenge");
+        let mut buf = [0u8; 64];
+        expected.challenge_bytes(b"challenge", &mut buf);
+        assert_eq!(challenge, Fq::from_le_bytes_mod_order(&buf));
     }
 
-    fn circuit_domain_sep(&mut self, n: u64) {
-        self.append_message(b"dom-sep", b"circuit_size");
-        self.append_u64(b"n", n);
+    #[test]
+    fn test_transcripts_are_keyed_with_the_domain_tag() {
+        let mut transcript = <Transcript as TranscriptProtocol>::new(b"test");
+        let mut untagged = Transcript::new(b"test");
+        let challenge: Fr = transcript.challenge_scalar(b"challenge");
+        assert_ne!(challenge, untagged.challenge_scalar::<Fr>(b"challenge"));
     }
 }
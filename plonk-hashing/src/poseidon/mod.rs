@@ -5,6 +5,7 @@ pub mod poseidon_ref;
 pub mod preprocessing;
 pub mod round_constant;
 pub mod round_numbers;
+pub mod transcript;
 pub mod zprize_constraints;
 
 use thiserror::Error;
@@ -134,7 +134,7 @@ pub trait PoseidonRefSpec<COM, const WIDTH: usize> {
 }
 
 #[derive(Derivative)]
-#[derivative(Debug(bound = ""))]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
 pub struct PoseidonRef<COM, S: PoseidonRefSpec<COM, WIDTH>, const WIDTH: usize>
 where
     S: ?Sized,
@@ -0,0 +1,296 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) ZK-GARAGE. All rights reserved.
+
+//! Algebraic Fiat-Shamir transcript built on the Poseidon sponge.
+//!
+//! [`PoseidonTranscript`] implements [`TranscriptProtocol`] so it can be used
+//! as the transcript backend of the PLONK prover and verifier. Challenges are
+//! derived with Poseidon over `F`, which makes the transcript cheap to
+//! re-derive inside a circuit over the same field.
+
+use crate::poseidon::{
+    constants::PoseidonConstants,
+    poseidon_ref::{NativeSpecRef, PoseidonRef},
+};
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use core::any::{Any, TypeId};
+use derivative::Derivative;
+use hashbrown::HashMap;
+use plonk_core::transcript::{
+    challenge_len, TranscriptProtocol, TRANSCRIPT_DOMAIN_TAG,
+};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Returns the Poseidon constants of width `WIDTH` over `F`.
+///
+/// Generating the constants is expensive, so they are generated once per
+/// process and field, and shared by every transcript.
+fn cached_constants<F, const WIDTH: usize>() -> PoseidonConstants<F>
+where
+    F: PrimeField,
+{
+    type Cache = HashMap<(TypeId, usize), Arc<dyn Any + Send + Sync>>;
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+    let mut cache = CACHE
+        .get_or_init(Default::default)
+        .lock()
+        .expect("the cache is not poisoned");
+    cache
+        .entry((TypeId::of::<F>(), WIDTH))
+        .or_insert_with(
+            || Arc::new(PoseidonConstants::<F>::generate::<WIDTH>()),
+        )
+        .downcast_ref::<PoseidonConstants<F>>()
+        .expect("the constants are keyed by their field")
+        .clone()
+}
+
+/// Fiat-Shamir transcript absorbing every message into a Poseidon sponge of
+/// width `WIDTH` over `F`.
+///
+/// Messages are absorbed as the field elements of their length followed by
+/// their bytes, packed in little-endian chunks that always fit in `F`. When a
+/// challenge is requested, the pending elements are padded with a single one
+/// and compressed into the running state, `WIDTH - 2` elements at a time.
+///
+/// Challenges over `F` are the running state itself, which is uniform in `F`.
+/// Challenges over any other field are reduced from enough successive states
+/// to be statistically uniform, see [`challenge_len`].
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""))]
+pub struct PoseidonTranscript<F, const WIDTH: usize>
+where
+    F: PrimeField,
+{
+    /// Poseidon instance used to compress the state.
+    sponge: PoseidonRef<(), NativeSpecRef<F>, WIDTH>,
+
+    /// Running state of the transcript.
+    state: F,
+
+    /// Elements absorbed since the last challenge.
+    pending: Vec<F>,
+}
+
+impl<F, const WIDTH: usize> PoseidonTranscript<F, WIDTH>
+where
+    F: PrimeField,
+{
+    /// Number of bytes packed in each absorbed field element.
+    fn chunk_size() -> usize {
+        (F::size_in_bits() - 1) / 8
+    }
+
+    /// Absorbs the length of `bytes` followed by their content.
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        self.pending.push(F::from(bytes.len() as u64));
+        self.pending.extend(
+            bytes
+                .chunks(Self::chunk_size())
+                .map(F::from_le_bytes_mod_order),
+        );
+    }
+
+    /// Compresses the pending elements into the state.
+    fn permute(&mut self) {
+        self.pending.push(F::one());
+        for chunk in self.pending.chunks(WIDTH - 2) {
+            self.sponge.reset(&mut ());
+            self.sponge
+                .input(self.state)
+                .expect("the state fits in the sponge");
+            for element in chunk {
+                self.sponge
+                    .input(*element)
+                    .expect("chunks fit in the sponge");
+            }
+            self.state = self.sponge.output_hash(&mut ());
+        }
+        self.pending.clear();
+    }
+
+    /// Returns the number of successive states reduced into a challenge of
+    /// `C`.
+    fn challenge_states<C>() -> usize
+    where
+        C: PrimeField,
+    {
+        if modulus::<C>() == modulus::<F>() {
+            return 1;
+        }
+        // Every state is uniform in `F`, so it holds at least
+        // `F::size_in_bits() - 1` random bits.
+        let bits = 8 * challenge_len::<C>();
+        let bits_per_state = F::size_in_bits() - 1;
+        (bits + bits_per_state - 1) / bits_per_state
+    }
+}
+
+/// Returns the little-endian bytes of the modulus of `F`.
+fn modulus<F>() -> Vec<u8>
+where
+    F: PrimeField,
+{
+    F::Params::MODULUS.to_bytes_le()
+}
+
+impl<F, const WIDTH: usize> TranscriptProtocol for PoseidonTranscript<F, WIDTH>
+where
+    F: PrimeField,
+{
+    fn new(label: &'static [u8]) -> Self {
+        assert!(WIDTH >= 3, "the sponge must absorb at least two elements");
+        let constants = cached_constants::<F, WIDTH>();
+        let mut transcript = Self {
+            sponge: PoseidonRef::new(&mut (), constants),
+            state: F::zero(),
+            pending: Vec::new(),
+        };
+        transcript.append_message(b"dom-sep", label);
+        transcript.append_message(b"dom-sep", TRANSCRIPT_DOMAIN_TAG);
+        transcript
+    }
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.absorb_bytes(label);
+        self.absorb_bytes(message);
+    }
+
+    /// Returns the state of the sponge after absorbing `label` when `C` is
+    /// `F` itself. Otherwise, the successive states `s_0, s_1, ...` of the
+    /// sponge are read as the integer `s_0 + s_1 * p + s_2 * p^2 + ...` in
+    /// base `p`, the modulus of `F`, which is reduced into `C`.
+    fn challenge_scalar<C>(&mut self, label: &'static [u8]) -> C
+    where
+        C: PrimeField,
+    {
+        self.absorb_bytes(label);
+        let modulus = C::from_le_bytes_mod_order(&modulus::<F>());
+        let mut challenge = C::zero();
+        let mut power = C::one();
+        for _ in 0..Self::challenge_states::<C>() {
+            self.permute();
+            let state = C::from_le_bytes_mod_order(
+                &self.state.into_repr().to_bytes_le(),
+            );
+            challenge += state * power;
+            power *= modulus;
+        }
+        challenge
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::PairingEngine;
+    use ark_ed_on_bls12_381::EdwardsParameters;
+    use ark_ff::UniformRand;
+    use ark_poly_commit::PolynomialCommitment;
+    use ark_std::test_rng;
+    use merlin::Transcript;
+    use plonk_core::{
+        commitment::KZG10,
+        constraint_system::StandardComposer,
+        proof_system::{Prover, Verifier},
+    };
+
+    type Fr = <Bls12_381 as PairingEngine>::Fr;
+    type PC = KZG10<Bls12_381>;
+    type Poseidon = PoseidonTranscript<Fr, 3>;
+
+    fn gadget(composer: &mut StandardComposer<Fr, EdwardsParameters>) {
+        let a = composer.add_input(Fr::from(3u64));
+        let b = composer.add_input(Fr::from(5u64));
+        let zero = composer.zero_var();
+        composer.arithmetic_gate(|gate| {
+            gate.witness(a, b, Some(zero))
+                .mul(Fr::from(1u64))
+                .pi(-Fr::from(15u64))
+        });
+    }
+
+    #[test]
+    fn test_constants_are_cached() {
+        let generated = PoseidonConstants::<Fr>::generate::<3>();
+        assert_eq!(cached_constants::<Fr, 3>(), generated);
+        assert_eq!(cached_constants::<Fr, 3>(), generated);
+        assert_eq!(
+            cached_constants::<Fr, 5>(),
+            PoseidonConstants::<Fr>::generate::<5>()
+        );
+    }
+
+    #[test]
+    fn test_challenges_depend_on_messages() {
+        let mut rng = test_rng();
+        let element = Fr::rand(&mut rng);
+
+        let mut first = Poseidon::new(b"test");
+        first.append(b"element", &element);
+        let mut second = first.clone();
+        let mut third = Poseidon::new(b"test");
+        third.append(b"element", &(element + Fr::from(1u64)));
+
+        let challenge: Fr = first.challenge_scalar(b"challenge");
+        assert_eq!(challenge, second.challenge_scalar::<Fr>(b"challenge"));
+        assert_ne!(challenge, third.challenge_scalar::<Fr>(b"challenge"));
+        assert_ne!(challenge, first.challenge_scalar::<Fr>(b"challenge"));
+    }
+
+    #[test]
+    fn test_challenges_over_other_fields_are_wide_reductions() {
+        type Fq = <Bls12_381 as PairingEngine>::Fq;
+        assert_eq!(Poseidon::challenge_states::<Fr>(), 1);
+        assert_eq!(Poseidon::challenge_states::<Fq>(), 3);
+
+        let mut transcript = Poseidon::new(b"test");
+        let mut expected = transcript.clone();
+        let challenge: Fq = transcript.challenge_scalar(b"challenge");
+
+        expected.absorb_bytes(b"challenge");
+        let modulus = Fq::from_le_bytes_mod_order(&modulus::<Fr>());
+        let mut power = Fq::from(1u64);
+        let mut sum = Fq::from(0u64);
+        for _ in 0..3 {
+            expected.permute();
+            let state = expected.state.into_repr().to_bytes_le();
+            sum += Fq::from_le_bytes_mod_order(&state) * power;
+            power *= modulus;
+        }
+        assert_eq!(challenge, sum);
+    }
+
+    #[test]
+    fn test_prove_and_verify_with_poseidon_transcript() {
+        let pp = PC::setup(1 << 6, None, &mut test_rng()).unwrap();
+
+        let mut prover =
+            Prover::<Fr, EdwardsParameters, PC, Poseidon>::new(b"test");
+        gadget(prover.mut_cs());
+        let (ck, vk) =
+            PC::trim(&pp, prover.commit_key_degree(), 0, None).unwrap();
+        prover.preprocess(&ck).unwrap();
+        let public_inputs = prover.mut_cs().get_pi().clone();
+        let proof = prover.prove(&ck, &mut test_rng()).unwrap();
+
+        let mut verifier =
+            Verifier::<Fr, EdwardsParameters, PC, Poseidon>::new(b"test");
+        gadget(verifier.mut_cs());
+        verifier.preprocess(&ck).unwrap();
+        assert!(verifier.verify(&proof, &vk, &public_inputs).is_ok());
+
+        // A verifier using a different transcript backend derives different
+        // challenges and must reject the proof.
+        let mut verifier =
+            Verifier::<Fr, EdwardsParameters, PC, Transcript>::new(b"test");
+        gadget(verifier.mut_cs());
+        verifier.preprocess(&ck).unwrap();
+        assert!(verifier.verify(&proof, &vk, &public_inputs).is_err());
+    }
+}